@@ -0,0 +1,77 @@
+//! Hot Path Benchmarks
+//!
+//! Pricing, strike search, path generation and a full one-year run. Run with
+//! `cargo bench`; compare against a saved baseline with
+//! `cargo bench -- --save-baseline main` / `--baseline main`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use trading_simulator_v2::calendar::intraday::TradingCalendar;
+use trading_simulator_v2::config::Config;
+use trading_simulator_v2::engine::SimulationEngine;
+use trading_simulator_v2::prices::GBM;
+use trading_simulator_v2::pricing::Black76;
+
+const FUTURES: f64 = 75.0;
+const EXPIRY: f64 = 1.0 / 252.0;
+const RATE: f64 = 0.05;
+const VOL: f64 = 0.35;
+
+fn pricing(c: &mut Criterion) {
+    c.bench_function("black76_price", |b| {
+        b.iter(|| Black76::price(black_box(FUTURES), black_box(74.5), EXPIRY, RATE, VOL, true))
+    });
+    c.bench_function("black76_greeks", |b| {
+        b.iter(|| Black76::greeks(black_box(FUTURES), black_box(74.5), EXPIRY, RATE, VOL, false))
+    });
+}
+
+/// Walk the strike ladder down from ATM until the put is at or below `target` delta
+fn put_strike_for_delta(futures: f64, tick: f64, target: f64) -> f64 {
+    let mut strike = (futures / tick).round() * tick;
+    while strike > tick {
+        let delta = Black76::greeks(futures, strike, EXPIRY, RATE, VOL, false).delta;
+        if delta.abs() <= target {
+            break;
+        }
+        strike -= tick;
+    }
+    strike
+}
+
+fn strike_search(c: &mut Criterion) {
+    c.bench_function("delta_strike_search_16", |b| {
+        b.iter(|| put_strike_for_delta(black_box(FUTURES), 0.25, 0.16))
+    });
+
+    let ladder: Vec<f64> = (0..81).map(|i| 65.0 + 0.25 * i as f64).collect();
+    c.bench_function("ladder_greeks_81", |b| {
+        b.iter(|| Black76::ladder(black_box(FUTURES), black_box(&ladder), EXPIRY, RATE, VOL, false))
+    });
+    c.bench_function("ladder_delta_search_16", |b| {
+        b.iter(|| Black76::ladder(black_box(FUTURES), &ladder, EXPIRY, RATE, VOL, false).strike_for_delta(0.16))
+    });
+}
+
+fn paths(c: &mut Criterion) {
+    let calendar = TradingCalendar::new();
+    c.bench_function("intraday_path_20d", |b| {
+        b.iter_batched(
+            || GBM::new(FUTURES, 0.0, VOL, 42),
+            |mut gbm| gbm.generate_intraday_path(&calendar, 20, 10, 0, 9 * 60),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn full_run(c: &mut Criterion) {
+    let mut config = Config::default_1dte_straddle();
+    config.simulation.days = 252;
+
+    let mut group = c.benchmark_group("engine");
+    group.sample_size(10);
+    group.bench_function("straddle_1dte_1y", |b| b.iter(|| SimulationEngine::run(black_box(&config)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, pricing, strike_search, paths, full_run);
+criterion_main!(benches);
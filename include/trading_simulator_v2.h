@@ -0,0 +1,54 @@
+/*
+ * Trading Simulator V2 - C ABI for the Black-76 pricer
+ *
+ * Build the shared library with:
+ *   cargo build --release --lib --features ffi
+ * and link against target/release/libtrading_simulator_v2.{so,dylib} or
+ * trading_simulator_v2.dll.
+ *
+ * Every function returns a TSV2_* status code and writes its result through
+ * the out pointer, which is left untouched on failure. Time is in years,
+ * rates and volatilities are annualized decimals (0.40 = 40%).
+ */
+
+#ifndef TRADING_SIMULATOR_V2_H
+#define TRADING_SIMULATOR_V2_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define TSV2_OK 0
+#define TSV2_INVALID_INPUT 1
+#define TSV2_NULL_POINTER 2
+#define TSV2_NO_CONVERGENCE 3
+
+/* Theta is per day, vega per vol point */
+typedef struct Tsv2Greeks {
+    double delta;
+    double gamma;
+    double theta;
+    double vega;
+    double rho;
+} Tsv2Greeks;
+
+int32_t tsv2_black76_price(double futures_price, double strike, double time_to_expiry,
+                           double risk_free_rate, double volatility, bool is_call,
+                           double *out);
+
+int32_t tsv2_black76_greeks(double futures_price, double strike, double time_to_expiry,
+                            double risk_free_rate, double volatility, bool is_call,
+                            Tsv2Greeks *out);
+
+int32_t tsv2_black76_implied_vol(double option_price, double futures_price, double strike,
+                                 double time_to_expiry, double risk_free_rate, bool is_call,
+                                 double *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TRADING_SIMULATOR_V2_H */
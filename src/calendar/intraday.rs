@@ -5,6 +5,12 @@
 //! - Daily maintenance: 17:00-18:00 ET
 //! - Weekend: Friday 17:00 - Sunday 18:00
 
+use super::{Holidays, TimeOfDay, WeeklyIndex};
+use serde::Serialize;
+
+/// Every day but Saturday trades (Sunday evening opens the week)
+const TRADING_WEEK: WeeklyIndex = WeeklyIndex::new([true, true, true, true, true, false, true]);
+
 /// Minutes in a day (24 hours)
 pub const MINUTES_PER_DAY: u32 = 24 * 60;
 /// Trading day start in minutes (18:00 = 1080 minutes)
@@ -17,7 +23,9 @@ pub const MAINTENANCE_START: u32 = 17 * 60;
 pub const MAINTENANCE_END: u32 = 18 * 60;
 
 /// Simple timestamp representation
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[repr(C)]
 pub struct Timestamp {
     /// Days since simulation start (Day 0 = start)
     pub day: u32,
@@ -36,6 +44,11 @@ impl Timestamp {
         self.day as u64 * MINUTES_PER_DAY as u64 + self.minute as u64
     }
 
+    /// Day of the session the bar trades in (evening bars open the next day's)
+    pub fn session_day(&self) -> u32 {
+        if self.minute >= TRADING_DAY_START { self.day + 1 } else { self.day }
+    }
+
     /// Format as human-readable string
     pub fn format(&self) -> String {
         let hours = self.minute / 60;
@@ -58,19 +71,39 @@ impl Timestamp {
 
 /// Trading calendar for /CL futures
 #[derive(Debug, Clone)]
-pub struct TradingCalendar;
+pub struct TradingCalendar {
+    /// Left out of trading days and DTE counts (bar sessions are unchanged)
+    holidays: Holidays,
+    /// Minute of the expiration day options expire at
+    expiration_minute: u32,
+}
 
 impl TradingCalendar {
-    /// Create new trading calendar
+    /// Create new trading calendar (options expire at 14:30)
     pub fn new() -> Self {
-        Self
+        Self::with_holidays(Holidays::default())
     }
 
-    /// Check if a day is a trading day (not Saturday)
+    /// A calendar whose DTE counts skip `holidays`
+    pub fn with_holidays(holidays: Holidays) -> Self {
+        Self { holidays, expiration_minute: 14 * 60 + 30 }
+    }
+
+    /// The same calendar with options expiring at `time` (e.g. 16:15 for SPX)
+    pub fn with_expiration(self, time: TimeOfDay) -> Self {
+        Self { expiration_minute: u32::from(time.minutes()), ..self }
+    }
+
+    /// Minute of the expiration day options expire at
+    pub fn expiration_minute(&self) -> u32 {
+        self.expiration_minute
+    }
+
+    /// Check if a day is a trading day (not Saturday or a holiday)
     pub fn is_trading_day(&self, day: u32) -> bool {
         // Day 0 = Monday, so day % 7 gives:
         // 0=Mon, 1=Tue, 2=Wed, 3=Thu, 4=Fri, 5=Sat, 6=Sun
-        day % 7 != 5 // Saturday is not a trading day
+        day % 7 != 5 && !self.holidays.contains(day) // Saturday is not a trading day
     }
 
     /// Check if timestamp is within trading hours
@@ -86,7 +119,7 @@ impl TradingCalendar {
             // Sunday: trading from 18:00 (1080 minutes)
             6 => minute >= MAINTENANCE_END,
             // Mon-Thu: trading except 17:00-18:00
-            _ => minute < MAINTENANCE_START || minute >= MAINTENANCE_END,
+            _ => !(MAINTENANCE_START..MAINTENANCE_END).contains(&minute),
         }
     }
 
@@ -100,7 +133,7 @@ impl TradingCalendar {
         // Handle minute overflow to next day
         if next.minute >= MINUTES_PER_DAY {
             next.day += next.minute / MINUTES_PER_DAY;
-            next.minute = next.minute % MINUTES_PER_DAY;
+            next.minute %= MINUTES_PER_DAY;
         }
         
         // Keep advancing until we find a trading time
@@ -108,7 +141,7 @@ impl TradingCalendar {
             next.minute += interval_minutes;
             if next.minute >= MINUTES_PER_DAY {
                 next.day += next.minute / MINUTES_PER_DAY;
-                next.minute = next.minute % MINUTES_PER_DAY;
+                next.minute %= MINUTES_PER_DAY;
             }
         }
         
@@ -164,21 +197,13 @@ impl TradingCalendar {
         }
         if current.day == expiration_day {
             // On expiration day, DTE is based on time remaining
-            // Assume expiration at 14:30 (870 minutes)
-            let expiration_minute = 14 * 60 + 30;
-            if current.minute >= expiration_minute {
+            if current.minute >= self.expiration_minute {
                 return 0.0;
             }
-            return (expiration_minute - current.minute) as f64 / (24.0 * 60.0);
+            return (self.expiration_minute - current.minute) as f64 / (24.0 * 60.0);
         }
         // Count trading days between
-        let mut trading_days = 0;
-        for day in current.day..expiration_day {
-            if self.is_trading_day(day) {
-                trading_days += 1;
-            }
-        }
-        trading_days as f64
+        self.trading_days_between(current.day, expiration_day) as f64
     }
 
     /// Get the next trading day after the given day
@@ -192,7 +217,7 @@ impl TradingCalendar {
 
     /// Count trading days between two days (exclusive of end)
     pub fn trading_days_between(&self, start: u32, end: u32) -> u32 {
-        (start..end).filter(|&d| self.is_trading_day(d)).count() as u32
+        TRADING_WEEK.between(start, end) - self.holidays.count_between(start, end, &TRADING_WEEK)
     }
 }
 
@@ -268,4 +293,15 @@ mod tests {
         // Should be approximately 4 trading days
         assert!(dte > 3.5 && dte < 4.5);
     }
+
+    #[test]
+    fn test_trading_days_between_matches_scan() {
+        let cal = TradingCalendar::new();
+        for start in 0..30 {
+            for end in 0..60 {
+                let scanned = (start..end).filter(|&d| cal.is_trading_day(d)).count() as u32;
+                assert_eq!(cal.trading_days_between(start, end), scanned, "{}..{}", start, end);
+            }
+        }
+    }
 }
@@ -4,46 +4,216 @@
 //! Day 0 = Monday, January 1, Year 0
 //! 
 //! Trading schedule (for /CL oil futures options):
-//! - Trading days: Monday-Friday (no weekends)
-//! - Expiration: 14:30 on trading days
+//! - Trading days: Monday-Friday (no weekends), less any configured holidays
+//! - Expiration: 14:30 on trading days; one that lands on a holiday moves to
+//!   the following (or preceding) trading day
 //! - Roll trigger: 14:00 on trading days
 
 pub mod intraday;
 
-use std::collections::HashSet;
+use crate::error::SimError;
 
 /// Trading day (0-indexed from Jan 1, Year 0)
 pub type Day = u32;
 
 /// Time of day in minutes from midnight (0-1439)
-pub type TimeOfDay = u16;
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay(u16);
+
+impl TimeOfDay {
+    /// Build from hours and minutes; out-of-range values wrap past midnight
+    pub const fn hm(hours: u16, minutes: u16) -> Self {
+        Self((hours * 60 + minutes) % 1440)
+    }
+
+    /// Build from minutes since midnight
+    pub const fn from_minutes(minutes: u16) -> Self {
+        Self(minutes % 1440)
+    }
+
+    /// Minutes since midnight
+    pub const fn minutes(self) -> u16 {
+        self.0
+    }
+
+    pub const fn hour(self) -> u16 {
+        self.0 / 60
+    }
+
+    pub const fn minute(self) -> u16 {
+        self.0 % 60
+    }
+
+    /// Parse "HH:MM" (24-hour clock), rejecting anything else
+    pub fn parse(time_str: &str) -> Result<Self, SimError> {
+        let invalid = || SimError::InvalidTime(time_str.to_string());
+        let (hours, minutes) = time_str.trim().split_once(':').ok_or_else(invalid)?;
+        let is_digits = |s: &str| !s.is_empty() && s.len() <= 2 && s.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(hours) || minutes.len() != 2 || !is_digits(minutes) {
+            return Err(invalid());
+        }
+        let hours: u16 = hours.parse().map_err(|_| invalid())?;
+        let minutes: u16 = minutes.parse().map_err(|_| invalid())?;
+        if hours >= 24 || minutes >= 60 {
+            return Err(invalid());
+        }
+        Ok(Self::hm(hours, minutes))
+    }
+}
+
+impl std::str::FromStr for TimeOfDay {
+    type Err = SimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour(), self.minute())
+    }
+}
+
+/// Days in each month of the synthetic year (no leap years)
+const MONTH_LENGTHS: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// Map a synthetic day onto its (year, month) pair, month being 1-12
+///
+/// Day 0 is January 1 of Year 0 and every year has 365 days.
+pub fn year_month(day: Day) -> (u32, u32) {
+    let year = day / 365;
+    let mut day_of_year = day % 365;
+    for (index, length) in MONTH_LENGTHS.iter().enumerate() {
+        if day_of_year < *length {
+            return (year, index as u32 + 1);
+        }
+        day_of_year -= length;
+    }
+    (year, 12)
+}
+
+/// Cumulative trading-day counts for a calendar that repeats every week
+///
+/// `cumulative[r]` is the number of trading days among weekdays `0..r`
+/// (Day 0 = Monday), so any range count is two lookups and no iteration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeeklyIndex {
+    cumulative: [u32; 8],
+}
+
+impl WeeklyIndex {
+    /// Build from which weekdays (Mon..Sun) trade
+    pub const fn new(trades: [bool; 7]) -> Self {
+        let mut cumulative = [0; 8];
+        let mut weekday = 0;
+        while weekday < 7 {
+            cumulative[weekday + 1] = cumulative[weekday] + trades[weekday] as u32;
+            weekday += 1;
+        }
+        Self { cumulative }
+    }
+
+    /// Trading days in `0..day`
+    pub fn count_before(&self, day: Day) -> u32 {
+        (day / 7) * self.cumulative[7] + self.cumulative[(day % 7) as usize]
+    }
+
+    /// Trading days in `start..end` (0 if `end <= start`)
+    pub fn between(&self, start: Day, end: Day) -> u32 {
+        if end <= start {
+            return 0;
+        }
+        self.count_before(end) - self.count_before(start)
+    }
+
+    /// Whether `day`'s weekday trades
+    pub fn trades(&self, day: Day) -> bool {
+        let weekday = (day % 7) as usize;
+        self.cumulative[weekday + 1] > self.cumulative[weekday]
+    }
+}
+
+/// Where an expiration that lands on a holiday moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExpirationAdjustment {
+    /// The next trading day
+    #[default]
+    Following,
+    /// The trading day before, unless that is not after the entry day
+    Preceding,
+}
+
+impl ExpirationAdjustment {
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "following" => Some(Self::Following),
+            "preceding" => Some(Self::Preceding),
+            _ => None,
+        }
+    }
+}
+
+/// Exchange holidays and how expirations avoid them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Holidays {
+    /// Sorted and deduplicated
+    days: Vec<Day>,
+    pub adjustment: ExpirationAdjustment,
+}
+
+impl Holidays {
+    pub fn new(mut days: Vec<Day>, adjustment: ExpirationAdjustment) -> Self {
+        days.sort_unstable();
+        days.dedup();
+        Self { days, adjustment }
+    }
+
+    pub fn contains(&self, day: Day) -> bool {
+        self.days.binary_search(&day).is_ok()
+    }
+
+    /// Holidays in `start..end` that fall on trading weekdays of `week`
+    pub fn count_between(&self, start: Day, end: Day, week: &WeeklyIndex) -> u32 {
+        let from = self.days.partition_point(|&day| day < start);
+        let to = self.days.partition_point(|&day| day < end);
+        self.days[from..to.max(from)].iter().filter(|&&day| week.trades(day)).count() as u32
+    }
+}
+
+/// Monday-Friday trading week
+const WEEKDAYS: WeeklyIndex = WeeklyIndex::new([true, true, true, true, true, false, false]);
 
 /// A synthetic trading calendar for backtesting
 #[derive(Debug, Clone)]
 pub struct Calendar {
-    /// Cache of trading days for fast lookup
-    trading_days: HashSet<Day>,
     /// Roll trigger time (default: 14:00 = 840 minutes)
     roll_trigger_time: TimeOfDay,
     /// Expiration time (default: 14:30 = 870 minutes)
     expiration_time: TimeOfDay,
+    holidays: Holidays,
 }
 
 impl Calendar {
     /// Create a new calendar with default /CL settings
     pub fn new() -> Self {
         Self {
-            trading_days: HashSet::new(),
-            roll_trigger_time: 14 * 60,      // 14:00
-            expiration_time: 14 * 60 + 30,   // 14:30
+            roll_trigger_time: TimeOfDay::hm(14, 0),
+            expiration_time: TimeOfDay::hm(14, 30),
+            holidays: Holidays::default(),
         }
     }
 
-    /// Check if a day is a trading day (Monday-Friday, no holidays in synthetic calendar)
+    /// The default calendar closed on `holidays`
+    pub fn with_holidays(holidays: Holidays) -> Self {
+        Self { holidays, ..Self::new() }
+    }
+
+    /// Check if a day is a trading day (Monday-Friday, not a holiday)
     pub fn is_trading_day(&self, day: Day) -> bool {
         // Day 0 = Monday, so day % 7 gives:
         // 0=Mon, 1=Tue, 2=Wed, 3=Thu, 4=Fri, 5=Sat, 6=Sun
-        matches!(day % 7, 0..=4)
+        matches!(day % 7, 0..=4) && !self.holidays.contains(day)
     }
 
     /// Get the next trading day after the given day
@@ -57,7 +227,22 @@ impl Calendar {
 
     /// Count trading days between two days (exclusive of end)
     pub fn trading_days_between(&self, start: Day, end: Day) -> u32 {
-        (start..end).filter(|&d| self.is_trading_day(d)).count() as u32
+        WEEKDAYS.between(start, end) - self.holidays.count_between(start, end, &WEEKDAYS)
+    }
+
+    /// Move an expiration on a non-trading `day` onto a trading day by the
+    /// holiday rule; a preceding day must still be after `entry_day`
+    pub fn adjust_expiration(&self, day: Day, entry_day: Day) -> Day {
+        if self.is_trading_day(day) {
+            return day;
+        }
+        if self.holidays.adjustment == ExpirationAdjustment::Preceding {
+            let preceding = (entry_day + 1..day).rev().find(|&candidate| self.is_trading_day(candidate));
+            if let Some(preceding) = preceding {
+                return preceding;
+            }
+        }
+        self.next_trading_day(day)
     }
 
     /// Get the expiration datetime for a given day
@@ -81,15 +266,20 @@ impl Calendar {
     }
 
     /// Find the expiration day that gives approximately target_dte from current_day
+    ///
+    /// The expiry is listed `target_dte` weekdays out and then moved off any
+    /// holiday by `adjust_expiration`.
     pub fn expiration_for_dte(&self, current_day: Day, target_dte: u32) -> Day {
         let mut day = current_day;
         let mut trading_days_count = 0;
         
         while trading_days_count < target_dte {
-            day = self.next_trading_day(day);
-            trading_days_count += 1;
+            day += 1;
+            if WEEKDAYS.trades(day) {
+                trading_days_count += 1;
+            }
         }
-        day
+        self.adjust_expiration(day, current_day)
     }
 }
 
@@ -99,6 +289,73 @@ impl Default for Calendar {
     }
 }
 
+/// What `entry_dte`, DTE roll thresholds and option time to expiry count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DteUnit {
+    /// Trading days, 252 to the year
+    #[default]
+    Trading,
+    /// Calendar days, 365 to the year
+    Calendar,
+}
+
+impl DteUnit {
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "trading" => Some(Self::Trading),
+            "calendar" => Some(Self::Calendar),
+            _ => None,
+        }
+    }
+
+    pub fn days_per_year(self) -> f64 {
+        match self {
+            Self::Trading => 252.0,
+            Self::Calendar => 365.0,
+        }
+    }
+
+    /// `dte` days of this unit in years
+    pub fn years(self, dte: f64) -> f64 {
+        dte / self.days_per_year()
+    }
+
+    /// Expiration `dte` days of this unit after `entry_day`, moved off
+    /// weekends and holidays by the calendar's adjustment rule
+    pub fn expiration_day(self, calendar: &Calendar, entry_day: Day, dte: u32) -> Day {
+        match self {
+            Self::Trading => calendar.expiration_for_dte(entry_day, dte),
+            Self::Calendar => calendar.adjust_expiration(entry_day + dte, entry_day),
+        }
+    }
+
+    /// Whole days of this unit from `current_day` to `expiration_day` (0 once reached)
+    pub fn days_between(self, calendar: &Calendar, current_day: Day, expiration_day: Day) -> u32 {
+        match self {
+            Self::Trading => calendar.calculate_dte(current_day, expiration_day),
+            Self::Calendar => expiration_day.saturating_sub(current_day),
+        }
+    }
+
+    /// Fractional DTE of this unit at an intraday bar: trading days left
+    /// (the expiration day counted by the hours left), or calendar time to
+    /// the calendar's expiry time in days
+    pub fn remaining(
+        self,
+        calendar: &intraday::TradingCalendar,
+        current: &intraday::Timestamp,
+        expiration_day: Day,
+    ) -> f64 {
+        match self {
+            Self::Trading => calendar.calculate_dte(current, expiration_day),
+            Self::Calendar => {
+                let expiry = intraday::Timestamp::new(expiration_day, calendar.expiration_minute());
+                calendar.fractional_days_between(current, &expiry).max(0.0)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -130,6 +387,42 @@ mod tests {
         assert_eq!(cal.calculate_dte(0, 4), 4);
     }
 
+    #[test]
+    fn test_trading_days_between_matches_scan() {
+        let cal = Calendar::new();
+        for start in 0..30 {
+            for end in 0..60 {
+                let scanned = (start..end).filter(|&d| cal.is_trading_day(d)).count() as u32;
+                assert_eq!(cal.trading_days_between(start, end), scanned, "{}..{}", start, end);
+            }
+        }
+        // Constant time over multi-year horizons
+        assert_eq!(cal.trading_days_between(0, 7 * 52 * 10), 5 * 52 * 10);
+    }
+
+    #[test]
+    fn test_year_month() {
+        assert_eq!(year_month(0), (0, 1));
+        assert_eq!(year_month(30), (0, 1));
+        assert_eq!(year_month(31), (0, 2));
+        assert_eq!(year_month(364), (0, 12));
+        assert_eq!(year_month(365), (1, 1));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(TimeOfDay::parse("14:00").unwrap().minutes(), 840);
+        assert_eq!(TimeOfDay::parse("09:05").unwrap(), TimeOfDay::hm(9, 5));
+        assert_eq!("9:30".parse::<TimeOfDay>().unwrap().to_string(), "09:30");
+        assert!(matches!(TimeOfDay::parse("1400"), Err(SimError::InvalidTime(_))));
+        assert!(TimeOfDay::parse("9am").is_err());
+        assert!(TimeOfDay::parse("24:00").is_err());
+        assert!(TimeOfDay::parse("14:60").is_err());
+        assert!(TimeOfDay::parse("14:5").is_err());
+        assert!(TimeOfDay::parse("+9:30").is_err());
+        assert!(TimeOfDay::parse("ab:cd").is_err());
+    }
+
     #[test]
     fn test_expiration_for_dte() {
         let cal = Calendar::new();
@@ -142,4 +435,41 @@ mod tests {
         let exp_day = cal.expiration_for_dte(0, 5);
         assert_eq!(cal.calculate_dte(0, exp_day), 5);
     }
+
+    #[test]
+    fn test_holiday_expirations_and_dte() {
+        // Wednesday (day 2) is a holiday
+        let following = Calendar::with_holidays(Holidays::new(vec![2, 2], ExpirationAdjustment::Following));
+        assert_eq!(following.expiration_for_dte(0, 2), 3);
+        assert_eq!(following.trading_days_between(0, 7), 4);
+        assert_eq!(following.calculate_dte(1, 3), 1);
+
+        let preceding = Calendar::with_holidays(Holidays::new(vec![2], ExpirationAdjustment::Preceding));
+        assert_eq!(preceding.expiration_for_dte(0, 2), 1);
+        // The day before is the entry day, so the expiry moves forward instead
+        assert_eq!(preceding.expiration_for_dte(1, 1), 3);
+        assert_eq!(DteUnit::Calendar.expiration_day(&preceding, 0, 5), 4);
+
+        let holidays = Holidays::new(vec![2], ExpirationAdjustment::Following);
+        let intraday = intraday::TradingCalendar::with_holidays(holidays);
+        assert_eq!(intraday.calculate_dte(&intraday::Timestamp::new(0, 600), 3), 2.0);
+    }
+
+    #[test]
+    fn test_calendar_dte_unit() {
+        let cal = Calendar::new();
+        let unit = DteUnit::from_config("calendar").unwrap();
+        // From Wednesday, 3 calendar days is Saturday, which rolls to Monday
+        assert_eq!(unit.expiration_day(&cal, 2, 3), 7);
+        assert_eq!(unit.expiration_day(&cal, 2, 2), 4);
+        assert_eq!(unit.days_between(&cal, 4, 7), 3);
+        assert_eq!(DteUnit::Trading.days_between(&cal, 4, 7), 1);
+
+        // Friday 14:30 to Monday's 14:30 expiry is three days of calendar time
+        let intraday = intraday::TradingCalendar::new();
+        let friday = intraday::Timestamp::new(4, 14 * 60 + 30);
+        assert_eq!(unit.remaining(&intraday, &friday, 7), 3.0);
+        assert_eq!(unit.years(365.0), 1.0);
+        assert_eq!(DteUnit::Trading.years(252.0), 1.0);
+    }
 }
@@ -0,0 +1,133 @@
+//! Strategy Comparison Runner
+//!
+//! Runs several configs on the same price paths (the first config's market,
+//! shared seeds) and prints them side by side with head-to-head numbers
+//! Usage: cargo run --bin compare -- <a.yaml> <b.yaml> [more.yaml ...] [--seeds n] [--out table.csv]
+
+use std::env;
+use std::io::Write;
+use std::path::Path;
+use trading_simulator_v2::config::Config;
+use trading_simulator_v2::montecarlo::{self, ComparisonResult};
+use trading_simulator_v2::reporting::presenter;
+
+/// Seeds compared unless `--seeds` says otherwise
+const DEFAULT_SEEDS: u64 = 20;
+
+#[derive(Debug, Default)]
+struct CliArgs {
+    config_paths: Vec<String>,
+    seeds: Option<u64>,
+    /// Per-seed table with every strategy's columns (CSV)
+    out_path: Option<String>,
+}
+
+fn parse_args() -> CliArgs {
+    let mut cli = CliArgs::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seeds" => cli.seeds = args.next().and_then(|n| n.parse().ok()),
+            "--out" => cli.out_path = args.next(),
+            _ => cli.config_paths.push(arg),
+        }
+    }
+    cli
+}
+
+fn main() {
+    println!("Trading Simulator V2 - Strategy Comparison\n");
+
+    let cli = parse_args();
+    if cli.config_paths.len() < 2 {
+        println!("Usage: cargo run --bin compare -- <a.yaml> <b.yaml> [more.yaml ...] [--seeds n] [--out table.csv]");
+        std::process::exit(1);
+    }
+
+    let mut configs = Vec::new();
+    for path in &cli.config_paths {
+        match Config::from_file(path) {
+            Ok(config) => configs.push((strategy_name(path, &configs), config)),
+            Err(e) => {
+                eprintln!("✗ Failed to load config {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let (market_name, market) = &configs[0];
+    let count = cli.seeds.unwrap_or(DEFAULT_SEEDS);
+    println!(
+        "Comparing {} strategies on {} seeds from {} (market of {})",
+        configs.len(),
+        count,
+        market.simulation.seed,
+        market_name
+    );
+
+    let result = montecarlo::compare_with_progress(&configs, market.simulation.seed, count, |p| {
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r{}", presenter::progress_bar(p.fraction(), p.runs_per_sec(), "runs", p.eta()));
+        let _ = stderr.flush();
+    });
+    eprintln!();
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("✗ Comparison failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+    print_comparison(&result);
+
+    if let Some(path) = &cli.out_path {
+        if let Err(e) = std::fs::write(path, result.table_csv()) {
+            eprintln!("✗ Failed to write {}: {}", path, e);
+            std::process::exit(1);
+        }
+        println!("✓ Per-seed table written to {}", path);
+    }
+}
+
+/// File stem of `path`, numbered if an earlier config has the same one
+fn strategy_name(path: &str, earlier: &[(String, Config)]) -> String {
+    let stem = Path::new(path).file_stem().map_or_else(|| path.to_string(), |s| s.to_string_lossy().into_owned());
+    if earlier.iter().any(|(name, _)| *name == stem) {
+        format!("{}_{}", stem, earlier.len() + 1)
+    } else {
+        stem
+    }
+}
+
+fn print_comparison(result: &ComparisonResult) {
+    println!("\n{}", "=".repeat(60));
+    println!("STRATEGY COMPARISON ({} seeds)", result.seeds.len());
+    println!("{}", "=".repeat(60));
+    println!(
+        "{:<22} {:>11} {:>11} {:>11} {:>11} {:>7} {:>6}",
+        "Strategy", "Mean P&L", "Median", "P5", "Worst DD", "Sharpe", "Win%"
+    );
+    for summary in result.summaries() {
+        let sharpe = summary.mean_sharpe.map_or_else(|| "-".to_string(), |s| format!("{:.2}", s));
+        println!(
+            "{:<22} {:>11.0} {:>11.0} {:>11.0} {:>11.0} {:>7} {:>5.0}%",
+            summary.name,
+            summary.pnl.mean,
+            summary.pnl.median,
+            summary.pnl.p5,
+            summary.max_drawdown.max,
+            sharpe,
+            summary.win_rate * 100.0
+        );
+    }
+
+    println!("\nHead to head:");
+    for pair in result.pairs() {
+        let (a, b) = (&result.strategies[pair.a].name, &result.strategies[pair.b].name);
+        let correlation = pair.daily_pnl_correlation.map_or_else(|| "-".to_string(), |c| format!("{:+.2}", c));
+        println!("  {} vs {}", a, b);
+        println!("    Daily P&L correlation: {}", correlation);
+        println!("    Mean P&L difference:   ${:+.0} per seed", pair.mean_pnl_difference);
+        println!("    {} made more on {:.0}% of seeds", a, pair.pnl_wins * 100.0);
+        println!("    {} had the shallower drawdown on {:.0}% of seeds", a, pair.shallower_drawdown * 100.0);
+    }
+}
@@ -2,13 +2,24 @@
 //!
 //! This module handles loading strategy and simulation parameters from YAML files.
 
+pub mod registry;
+
+pub use registry::{ContractRegistry, ContractSpec};
+
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
 /// Root configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Config {
+    /// Contract to trade, looked up in the contract registry; sets `product`,
+    /// `simulation.contract_multiplier`, `simulation.calendar_type` and
+    /// `strike_config.tick_size` (optional)
+    #[serde(default)]
+    pub symbol: Option<String>,
     /// Simulation settings
     pub simulation: SimulationConfig,
     /// Strategy configuration (legacy, for backward compatibility)
@@ -25,13 +36,70 @@ pub struct Config {
     /// Strike configuration
     #[serde(default = "default_strike_config")]
     pub strike_config: StrikeConfig,
+    /// End-of-run reporting options
+    #[serde(default = "default_reporting_config")]
+    pub reporting: ReportingConfig,
+    /// Margin model parameters
+    #[serde(default = "default_margin_config")]
+    pub margin: MarginConfig,
+    /// What to do about bad bars in imported price data
+    #[serde(default = "default_data_quality_config")]
+    pub data_quality: DataQualityConfig,
+    /// HTTP source for historical bars (optional, used with `--fetch`)
+    #[serde(default)]
+    pub market_data: Option<MarketDataConfig>,
+    /// Parameter grid for the `sweep` binary (optional)
+    #[serde(default)]
+    pub sweep: Option<SweepConfig>,
+    /// Entry filters and pricing from a volatility index such as OVX (optional, used with `--vol-index`)
+    #[serde(default)]
+    pub vol_index: Option<VolIndexConfig>,
+    /// Entry filters, sizing and pricing from the path's own realized vol (optional)
+    #[serde(default)]
+    pub realized_vol: Option<RealizedVolConfig>,
+    /// Train/validation/test date ranges for sweeps over historical bars (optional)
+    #[serde(default)]
+    pub splits: Option<SplitsConfig>,
+    /// Limits on the portfolio's exposure and net Greeks that block new entries (optional)
+    #[serde(default)]
+    pub risk_limits: Option<RiskLimitsConfig>,
+    /// Drawdown circuit breaker that liquidates and stands down (optional)
+    #[serde(default)]
+    pub kill_switch: Option<KillSwitchConfig>,
+    /// Per-day loss cap that closes out and halts for the rest of the day (optional)
+    #[serde(default)]
+    pub daily_loss: Option<DailyLossConfig>,
+    /// How account capital is split between `short_leg` and `long_leg` (optional)
+    #[serde(default)]
+    pub allocation: Option<AllocationConfig>,
+    /// Several products traded side by side, each on its own price path (optional)
+    #[serde(default)]
+    pub portfolio: Option<PortfolioConfig>,
+    /// Settlement uncertainty of short strikes close to the money at expiration (optional)
+    #[serde(default)]
+    pub pin_risk: Option<PinRiskConfig>,
+    /// Exchange holidays and the expiration adjustment rule (optional)
+    #[serde(default)]
+    pub holidays: Option<HolidaysConfig>,
+    /// Account currency and FX rates for portfolios of products quoted in other currencies (optional)
+    #[serde(default)]
+    pub fx: Option<FxConfig>,
+    /// ATM term structure and smile options are priced at (optional; flat at
+    /// `volatility + volatility_risk_premium` without it)
+    #[serde(default)]
+    pub vol_surface: Option<VolSurfaceConfig>,
 }
 
 /// Simulation parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SimulationConfig {
     /// Number of days to simulate
     pub days: usize,
+    /// Sessions at the start of the path that open no positions, so
+    /// estimators and filters have data when trading starts (0 for none)
+    #[serde(default)]
+    pub warmup_days: u32,
     /// Initial underlying price
     pub initial_price: f64,
     /// Annual drift (μ), e.g., 0.0 for no drift
@@ -44,24 +112,55 @@ pub struct SimulationConfig {
     /// Example: 0.30 realized + 0.05 VRP = 0.35 implied (35% IV)
     #[serde(default)]
     pub volatility_risk_premium: f64,
-    /// Random seed for reproducibility
+    /// Random seed for reproducibility ("random" or omitted: drawn from entropy when loaded)
     pub seed: u64,
+    /// Whether `seed` was drawn at load time rather than given
+    #[serde(skip)]
+    pub seed_generated: bool,
     /// Risk-free rate (e.g., 0.05 for 5%)
     #[serde(default = "default_risk_free_rate")]
     pub risk_free_rate: f64,
     /// Contract multiplier (1000 for /CL, 100 for stocks)
     #[serde(default = "default_contract_multiplier")]
     pub contract_multiplier: f64,
-    /// Intraday resolution in minutes (0 = daily mode, 10 = 10-minute bars)
+    /// Option model: "black76" (options on futures) or "black_scholes"
+    /// (options on a spot index or stock, e.g. SPX)
+    #[serde(default = "default_pricing_model")]
+    pub pricing_model: String,
+    /// Continuous dividend yield of a spot underlying (e.g. 0.013), used by
+    /// "black_scholes"
+    #[serde(default)]
+    pub dividend_yield: f64,
+    /// Bar granularity: "intraday" (the legacy daily loop is gone, so the only mode)
+    #[serde(default = "default_resolution")]
+    pub resolution: String,
+    /// Minutes between intraday bars (1 to 1380, e.g. 10 for 10-minute bars)
     #[serde(default = "default_intraday_resolution")]
     pub intraday_resolution_minutes: u32,
+    /// Minutes between `RiskSnapshot` events in the event log (e.g. 60 for
+    /// hourly; none when unset)
+    #[serde(default)]
+    pub risk_snapshot_minutes: Option<u32>,
+    /// Relative move in price or time to expiry within which Black-76 marks
+    /// are updated from the last full evaluation (e.g. 0.01); 0 revalues
+    /// every changed leg exactly
+    #[serde(default)]
+    pub valuation_tolerance: f64,
+    /// What `entry_dte`, DTE triggers and time to expiry count: "trading"
+    /// days (252 a year) or "calendar" days (365 a year)
+    #[serde(default = "default_dte_unit")]
+    pub dte_unit: String,
     /// Trading calendar type: "standard" (Mon-Fri 9-5) or "cl_futures" (23/5)
     #[serde(default = "default_calendar_type")]
     pub calendar_type: String,
+    /// Path RNG: "std" (ChaCha-based `StdRng`) or "xoshiro" (faster, batched normals)
+    #[serde(default = "default_rng")]
+    pub rng: String,
 }
 
 /// Strategy configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct StrategyConfig {
     /// Whether this leg is enabled
     #[serde(default = "default_enabled")]
@@ -73,10 +172,15 @@ pub struct StrategyConfig {
     /// Entry time in HH:MM format
     #[serde(default = "default_entry_time")]
     pub entry_time: String,
+    /// When a session has no bar at `entry_time`: "next_bar" (the first bar
+    /// after it), "skip_day" or "open" (the next session's first bar)
+    #[serde(default = "default_missed_entry")]
+    pub missed_entry: String,
     /// Roll time in HH:MM format
     #[serde(default = "default_roll_time")]
     pub roll_time: String,
-    /// Strike selection: "ATM", "OTM", or specific offset
+    /// Strike selection: "ATM", "OTM" (`strike_offset` out), or a target delta
+    /// for both legs ("delta_16") or per leg ("delta_put_16", "delta_call_16")
     #[serde(default = "default_strike_selection")]
     pub strike_selection: String,
     /// Strike offset in price points (for OTM strategies)
@@ -85,6 +189,21 @@ pub struct StrategyConfig {
     /// Position side: "short" (collect premium) or "long" (pay premium)
     #[serde(default = "default_side")]
     pub side: String,
+    /// Contracts of the put leg
+    #[serde(default = "default_quantity")]
+    pub put_quantity: f64,
+    /// Contracts of the call leg
+    #[serde(default = "default_quantity")]
+    pub call_quantity: f64,
+    /// Allow fractional quantities (index-style products); otherwise whole contracts
+    #[serde(default)]
+    pub fractional_quantity: bool,
+    /// Tags for positions this strategy opens fresh (e.g. "weekly_entry")
+    #[serde(default)]
+    pub entry_tags: Vec<String>,
+    /// Tags for positions opened by a roll (e.g. "defensive_roll")
+    #[serde(default)]
+    pub roll_tags: Vec<String>,
     /// Roll triggers
     #[serde(default)]
     pub roll_triggers: Vec<RollTriggerConfig>,
@@ -92,6 +211,7 @@ pub struct StrategyConfig {
 
 /// Roll trigger configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct RollTriggerConfig {
     /// Trigger type: "time", "dte", "profit_target", "stop_loss"
     pub trigger_type: String,
@@ -104,19 +224,24 @@ pub struct RollTriggerConfig {
 
 /// Product-specific configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ProductConfig {
     /// Product symbol (e.g., "/CL", "/ES", "SPX")
     pub symbol: String,
     /// Tick size (minimum price increment)
     pub tick_size: f64,
-    /// Point value in dollars
+    /// Point value, in `currency`
     pub point_value: f64,
     /// Trading hours
     pub trading_hours: TradingHoursConfig,
+    /// Currency prices and P&L are quoted in
+    #[serde(default = "default_currency")]
+    pub currency: String,
 }
 
 /// Trading hours configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TradingHoursConfig {
     /// Market open time in HH:MM
     pub open: String,
@@ -128,50 +253,823 @@ pub struct TradingHoursConfig {
 
 /// Strike configuration for a product
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct StrikeConfig {
     /// Strike tick size (0.25 for /CL, 1.0 for SPY, 5.0 for SPX)
     #[serde(default = "default_strike_tick_size")]
     pub tick_size: f64,
-    /// Roll type: "recenter" (to ATM) or "same_strikes" (keep old strikes)
+    /// Roll type: "recenter" (entry rule again), "same_strikes" (keep old
+    /// strikes) or "delta" (each leg at its delta at entry)
     #[serde(default = "default_roll_type")]
     pub roll_type: String,
+    /// Wider strike spacing away from the money (none: `tick_size` everywhere)
+    #[serde(default)]
+    pub bands: Vec<StrikeBandConfig>,
 }
 
-impl StrikeConfig {
-    /// Round a price to the nearest valid strike
-    pub fn round_to_strike(&self, price: f64) -> f64 {
-        (price / self.tick_size).round() * self.tick_size
+/// Strike spacing from some distance from the money out
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StrikeBandConfig {
+    /// Points from the money where the band starts
+    pub beyond: f64,
+    /// Strike spacing in the band (a multiple of the spacing inside it)
+    pub increment: f64,
+}
+
+/// Reporting configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ReportingConfig {
+    /// Periodic P&L breakdown: "week", "month", or "none"
+    #[serde(default = "default_breakdown")]
+    pub breakdown: String,
+}
+
+/// Margin model configuration (naked short option formula)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MarginConfig {
+    /// Fraction of underlying charged before subtracting the OTM amount (e.g., 0.20)
+    #[serde(default = "default_margin_base_rate")]
+    pub base_rate: f64,
+    /// Floor as a fraction of underlying (e.g., 0.10)
+    #[serde(default = "default_margin_minimum_rate")]
+    pub minimum_rate: f64,
+}
+
+/// Policies for imported bars (see `data::ohlc`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DataQualityConfig {
+    /// Bad prices and repeated or out-of-order timestamps: "error", "drop" or "fill_forward"
+    #[serde(default = "default_bad_bars")]
+    pub bad_bars: String,
+    /// Bars outside the trading session: "keep", "error" or "drop"
+    #[serde(default = "default_off_session")]
+    pub off_session: String,
+    /// Missing bars: "keep", "fill_forward" or "error"
+    #[serde(default = "default_gaps")]
+    pub gaps: String,
+}
+
+/// Historical bars from an HTTP data API (needs the `market-data` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MarketDataConfig {
+    /// Request URL; `{symbol}`, `{start}`, `{end}` and `{api_key}` are filled in
+    pub url: String,
+    /// Symbol as the API names it (e.g., "CL.c.0" or "C:CL")
+    pub symbol: String,
+    /// First date to fetch, YYYY-MM-DD
+    pub start: String,
+    /// Last date to fetch, YYYY-MM-DD
+    pub end: String,
+    /// Bar spacing: "daily" or "intraday"
+    #[serde(default = "default_market_data_bars")]
+    pub bars: String,
+    /// Response body: "csv" (OHLC columns) or "polygon" (JSON aggregates)
+    #[serde(default = "default_market_data_format")]
+    pub format: String,
+    /// How the API key is sent: "bearer", "basic" (as the user name) or "none"
+    #[serde(default = "default_market_data_auth")]
+    pub auth: String,
+    /// Environment variable holding the API key
+    #[serde(default = "default_market_data_api_key_env")]
+    pub api_key_env: String,
+    /// Offset of exchange time from UTC, for APIs that send epoch timestamps
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// Directory fetched bars are cached in
+    #[serde(default = "default_market_data_cache_dir")]
+    pub cache_dir: String,
+}
+
+/// Caps on the portfolio's exposure and net dollar Greeks (see `risk::portfolio`)
+///
+/// An entry or roll whose position would take the book past any cap is
+/// blocked. Unset caps are not checked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RiskLimitsConfig {
+    /// Most positions open at once
+    #[serde(default)]
+    pub max_positions: Option<usize>,
+    /// Largest total premium collected on open short positions (dollars)
+    #[serde(default)]
+    pub max_short_premium: Option<f64>,
+    /// Largest underlying notional controlled by all open legs (dollars)
+    #[serde(default)]
+    pub max_notional: Option<f64>,
+    /// Largest |net delta|, dollars per point of the underlying
+    #[serde(default)]
+    pub max_delta: Option<f64>,
+    /// Largest |net gamma|, dollars of delta per point
+    #[serde(default)]
+    pub max_gamma: Option<f64>,
+    /// Largest |net theta|, dollars per day
+    #[serde(default)]
+    pub max_theta: Option<f64>,
+    /// Largest |net vega|, dollars per vol point
+    #[serde(default)]
+    pub max_vega: Option<f64>,
+    /// "reject" (skip the entry for the day) or "delay" (retry every bar until it fits)
+    #[serde(default = "default_risk_limits_action")]
+    pub action: String,
+}
+
+/// Drawdown circuit breaker
+///
+/// When equity (realized plus marked P&L) falls more than `max_drawdown` below
+/// its peak, every open position is closed at the bar's mark and no new ones
+/// open for `stand_down_days` trading sessions. Drawdown is then measured from
+/// the new equity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct KillSwitchConfig {
+    /// Drawdown from peak equity that trips the switch (dollars)
+    pub max_drawdown: f64,
+    /// Trading sessions without new entries after a trip, counting the rest of
+    /// the trip's session as the first (0 resumes on the next bar)
+    #[serde(default)]
+    pub stand_down_days: u32,
+}
+
+/// Daily loss limit
+///
+/// Once equity falls more than `max_loss` below where it stood at the start
+/// of the calendar day, every open position is closed at the bar's mark and
+/// nothing new opens until the next day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DailyLossConfig {
+    /// Intraday loss that halts trading (dollars)
+    pub max_loss: f64,
+}
+
+/// Pin risk at expiration (see `risk::pin`)
+///
+/// A short leg that settles within `band` points of its strike is settled as
+/// assigned or abandoned instead of at intrinsic value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PinRiskConfig {
+    /// Distance from a short strike within which settlement is uncertain (points)
+    pub band: f64,
+    /// "worst_case" or "random"
+    #[serde(default = "default_pin_risk_mode")]
+    pub mode: String,
+}
+
+/// Exchange holidays
+///
+/// Holidays are left out of trading-day DTE counts, and an expiration that
+/// lands on one moves by `expiration_adjustment`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HolidaysConfig {
+    /// Calendar days (day 0 is the first Monday) the exchange is closed
+    #[serde(default)]
+    pub days: Vec<u32>,
+    /// "following" or "preceding" trading day
+    #[serde(default = "default_expiration_adjustment")]
+    pub expiration_adjustment: String,
+}
+
+/// Implied vol surface (see `pricing::VolSurface`)
+///
+/// IV series and vol index levels, when loaded, replace the ATM level; the
+/// smile still applies around them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VolSurfaceConfig {
+    /// ATM vols as [DTE, vol] pairs (empty: flat at `volatility + volatility_risk_premium`)
+    #[serde(default)]
+    pub term: Vec<[f64; 2]>,
+    /// Vol points per unit of log-moneyness ln(K/F), e.g. -0.2 for a put skew
+    #[serde(default)]
+    pub skew: f64,
+    /// Vol points per squared unit of log-moneyness, e.g. 0.5 for rich wings
+    #[serde(default)]
+    pub curvature: f64,
+}
+
+/// Account currency and FX rates (see `data::fx`)
+///
+/// Portfolio P&L and risk are reported in `account_currency`; each product
+/// quoted in another currency needs a rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FxConfig {
+    #[serde(default = "default_currency")]
+    pub account_currency: String,
+    /// Rate by currency code
+    #[serde(default)]
+    pub rates: BTreeMap<String, FxRateConfig>,
+}
+
+/// Value of one unit of a currency in the account currency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FxRateConfig {
+    /// Rate before the first `series` day, or throughout without a series
+    pub rate: f64,
+    /// `[day, rate]` pairs; each day uses the latest rate on or before it
+    #[serde(default)]
+    pub series: Vec<(u32, f64)>,
+}
+
+/// Capital allocation across the legs of a combined config (see `reporting::allocation`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AllocationConfig {
+    /// Starting account capital (dollars)
+    pub capital: f64,
+    /// "fixed" (by `weights`) or "risk_parity" (inverse rolling vol of each leg's returns)
+    #[serde(default = "default_allocation_method")]
+    pub method: String,
+    /// Fixed weight of each leg by name ("short", "long"); unlisted legs weigh 0, none listed is equal weight
+    #[serde(default)]
+    pub weights: BTreeMap<String, f64>,
+    /// Trading days between rebalances
+    #[serde(default = "default_allocation_rebalance_days")]
+    pub rebalance_days: usize,
+    /// Trading days of returns the risk parity vols are estimated from
+    #[serde(default = "default_allocation_lookback_days")]
+    pub lookback_days: usize,
+}
+
+/// Products simulated together (see `engine::portfolio`)
+///
+/// Each product runs as a copy of the whole config with its symbol's specs,
+/// its own GBM parameters and strategy, and seed `simulation.seed + index`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PortfolioConfig {
+    pub products: Vec<PortfolioProductConfig>,
+    /// Correlation matrix of the products' returns, in product order (independent if unset)
+    #[serde(default)]
+    pub correlation: Option<Vec<Vec<f64>>>,
+}
+
+/// One product of a portfolio
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PortfolioProductConfig {
+    /// Contract registry symbol (e.g., "/CL", "/ES")
+    pub symbol: String,
+    pub initial_price: f64,
+    pub volatility: f64,
+    #[serde(default)]
+    pub drift: f64,
+    pub strategy: StrategyConfig,
+}
+
+/// Date ranges of historical data for out-of-sample testing (see `data::splits`)
+///
+/// Sweeps and searches only see `train`; the chosen parameters are then
+/// reported on every split.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SplitsConfig {
+    pub train: DateRangeConfig,
+    #[serde(default)]
+    pub validation: Option<DateRangeConfig>,
+    #[serde(default)]
+    pub test: Option<DateRangeConfig>,
+}
+
+/// Inclusive range of dates, YYYY-MM-DD
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DateRangeConfig {
+    pub start: String,
+    pub end: String,
+}
+
+impl RiskLimitsConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(format!("risk_limits: {}", msg)));
+        if self.max_positions == Some(0) {
+            return invalid("max_positions must be at least 1".to_string());
+        }
+        let caps = [
+            ("max_short_premium", self.max_short_premium),
+            ("max_notional", self.max_notional),
+            ("max_delta", self.max_delta),
+            ("max_gamma", self.max_gamma),
+            ("max_theta", self.max_theta),
+            ("max_vega", self.max_vega),
+        ];
+        for (name, cap) in caps {
+            if let Some(cap) = cap.filter(|cap| !(cap.is_finite() && *cap >= 0.0)) {
+                return invalid(format!("{} must not be negative, got {}", name, cap));
+            }
+        }
+        if crate::risk::LimitAction::from_config(&self.action).is_none() {
+            return invalid(format!("unknown action {} (expected reject or delay)", self.action));
+        }
+        Ok(())
     }
-    
-    /// Round down to nearest strike (for puts when going OTM)
-    pub fn round_down_to_strike(&self, price: f64) -> f64 {
-        (price / self.tick_size).floor() * self.tick_size
+}
+
+impl AllocationConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(format!("allocation: {}", msg)));
+        if !(self.capital.is_finite() && self.capital > 0.0) {
+            return invalid(format!("capital must be positive, got {}", self.capital));
+        }
+        if crate::reporting::allocation::AllocationMethod::from_config(&self.method).is_none() {
+            return invalid(format!("unknown method {} (expected fixed or risk_parity)", self.method));
+        }
+        for (leg, weight) in &self.weights {
+            if !["short", "long"].contains(&leg.as_str()) {
+                return invalid(format!("unknown leg {} in weights (expected short or long)", leg));
+            }
+            if !(weight.is_finite() && *weight >= 0.0) {
+                return invalid(format!("weight of {} must not be negative, got {}", leg, weight));
+            }
+        }
+        if !self.weights.is_empty() && self.weights.values().sum::<f64>() <= 0.0 {
+            return invalid("weights must not all be zero".to_string());
+        }
+        if self.rebalance_days == 0 {
+            return invalid("rebalance_days must be at least 1".to_string());
+        }
+        if self.lookback_days < 2 {
+            return invalid(format!("lookback_days must be at least 2, got {}", self.lookback_days));
+        }
+        Ok(())
     }
-    
-    /// Round up to nearest strike (for calls when going OTM)
-    pub fn round_up_to_strike(&self, price: f64) -> f64 {
-        (price / self.tick_size).ceil() * self.tick_size
+}
+
+impl SplitsConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(format!("splits: {}", msg)));
+        let mut ranges = Vec::new();
+        let named = [("validation", self.validation.as_ref()), ("test", self.test.as_ref())];
+        for (name, range) in [("train", Some(&self.train))].into_iter().chain(named) {
+            let Some(range) = range else {
+                continue;
+            };
+            match (crate::data::Date::parse(&range.start), crate::data::Date::parse(&range.end)) {
+                (Some(start), Some(end)) if start <= end => ranges.push((name, start, end)),
+                (Some(_), Some(_)) => return invalid(format!("{} starts after it ends", name)),
+                _ => {
+                    let (start, end) = (&range.start, &range.end);
+                    return invalid(format!("{} dates must be YYYY-MM-DD, got {} and {}", name, start, end));
+                }
+            }
+        }
+        for (i, &(a, a_start, a_end)) in ranges.iter().enumerate() {
+            for &(b, b_start, b_end) in &ranges[i + 1..] {
+                if a_start <= b_end && b_start <= a_end {
+                    return invalid(format!("{} and {} overlap", a, b));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parameter grid run by the `sweep` binary (see `montecarlo::Sweep`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SweepConfig {
+    /// Swept fields; every combination of their values is run
+    pub parameters: Vec<SweepParameterConfig>,
+    /// Seeds per combination, starting at `simulation.seed`
+    #[serde(default = "default_sweep_seeds")]
+    pub seeds: u64,
+    /// What ranks the points (default: highest mean P&L)
+    #[serde(default)]
+    pub objective: Option<ObjectiveConfig>,
+    /// Settings for searching the grid with `sweep --optimize` instead of running all of it
+    #[serde(default)]
+    pub optimizer: Option<OptimizerConfig>,
+}
+
+/// How a study ranks parameter points (see `montecarlo::Objective`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ObjectiveConfig {
+    /// Maximized: "mean_pnl", "median_pnl", "sharpe" or "sharpe_drawdown";
+    /// minimized: "cvar" (mean loss over the worst `cvar_level` of seeds)
+    #[serde(default = "default_objective_metric")]
+    pub metric: String,
+    /// Points with a seed whose max drawdown exceeds this (dollars) rank below every other point
+    #[serde(default)]
+    pub max_drawdown: Option<f64>,
+    /// Tail fraction for "cvar" (e.g., 0.05 = worst 5% of seeds)
+    #[serde(default = "default_objective_cvar_level")]
+    pub cvar_level: f64,
+    /// Sharpe points "sharpe_drawdown" subtracts per dollar of mean max drawdown
+    #[serde(default = "default_objective_drawdown_penalty")]
+    pub drawdown_penalty: f64,
+    /// How seeds combine: "pooled" (the metric over all runs), or "median" /
+    /// "worst" of the metric on each seed alone, to favor points that hold up
+    /// on every path
+    #[serde(default = "default_objective_aggregate")]
+    pub aggregate: String,
+}
+
+impl ObjectiveConfig {
+    /// Check the metric name and its settings (also part of `Config::validate`)
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(format!("sweep.objective: {}", msg)));
+        if !["mean_pnl", "median_pnl", "sharpe", "sharpe_drawdown", "cvar"].contains(&self.metric.as_str()) {
+            let expected = "expected mean_pnl, median_pnl, sharpe, sharpe_drawdown or cvar";
+            return invalid(format!("unknown metric {} ({})", self.metric, expected));
+        }
+        if self.max_drawdown.is_some_and(|limit| !(limit.is_finite() && limit > 0.0)) {
+            return invalid("max_drawdown must be positive".to_string());
+        }
+        if !(self.cvar_level > 0.0 && self.cvar_level <= 1.0) {
+            return invalid(format!("cvar_level must be in (0, 1], got {}", self.cvar_level));
+        }
+        if !(self.drawdown_penalty.is_finite() && self.drawdown_penalty >= 0.0) {
+            return invalid("drawdown_penalty must be non-negative".to_string());
+        }
+        if !["pooled", "median", "worst"].contains(&self.aggregate.as_str()) {
+            return invalid(format!("unknown aggregate {} (expected pooled, median or worst)", self.aggregate));
+        }
+        Ok(())
+    }
+}
+
+/// Random search with successive halving over a sweep grid (see `montecarlo::Optimizer`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OptimizerConfig {
+    /// Most simulations the search may run
+    #[serde(default = "default_optimizer_budget")]
+    pub budget: usize,
+    /// Seeds each candidate starts with; survivors get `eta` times more per round
+    #[serde(default = "default_optimizer_min_seeds")]
+    pub min_seeds: u64,
+    /// One in `eta` candidates survives each round
+    #[serde(default = "default_optimizer_eta")]
+    pub eta: usize,
+    /// Candidates sampled up front (default: as many as the budget allows)
+    #[serde(default)]
+    pub candidates: Option<usize>,
+}
+
+impl Default for OptimizerConfig {
+    fn default() -> Self {
+        Self {
+            budget: default_optimizer_budget(),
+            min_seeds: default_optimizer_min_seeds(),
+            eta: default_optimizer_eta(),
+            candidates: None,
+        }
     }
-    
-    /// Get strikes for a straddle given underlying price
-    pub fn get_straddle_strikes(&self, underlying: f64, offset: f64) -> (f64, f64) {
-        let atm = self.round_to_strike(underlying);
-        let put_strike = self.round_to_strike(atm - offset);
-        let call_strike = self.round_to_strike(atm + offset);
-        (put_strike, call_strike)
+}
+
+impl OptimizerConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(format!("sweep.optimizer: {}", msg)));
+        if self.budget == 0 || self.min_seeds == 0 || self.candidates == Some(0) {
+            return invalid("budget, min_seeds and candidates must be at least 1".to_string());
+        }
+        if self.eta < 2 {
+            return invalid("eta must be at least 2".to_string());
+        }
+        Ok(())
     }
-    
-    /// Find nearest available strike
-    pub fn nearest_strike(&self, price: f64) -> f64 {
-        self.round_to_strike(price)
+}
+
+/// A swept config field and either its values or an inclusive range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SweepParameterConfig {
+    /// Dotted config path, e.g. `strategy.entry_dte` (see `Config::with_override`)
+    pub path: String,
+    #[serde(default)]
+    pub values: Option<Vec<f64>>,
+    #[serde(default)]
+    pub start: Option<f64>,
+    #[serde(default)]
+    pub end: Option<f64>,
+    #[serde(default)]
+    pub step: Option<f64>,
+}
+
+impl SweepParameterConfig {
+    /// Most values one swept field may take
+    pub const MAX_VALUES: usize = 10_000;
+
+    /// The listed values, or `start`, `start + step`, ... up to and including `end`
+    pub fn values(&self) -> Result<Vec<f64>, String> {
+        if let Some(values) = &self.values {
+            if values.is_empty() || values.iter().any(|v| !v.is_finite()) {
+                return Err(format!("{}: values must be finite and not empty", self.path));
+            }
+            if values.len() > Self::MAX_VALUES {
+                return Err(format!("{}: {} values is too many", self.path, values.len()));
+            }
+            return Ok(values.clone());
+        }
+        let (Some(start), Some(end), Some(step)) = (self.start, self.end, self.step) else {
+            return Err(format!("{}: give either values or start, end and step", self.path));
+        };
+        if ![start, end, step].iter().all(|v| v.is_finite()) || step <= 0.0 || end < start {
+            return Err(format!("{}: need finite values, step > 0 and end >= start", self.path));
+        }
+        // Tolerate rounding so `end` itself is included; checked as a float,
+        // since huge ranges don't fit a usize
+        let steps = ((end - start) / step + 1e-9).floor();
+        if steps >= Self::MAX_VALUES as f64 {
+            return Err(format!("{}: {} values is too many", self.path, steps + 1.0));
+        }
+        let count = steps as usize + 1;
+        // Round off accumulated float error so 0.075 reads as 0.075 in results
+        Ok((0..count).map(|i| ((start + i as f64 * step) * 1e12).round() / 1e12).collect())
+    }
+}
+
+/// Rules driven by a volatility index series (see `data::vol_index`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VolIndexConfig {
+    /// Only open positions while the index is at or above this level (e.g., 35.0)
+    #[serde(default)]
+    pub min_entry: Option<f64>,
+    /// Only open positions while the index is at or below this level
+    #[serde(default)]
+    pub max_entry: Option<f64>,
+    /// IV to price at: "config" (volatility + VRP) or "index" (level / 100 * iv_scale)
+    #[serde(default = "default_vol_index_implied_vol")]
+    pub implied_vol: String,
+    /// Scales the index to the IV of the traded tenor when pricing at the index
+    #[serde(default = "default_vol_index_iv_scale")]
+    pub iv_scale: f64,
+}
+
+impl VolIndexConfig {
+    /// Whether positions may be opened at index level `level`
+    pub fn allows_entry(&self, level: f64) -> bool {
+        self.min_entry.is_none_or(|min| level >= min) && self.max_entry.is_none_or(|max| level <= max)
+    }
+
+    /// Whether the filters are set
+    pub fn filters_entries(&self) -> bool {
+        self.min_entry.is_some() || self.max_entry.is_some()
+    }
+}
+
+/// Rules driven by rolling realized vol of the price path (see `prices::RealizedVol`)
+///
+/// Levels are annualized vols (0.25 for 25%). Days before the window has
+/// filled have no estimate: filters open nothing, sizing keeps the
+/// configured quantities and pricing falls back to the config IV.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RealizedVolConfig {
+    /// "close_to_close", "parkinson" (session high/low) or "ewma"
+    #[serde(default = "default_realized_vol_estimator")]
+    pub estimator: String,
+    /// Sessions in the estimate (the warm-up of "ewma")
+    #[serde(default = "default_realized_vol_window_days")]
+    pub window_days: usize,
+    /// Weight of the previous variance for "ewma"
+    #[serde(default = "default_realized_vol_lambda")]
+    pub lambda: f64,
+    /// Only open positions while realized vol is at or above this level
+    #[serde(default)]
+    pub min_entry: Option<f64>,
+    /// Only open positions while realized vol is at or below this level
+    #[serde(default)]
+    pub max_entry: Option<f64>,
+    /// Scale leg quantities by target / realized vol at entry (whole
+    /// contracts, at least one, unless `fractional_quantity`)
+    #[serde(default)]
+    pub target_vol: Option<f64>,
+    /// IV to price at: "config" (volatility + VRP) or "realized" (estimate + VRP)
+    #[serde(default = "default_realized_vol_implied_vol")]
+    pub implied_vol: String,
+}
+
+impl RealizedVolConfig {
+    /// Whether positions may be opened at realized vol `vol`
+    pub fn allows_entry(&self, vol: f64) -> bool {
+        self.min_entry.is_none_or(|min| vol >= min) && self.max_entry.is_none_or(|max| vol <= max)
+    }
+
+    /// Whether the filters are set
+    pub fn filters_entries(&self) -> bool {
+        self.min_entry.is_some() || self.max_entry.is_some()
+    }
+
+    /// How the estimate is computed (close-to-close for an unknown value, which validation rejects)
+    pub fn estimator(&self) -> crate::prices::VolEstimator {
+        crate::prices::VolEstimator::from_config(&self.estimator, self.lambda)
+            .unwrap_or(crate::prices::VolEstimator::CloseToClose)
+    }
+
+    /// Quantity multiplier for an entry at realized vol `vol`
+    pub fn size(&self, vol: Option<f64>) -> f64 {
+        match (self.target_vol, vol) {
+            (Some(target), Some(vol)) if vol > 0.0 => target / vol,
+            _ => 1.0,
+        }
+    }
+}
+
+impl StrategyConfig {
+    /// Side of every leg ("long", in any case, pays premium; anything else sells it)
+    pub fn side(&self) -> crate::events::Side {
+        if self.side.trim().eq_ignore_ascii_case("long") {
+            crate::events::Side::Long
+        } else {
+            crate::events::Side::Short
+        }
+    }
+}
+
+impl StrikeConfig {
+    /// Listed strikes with the money at `underlying`
+    pub fn ladder(&self, underlying: f64) -> Result<crate::pricing::StrikeLadder, ConfigError> {
+        let bands = self.bands.iter().map(|band| (band.beyond, band.increment)).collect();
+        crate::pricing::StrikeLadder::new(underlying, self.tick_size, bands)
+            .map_err(|msg| ConfigError::Validation(format!("strike_config: {}", msg)))
     }
 }
 
 impl Config {
     /// Load configuration from a YAML file
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::from_file_with(path, ContractRegistry::builtin())
+    }
+
+    /// Load configuration from a YAML file, resolving `symbol` in `registry`
+    pub fn from_file_with<P: AsRef<Path>>(path: P, registry: &ContractRegistry) -> Result<Self, ConfigError> {
         let contents = fs::read_to_string(path)?;
-        let config: Config = serde_yaml::from_str(&contents)?;
+        Self::from_yaml_str_with(&contents, registry)
+    }
+
+    /// Parse and validate configuration from a YAML string
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, ConfigError> {
+        Self::from_yaml_str_with(yaml, ContractRegistry::builtin())
+    }
+
+    /// Parse configuration, resolve `symbol` in `registry` and validate
+    pub fn from_yaml_str_with(yaml: &str, registry: &ContractRegistry) -> Result<Self, ConfigError> {
+        let mut value: serde_yaml::Value = serde_yaml::from_str(yaml)?;
+        let seed_generated = draw_random_seed(&mut value);
+        let mut config: Config = serde_yaml::from_value(value)?;
+        config.simulation.seed_generated = seed_generated;
+        config.resolve_symbol(registry)?;
+        config.validate()?;
+        config.product_configs(registry)?;
+        Ok(config)
+    }
+
+    /// The configured holidays (none without a `holidays` section)
+    pub fn holidays(&self) -> crate::calendar::Holidays {
+        let Some(holidays) = &self.holidays else {
+            return crate::calendar::Holidays::default();
+        };
+        let adjustment = crate::calendar::ExpirationAdjustment::from_config(&holidays.expiration_adjustment);
+        crate::calendar::Holidays::new(holidays.days.clone(), adjustment.unwrap_or_default())
+    }
+
+    /// The configured DTE unit (trading days for an unknown value, which validation rejects)
+    pub fn dte_unit(&self) -> crate::calendar::DteUnit {
+        crate::calendar::DteUnit::from_config(&self.simulation.dte_unit).unwrap_or_default()
+    }
+
+    /// Option model for the product (Black-76 for an unknown value, which validation rejects)
+    pub fn pricing_model(&self) -> crate::pricing::PricingModel {
+        crate::pricing::PricingModel::from_config(&self.simulation.pricing_model, self.simulation.dividend_yield)
+            .unwrap_or_default()
+    }
+
+    /// Time options expire on their expiration day (14:30 without a product)
+    pub fn option_expiry(&self) -> crate::calendar::TimeOfDay {
+        self.product
+            .as_ref()
+            .and_then(|product| crate::calendar::TimeOfDay::parse(&product.trading_hours.option_expiry).ok())
+            .unwrap_or(crate::calendar::TimeOfDay::hm(14, 30))
+    }
+
+    /// The intraday calendar bars are counted on: holidays and the option expiry time
+    pub fn trading_calendar(&self) -> crate::calendar::intraday::TradingCalendar {
+        crate::calendar::intraday::TradingCalendar::with_holidays(self.holidays()).with_expiration(self.option_expiry())
+    }
+
+    /// The vol surface options are priced at, before any IV data is loaded
+    pub fn vol_surface(&self) -> Result<crate::pricing::VolSurface, ConfigError> {
+        let flat = self.simulation.volatility + self.simulation.volatility_risk_premium;
+        let Some(section) = &self.vol_surface else {
+            return Ok(crate::pricing::VolSurface::flat(flat));
+        };
+        let surface = if section.term.is_empty() {
+            crate::pricing::VolSurface::flat(flat)
+        } else {
+            let tenors = section.term.iter().map(|&[dte, vol]| (dte, vol)).collect();
+            crate::pricing::VolSurface::term_structure(tenors)
+                .map_err(|msg| ConfigError::Validation(format!("vol_surface.term: {}", msg)))?
+        };
+        if !(section.skew.is_finite() && section.curvature.is_finite()) {
+            return Err(ConfigError::Validation("vol_surface skew and curvature must be finite".to_string()));
+        }
+        Ok(surface.with_smile(crate::pricing::Smile { skew: section.skew, curvature: section.curvature }))
+    }
+
+    /// How entries pick strikes (at the money for an unknown value, which validation rejects)
+    pub fn strike_selection(&self) -> crate::engine::StrikeSelection {
+        let strategy = &self.strategy;
+        crate::engine::StrikeSelection::from_config(&strategy.strike_selection, strategy.strike_offset)
+            .unwrap_or(crate::engine::StrikeSelection::Atm)
+    }
+
+    /// What entries do when the entry time has no bar (next bar for an unknown value, which validation rejects)
+    pub fn missed_entry(&self) -> crate::engine::MissedEntry {
+        crate::engine::MissedEntry::from_config(&self.strategy.missed_entry).unwrap_or_default()
+    }
+
+    /// Where rolls put the new strikes (recenter for an unknown value, which validation rejects)
+    pub fn roll_destination(&self) -> crate::engine::RollDestination {
+        crate::engine::RollDestination::from_config(&self.strike_config.roll_type).unwrap_or_default()
+    }
+
+    /// The config each `portfolio` product runs with (empty without a portfolio)
+    pub fn product_configs(&self, registry: &ContractRegistry) -> Result<Vec<Config>, ConfigError> {
+        let Some(portfolio) = &self.portfolio else {
+            return Ok(Vec::new());
+        };
+        let mut configs = Vec::with_capacity(portfolio.products.len());
+        for (i, product) in portfolio.products.iter().enumerate() {
+            let mut config = self.clone();
+            config.portfolio = None;
+            config.symbol = Some(product.symbol.clone());
+            config.resolve_symbol(registry)?;
+            config.simulation.initial_price = product.initial_price;
+            config.simulation.volatility = product.volatility;
+            config.simulation.drift = product.drift;
+            config.simulation.seed = self.simulation.seed.wrapping_add(i as u64);
+            config.strategy = product.strategy.clone();
+            config.validate().map_err(|e| match e {
+                ConfigError::Validation(msg) => ConfigError::Validation(format!("portfolio product {}: {}", i, msg)),
+                other => other,
+            })?;
+            configs.push(config);
+        }
+        Ok(configs)
+    }
+
+    /// Replace the contract fields with the registry's specs for `symbol`
+    pub fn resolve_symbol(&mut self, registry: &ContractRegistry) -> Result<(), ConfigError> {
+        let Some(symbol) = &self.symbol else {
+            return Ok(());
+        };
+        let Some((name, spec)) = registry.get(symbol) else {
+            let known: Vec<&str> = registry.symbols().collect();
+            return Err(ConfigError::Validation(format!("Unknown symbol: {} (known: {})", symbol, known.join(", "))));
+        };
+        self.product = Some(ProductConfig {
+            symbol: name.to_string(),
+            tick_size: spec.tick_size,
+            point_value: spec.multiplier,
+            trading_hours: spec.trading_hours.clone(),
+            currency: spec.currency.clone(),
+        });
+        self.simulation.contract_multiplier = spec.multiplier;
+        self.simulation.calendar_type = spec.calendar_type.clone();
+        self.simulation.pricing_model = spec.pricing_model.clone();
+        self.strike_config.tick_size = spec.strike_increment;
+        self.strike_config.bands = spec.strike_bands.clone();
+        Ok(())
+    }
+
+    /// Copy of this config with one numeric field replaced and revalidated
+    ///
+    /// `path` is dotted YAML keys, with list indices as numbers, e.g.
+    /// `simulation.volatility` or `strategy.roll_triggers.0.value`.
+    /// Integral values also fit integer fields such as `strategy.entry_dte`.
+    pub fn with_override(&self, path: &str, value: f64) -> Result<Self, ConfigError> {
+        let unknown = || ConfigError::Validation(format!("unknown config field '{}'", path));
+        let mut root = serde_yaml::to_value(self)?;
+        let mut node = &mut root;
+        for key in path.split('.') {
+            node = match node {
+                serde_yaml::Value::Mapping(map) => map.get_mut(&serde_yaml::Value::from(key)),
+                serde_yaml::Value::Sequence(items) => key.parse::<usize>().ok().and_then(|i| items.get_mut(i)),
+                _ => None,
+            }
+            .ok_or_else(unknown)?;
+        }
+        if !node.is_number() {
+            return Err(ConfigError::Validation(format!("config field '{}' is not numeric", path)));
+        }
+        *node = if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+            serde_yaml::Value::from(value as i64)
+        } else {
+            serde_yaml::Value::from(value)
+        };
+
+        let config: Config = serde_yaml::from_value(root)?;
         config.validate()?;
         Ok(config)
     }
@@ -179,8 +1077,10 @@ impl Config {
     /// Create a default configuration (1DTE straddle)
     pub fn default_1dte_straddle() -> Self {
         Self {
+            symbol: None,
             simulation: SimulationConfig {
                 days: 30,
+                warmup_days: 0,
                 initial_price: 75.0,
                 drift: 0.0,
                 volatility: 0.30,
@@ -188,18 +1088,32 @@ impl Config {
                 seed: 42,
                 risk_free_rate: 0.05,
                 contract_multiplier: 1000.0,
+                pricing_model: default_pricing_model(),
+                dividend_yield: 0.0,
+                resolution: default_resolution(),
+                seed_generated: false,
                 intraday_resolution_minutes: 10, // 10-minute bars
+                risk_snapshot_minutes: None,
+                valuation_tolerance: 0.0,
+                dte_unit: default_dte_unit(),
                 calendar_type: "cl_futures".to_string(), // 23/5 calendar
+                rng: default_rng(),
             },
             strategy: StrategyConfig {
                 enabled: true,
                 strategy_type: "straddle".to_string(),
                 entry_dte: 1,
                 entry_time: "15:00".to_string(),
+                missed_entry: default_missed_entry(),
                 roll_time: "14:00".to_string(),
                 strike_selection: "ATM".to_string(),
                 strike_offset: 0.0,
                 side: "short".to_string(),
+                put_quantity: default_quantity(),
+                call_quantity: default_quantity(),
+                fractional_quantity: false,
+                entry_tags: Vec::new(),
+                roll_tags: Vec::new(),
                 roll_triggers: vec![
                     RollTriggerConfig {
                         trigger_type: "time".to_string(),
@@ -219,16 +1133,35 @@ impl Config {
                     close: "17:00".to_string(),
                     option_expiry: "14:30".to_string(),
                 },
+                currency: default_currency(),
             }),
             strike_config: StrikeConfig {
                 tick_size: 0.25,
                 roll_type: "recenter".to_string(),
+                bands: Vec::new(),
             },
+            reporting: default_reporting_config(),
+            margin: default_margin_config(),
+            data_quality: default_data_quality_config(),
+            market_data: None,
+            sweep: None,
+            vol_index: None,
+            realized_vol: None,
+            splits: None,
+            risk_limits: None,
+            kill_switch: None,
+            daily_loss: None,
+            allocation: None,
+            portfolio: None,
+            pin_risk: None,
+            holidays: None,
+            fx: None,
+            vol_surface: None,
         }
     }
 
     /// Validate the configuration
-    fn validate(&self) -> Result<(), ConfigError> {
+    pub fn validate(&self) -> Result<(), ConfigError> {
         // Check volatility is positive
         if self.simulation.volatility <= 0.0 {
             return Err(ConfigError::Validation(
@@ -243,6 +1176,29 @@ impl Config {
             ));
         }
 
+        // Marks, triggers and expirations all run on intraday bars
+        if self.simulation.resolution != "intraday" {
+            return Err(ConfigError::Validation(format!(
+                "simulation.resolution must be intraday, got {}",
+                self.simulation.resolution
+            )));
+        }
+        if !(1..=23 * 60).contains(&self.simulation.intraday_resolution_minutes) {
+            return Err(ConfigError::Validation(format!(
+                "simulation.intraday_resolution_minutes must be between 1 and 1380, got {}",
+                self.simulation.intraday_resolution_minutes
+            )));
+        }
+        if self.simulation.risk_snapshot_minutes == Some(0) {
+            return Err(ConfigError::Validation("simulation.risk_snapshot_minutes must be positive".to_string()));
+        }
+        if !(0.0..=0.1).contains(&self.simulation.valuation_tolerance) {
+            return Err(ConfigError::Validation(format!(
+                "simulation.valuation_tolerance must be between 0 and 0.1, got {}",
+                self.simulation.valuation_tolerance
+            )));
+        }
+
         // Validate strategy type
         let valid_strategies = ["straddle", "strangle", "iron_condor"];
         if !valid_strategies.contains(&self.strategy.strategy_type.as_str()) {
@@ -251,9 +1207,242 @@ impl Config {
             ));
         }
 
+        // Validate leg quantities
+        for strategy in std::iter::once(&self.strategy).chain(&self.short_leg).chain(&self.long_leg) {
+            for (leg, quantity) in [("put", strategy.put_quantity), ("call", strategy.call_quantity)] {
+                if !(quantity.is_finite() && quantity > 0.0) {
+                    return Err(ConfigError::Validation(format!("{}_quantity must be positive, got {}", leg, quantity)));
+                }
+                if !strategy.fractional_quantity && quantity.fract() != 0.0 {
+                    return Err(ConfigError::Validation(format!(
+                        "{}_quantity must be whole contracts without fractional_quantity, got {}",
+                        leg, quantity
+                    )));
+                }
+            }
+        }
+
+        // Validate trade tags
+        for strategy in std::iter::once(&self.strategy).chain(&self.short_leg).chain(&self.long_leg) {
+            if let Some(tag) = strategy.entry_tags.iter().chain(&strategy.roll_tags).find(|tag| !is_tag(tag)) {
+                return Err(ConfigError::Validation(format!(
+                    "invalid tag {:?}: use letters, digits, '_', '-' and '.'",
+                    tag
+                )));
+            }
+        }
+
+        // Validate entry/roll times
+        for time in [&self.strategy.entry_time, &self.strategy.roll_time] {
+            if let Err(e) = crate::calendar::TimeOfDay::parse(time) {
+                return Err(ConfigError::Validation(e.to_string()));
+            }
+        }
+
+        self.strike_config.ladder(self.strike_config.tick_size)?;
+        for strategy in std::iter::once(&self.strategy).chain(&self.short_leg).chain(&self.long_leg) {
+            let selection = &strategy.strike_selection;
+            if crate::engine::StrikeSelection::from_config(selection, strategy.strike_offset).is_none() {
+                return Err(ConfigError::Validation(format!(
+                    "Unknown strike_selection: {} (expected ATM, OTM or delta_N, delta_put_N, delta_call_N)",
+                    selection
+                )));
+            }
+        }
+        if crate::engine::MissedEntry::from_config(&self.strategy.missed_entry).is_none() {
+            return Err(ConfigError::Validation(format!(
+                "Unknown strategy.missed_entry: {} (expected next_bar, skip_day or open)",
+                self.strategy.missed_entry
+            )));
+        }
+        if crate::engine::RollDestination::from_config(&self.strike_config.roll_type).is_none() {
+            return Err(ConfigError::Validation(format!(
+                "Unknown strike_config.roll_type: {} (expected recenter, same_strikes or delta)",
+                self.strike_config.roll_type
+            )));
+        }
+
+        if crate::calendar::DteUnit::from_config(&self.simulation.dte_unit).is_none() {
+            return Err(ConfigError::Validation(
+                format!("Unknown dte_unit: {} (expected trading or calendar)", self.simulation.dte_unit)
+            ));
+        }
+
+        // Validate price path RNG
+        if crate::prices::RngKind::from_config(&self.simulation.rng).is_none() {
+            return Err(ConfigError::Validation(
+                format!("Unknown rng: {} (expected std or xoshiro)", self.simulation.rng)
+            ));
+        }
+
+        // Validate reporting options
+        let valid_breakdowns = ["week", "month", "none"];
+        if !valid_breakdowns.contains(&self.reporting.breakdown.as_str()) {
+            return Err(ConfigError::Validation(
+                format!("Unknown reporting breakdown: {}", self.reporting.breakdown)
+            ));
+        }
+
+        for (name, rate) in [("base_rate", self.margin.base_rate), ("minimum_rate", self.margin.minimum_rate)] {
+            if !(rate.is_finite() && rate >= 0.0) {
+                return Err(ConfigError::Validation(format!("margin.{} must be non-negative, got {}", name, rate)));
+            }
+        }
+
+        // Validate data quality policies
+        let quality = &self.data_quality;
+        if crate::data::BarPolicy::from_config(&quality.bad_bars).is_none() {
+            return Err(ConfigError::Validation(
+                format!("Unknown data_quality.bad_bars: {} (expected error, drop or fill_forward)", quality.bad_bars)
+            ));
+        }
+        if !["keep", "error", "drop"].contains(&quality.off_session.as_str()) {
+            return Err(ConfigError::Validation(
+                format!("Unknown data_quality.off_session: {} (expected keep, error or drop)", quality.off_session)
+            ));
+        }
+        if crate::data::GapPolicy::from_config(&quality.gaps).is_none() {
+            return Err(ConfigError::Validation(
+                format!("Unknown data_quality.gaps: {} (expected keep, fill_forward or error)", quality.gaps)
+            ));
+        }
+
+        if let Some(market_data) = &self.market_data {
+            market_data.validate()?;
+        }
+
+        if let Some(vol_index) = &self.vol_index {
+            vol_index.validate()?;
+        }
+
+        if let Some(realized_vol) = &self.realized_vol {
+            realized_vol.validate()?;
+        }
+
+        if let Some(splits) = &self.splits {
+            splits.validate()?;
+        }
+
+        if let Some(limits) = &self.risk_limits {
+            limits.validate()?;
+        }
+
+        if let Some(kill_switch) = &self.kill_switch {
+            if !(kill_switch.max_drawdown.is_finite() && kill_switch.max_drawdown > 0.0) {
+                return Err(ConfigError::Validation(format!(
+                    "kill_switch.max_drawdown must be positive, got {}",
+                    kill_switch.max_drawdown
+                )));
+            }
+        }
+
+        if let Some(portfolio) = &self.portfolio {
+            let n = portfolio.products.len();
+            if n == 0 {
+                return Err(ConfigError::Validation("portfolio.products must not be empty".to_string()));
+            }
+            if let Some(matrix) = &portfolio.correlation {
+                let unit_diagonal = (0..n).all(|i| matrix.get(i).and_then(|row| row.get(i)) == Some(&1.0));
+                if matrix.len() != n || !unit_diagonal || crate::prices::cholesky(matrix).is_none() {
+                    return Err(ConfigError::Validation(format!(
+                        "portfolio.correlation must be a {0}x{0} positive definite correlation matrix",
+                        n
+                    )));
+                }
+            }
+        }
+
+        if let Some(allocation) = &self.allocation {
+            allocation.validate()?;
+        }
+
+        if let Some(daily_loss) = &self.daily_loss {
+            if !(daily_loss.max_loss.is_finite() && daily_loss.max_loss > 0.0) {
+                return Err(ConfigError::Validation(format!(
+                    "daily_loss.max_loss must be positive, got {}",
+                    daily_loss.max_loss
+                )));
+            }
+        }
+
+        if let Some(pin_risk) = &self.pin_risk {
+            if !(pin_risk.band.is_finite() && pin_risk.band > 0.0) {
+                return Err(ConfigError::Validation(format!("pin_risk.band must be positive, got {}", pin_risk.band)));
+            }
+            if crate::risk::PinMode::from_config(&pin_risk.mode).is_none() {
+                return Err(ConfigError::Validation(format!(
+                    "pin_risk.mode must be worst_case or random, got {}",
+                    pin_risk.mode
+                )));
+            }
+        }
+
+        if crate::pricing::PricingModel::from_config(&self.simulation.pricing_model, 0.0).is_none() {
+            return Err(ConfigError::Validation(format!(
+                "Unknown simulation.pricing_model: {} (expected black76 or black_scholes)",
+                self.simulation.pricing_model
+            )));
+        }
+        if !(self.simulation.dividend_yield.is_finite() && self.simulation.dividend_yield.abs() < 1.0) {
+            return Err(ConfigError::Validation(format!(
+                "simulation.dividend_yield must be a fraction, got {}",
+                self.simulation.dividend_yield
+            )));
+        }
+
+        if let Some(product) = &self.product {
+            if let Err(e) = crate::calendar::TimeOfDay::parse(&product.trading_hours.option_expiry) {
+                return Err(ConfigError::Validation(format!("product.trading_hours.option_expiry: {}", e)));
+            }
+            if !registry::is_currency_code(&product.currency) {
+                return Err(ConfigError::Validation(format!(
+                    "product.currency must be a three-letter code, got {}",
+                    product.currency
+                )));
+            }
+        }
+        if let Some(fx) = &self.fx {
+            crate::data::FxRates::from_config(fx).map_err(ConfigError::Validation)?;
+        }
+        self.vol_surface()?;
+
+        if let Some(holidays) = &self.holidays {
+            if crate::calendar::ExpirationAdjustment::from_config(&holidays.expiration_adjustment).is_none() {
+                return Err(ConfigError::Validation(format!(
+                    "holidays.expiration_adjustment must be following or preceding, got {}",
+                    holidays.expiration_adjustment
+                )));
+            }
+        }
+
+        if let Some(sweep) = &self.sweep {
+            for parameter in &sweep.parameters {
+                parameter.values().map_err(ConfigError::Validation)?;
+            }
+            if sweep.seeds == 0 {
+                return Err(ConfigError::Validation("sweep.seeds must be at least 1".to_string()));
+            }
+            if let Some(objective) = &sweep.objective {
+                objective.validate()?;
+            }
+            if let Some(optimizer) = &sweep.optimizer {
+                optimizer.validate()?;
+            }
+        }
+
         Ok(())
     }
 
+    /// Stable hash of everything that shapes a run except `simulation.seed`
+    /// (the `sweep` study settings are left out too), so equal fingerprints
+    /// mean the same strategy on the same market
+    pub fn fingerprint(&self) -> Result<String, ConfigError> {
+        let mut config = self.clone();
+        config.simulation.seed = 0;
+        config.sweep = None;
+        Ok(crate::engine::fnv_hex(serde_yaml::to_string(&config)?.as_bytes()))
+    }
+
     /// Save configuration to a YAML file
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
         let yaml = serde_yaml::to_string(self)?;
@@ -262,6 +1451,93 @@ impl Config {
     }
 }
 
+impl MarketDataConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(msg));
+        if !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return invalid(format!("market_data.url must be an http(s) URL, got {}", self.url));
+        }
+        if self.symbol.trim().is_empty() {
+            return invalid("market_data.symbol must not be empty".to_string());
+        }
+        let start = crate::data::Date::parse(&self.start);
+        let end = crate::data::Date::parse(&self.end);
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => {}
+            (Some(_), Some(_)) => return invalid("market_data.start must not be after market_data.end".to_string()),
+            _ => return invalid(format!("market_data dates must be YYYY-MM-DD, got {} and {}", self.start, self.end)),
+        }
+        if !["daily", "intraday"].contains(&self.bars.as_str()) {
+            return invalid(format!("Unknown market_data.bars: {} (expected daily or intraday)", self.bars));
+        }
+        if !["csv", "polygon"].contains(&self.format.as_str()) {
+            return invalid(format!("Unknown market_data.format: {} (expected csv or polygon)", self.format));
+        }
+        if !["bearer", "basic", "none"].contains(&self.auth.as_str()) {
+            return invalid(format!("Unknown market_data.auth: {} (expected bearer, basic or none)", self.auth));
+        }
+        Ok(())
+    }
+}
+
+impl VolIndexConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(msg));
+        for level in [self.min_entry, self.max_entry].into_iter().flatten() {
+            if !(level.is_finite() && level > 0.0) {
+                return invalid(format!("vol_index entry levels must be positive, got {}", level));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_entry, self.max_entry) {
+            if min > max {
+                return invalid("vol_index.min_entry must not be above vol_index.max_entry".to_string());
+            }
+        }
+        if !["config", "index"].contains(&self.implied_vol.as_str()) {
+            return invalid(format!("Unknown vol_index.implied_vol: {} (expected config or index)", self.implied_vol));
+        }
+        if !(self.iv_scale.is_finite() && self.iv_scale > 0.0) {
+            return invalid(format!("vol_index.iv_scale must be positive, got {}", self.iv_scale));
+        }
+        Ok(())
+    }
+}
+
+impl RealizedVolConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(msg));
+        if crate::prices::VolEstimator::from_config(&self.estimator, self.lambda).is_none() {
+            return invalid(format!(
+                "Unknown realized_vol.estimator: {} (expected close_to_close, parkinson or ewma)",
+                self.estimator
+            ));
+        }
+        if self.window_days < 2 {
+            return invalid(format!("realized_vol.window_days must be at least 2, got {}", self.window_days));
+        }
+        if !(self.lambda > 0.0 && self.lambda < 1.0) {
+            return invalid(format!("realized_vol.lambda must be between 0 and 1, got {}", self.lambda));
+        }
+        for level in [self.min_entry, self.max_entry, self.target_vol].into_iter().flatten() {
+            if !(level.is_finite() && level > 0.0) {
+                return invalid(format!("realized_vol levels must be positive, got {}", level));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_entry, self.max_entry) {
+            if min > max {
+                return invalid("realized_vol.min_entry must not be above realized_vol.max_entry".to_string());
+            }
+        }
+        if !["config", "realized"].contains(&self.implied_vol.as_str()) {
+            return invalid(format!(
+                "Unknown realized_vol.implied_vol: {} (expected config or realized)",
+                self.implied_vol
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Configuration errors
 #[derive(Debug)]
 pub enum ConfigError {
@@ -319,6 +1595,14 @@ fn default_roll_time() -> String {
     "14:00".to_string()
 }
 
+fn default_pricing_model() -> String {
+    "black76".to_string()
+}
+
+fn default_missed_entry() -> String {
+    "next_bar".to_string()
+}
+
 fn default_strike_selection() -> String {
     "ATM".to_string()
 }
@@ -335,10 +1619,20 @@ fn default_side() -> String {
     "short".to_string()
 }
 
+fn default_quantity() -> f64 {
+    1.0
+}
+
+/// A trade tag: non-empty letters, digits, '_', '-' and '.', so it survives CSV columns
+pub fn is_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
 fn default_strike_config() -> StrikeConfig {
     StrikeConfig {
         tick_size: 0.25,
         roll_type: "recenter".to_string(),
+        bands: Vec::new(),
     }
 }
 
@@ -350,17 +1644,240 @@ fn default_roll_type() -> String {
     "recenter".to_string()
 }
 
+fn default_reporting_config() -> ReportingConfig {
+    ReportingConfig {
+        breakdown: default_breakdown(),
+    }
+}
+
+fn default_breakdown() -> String {
+    "month".to_string()
+}
+
+fn default_margin_config() -> MarginConfig {
+    MarginConfig {
+        base_rate: default_margin_base_rate(),
+        minimum_rate: default_margin_minimum_rate(),
+    }
+}
+
+fn default_margin_base_rate() -> f64 {
+    0.20
+}
+
+fn default_margin_minimum_rate() -> f64 {
+    0.10
+}
+
+fn default_data_quality_config() -> DataQualityConfig {
+    DataQualityConfig {
+        bad_bars: default_bad_bars(),
+        off_session: default_off_session(),
+        gaps: default_gaps(),
+    }
+}
+
+fn default_bad_bars() -> String {
+    "error".to_string()
+}
+
+fn default_off_session() -> String {
+    "keep".to_string()
+}
+
+fn default_gaps() -> String {
+    "keep".to_string()
+}
+
+/// Replace an omitted or `random` `simulation.seed` with one drawn from
+/// entropy; 32 bits so it is easy to copy back into a config
+fn draw_random_seed(value: &mut serde_yaml::Value) -> bool {
+    let Some(simulation) = value.get_mut("simulation").and_then(|s| s.as_mapping_mut()) else {
+        return false;
+    };
+    let key = serde_yaml::Value::from("seed");
+    if simulation.get(&key).is_some_and(|seed| seed.as_str() != Some("random")) {
+        return false;
+    }
+    simulation.insert(key, serde_yaml::Value::from(u64::from(rand::random::<u32>())));
+    true
+}
+
+fn default_resolution() -> String {
+    "intraday".to_string()
+}
+
 fn default_intraday_resolution() -> u32 {
     10 // Default to 10-minute bars for intraday simulation
 }
 
+fn default_dte_unit() -> String {
+    "trading".to_string()
+}
+
 fn default_calendar_type() -> String {
     "cl_futures".to_string() // Default to /CL 23/5 calendar
 }
 
+fn default_rng() -> String {
+    "std".to_string()
+}
+
+fn default_market_data_bars() -> String {
+    "daily".to_string()
+}
+
+fn default_market_data_format() -> String {
+    "csv".to_string()
+}
+
+fn default_market_data_auth() -> String {
+    "bearer".to_string()
+}
+
+fn default_market_data_api_key_env() -> String {
+    "MARKET_DATA_API_KEY".to_string()
+}
+
+fn default_market_data_cache_dir() -> String {
+    ".cache/market_data".to_string()
+}
+
+fn default_sweep_seeds() -> u64 {
+    1
+}
+
+fn default_objective_metric() -> String {
+    "mean_pnl".to_string()
+}
+
+fn default_objective_cvar_level() -> f64 {
+    0.05
+}
+
+fn default_objective_drawdown_penalty() -> f64 {
+    0.001
+}
+
+fn default_objective_aggregate() -> String {
+    "pooled".to_string()
+}
+
+fn default_optimizer_budget() -> usize {
+    500
+}
+
+fn default_optimizer_min_seeds() -> u64 {
+    2
+}
+
+fn default_optimizer_eta() -> usize {
+    3
+}
+
+fn default_allocation_method() -> String {
+    "fixed".to_string()
+}
+
+fn default_allocation_rebalance_days() -> usize {
+    5
+}
+
+fn default_allocation_lookback_days() -> usize {
+    20
+}
+
+fn default_pin_risk_mode() -> String {
+    "worst_case".to_string()
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+fn default_expiration_adjustment() -> String {
+    "following".to_string()
+}
+
+fn default_risk_limits_action() -> String {
+    "reject".to_string()
+}
+
+fn default_vol_index_implied_vol() -> String {
+    "config".to_string()
+}
+
+fn default_vol_index_iv_scale() -> f64 {
+    1.0
+}
+
+fn default_realized_vol_estimator() -> String {
+    "close_to_close".to_string()
+}
+
+fn default_realized_vol_window_days() -> usize {
+    20
+}
+
+fn default_realized_vol_lambda() -> f64 {
+    0.94
+}
+
+fn default_realized_vol_implied_vol() -> String {
+    "config".to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::events::Side;
+
+    #[test]
+    fn test_side_ignores_case() {
+        let mut strategy = Config::default_1dte_straddle().strategy;
+        assert_eq!(strategy.side(), Side::Short);
+        for spelling in ["long", "Long", " LONG "] {
+            strategy.side = spelling.to_string();
+            assert_eq!(strategy.side(), Side::Long);
+        }
+    }
+
+    #[test]
+    fn test_sweep_parameter_values() {
+        let range = |start, end, step| SweepParameterConfig {
+            path: "strategy.entry_dte".to_string(),
+            values: None,
+            start: Some(start),
+            end: Some(end),
+            step: Some(step),
+        };
+        assert_eq!(range(0.05, 0.075, 0.0125).values().unwrap(), vec![0.05, 0.0625, 0.075]);
+        assert_eq!(range(1.0, 1.0, 1.0).values().unwrap(), vec![1.0]);
+        assert!(range(2.0, 1.0, 1.0).values().is_err());
+        assert!(range(0.0, 1.0, 0.0).values().is_err());
+        // Finite, but far more values than a usize count could hold
+        assert!(range(0.0, 1e20, 1.0).values().unwrap_err().contains("too many"));
+        assert!(range(0.0, SweepParameterConfig::MAX_VALUES as f64, 1.0).values().is_err());
+        assert_eq!(range(1.0, SweepParameterConfig::MAX_VALUES as f64, 1.0).values().unwrap().len(), 10_000);
+
+        let listed = SweepParameterConfig { values: Some(vec![0.0; 10_001]), ..range(0.0, 1.0, 1.0) };
+        assert!(listed.values().is_err());
+    }
+
+    #[test]
+    fn test_with_override() {
+        let config = Config::default_1dte_straddle();
+        let changed = config.with_override("simulation.volatility", 0.45).unwrap();
+        assert_eq!(changed.simulation.volatility, 0.45);
+        assert_eq!(config.with_override("strategy.entry_dte", 3.0).unwrap().strategy.entry_dte, 3);
+        assert_eq!(config.with_override("strategy.roll_triggers.0.value", 13.0).unwrap().strategy.roll_triggers[0].value, 13.0);
+
+        assert!(matches!(config.with_override("simulation.volatilty", 0.4), Err(ConfigError::Validation(_))));
+        assert!(config.with_override("strategy.side", 1.0).is_err());
+        assert!(config.with_override("strategy.entry_dte", 1.5).is_err());
+        // Overrides are revalidated
+        assert!(config.with_override("simulation.volatility", -0.1).is_err());
+    }
 
     #[test]
     fn test_default_config() {
@@ -374,6 +1891,63 @@ mod tests {
         let mut config = Config::default_1dte_straddle();
         config.simulation.volatility = -0.1;
         assert!(config.validate().is_err());
+
+        let mut config = Config::default_1dte_straddle();
+        config.data_quality.off_session = "fill_forward".to_string();
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.intraday_resolution_minutes = 0;
+        assert!(config.validate().is_err());
+        config.simulation.intraday_resolution_minutes = 10;
+        config.simulation.resolution = "daily".to_string();
+        assert!(config.validate().is_err());
+        config.simulation.resolution = "intraday".to_string();
+        config.simulation.dte_unit = "weeks".to_string();
+        assert!(config.validate().is_err());
+        config.simulation.dte_unit = "trading".to_string();
+        config.holidays = Some(HolidaysConfig { days: vec![2], expiration_adjustment: "modified".to_string() });
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default_1dte_straddle();
+        config.market_data = Some(MarketDataConfig {
+            url: "https://api.example.com/{symbol}".to_string(),
+            symbol: "CL".to_string(),
+            start: "2024-03-04".to_string(),
+            end: "2024-03-08".to_string(),
+            bars: default_market_data_bars(),
+            format: default_market_data_format(),
+            auth: default_market_data_auth(),
+            api_key_env: default_market_data_api_key_env(),
+            utc_offset_minutes: 0,
+            cache_dir: default_market_data_cache_dir(),
+        });
+        assert!(config.validate().is_ok());
+        config.market_data.as_mut().unwrap().end = "2024-03-01".to_string();
+        assert!(config.validate().is_err());
+
+        let yaml = "simulation: {days: 5, initial_price: 75.0, volatility: 0.3, seed: 1}\n\
+                    strategy: {strategy_type: straddle, entry_dte: 1}\n\
+                    vol_index: {min_entry: 35.0}\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        let vol_index = config.vol_index.as_ref().unwrap();
+        assert!(vol_index.allows_entry(35.0) && !vol_index.allows_entry(34.9));
+        assert_eq!(vol_index.implied_vol, "config");
+        assert!(Config::from_yaml_str(&yaml.replace("min_entry: 35.0", "implied_vol: ovx")).is_err());
+    }
+
+    #[test]
+    fn test_margin_validation() {
+        let mut config = Config::default_1dte_straddle();
+        config.margin.minimum_rate = 0.0;
+        assert!(config.validate().is_ok());
+        config.margin.base_rate = -0.2;
+        assert!(config.validate().is_err());
+        config.margin.base_rate = 0.2;
+        config.margin.minimum_rate = f64::NAN;
+        assert!(config.validate().is_err());
+        config.margin.minimum_rate = f64::INFINITY;
+        assert!(config.validate().is_err());
     }
 
     #[test]
@@ -383,4 +1957,22 @@ mod tests {
         let parsed: Config = serde_yaml::from_str(&yaml).unwrap();
         assert_eq!(parsed.strategy.entry_dte, config.strategy.entry_dte);
     }
+
+    #[test]
+    fn test_random_seed_is_drawn_and_kept() {
+        let yaml = "simulation: {days: 5, initial_price: 75.0, volatility: 0.3, seed: random}\n\
+                    strategy: {strategy_type: straddle, entry_dte: 1}\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        assert!(config.simulation.seed_generated);
+        let omitted = Config::from_yaml_str(&yaml.replace(", seed: random", "")).unwrap();
+        assert!(omitted.simulation.seed_generated);
+        let fixed = Config::from_yaml_str(&yaml.replace("random", "7")).unwrap();
+        assert!(!fixed.simulation.seed_generated);
+        assert_eq!(fixed.simulation.seed, 7);
+
+        // Written back out, the drawn seed reproduces the run
+        let saved = Config::from_yaml_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(saved.simulation.seed, config.simulation.seed);
+        assert!(!saved.simulation.seed_generated);
+    }
 }
@@ -0,0 +1,177 @@
+//! Contract Registry
+//!
+//! Contract specs by symbol, read from a YAML catalog (`config/contracts.yaml`
+//! is built in). A config that names a `symbol` takes its product, contract
+//! multiplier, strike increment and calendar from the registry when it is
+//! loaded, so pricing and reporting see the same numbers they would from a
+//! hand-written config, and a new product only needs a catalog entry.
+
+use super::{ConfigError, StrikeBandConfig, TradingHoursConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// The catalog shipped with the simulator
+const BUILTIN_CATALOG: &str = include_str!("../../config/contracts.yaml");
+
+/// Specs of one contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ContractSpec {
+    #[serde(default)]
+    pub description: String,
+    /// Units of underlying per contract (1000 barrels for /CL)
+    pub multiplier: f64,
+    /// Minimum price increment of the underlying
+    pub tick_size: f64,
+    /// Spacing of listed option strikes near the money
+    pub strike_increment: f64,
+    /// Wider strike spacing further from the money
+    #[serde(default)]
+    pub strike_bands: Vec<StrikeBandConfig>,
+    /// Trading calendar: "standard" or "cl_futures"
+    pub calendar_type: String,
+    /// Option model: "black76" for options on futures, "black_scholes" for a spot index
+    #[serde(default = "super::default_pricing_model")]
+    pub pricing_model: String,
+    /// Session and option expiry times
+    pub trading_hours: TradingHoursConfig,
+    /// Currency prices and P&L are quoted in (ISO code, e.g. "USD")
+    #[serde(default = "super::default_currency")]
+    pub currency: String,
+}
+
+/// Contract specs by symbol
+#[derive(Debug, Clone, Default)]
+pub struct ContractRegistry {
+    /// Keyed by normalized symbol; values keep the catalog's spelling
+    contracts: BTreeMap<String, (String, ContractSpec)>,
+}
+
+impl ContractRegistry {
+    /// The built-in catalog
+    pub fn builtin() -> &'static ContractRegistry {
+        static BUILTIN: OnceLock<ContractRegistry> = OnceLock::new();
+        BUILTIN.get_or_init(|| Self::from_yaml_str(BUILTIN_CATALOG).expect("built-in contract catalog is valid"))
+    }
+
+    /// Load a catalog file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        Self::from_yaml_str(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse and validate a catalog: a map from symbol to `ContractSpec`
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, ConfigError> {
+        let specs: BTreeMap<String, ContractSpec> = serde_yaml::from_str(yaml)?;
+        let mut registry = Self::default();
+        for (symbol, spec) in specs {
+            spec.validate(&symbol)?;
+            if registry.contracts.insert(normalize(&symbol), (symbol.clone(), spec)).is_some() {
+                return Err(ConfigError::Validation(format!("contract {} is listed twice", symbol)));
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Add the contracts of `other`, replacing any with the same symbol
+    pub fn extend(&mut self, other: ContractRegistry) {
+        self.contracts.extend(other.contracts);
+    }
+
+    /// Catalog spelling and specs of `symbol` ("cl", "/CL" and "CL" all match)
+    pub fn get(&self, symbol: &str) -> Option<(&str, &ContractSpec)> {
+        self.contracts.get(&normalize(symbol)).map(|(name, spec)| (name.as_str(), spec))
+    }
+
+    /// Symbols in the catalog, as spelled there
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.contracts.values().map(|(name, _)| name.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.contracts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty()
+    }
+}
+
+impl ContractSpec {
+    fn validate(&self, symbol: &str) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Validation(format!("contract {}: {}", symbol, msg)));
+        let sizes = [
+            ("multiplier", self.multiplier),
+            ("tick_size", self.tick_size),
+            ("strike_increment", self.strike_increment),
+        ];
+        for (name, value) in sizes {
+            if !(value.is_finite() && value > 0.0) {
+                return invalid(format!("{} must be positive, got {}", name, value));
+            }
+        }
+        let bands = self.strike_bands.iter().map(|band| (band.beyond, band.increment)).collect();
+        if let Err(msg) = crate::pricing::StrikeLadder::new(self.strike_increment, self.strike_increment, bands) {
+            return invalid(msg);
+        }
+        if !["standard", "cl_futures"].contains(&self.calendar_type.as_str()) {
+            return invalid(format!("unknown calendar_type {} (expected standard or cl_futures)", self.calendar_type));
+        }
+        if crate::pricing::PricingModel::from_config(&self.pricing_model, 0.0).is_none() {
+            return invalid(format!("unknown pricing_model {} (expected black76 or black_scholes)", self.pricing_model));
+        }
+        if !is_currency_code(&self.currency) {
+            return invalid(format!("currency must be a three-letter code, got {}", self.currency));
+        }
+        let hours = &self.trading_hours;
+        for time in [&hours.open, &hours.close, &hours.option_expiry] {
+            if let Err(e) = crate::calendar::TimeOfDay::parse(time) {
+                return invalid(e.to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Three uppercase letters, like "USD" or "EUR"
+pub(crate) fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.bytes().all(|b| b.is_ascii_uppercase())
+}
+
+fn normalize(symbol: &str) -> String {
+    symbol.trim().trim_start_matches('/').to_ascii_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_symbols_resolve_from_the_catalog() {
+        let registry = ContractRegistry::builtin();
+        assert!(registry.symbols().any(|s| s == "SPX"));
+        let (name, spec) = registry.get("es").unwrap();
+        assert_eq!((name, spec.multiplier, spec.strike_increment), ("/ES", 50.0, 5.0));
+
+        let yaml = "symbol: es\n\
+                    simulation: {days: 5, initial_price: 5000.0, volatility: 0.15, seed: 1}\n\
+                    strategy: {strategy_type: straddle, entry_dte: 1}\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        assert_eq!(config.simulation.contract_multiplier, 50.0);
+        assert_eq!(config.strike_config.tick_size, 5.0);
+        assert_eq!(config.product.as_ref().map(|p| p.symbol.as_str()), Some("/ES"));
+
+        // A new product is only a catalog entry away
+        let extra = "/ZW:\n  multiplier: 50.0\n  tick_size: 0.25\n  strike_increment: 5.0\n\
+                     \x20 calendar_type: standard\n\
+                     \x20 trading_hours: {open: \"08:30\", close: \"13:20\", option_expiry: \"13:20\"}\n";
+        let mut registry = registry.clone();
+        registry.extend(ContractRegistry::from_yaml_str(extra).unwrap());
+        let config = Config::from_yaml_str_with(&yaml.replace("symbol: es", "symbol: ZW"), &registry).unwrap();
+        assert_eq!(config.simulation.calendar_type, "standard");
+        assert!(Config::from_yaml_str(&yaml.replace("symbol: es", "symbol: ZW")).is_err());
+        assert!(ContractRegistry::from_yaml_str(&extra.replace("50.0", "-1")).is_err());
+    }
+}
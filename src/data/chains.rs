@@ -0,0 +1,289 @@
+//! Option Chain Snapshots from CSV
+//!
+//! End-of-day chains: one row per quoted option with the snapshot date,
+//! expiry date, strike, type (`P`/`C` or `put`/`call`), bid, ask and an
+//! optional implied volatility (as a fraction, 0.35 = 35%). As with OHLC
+//! files the header is optional; with one, columns are found by name
+//! (`date`, `expiry`, `strike`, `type`, `bid`, `ask`, `iv`) in any order.
+//!
+//! The engine prices a leg at the quote's mid whenever the chain for that
+//! day has its exact expiry and strike, and falls back to Black-76
+//! otherwise. A snapshot only applies to bars of its own date at or after
+//! `ChainOptions::snapshot_time`, so intraday runs never see quotes from
+//! later in the day. Greeks of quoted legs use the quote's IV when given.
+
+use super::{Date, DayMapping};
+use crate::calendar::intraday::Timestamp;
+use crate::calendar::TimeOfDay;
+use crate::error::SimError;
+use crate::pricing::{Black76, Valuation};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// One quoted option
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OptionQuote {
+    pub strike: f64,
+    pub is_call: bool,
+    pub bid: f64,
+    pub ask: f64,
+    pub implied_vol: Option<f64>,
+}
+
+impl OptionQuote {
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+
+    /// Mid price, with Greeks at the quote's IV (or `fallback_vol` without one)
+    pub fn valuation(
+        &self,
+        futures_price: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        fallback_vol: f64,
+    ) -> Valuation {
+        let vol = self.implied_vol.unwrap_or(fallback_vol);
+        Valuation {
+            price: self.mid(),
+            greeks: Black76::greeks(futures_price, self.strike, time_to_expiry, risk_free_rate, vol, self.is_call),
+        }
+    }
+}
+
+/// Options for `parse_option_chains`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChainOptions {
+    /// When each day's snapshot becomes usable
+    pub snapshot_time: TimeOfDay,
+    /// Calendar days of the price data (e.g. `OhlcSeries::mapping`); defaults
+    /// to the week of the first snapshot
+    pub mapping: Option<DayMapping>,
+}
+
+impl Default for ChainOptions {
+    fn default() -> Self {
+        // Same as `OhlcOptions::daily_bar_time`, so daily bars see their day's chain
+        Self { snapshot_time: TimeOfDay::hm(16, 0), mapping: None }
+    }
+}
+
+/// Snapshot day, expiry day, strike in 1/10000ths, call
+type QuoteKey = (u32, u32, i64, bool);
+
+/// Quotes read from a file, by calendar day
+#[derive(Debug, Clone)]
+pub struct OptionChains {
+    quotes: HashMap<QuoteKey, OptionQuote>,
+    mapping: DayMapping,
+    snapshot_minute: u32,
+}
+
+impl OptionChains {
+    /// The quote for a leg, if the chain at `at` has one
+    pub fn quote(&self, at: &Timestamp, expiration_day: u32, strike: f64, is_call: bool) -> Option<&OptionQuote> {
+        if at.minute < self.snapshot_minute {
+            return None;
+        }
+        self.quotes.get(&(at.day, expiration_day, strike_key(strike), is_call))
+    }
+
+    /// Number of quotes
+    pub fn len(&self) -> usize {
+        self.quotes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.quotes.is_empty()
+    }
+
+    /// Real dates of the calendar days
+    pub fn mapping(&self) -> DayMapping {
+        self.mapping
+    }
+}
+
+/// Strikes from the file and from `StrikeLadder::nearest` may differ in the last bits
+fn strike_key(strike: f64) -> i64 {
+    (strike * 10_000.0).round() as i64
+}
+
+/// Read and parse an option chain CSV file
+pub fn read_option_chains(path: impl AsRef<Path>, options: &ChainOptions) -> Result<OptionChains, SimError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)?;
+    parse_option_chains(&text, options).map_err(|e| match e {
+        SimError::Data(reason) => SimError::Data(format!("{}: {}", path.display(), reason)),
+        other => other,
+    })
+}
+
+/// Column positions in a row
+#[derive(Debug, Clone, Copy)]
+struct Columns {
+    date: usize,
+    expiry: usize,
+    strike: usize,
+    kind: usize,
+    bid: usize,
+    ask: usize,
+    iv: Option<usize>,
+}
+
+impl Columns {
+    /// date, expiry, strike, type, bid, ask[, iv]
+    const POSITIONAL: Columns = Columns { date: 0, expiry: 1, strike: 2, kind: 3, bid: 4, ask: 5, iv: Some(6) };
+
+    fn from_header(fields: &[&str]) -> Result<Self, String> {
+        let names: Vec<String> = fields.iter().map(|f| f.trim().to_ascii_lowercase()).collect();
+        let find = |candidates: &[&str]| names.iter().position(|name| candidates.contains(&name.as_str()));
+        let require = |candidates: &[&str]| find(candidates).ok_or_else(|| format!("no {} column", candidates[0]));
+        Ok(Self {
+            date: require(&["date", "quote_date", "snapshot"])?,
+            expiry: require(&["expiry", "expiration", "expiration_date"])?,
+            strike: require(&["strike"])?,
+            kind: require(&["type", "option_type", "right", "cp"])?,
+            bid: require(&["bid"])?,
+            ask: require(&["ask"])?,
+            iv: find(&["iv", "implied_vol", "implied_volatility"]),
+        })
+    }
+}
+
+/// A row before its dates are mapped onto the calendar
+struct RawQuote {
+    line: usize,
+    date: Date,
+    expiry: Date,
+    quote: OptionQuote,
+}
+
+/// Parse option chain CSV text
+///
+/// Snapshots dated before day 0 of `ChainOptions::mapping` are skipped.
+pub fn parse_option_chains(text: &str, options: &ChainOptions) -> Result<OptionChains, SimError> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let Some((first_index, first_line)) = lines.next() else {
+        return Err(SimError::Data("no quotes".to_string()));
+    };
+    let first_fields: Vec<&str> = first_line.split(',').collect();
+    let (columns, first_row) = if Date::parse(first_fields[0].trim()).is_some() {
+        (Columns::POSITIONAL, Some((first_index, first_line)))
+    } else {
+        let columns = Columns::from_header(&first_fields).map_err(|e| data_error(first_index + 1, &e))?;
+        (columns, None)
+    };
+
+    let rows = first_row.into_iter().chain(lines);
+    let raw = rows.map(|(index, line)| parse_row(index + 1, line, &columns)).collect::<Result<Vec<_>, _>>()?;
+    let Some(first_date) = raw.iter().map(|quote| quote.date).min() else {
+        return Err(SimError::Data("no quotes".to_string()));
+    };
+
+    let mapping = options.mapping.unwrap_or_else(|| DayMapping::starting_week_of(first_date));
+    let mut quotes = HashMap::with_capacity(raw.len());
+    for row in raw {
+        let (Some(day), Some(expiry)) = (mapping.day(row.date), mapping.day(row.expiry)) else {
+            continue;
+        };
+        let key = (day, expiry, strike_key(row.quote.strike), row.quote.is_call);
+        if quotes.insert(key, row.quote).is_some() {
+            return Err(data_error(row.line, "repeats an earlier quote"));
+        }
+    }
+    Ok(OptionChains { quotes, mapping, snapshot_minute: u32::from(options.snapshot_time.minutes()) })
+}
+
+fn data_error(line: usize, reason: &str) -> SimError {
+    SimError::Data(format!("line {}: {}", line, reason))
+}
+
+fn parse_row(line: usize, text: &str, columns: &Columns) -> Result<RawQuote, SimError> {
+    let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+    let field = |index: usize| fields.get(index).copied().ok_or_else(|| data_error(line, "too few columns"));
+    let number = |index: usize, name: &str| -> Result<f64, SimError> {
+        let value: f64 = field(index)?.parse().map_err(|_| data_error(line, &format!("{} is not a number", name)))?;
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(data_error(line, &format!("{} is not finite", name)))
+        }
+    };
+    let date = |index: usize, name: &str| -> Result<Date, SimError> {
+        let text = field(index)?;
+        Date::parse(text).ok_or_else(|| data_error(line, &format!("bad {} {:?}", name, text)))
+    };
+
+    let snapshot = date(columns.date, "date")?;
+    let expiry = date(columns.expiry, "expiry")?;
+    let strike = number(columns.strike, "strike")?;
+    let is_call = match field(columns.kind)?.to_ascii_lowercase().as_str() {
+        "c" | "call" => true,
+        "p" | "put" => false,
+        other => return Err(data_error(line, &format!("type must be put or call, got {:?}", other))),
+    };
+    let bid = number(columns.bid, "bid")?;
+    let ask = number(columns.ask, "ask")?;
+    // IV is optional even when positional, as long as the row ends there
+    let implied_vol = match columns.iv {
+        Some(index) if index < fields.len() && !fields[index].is_empty() => Some(number(index, "iv")?),
+        _ => None,
+    };
+
+    if expiry < snapshot {
+        return Err(data_error(line, "expiry is before the snapshot date"));
+    }
+    if strike <= 0.0 {
+        return Err(data_error(line, "strike must be positive"));
+    }
+    if bid < 0.0 || ask < bid {
+        return Err(data_error(line, "bid must be non-negative and no more than ask"));
+    }
+    if implied_vol.is_some_and(|iv| iv <= 0.0) {
+        return Err(data_error(line, "iv must be positive"));
+    }
+    let quote = OptionQuote { strike, is_call, bid, ask, implied_vol };
+    Ok(RawQuote { line, date: snapshot, expiry, quote })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotes_by_day_expiry_and_strike() {
+        let csv = "Date,Expiry,Strike,Type,Bid,Ask,IV\n\
+                   2024-03-04,2024-03-05,80.0,P,0.60,0.64,0.36\n\
+                   2024-03-04,2024-03-05,80.0,C,0.58,0.62,\n\
+                   2024-02-23,2024-03-05,80.0,C,1.00,1.10,0.40\n";
+        let mapping = DayMapping::starting_week_of(Date::new(2024, 3, 4).unwrap());
+        let options = ChainOptions { mapping: Some(mapping), ..ChainOptions::default() };
+        let chains = parse_option_chains(csv, &options).unwrap();
+        // The February snapshot falls before day 0 of the price data
+        assert_eq!(chains.len(), 2);
+
+        let close = Timestamp::new(0, 16 * 60);
+        let put = chains.quote(&close, 1, 80.0, false).unwrap();
+        assert!((put.mid() - 0.62).abs() < 1e-12);
+        assert_eq!(put.implied_vol, Some(0.36));
+        assert_eq!(chains.quote(&close, 1, 80.0 + 1e-9, true).map(|q| q.implied_vol), Some(None));
+        // Not before the snapshot, nor for other strikes, expiries or days
+        assert!(chains.quote(&Timestamp::new(0, 9 * 60), 1, 80.0, false).is_none());
+        assert!(chains.quote(&close, 1, 80.5, false).is_none());
+        assert!(chains.quote(&close, 2, 80.0, false).is_none());
+        assert!(chains.quote(&Timestamp::new(1, 16 * 60), 1, 80.0, false).is_none());
+    }
+
+    #[test]
+    fn test_malformed_quotes_name_the_line() {
+        let error = |csv: &str| parse_option_chains(csv, &ChainOptions::default()).unwrap_err().to_string();
+        assert!(error("").contains("no quotes"));
+        assert!(error("2024-03-04,2024-03-05,80,X,0.6,0.7\n").contains("line 1: type must be put or call"));
+        assert!(error("2024-03-04,2024-03-05,80,P,0.7,0.6\n").contains("bid must be non-negative"));
+        assert!(error("2024-03-04,2024-03-01,80,P,0.6,0.7\n").contains("expiry is before"));
+        assert!(error("2024-03-04,2024-03-05,80,P,0.6\n").contains("too few columns"));
+        let repeated = "2024-03-04,2024-03-05,80,P,0.6,0.7\n2024-03-04,2024-03-05,80.0,put,0.6,0.7\n";
+        assert!(error(repeated).contains("line 2: repeats"));
+        assert!(error("day,expiry,strike,type,bid,ask\n").contains("no date column"));
+    }
+}
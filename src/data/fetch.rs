@@ -0,0 +1,268 @@
+//! Historical Bars over HTTP
+//!
+//! Fetches futures bars from the data API described by `config.market_data`
+//! and returns them as an `OhlcSeries`, so fetched data runs exactly like a
+//! CSV from disk. The API key is read from the environment variable named
+//! by `api_key_env` and never written to errors or the cache.
+//!
+//! Responses are either OHLC CSV (as `data::ohlc` reads it) or Polygon-style
+//! JSON aggregates (`results` of `t`/`o`/`h`/`l`/`c`/`v`, following
+//! `next_url` pages, which get the key query parameter of `url` added back).
+//! Either way the bars are validated, then cached as CSV
+//! under `cache_dir`, keyed by symbol, bar spacing and date range; later runs
+//! with the same range read the cache and make no request.
+
+use super::{parse_ohlc_csv, read_ohlc_csv, Date, OhlcOptions, OhlcSeries};
+use crate::config::MarketDataConfig;
+use crate::error::SimError;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Per-request timeout
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Most `next_url` pages followed for one fetch
+const MAX_PAGES: usize = 1_000;
+
+/// Fetch (or read from the cache) the bars for `config`
+pub fn fetch_ohlc(config: &MarketDataConfig, options: &OhlcOptions) -> Result<OhlcSeries, SimError> {
+    let cache_path = cache_path(config);
+    if cache_path.exists() {
+        return read_ohlc_csv(&cache_path, options);
+    }
+
+    let api_key = api_key(config)?;
+    let url = request_url(config, api_key.as_deref().unwrap_or_default());
+    let csv = match config.format.as_str() {
+        "polygon" => {
+            let mut csv = String::new();
+            let mut next = Some(url);
+            for _ in 0..MAX_PAGES {
+                let Some(url) = next.take() else { break };
+                let body = get(&url, config, api_key.as_deref())?;
+                next = polygon_to_csv(&body, config, &mut csv)?.map(|next| page_url(&next, config, api_key.as_deref()));
+            }
+            if next.is_some() {
+                return Err(SimError::Data(format!("more than {} pages of bars; fetch a shorter range", MAX_PAGES)));
+            }
+            csv
+        }
+        _ => get(&url, config, api_key.as_deref())?,
+    };
+
+    // Validate before caching, so a bad response is fetched again next time
+    let series = parse_ohlc_csv(&csv, options)?;
+    if let Some(dir) = cache_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let partial = cache_path.with_extension("partial");
+    std::fs::write(&partial, csv)?;
+    std::fs::rename(&partial, &cache_path)?;
+    Ok(series)
+}
+
+/// Where the bars for `config` are cached
+pub fn cache_path(config: &MarketDataConfig) -> PathBuf {
+    let symbol: String =
+        config.symbol.chars().map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '_' }).collect();
+    let name = format!("{}_{}_{}_{}.csv", symbol, config.bars, config.start, config.end);
+    PathBuf::from(&config.cache_dir).join(name)
+}
+
+/// The key from the environment, if the request needs one
+fn api_key(config: &MarketDataConfig) -> Result<Option<String>, SimError> {
+    if config.auth == "none" && !config.url.contains("{api_key}") {
+        return Ok(None);
+    }
+    match std::env::var(&config.api_key_env) {
+        Ok(key) if !key.trim().is_empty() => Ok(Some(key.trim().to_string())),
+        _ => Err(SimError::Data(format!("set {} to the market data API key", config.api_key_env))),
+    }
+}
+
+fn request_url(config: &MarketDataConfig, api_key: &str) -> String {
+    config
+        .url
+        .replace("{symbol}", &encode(&config.symbol))
+        .replace("{start}", &config.start)
+        .replace("{end}", &config.end)
+        .replace("{api_key}", &encode(api_key))
+}
+
+/// `next_url` with the key added as `url` passes it (`name={api_key}` in the
+/// query); APIs leave it off follow-up pages
+fn page_url(next: &str, config: &MarketDataConfig, api_key: Option<&str>) -> String {
+    let name = config
+        .url
+        .split_once('?')
+        .and_then(|(_, query)| query.split('&').find_map(|pair| pair.strip_suffix("={api_key}")));
+    let (Some(name), Some(key)) = (name, api_key) else {
+        return next.to_string();
+    };
+    let query = next.split_once('?').map_or("", |(_, query)| query);
+    if query.split('&').any(|pair| pair.split('=').next() == Some(name)) {
+        return next.to_string();
+    }
+    let separator = if next.contains('?') { '&' } else { '?' };
+    format!("{}{}{}={}", next, separator, name, encode(key))
+}
+
+/// Percent-encode everything but unreserved URL characters
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+/// GET `url` and return the body; errors never include the key
+fn get(url: &str, config: &MarketDataConfig, api_key: Option<&str>) -> Result<String, SimError> {
+    let redact = |text: &str| match api_key {
+        Some(key) => text.replace(&encode(key), "***").replace(key, "***"),
+        None => text.to_string(),
+    };
+    let mut request = ureq::get(url).timeout(TIMEOUT);
+    match (config.auth.as_str(), api_key) {
+        ("bearer", Some(key)) => request = request.set("Authorization", &format!("Bearer {}", key)),
+        ("basic", Some(key)) => {
+            request = request.set("Authorization", &format!("Basic {}", base64(&format!("{}:", key))))
+        }
+        _ => {}
+    }
+    match request.call() {
+        Ok(response) => response.into_string().map_err(SimError::from),
+        Err(ureq::Error::Status(status, response)) => {
+            let body = response.into_string().unwrap_or_default();
+            let detail: String = body.chars().take(200).collect();
+            Err(SimError::Data(redact(&format!("{} returned HTTP {}: {}", url, status, detail.trim()))))
+        }
+        Err(e) => Err(SimError::Data(redact(&format!("request to {} failed: {}", url, e)))),
+    }
+}
+
+fn base64(text: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in text.as_bytes().chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Append one page of Polygon aggregates to `csv`; returns the next page's URL
+fn polygon_to_csv(body: &str, config: &MarketDataConfig, csv: &mut String) -> Result<Option<String>, SimError> {
+    let page: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| SimError::Data(format!("response is not JSON: {}", e)))?;
+    if page["status"] == "ERROR" {
+        let message = page["error"].as_str().or(page["message"].as_str()).unwrap_or("unknown error");
+        return Err(SimError::Data(format!("market data API error: {}", message)));
+    }
+    if csv.is_empty() {
+        csv.push_str("datetime,open,high,low,close,volume\n");
+    }
+    let results = page["results"].as_array().map(Vec::as_slice).unwrap_or_default();
+    for (index, bar) in results.iter().enumerate() {
+        let field = |name: &str| {
+            bar[name].as_f64().ok_or_else(|| SimError::Data(format!("result {}: no numeric {:?}", index, name)))
+        };
+        let millis = field("t")? as i64;
+        let local = millis.div_euclid(1000) + i64::from(config.utc_offset_minutes) * 60;
+        if config.bars == "daily" {
+            // Daily bars start around local midnight, give or take daylight saving
+            let _ = write!(csv, "{}", Date::from_days_since_epoch((local + 43_200).div_euclid(86_400)));
+        } else {
+            let minutes = local.rem_euclid(86_400) / 60;
+            let date = Date::from_days_since_epoch(local.div_euclid(86_400));
+            let _ = write!(csv, "{} {:02}:{:02}", date, minutes / 60, minutes % 60);
+        }
+        let volume = bar["v"].as_f64().unwrap_or(0.0);
+        let _ = writeln!(csv, ",{},{},{},{},{}", field("o")?, field("h")?, field("l")?, field("c")?, volume);
+    }
+    Ok(page["next_url"].as_str().map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(format: &str, bars: &str) -> MarketDataConfig {
+        MarketDataConfig {
+            url: "https://api.example.com/bars/{symbol}/{start}/{end}?key={api_key}".to_string(),
+            symbol: "C:CL".to_string(),
+            start: "2024-03-04".to_string(),
+            end: "2024-03-08".to_string(),
+            bars: bars.to_string(),
+            format: format.to_string(),
+            auth: "none".to_string(),
+            api_key_env: "TSV2_TEST_MARKET_DATA_KEY".to_string(),
+            utc_offset_minutes: -300,
+            cache_dir: "cache".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_urls_cache_paths_and_auth() {
+        let config = config("polygon", "daily");
+        assert_eq!(request_url(&config, "a b"), "https://api.example.com/bars/C%3ACL/2024-03-04/2024-03-08?key=a%20b");
+        assert_eq!(cache_path(&config), PathBuf::from("cache").join("C_CL_daily_2024-03-04_2024-03-08.csv"));
+        assert_eq!(base64("key:"), "a2V5Og==");
+        assert_eq!(base64("ab"), "YWI=");
+        // The key is in the URL, so it is required even without an auth header
+        let missing = api_key(&config).unwrap_err().to_string();
+        assert!(missing.contains("set TSV2_TEST_MARKET_DATA_KEY"));
+    }
+
+    #[test]
+    fn test_next_pages_get_the_query_key() {
+        let config = config("polygon", "intraday");
+        let next = "https://api.example.com/bars/C%3ACL?cursor=abc";
+        assert_eq!(page_url(next, &config, Some("a b")), "https://api.example.com/bars/C%3ACL?cursor=abc&key=a%20b");
+        assert_eq!(page_url("https://api.example.com/p2", &config, Some("k")), "https://api.example.com/p2?key=k");
+        // Already there, or sent in a header instead
+        let keyed = "https://api.example.com/p2?key=k&cursor=abc";
+        assert_eq!(page_url(keyed, &config, Some("k")), keyed);
+        let header = MarketDataConfig { url: "https://api.example.com/bars".to_string(), ..config.clone() };
+        assert_eq!(page_url(next, &header, Some("k")), next);
+        assert_eq!(page_url(next, &config, None), next);
+    }
+
+    #[test]
+    fn test_polygon_aggregates_become_ohlc_csv() {
+        // 2024-03-04 14:00 and 14:10 UTC, i.e. 09:00 and 09:10 at UTC-5
+        let body = r#"{"status":"OK","results":[
+            {"t":1709560800000,"o":78.0,"h":78.4,"l":77.9,"c":78.2,"v":120},
+            {"t":1709561400000,"o":78.2,"h":78.5,"l":78.1,"c":78.3}
+        ],"next_url":"https://api.example.com/page2"}"#;
+        let mut csv = String::new();
+        let next = polygon_to_csv(body, &config("polygon", "intraday"), &mut csv).unwrap();
+        assert_eq!(next.as_deref(), Some("https://api.example.com/page2"));
+        assert_eq!(
+            csv,
+            "datetime,open,high,low,close,volume\n\
+             2024-03-04 09:00,78,78.4,77.9,78.2,120\n\
+             2024-03-04 09:10,78.2,78.5,78.1,78.3,0\n"
+        );
+        let series = parse_ohlc_csv(&csv, &OhlcOptions::default()).unwrap();
+        assert_eq!(series.bars.len(), 2);
+
+        let mut daily = String::new();
+        polygon_to_csv(body, &config("polygon", "daily"), &mut daily).unwrap();
+        assert!(daily.lines().nth(1).unwrap().starts_with("2024-03-04,"));
+
+        let error = polygon_to_csv(r#"{"status":"ERROR","error":"bad key"}"#, &config("polygon", "daily"), &mut csv);
+        assert!(error.unwrap_err().to_string().contains("bad key"));
+    }
+}
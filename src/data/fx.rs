@@ -0,0 +1,86 @@
+//! FX Rates
+//!
+//! Converts amounts quoted in a product's currency into the account currency
+//! of a portfolio report. Each currency has a constant rate or steps of
+//! `[day, rate]`, where a calendar day takes the latest step on or before it
+//! (and the constant rate before the first step).
+
+use crate::config::FxConfig;
+use std::collections::BTreeMap;
+
+/// Rates into one account currency
+#[derive(Debug, Clone, PartialEq)]
+pub struct FxRates {
+    account_currency: String,
+    /// Rate before the first step, and the steps by day, per currency
+    rates: BTreeMap<String, (f64, BTreeMap<u32, f64>)>,
+}
+
+impl FxRates {
+    /// No conversion: every amount is already in `account_currency`
+    pub fn new(account_currency: &str) -> Self {
+        Self { account_currency: account_currency.to_string(), rates: BTreeMap::new() }
+    }
+
+    /// Validate and index the rates of `config`
+    pub fn from_config(config: &FxConfig) -> Result<Self, String> {
+        let is_code = crate::config::registry::is_currency_code;
+        if !is_code(&config.account_currency) {
+            return Err(format!("fx.account_currency must be a three-letter code, got {}", config.account_currency));
+        }
+        let mut fx = Self::new(&config.account_currency);
+        for (currency, rate) in &config.rates {
+            if !is_code(currency) {
+                return Err(format!("fx.rates: {} is not a three-letter currency code", currency));
+            }
+            let mut rates = std::iter::once(rate.rate).chain(rate.series.iter().map(|&(_, rate)| rate));
+            if let Some(bad) = rates.find(|rate| !(rate.is_finite() && *rate > 0.0)) {
+                return Err(format!("fx.rates.{} must be positive, got {}", currency, bad));
+            }
+            fx.rates.insert(currency.clone(), (rate.rate, rate.series.iter().copied().collect()));
+        }
+        Ok(fx)
+    }
+
+    pub fn account_currency(&self) -> &str {
+        &self.account_currency
+    }
+
+    /// Value of one unit of `currency` on `day` in the account currency
+    /// (`None` for a currency without a rate)
+    pub fn rate(&self, currency: &str, day: u32) -> Option<f64> {
+        if currency == self.account_currency {
+            return Some(1.0);
+        }
+        let (rate, steps) = self.rates.get(currency)?;
+        Some(steps.range(..=day).next_back().map_or(*rate, |(_, &step)| step))
+    }
+}
+
+impl Default for FxRates {
+    fn default() -> Self {
+        Self::new("USD")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FxRateConfig;
+
+    #[test]
+    fn test_rates_step_by_day() {
+        let eur = FxRateConfig { rate: 1.08, series: vec![(10, 1.10), (20, 1.05)] };
+        let config = FxConfig { account_currency: "USD".to_string(), rates: [("EUR".to_string(), eur)].into() };
+        let fx = FxRates::from_config(&config).unwrap();
+        assert_eq!(fx.rate("USD", 3), Some(1.0));
+        assert_eq!(fx.rate("EUR", 3), Some(1.08));
+        assert_eq!(fx.rate("EUR", 15), Some(1.10));
+        assert_eq!(fx.rate("EUR", 40), Some(1.05));
+        assert_eq!(fx.rate("JPY", 3), None);
+
+        let mut bad = config.clone();
+        bad.rates.get_mut("EUR").unwrap().series.push((30, 0.0));
+        assert!(FxRates::from_config(&bad).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Implied Volatility Series from CSV
+//!
+//! Either one IV per date (`date,iv`) or a term structure per date
+//! (`date,dte,iv`, DTE in trading days like `strategy.entry_dte`). IVs are
+//! fractions (0.35 = 35%). A header is optional; with one, columns are found
+//! by name (`date`, `dte`, `iv`) in any order.
+//!
+//! The engine prices each bar at the IV of the latest date on or before it,
+//! so weekends and missing dates carry the last value forward. Term
+//! structures are interpolated linearly in DTE and held flat beyond the
+//! shortest and longest tenors. The series is a source of ATM vols for the
+//! engine's `VolSurface`; bars before the first date fall back to the rest
+//! of the surface.
+
+use super::{Date, DayMapping};
+use crate::error::SimError;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// IVs above this are almost certainly percentages
+const MAX_IV: f64 = 5.0;
+
+/// Options for `parse_implied_vols`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IvOptions {
+    /// Calendar days of the price data (e.g. `OhlcSeries::mapping`); defaults
+    /// to the week of the first date
+    pub mapping: Option<DayMapping>,
+}
+
+/// Implied volatility by calendar day
+#[derive(Debug, Clone)]
+pub struct ImpliedVolSeries {
+    /// (DTE, IV) pairs sorted by DTE; a flat series has one pair per day
+    days: BTreeMap<u32, Vec<(f64, f64)>>,
+    mapping: DayMapping,
+}
+
+impl ImpliedVolSeries {
+    /// IV for an option with `dte` trading days left, as of `day`
+    pub fn vol(&self, day: u32, dte: f64) -> Option<f64> {
+        let (_, curve) = self.days.range(..=day).next_back()?;
+        Some(crate::pricing::interpolate(curve, dte))
+    }
+
+    /// Number of dates
+    pub fn len(&self) -> usize {
+        self.days.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+
+    /// Real dates of the calendar days
+    pub fn mapping(&self) -> DayMapping {
+        self.mapping
+    }
+}
+
+impl crate::pricing::AtmVolSource for ImpliedVolSeries {
+    fn atm_vol(&self, day: u32, dte: f64) -> Option<f64> {
+        self.vol(day, dte)
+    }
+}
+
+/// Read and parse an implied volatility CSV file
+pub fn read_implied_vols(path: impl AsRef<Path>, options: &IvOptions) -> Result<ImpliedVolSeries, SimError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)?;
+    parse_implied_vols(&text, options).map_err(|e| match e {
+        SimError::Data(reason) => SimError::Data(format!("{}: {}", path.display(), reason)),
+        other => other,
+    })
+}
+
+/// Column positions in a row
+#[derive(Debug, Clone, Copy)]
+struct Columns {
+    date: usize,
+    dte: Option<usize>,
+    iv: usize,
+}
+
+impl Columns {
+    fn from_header(fields: &[&str]) -> Result<Self, String> {
+        let names: Vec<String> = fields.iter().map(|f| f.trim().to_ascii_lowercase()).collect();
+        let find = |candidates: &[&str]| names.iter().position(|name| candidates.contains(&name.as_str()));
+        let require = |candidates: &[&str]| find(candidates).ok_or_else(|| format!("no {} column", candidates[0]));
+        Ok(Self {
+            date: require(&["date"])?,
+            dte: find(&["dte", "days_to_expiry", "tenor"]),
+            iv: require(&["iv", "implied_vol", "implied_volatility"])?,
+        })
+    }
+
+    /// date,iv or date,dte,iv
+    fn positional(field_count: usize) -> Self {
+        if field_count >= 3 {
+            Self { date: 0, dte: Some(1), iv: 2 }
+        } else {
+            Self { date: 0, dte: None, iv: 1 }
+        }
+    }
+}
+
+/// Parse implied volatility CSV text
+///
+/// Dates before day 0 of `IvOptions::mapping` are skipped.
+pub fn parse_implied_vols(text: &str, options: &IvOptions) -> Result<ImpliedVolSeries, SimError> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let Some((first_index, first_line)) = lines.next() else {
+        return Err(SimError::Data("no volatilities".to_string()));
+    };
+    let first_fields: Vec<&str> = first_line.split(',').collect();
+    let (columns, first_row) = if Date::parse(first_fields[0].trim()).is_some() {
+        (Columns::positional(first_fields.len()), Some((first_index, first_line)))
+    } else {
+        let columns = Columns::from_header(&first_fields).map_err(|e| data_error(first_index + 1, &e))?;
+        (columns, None)
+    };
+
+    let rows = first_row.into_iter().chain(lines);
+    let raw = rows.map(|(index, line)| parse_row(index + 1, line, &columns)).collect::<Result<Vec<_>, _>>()?;
+    let Some(first_date) = raw.iter().map(|&(_, date, _, _)| date).min() else {
+        return Err(SimError::Data("no volatilities".to_string()));
+    };
+
+    let mapping = options.mapping.unwrap_or_else(|| DayMapping::starting_week_of(first_date));
+    let mut days: BTreeMap<u32, Vec<(f64, f64)>> = BTreeMap::new();
+    for (line, date, dte, iv) in raw {
+        let Some(day) = mapping.day(date) else {
+            continue;
+        };
+        let curve = days.entry(day).or_default();
+        if curve.iter().any(|&(tenor, _)| tenor == dte) {
+            return Err(data_error(line, &format!("repeats {}", date)));
+        }
+        curve.push((dte, iv));
+    }
+    for curve in days.values_mut() {
+        curve.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+    Ok(ImpliedVolSeries { days, mapping })
+}
+
+fn data_error(line: usize, reason: &str) -> SimError {
+    SimError::Data(format!("line {}: {}", line, reason))
+}
+
+/// Line, date, DTE (0 for a flat series) and IV of a row
+fn parse_row(line: usize, text: &str, columns: &Columns) -> Result<(usize, Date, f64, f64), SimError> {
+    let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+    let field = |index: usize| fields.get(index).copied().ok_or_else(|| data_error(line, "too few columns"));
+    let number = |index: usize, name: &str| -> Result<f64, SimError> {
+        let value: f64 = field(index)?.parse().map_err(|_| data_error(line, &format!("{} is not a number", name)))?;
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(data_error(line, &format!("{} is not finite", name)))
+        }
+    };
+
+    let date = Date::parse(field(columns.date)?)
+        .ok_or_else(|| data_error(line, &format!("bad date {:?}", fields[columns.date])))?;
+    let dte = match columns.dte {
+        Some(index) => number(index, "dte")?,
+        None => 0.0,
+    };
+    let iv = number(columns.iv, "iv")?;
+    if dte < 0.0 {
+        return Err(data_error(line, "dte is negative"));
+    }
+    if iv <= 0.0 {
+        return Err(data_error(line, "iv must be positive"));
+    }
+    if iv > MAX_IV {
+        return Err(data_error(line, "iv looks like a percentage; use fractions (0.35 = 35%)"));
+    }
+    Ok((line, date, dte, iv))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flat_series_carries_forward() {
+        // Friday, then the following Tuesday
+        let series = parse_implied_vols("2024-03-01,0.30\n2024-03-05,0.55\n", &IvOptions::default()).unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series.vol(3, 1.0), None);
+        assert_eq!(series.vol(4, 1.0), Some(0.30));
+        assert_eq!(series.vol(7, 20.0), Some(0.30));
+        assert_eq!(series.vol(8, 1.0), Some(0.55));
+        assert_eq!(series.vol(100, 1.0), Some(0.55));
+    }
+
+    #[test]
+    fn test_term_structure_interpolates_in_dte() {
+        let csv = "iv,date,dte\n0.50,2024-03-04,1\n0.40,2024-03-04,21\n0.30,2024-03-04,63\n";
+        let series = parse_implied_vols(csv, &IvOptions::default()).unwrap();
+        assert_eq!(series.vol(0, 1.0), Some(0.50));
+        assert!((series.vol(0, 11.0).unwrap() - 0.45).abs() < 1e-12);
+        assert!((series.vol(0, 42.0).unwrap() - 0.35).abs() < 1e-12);
+        // Flat beyond the ends
+        assert_eq!(series.vol(0, 0.25), Some(0.50));
+        assert_eq!(series.vol(0, 120.0), Some(0.30));
+    }
+
+    #[test]
+    fn test_malformed_rows_name_the_line() {
+        let error = |csv: &str| parse_implied_vols(csv, &IvOptions::default()).unwrap_err().to_string();
+        assert!(error("").contains("no volatilities"));
+        assert!(error("2024-03-04,35\n").contains("line 1: iv looks like a percentage"));
+        assert!(error("2024-03-04,0.3\n2024-03-04,0.4\n").contains("line 2: repeats 2024-03-04"));
+        assert!(error("2024-03-04,-1,0.3\n").contains("dte is negative"));
+        assert!(error("2024-03-04,abc\n").contains("iv is not a number"));
+        assert!(error("day,iv\n").contains("no date column"));
+    }
+}
@@ -0,0 +1,195 @@
+//! Historical Market Data
+//!
+//! Loaders that turn recorded market data into the types the engine runs on.
+//! Real dates are mapped onto the synthetic calendar (day 0 = Monday) by
+//! counting days from the Monday on or before the first record, so weekdays,
+//! weekends and the 23/5 session rules line up with the data. A `DayMapping`
+//! converts back, for reports that want real dates.
+//!
+//! - `ohlc`: OHLC(V) bars from CSV, daily or intraday
+//! - `chains`: end-of-day option chain snapshots, for quoted premiums
+//! - `iv`: implied volatility by date (optionally by DTE), for pricing
+//! - `vol_index`: daily volatility index levels (OVX, VIX), for entry filters
+//! - `fx`: currency rates by calendar day, for portfolio reports in one currency
+//! - `fetch`: bars from an HTTP market data API (`market-data` feature)
+//! - `parquet_import`: OHLC(V) bars from Parquet (`parquet-import` feature)
+//! - `splits`: train/validation/test date ranges, for out-of-sample tests
+
+pub mod chains;
+#[cfg(feature = "market-data")]
+pub mod fetch;
+pub mod fx;
+pub mod iv;
+pub mod ohlc;
+#[cfg(feature = "parquet-import")]
+pub mod parquet_import;
+pub mod splits;
+pub mod vol_index;
+
+pub use chains::{parse_option_chains, read_option_chains, ChainOptions, OptionChains, OptionQuote};
+pub use fx::FxRates;
+pub use splits::{DataSplits, DateRange, Split};
+pub use iv::{parse_implied_vols, read_implied_vols, ImpliedVolSeries, IvOptions};
+pub use vol_index::{parse_vol_index, read_vol_index, IndexVol, VolIndexOptions, VolIndexSeries};
+pub use ohlc::{
+    parse_ohlc_csv, read_ohlc_csv, BarPolicy, DataQuality, Gap, GapPolicy, OhlcBar, OhlcOptions, OhlcSeries, Resolution,
+};
+
+use std::fmt;
+
+/// A proleptic Gregorian calendar date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        let valid = (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month);
+        valid.then_some(Self { year, month, day })
+    }
+
+    /// Parse `YYYY-MM-DD` (or `YYYY/MM/DD`)
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split(['-', '/']);
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Self::new(year, month, day)
+    }
+
+    /// Days since 1970-01-01
+    pub fn days_since_epoch(&self) -> i64 {
+        // Howard Hinnant's days_from_civil
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let month = i64::from(self.month);
+        let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Inverse of `days_since_epoch`
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self { year: year as i32, month, day }
+    }
+
+    /// 0 = Monday ... 6 = Sunday, as on the synthetic calendar
+    pub fn weekday(&self) -> u32 {
+        // 1970-01-01 was a Thursday
+        (self.days_since_epoch() + 3).rem_euclid(7) as u32
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parse a date with an optional time of day
+///
+/// Accepts `YYYY-MM-DD`, `YYYY-MM-DD HH:MM[:SS]` and `YYYY-MM-DDTHH:MM[:SS][Z]`;
+/// seconds are dropped. Returns the date and, if given, minutes from midnight.
+pub fn parse_datetime(text: &str) -> Option<(Date, Option<u32>)> {
+    let text = text.trim().trim_end_matches('Z');
+    let (date, time) = match text.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (text, None),
+    };
+    let date = Date::parse(date)?;
+    let Some(time) = time else {
+        return Some((date, None));
+    };
+    let mut parts = time.split(':');
+    let hours: u32 = parts.next()?.parse().ok()?;
+    let minutes: u32 = parts.next()?.parse().ok()?;
+    if let Some(seconds) = parts.next() {
+        // Fractional seconds are fine, the bar still starts on its minute
+        let seconds: f64 = seconds.parse().ok()?;
+        if !(0.0..60.0).contains(&seconds) {
+            return None;
+        }
+    }
+    if parts.next().is_some() || hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some((date, Some(hours * 60 + minutes)))
+}
+
+/// Converts between real dates and synthetic calendar days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayMapping {
+    /// Epoch day of calendar day 0 (always a Monday)
+    day_zero: i64,
+}
+
+impl DayMapping {
+    /// Day 0 is the Monday on or before `first`
+    pub fn starting_week_of(first: Date) -> Self {
+        Self { day_zero: first.days_since_epoch() - i64::from(first.weekday()) }
+    }
+
+    /// Calendar day of `date` (None before day 0)
+    pub fn day(&self, date: Date) -> Option<u32> {
+        u32::try_from(date.days_since_epoch() - self.day_zero).ok()
+    }
+
+    /// Real date of calendar day `day`
+    pub fn date(&self, day: u32) -> Date {
+        Date::from_days_since_epoch(self.day_zero + i64::from(day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dates_round_trip_and_map_to_weekdays() {
+        for days in [-800_000, -1, 0, 59, 11_016, 19_723, 60_000] {
+            assert_eq!(Date::from_days_since_epoch(days).days_since_epoch(), days);
+        }
+        let date = Date::new(2024, 2, 29).unwrap();
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert_eq!(date.weekday(), 3); // Thursday
+        assert!(Date::new(2023, 2, 29).is_none());
+
+        assert_eq!(parse_datetime("2024-03-01"), Some((Date::new(2024, 3, 1).unwrap(), None)));
+        assert_eq!(parse_datetime("2024-03-01T14:30:00Z"), Some((Date::new(2024, 3, 1).unwrap(), Some(870))));
+        assert_eq!(parse_datetime("2024/03/01 09:05"), Some((Date::new(2024, 3, 1).unwrap(), Some(545))));
+        assert_eq!(parse_datetime("2024-03-01 24:00"), None);
+        assert_eq!(parse_datetime("yesterday"), None);
+
+        // Friday 2024-03-01 lands on calendar day 4 of the week starting Monday
+        let mapping = DayMapping::starting_week_of(Date::new(2024, 3, 1).unwrap());
+        assert_eq!(mapping.day(Date::new(2024, 3, 1).unwrap()), Some(4));
+        assert_eq!(mapping.day(Date::new(2024, 3, 4).unwrap()), Some(7));
+        assert_eq!(mapping.date(0), Date::new(2024, 2, 26).unwrap());
+        assert_eq!(mapping.day(Date::new(2024, 2, 25).unwrap()), None);
+    }
+}
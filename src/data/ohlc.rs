@@ -0,0 +1,566 @@
+//! OHLC Bars from CSV
+//!
+//! Reads the usual vendor export: one row per bar with a timestamp, open,
+//! high, low, close and (optionally) volume. A header row is optional; with
+//! one, columns are found by name (`datetime`/`timestamp`/`date`, an optional
+//! separate `time`, `open`, `high`, `low`, `close`, `volume`) in any order.
+//! Without one they must be in that order.
+//!
+//! Rows with a time of day are intraday bars; the resolution is the most
+//! common spacing between bars of the same day. Rows with only a date are
+//! daily bars, placed at `OhlcOptions::daily_bar_time` so entries and rolls
+//! earlier in the day still see them. Timestamps are taken as exchange time.
+//!
+//! Missing bars are gaps: skipped weekdays for daily data, skipped
+//! calendar trading times between two bars of the same day for intraday
+//! data (overnight and weekend breaks are not gaps, since many feeds only
+//! record the day session). `GapPolicy` keeps, forward-fills or rejects
+//! them. Malformed rows fail with `SimError::Data` naming the line.
+//!
+//! Rows that parse but make bad bars (non-positive or inconsistent prices,
+//! repeated or out-of-order timestamps) and bars outside the trading
+//! session follow a `BarPolicy`. Whatever is found is counted in the
+//! series' `DataQuality`, to report before a run starts.
+
+use super::{parse_datetime, Date, DayMapping};
+use crate::calendar::intraday::{Timestamp, TradingCalendar};
+use crate::calendar::TimeOfDay;
+use crate::config::DataQualityConfig;
+use crate::engine::PricePath;
+use crate::error::SimError;
+use crate::prices::PricePoint;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// One bar of market data
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OhlcBar {
+    pub timestamp: Timestamp,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// 0 when the file has no volume column (and for filled bars)
+    pub volume: f64,
+}
+
+/// Spacing of the bars in a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Daily,
+    /// Minutes between bars
+    Intraday(u32),
+}
+
+/// What to do about missing bars
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GapPolicy {
+    /// Leave them out; the path simply has no bars there
+    #[default]
+    Keep,
+    /// Insert flat bars at the previous close (volume 0)
+    FillForward,
+    /// Fail on the first gap
+    Reject,
+}
+
+impl GapPolicy {
+    /// Parse the `data_quality.gaps` config value
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "keep" => Some(GapPolicy::Keep),
+            "fill_forward" => Some(GapPolicy::FillForward),
+            "error" => Some(GapPolicy::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// What to do about a bad or off-session bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BarPolicy {
+    /// Fail naming the line
+    #[default]
+    Error,
+    /// Leave the bar out
+    Drop,
+    /// Replace bad prices with a flat bar at the previous close (volume 0);
+    /// repeated timestamps and off-session bars have no slot to fill, so
+    /// they are dropped, as is a bad first bar
+    FillForward,
+}
+
+impl BarPolicy {
+    /// Parse the `data_quality.bad_bars` config value
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "error" => Some(BarPolicy::Error),
+            "drop" => Some(BarPolicy::Drop),
+            "fill_forward" => Some(BarPolicy::FillForward),
+            _ => None,
+        }
+    }
+}
+
+/// Problems found while building a series, and what was done about them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DataQuality {
+    /// Rows read
+    pub rows: usize,
+    /// Bars with non-positive or inconsistent prices
+    pub bad_prices: usize,
+    /// Bars repeating or going back before an earlier timestamp
+    pub out_of_order: usize,
+    /// Bars outside the trading session (weekends for daily bars)
+    pub off_session: usize,
+    pub dropped: usize,
+    /// Bad bars replaced at the previous close
+    pub filled: usize,
+}
+
+impl DataQuality {
+    /// Whether nothing was found
+    pub fn is_clean(&self) -> bool {
+        self.bad_prices == 0 && self.out_of_order == 0 && self.off_session == 0
+    }
+}
+
+/// Options for `parse_ohlc_csv`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OhlcOptions {
+    pub gaps: GapPolicy,
+    /// Bars with bad prices or timestamps
+    pub bad_bars: BarPolicy,
+    /// Bars outside the trading session; `None` keeps them
+    pub off_session: Option<BarPolicy>,
+    /// Time of day given to daily bars
+    pub daily_bar_time: TimeOfDay,
+    /// Offset of exchange time from UTC, applied to epoch timestamps (as in
+    /// Parquet files); text timestamps are taken as exchange time already
+    pub utc_offset_minutes: i32,
+}
+
+impl Default for OhlcOptions {
+    fn default() -> Self {
+        // Late in the /CL day session, after the default entry and roll times
+        Self {
+            gaps: GapPolicy::Keep,
+            bad_bars: BarPolicy::Error,
+            off_session: None,
+            daily_bar_time: TimeOfDay::hm(16, 0),
+            utc_offset_minutes: 0,
+        }
+    }
+}
+
+impl OhlcOptions {
+    /// Policies from `config.data_quality`; `None` if a value is unknown
+    pub fn from_config(config: &DataQualityConfig) -> Option<Self> {
+        let off_session = match config.off_session.as_str() {
+            "keep" => None,
+            "fill_forward" => return None,
+            other => Some(BarPolicy::from_config(other)?),
+        };
+        Some(Self {
+            gaps: GapPolicy::from_config(&config.gaps)?,
+            bad_bars: BarPolicy::from_config(&config.bad_bars)?,
+            off_session,
+            ..Self::default()
+        })
+    }
+}
+
+/// A run of missing bars between two recorded ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// Last bar before the gap
+    pub after: Timestamp,
+    /// First bar after it
+    pub before: Timestamp,
+    pub missing: usize,
+}
+
+/// Bars read from a file, in time order
+#[derive(Debug, Clone)]
+pub struct OhlcSeries {
+    pub bars: Vec<OhlcBar>,
+    pub resolution: Resolution,
+    /// Real dates of the calendar days
+    pub mapping: DayMapping,
+    /// Gaps found (and, with `GapPolicy::FillForward`, filled)
+    pub gaps: Vec<Gap>,
+    pub quality: DataQuality,
+}
+
+impl OhlcSeries {
+    /// Closing prices as a path the engine can run over
+    pub fn price_path(&self) -> Result<PricePath, SimError> {
+        PricePath::from_bars(
+            self.bars.iter().map(|bar| PricePoint { timestamp: bar.timestamp, price: bar.close }).collect(),
+        )
+    }
+
+    /// Real date of the first bar
+    pub fn start_date(&self) -> Option<Date> {
+        self.bars.first().map(|bar| self.mapping.date(bar.timestamp.day))
+    }
+}
+
+/// Read and parse an OHLC CSV file
+pub fn read_ohlc_csv(path: impl AsRef<Path>, options: &OhlcOptions) -> Result<OhlcSeries, SimError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)?;
+    parse_ohlc_csv(&text, options).map_err(|e| match e {
+        SimError::Data(reason) => SimError::Data(format!("{}: {}", path.display(), reason)),
+        other => other,
+    })
+}
+
+/// Column positions in a row
+#[derive(Debug, Clone, Copy)]
+struct Columns {
+    datetime: usize,
+    time: Option<usize>,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: Option<usize>,
+}
+
+impl Columns {
+    /// datetime, open, high, low, close[, volume]
+    const POSITIONAL: Columns =
+        Columns { datetime: 0, time: None, open: 1, high: 2, low: 3, close: 4, volume: Some(5) };
+
+    fn from_header(fields: &[&str]) -> Result<Self, String> {
+        let names: Vec<String> = fields.iter().map(|f| f.trim().to_ascii_lowercase()).collect();
+        let find = |candidates: &[&str]| names.iter().position(|name| candidates.contains(&name.as_str()));
+        let require = |candidates: &[&str]| find(candidates).ok_or_else(|| format!("no {} column", candidates[0]));
+        Ok(Self {
+            datetime: require(&["datetime", "timestamp", "date"])?,
+            time: find(&["time"]),
+            open: require(&["open"])?,
+            high: require(&["high"])?,
+            low: require(&["low"])?,
+            close: require(&["close"])?,
+            volume: find(&["volume"]),
+        })
+    }
+}
+
+/// A row before its date is mapped onto the calendar
+pub(super) struct RawBar {
+    /// 1-based line (or row) named in errors
+    pub line: usize,
+    pub date: Date,
+    /// Minutes from midnight; None for daily bars
+    pub minute: Option<u32>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl RawBar {
+    /// Prices positive and consistent, volume non-negative
+    pub(super) fn check(&self) -> Result<(), SimError> {
+        let (open, high, low, close) = (self.open, self.high, self.low, self.close);
+        if low <= 0.0 {
+            return Err(data_error(self.line, "prices must be positive"));
+        }
+        if high < low || !(low..=high).contains(&open) || !(low..=high).contains(&close) {
+            return Err(data_error(self.line, "open and close must lie between low and high"));
+        }
+        if self.volume < 0.0 {
+            return Err(data_error(self.line, "volume is negative"));
+        }
+        Ok(())
+    }
+}
+
+/// Parse OHLC CSV text
+pub fn parse_ohlc_csv(text: &str, options: &OhlcOptions) -> Result<OhlcSeries, SimError> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let Some((first_index, first_line)) = lines.next() else {
+        return Err(SimError::Data("no bars".to_string()));
+    };
+    let first_fields: Vec<&str> = first_line.split(',').collect();
+    let (columns, first_row) = if parse_datetime(first_fields[0]).is_some() {
+        (Columns::POSITIONAL, Some((first_index, first_line)))
+    } else {
+        let columns = Columns::from_header(&first_fields).map_err(|e| data_error(first_index + 1, &e))?;
+        (columns, None)
+    };
+
+    let rows = first_row.into_iter().chain(lines);
+    let raw = rows.map(|(index, line)| parse_row(index + 1, line, &columns)).collect::<Result<Vec<_>, _>>()?;
+    build_series(&raw, options)
+}
+
+/// Check rows, map them onto the calendar and find (and handle) gaps
+pub(super) fn build_series(raw: &[RawBar], options: &OhlcOptions) -> Result<OhlcSeries, SimError> {
+    let Some(first) = raw.first() else {
+        return Err(SimError::Data("no bars".to_string()));
+    };
+
+    let mapping = DayMapping::starting_week_of(first.date);
+    let daily = raw.iter().all(|bar| bar.minute.is_none());
+    if !daily && raw.iter().any(|bar| bar.minute.is_none()) {
+        return Err(SimError::Data("mix of daily (date only) and intraday rows".to_string()));
+    }
+    let daily_minute = u32::from(options.daily_bar_time.minutes());
+    let calendar = TradingCalendar::new();
+    let mut quality = DataQuality { rows: raw.len(), ..DataQuality::default() };
+    let mut bars: Vec<OhlcBar> = Vec::with_capacity(raw.len());
+    for bar in raw {
+        let timestamp = mapping.day(bar.date).map(|day| Timestamp::new(day, bar.minute.unwrap_or(daily_minute)));
+        let Some(timestamp) = timestamp.filter(|t| bars.last().is_none_or(|last| last.timestamp < *t)) else {
+            quality.out_of_order += 1;
+            if options.bad_bars == BarPolicy::Error {
+                return Err(data_error(bar.line, "bars are not in time order (or repeat a timestamp)"));
+            }
+            quality.dropped += 1;
+            continue;
+        };
+
+        let in_session = if daily { timestamp.day % 7 < 5 } else { calendar.is_trading_time(&timestamp) };
+        if !in_session {
+            quality.off_session += 1;
+            match options.off_session {
+                None => {}
+                Some(BarPolicy::Error) => {
+                    return Err(data_error(bar.line, &format!("{} is outside the trading session", timestamp.format())))
+                }
+                Some(_) => {
+                    quality.dropped += 1;
+                    continue;
+                }
+            }
+        }
+
+        let mut ohlc = OhlcBar {
+            timestamp,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+        };
+        if let Err(e) = bar.check() {
+            quality.bad_prices += 1;
+            match (options.bad_bars, bars.last()) {
+                (BarPolicy::Error, _) => return Err(e),
+                (BarPolicy::FillForward, Some(previous)) => {
+                    let close = previous.close;
+                    ohlc = OhlcBar { timestamp, open: close, high: close, low: close, close, volume: 0.0 };
+                    quality.filled += 1;
+                }
+                _ => {
+                    quality.dropped += 1;
+                    continue;
+                }
+            }
+        }
+        bars.push(ohlc);
+    }
+    if bars.is_empty() {
+        return Err(SimError::Data("no usable bars".to_string()));
+    }
+
+    let resolution = if daily { Resolution::Daily } else { Resolution::Intraday(intraday_spacing(&bars)) };
+    let gaps = find_gaps(&bars, resolution);
+    match (options.gaps, gaps.first()) {
+        (GapPolicy::Reject, Some(gap)) => {
+            return Err(SimError::Data(format!(
+                "{} bar(s) missing between {} and {}",
+                gap.missing,
+                gap.after.format(),
+                gap.before.format()
+            )));
+        }
+        (GapPolicy::FillForward, Some(_)) => bars = fill_gaps(&bars, resolution),
+        _ => {}
+    }
+    Ok(OhlcSeries { bars, resolution, mapping, gaps, quality })
+}
+
+pub(super) fn data_error(line: usize, reason: &str) -> SimError {
+    SimError::Data(format!("line {}: {}", line, reason))
+}
+
+fn parse_row(line: usize, text: &str, columns: &Columns) -> Result<RawBar, SimError> {
+    let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+    let field = |index: usize| fields.get(index).copied().ok_or_else(|| data_error(line, "too few columns"));
+    let number = |index: usize, name: &str| -> Result<f64, SimError> {
+        let value: f64 = field(index)?.parse().map_err(|_| data_error(line, &format!("{} is not a number", name)))?;
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(data_error(line, &format!("{} is not finite", name)))
+        }
+    };
+
+    let stamp = match columns.time {
+        Some(time) => format!("{} {}", field(columns.datetime)?, field(time)?),
+        None => field(columns.datetime)?.to_string(),
+    };
+    let (date, minute) = parse_datetime(&stamp).ok_or_else(|| data_error(line, &format!("bad timestamp {:?}", stamp)))?;
+    let open = number(columns.open, "open")?;
+    let high = number(columns.high, "high")?;
+    let low = number(columns.low, "low")?;
+    let close = number(columns.close, "close")?;
+    // Volume is optional even when positional, as long as the row ends there
+    let volume = match columns.volume {
+        Some(index) if index < fields.len() && !fields[index].is_empty() => number(index, "volume")?,
+        _ => 0.0,
+    };
+
+    Ok(RawBar { line, date, minute, open, high, low, close, volume })
+}
+
+/// Most common spacing between bars of the same day, in minutes
+fn intraday_spacing(bars: &[OhlcBar]) -> u32 {
+    let mut counts: HashMap<u32, usize> = HashMap::new();
+    for pair in bars.windows(2).filter(|w| w[0].timestamp.day == w[1].timestamp.day) {
+        *counts.entry(pair[1].timestamp.minute - pair[0].timestamp.minute).or_default() += 1;
+    }
+    // Ties go to the finer spacing
+    counts.into_iter().max_by_key(|&(spacing, count)| (count, std::cmp::Reverse(spacing))).map_or(1, |(s, _)| s)
+}
+
+/// The bar times that should follow `after`, up to (not including) `before`
+fn expected_between(after: Timestamp, before: Timestamp, resolution: Resolution) -> Vec<Timestamp> {
+    let calendar = TradingCalendar::new();
+    let mut expected = Vec::new();
+    match resolution {
+        Resolution::Daily => {
+            for day in after.day + 1..before.day {
+                if day % 7 < 5 {
+                    expected.push(Timestamp::new(day, after.minute));
+                }
+            }
+        }
+        Resolution::Intraday(spacing) if after.day == before.day => {
+            let mut next = calendar.next_trading_time(&after, spacing);
+            while next < before {
+                expected.push(next);
+                next = calendar.next_trading_time(&next, spacing);
+            }
+        }
+        Resolution::Intraday(_) => {}
+    }
+    expected
+}
+
+fn find_gaps(bars: &[OhlcBar], resolution: Resolution) -> Vec<Gap> {
+    bars.windows(2)
+        .filter_map(|pair| {
+            let missing = expected_between(pair[0].timestamp, pair[1].timestamp, resolution).len();
+            (missing > 0).then_some(Gap { after: pair[0].timestamp, before: pair[1].timestamp, missing })
+        })
+        .collect()
+}
+
+fn fill_gaps(bars: &[OhlcBar], resolution: Resolution) -> Vec<OhlcBar> {
+    let mut filled = Vec::with_capacity(bars.len());
+    for (index, bar) in bars.iter().enumerate() {
+        if let Some(previous) = index.checked_sub(1).map(|i| bars[i]) {
+            let close = previous.close;
+            filled.extend(expected_between(previous.timestamp, bar.timestamp, resolution).into_iter().map(
+                |timestamp| OhlcBar { timestamp, open: close, high: close, low: close, close, volume: 0.0 },
+            ));
+        }
+        filled.push(*bar);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_bars_with_header_and_gaps() {
+        let csv = "Date,Open,High,Low,Close,Volume\n\
+                   2024-03-01,78.0,79.5,77.8,79.2,1000\n\
+                   2024-03-04,79.2,80.0,78.9,79.9,1200\n\
+                   2024-03-06,80.0,80.4,79.0,79.5,900\n";
+        let series = parse_ohlc_csv(csv, &OhlcOptions::default()).unwrap();
+        assert_eq!(series.resolution, Resolution::Daily);
+        assert_eq!(series.start_date(), Date::new(2024, 3, 1));
+        // Friday, then Monday (the weekend is no gap), then a missing Tuesday
+        let days: Vec<u32> = series.bars.iter().map(|b| b.timestamp.day).collect();
+        assert_eq!(days, [4, 7, 9]);
+        assert_eq!(series.bars[0].timestamp.minute, 16 * 60);
+        let gap = Gap { after: series.bars[1].timestamp, before: series.bars[2].timestamp, missing: 1 };
+        assert_eq!(series.gaps, [gap]);
+        assert_eq!(series.price_path().unwrap().len(), 3);
+
+        let filled = parse_ohlc_csv(csv, &OhlcOptions { gaps: GapPolicy::FillForward, ..OhlcOptions::default() });
+        let filled = filled.unwrap();
+        assert_eq!(filled.bars.len(), 4);
+        assert_eq!((filled.bars[2].timestamp.day, filled.bars[2].close, filled.bars[2].volume), (8, 79.9, 0.0));
+
+        let rejected = parse_ohlc_csv(csv, &OhlcOptions { gaps: GapPolicy::Reject, ..OhlcOptions::default() });
+        assert!(matches!(rejected, Err(SimError::Data(_))));
+    }
+
+    #[test]
+    fn test_intraday_bars_without_header() {
+        let csv = "2024-03-04T09:00:00,80.0,80.2,79.9,80.1\n\
+                   2024-03-04T09:10:00,80.1,80.3,80.0,80.2\n\
+                   2024-03-04T09:30:00,80.2,80.2,79.8,79.9\n\
+                   2024-03-05T09:00:00,79.9,80.0,79.5,79.6\n";
+        let series = parse_ohlc_csv(csv, &OhlcOptions { gaps: GapPolicy::FillForward, ..OhlcOptions::default() });
+        let series = series.unwrap();
+        assert_eq!(series.resolution, Resolution::Intraday(10));
+        // 09:20 is filled; the overnight break is not a gap
+        let times: Vec<(u32, u32)> = series.bars.iter().map(|b| (b.timestamp.day, b.timestamp.minute)).collect();
+        assert_eq!(times, [(0, 540), (0, 550), (0, 560), (0, 570), (1, 540)]);
+        assert_eq!(series.gaps.len(), 1);
+    }
+
+    #[test]
+    fn test_malformed_rows_name_the_line() {
+        let error = |csv: &str| parse_ohlc_csv(csv, &OhlcOptions::default()).unwrap_err().to_string();
+        assert!(error("").contains("no bars"));
+        assert!(error("2024-03-04,80,81,79,80\n2024-03-05,80,81,79\n").contains("line 2: too few columns"));
+        assert!(error("2024-03-04,80,81,79,82\n").contains("line 1: open and close"));
+        assert!(error("2024-03-04,80,81,79,abc\n").contains("close is not a number"));
+        let reversed = "2024-03-05,80,81,79,80\n2024-03-04,80,81,79,80\n";
+        assert!(error(reversed).contains("line 2: bars are not in time order"));
+        assert!(error("when,open,high,low,close\n").contains("no datetime column"));
+        assert!(error("2024-03-04,80,81,79,80\n2024-03-05 09:00,80,81,79,80\n").contains("mix of daily"));
+    }
+
+    #[test]
+    fn test_bad_bars_follow_the_policy() {
+        // A repeat, a bad low, a Friday bar at 17:30 and a Saturday bar
+        let csv = "2024-03-04 09:00,80.0,80.2,79.9,80.1\n\
+                   2024-03-04 09:00,80.0,80.2,79.9,80.1\n\
+                   2024-03-04 09:10,80.1,80.3,0.0,80.2\n\
+                   2024-03-08 17:30,80.2,80.3,80.1,80.2\n\
+                   2024-03-09 10:00,80.2,80.3,80.1,80.2\n\
+                   2024-03-11 09:00,80.2,80.4,80.1,80.3\n";
+        let parse = |bad_bars, off_session| {
+            parse_ohlc_csv(csv, &OhlcOptions { bad_bars, off_session, ..OhlcOptions::default() })
+        };
+        assert!(parse(BarPolicy::Error, None).unwrap_err().to_string().contains("line 2: bars are not in time order"));
+
+        let dropped = parse(BarPolicy::Drop, Some(BarPolicy::Drop)).unwrap();
+        assert_eq!(dropped.bars.len(), 2);
+        let quality = DataQuality { rows: 6, bad_prices: 1, out_of_order: 1, off_session: 2, dropped: 4, filled: 0 };
+        assert_eq!(dropped.quality, quality);
+        assert!(!quality.is_clean());
+
+        // Off-session bars are kept unless asked otherwise; the bad low becomes a flat bar
+        let filled = parse(BarPolicy::FillForward, None).unwrap();
+        assert_eq!(filled.bars.len(), 5);
+        assert_eq!((filled.bars[1].low, filled.bars[1].close, filled.quality.filled), (80.1, 80.1, 1));
+        let off_session = parse(BarPolicy::Drop, Some(BarPolicy::Error)).unwrap_err().to_string();
+        assert!(off_session.contains("line 4:") && off_session.contains("outside the trading session"));
+        assert!(parse_ohlc_csv("2024-03-04,80,81,79,80\n", &OhlcOptions::default()).unwrap().quality.is_clean());
+    }
+}
@@ -0,0 +1,198 @@
+//! Parquet Import (feature = "parquet-import")
+//!
+//! Multi-year 1-minute files are millions of rows, which Parquet stores far
+//! more compactly than CSV. Columns are found by name as in `data::ohlc`
+//! (`datetime`/`timestamp`/`ts_event`/`date`, `open`, `high`, `low`,
+//! `close`, optional `volume`); any other columns are ignored.
+//!
+//! Timestamps may be Parquet timestamps (milli-, micro- or nanoseconds since
+//! the epoch, shifted by `OhlcOptions::utc_offset_minutes`), dates (daily
+//! bars) or strings in the CSV formats. Prices may be any float or integer
+//! type. Rows are checked, mapped and gap-checked exactly like CSV rows;
+//! errors name the 1-based row as `line N`.
+
+use super::ohlc::{build_series, data_error, RawBar};
+use super::{parse_datetime, Date, OhlcOptions, OhlcSeries};
+use crate::error::SimError;
+use parquet::basic::{LogicalType, TimeUnit};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::record::Field;
+use std::fs::File;
+use std::path::Path;
+
+/// Column positions in a row, and the unit of integer timestamps
+#[derive(Debug, Clone, Copy)]
+struct Columns {
+    datetime: usize,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: Option<usize>,
+    /// Ticks per second of a timestamp column read as a plain integer
+    ticks_per_second: Option<i64>,
+}
+
+/// Read an OHLC Parquet file
+pub fn read_ohlc_parquet(path: impl AsRef<Path>, options: &OhlcOptions) -> Result<OhlcSeries, SimError> {
+    let path = path.as_ref();
+    read(path, options).map_err(|e| match e {
+        SimError::Data(reason) => SimError::Data(format!("{}: {}", path.display(), reason)),
+        other => other,
+    })
+}
+
+fn read(path: &Path, options: &OhlcOptions) -> Result<OhlcSeries, SimError> {
+    let reader = SerializedFileReader::new(File::open(path)?).map_err(parquet_error)?;
+    let schema = reader.metadata().file_metadata().schema_descr_ptr();
+    let fields = schema.root_schema().get_fields();
+    let names: Vec<String> = fields.iter().map(|field| field.name().to_ascii_lowercase()).collect();
+    let find = |candidates: &[&str]| names.iter().position(|name| candidates.contains(&name.as_str()));
+    let require = |candidates: &[&str]| {
+        find(candidates).ok_or_else(|| SimError::Data(format!("no {} column", candidates[0])))
+    };
+    let datetime = require(&["datetime", "timestamp", "ts_event", "date"])?;
+    let ticks_per_second = match fields[datetime].get_basic_info().logical_type() {
+        Some(LogicalType::Timestamp { unit, .. }) => Some(match unit {
+            TimeUnit::MILLIS(_) => 1_000,
+            TimeUnit::MICROS(_) => 1_000_000,
+            TimeUnit::NANOS(_) => 1_000_000_000,
+        }),
+        _ => None,
+    };
+    let columns = Columns {
+        datetime,
+        open: require(&["open"])?,
+        high: require(&["high"])?,
+        low: require(&["low"])?,
+        close: require(&["close"])?,
+        volume: find(&["volume"]),
+        ticks_per_second,
+    };
+
+    let rows = reader.get_row_iter(None).map_err(parquet_error)?;
+    let mut raw = Vec::with_capacity(reader.metadata().file_metadata().num_rows().max(0) as usize);
+    for (index, row) in rows.enumerate() {
+        let row = row.map_err(parquet_error)?;
+        let values: Vec<&Field> = row.get_column_iter().map(|(_, value)| value).collect();
+        raw.push(parse_row(index + 1, &values, &columns, options)?);
+    }
+    build_series(&raw, options)
+}
+
+fn parquet_error(e: parquet::errors::ParquetError) -> SimError {
+    SimError::Data(e.to_string())
+}
+
+fn parse_row(line: usize, values: &[&Field], columns: &Columns, options: &OhlcOptions) -> Result<RawBar, SimError> {
+    let number = |index: usize, name: &str| -> Result<f64, SimError> {
+        let value = match values[index] {
+            Field::Double(v) => *v,
+            Field::Float(v) => f64::from(*v),
+            Field::Long(v) => *v as f64,
+            Field::Int(v) => f64::from(*v),
+            Field::ULong(v) => *v as f64,
+            Field::UInt(v) => f64::from(*v),
+            Field::Null => return Err(data_error(line, &format!("{} is missing", name))),
+            _ => return Err(data_error(line, &format!("{} is not a number", name))),
+        };
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(data_error(line, &format!("{} is not finite", name)))
+        }
+    };
+
+    let epoch = |seconds: i64| {
+        let local = seconds + i64::from(options.utc_offset_minutes) * 60;
+        let minute = (local.rem_euclid(86_400) / 60) as u32;
+        (Date::from_days_since_epoch(local.div_euclid(86_400)), Some(minute))
+    };
+    let (date, minute) = match (values[columns.datetime], columns.ticks_per_second) {
+        (Field::TimestampMillis(ms), _) => epoch(ms.div_euclid(1_000)),
+        (Field::TimestampMicros(us), _) => epoch(us.div_euclid(1_000_000)),
+        (Field::Long(ticks), Some(per_second)) => epoch(ticks.div_euclid(per_second)),
+        (Field::Date(days), _) => (Date::from_days_since_epoch(i64::from(*days)), None),
+        (Field::Str(text), _) => {
+            parse_datetime(text).ok_or_else(|| data_error(line, &format!("bad timestamp {:?}", text)))?
+        }
+        (other, _) => return Err(data_error(line, &format!("unsupported timestamp {}", other))),
+    };
+    let volume = match columns.volume {
+        Some(index) if !matches!(values[index], Field::Null) => number(index, "volume")?,
+        _ => 0.0,
+    };
+    Ok(RawBar {
+        line,
+        date,
+        minute,
+        open: number(columns.open, "open")?,
+        high: number(columns.high, "high")?,
+        low: number(columns.low, "low")?,
+        close: number(columns.close, "close")?,
+        volume,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Resolution;
+    use parquet::data_type::{DoubleType, Int64Type};
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::SerializedFileWriter;
+    use parquet::schema::parser::parse_message_type;
+    use std::sync::Arc;
+
+    /// Write minute bars with nanosecond timestamps, as vendor exports do
+    fn write_bars(path: &Path, stamps: &[i64], closes: &[f64]) {
+        let schema = "message bars {
+            REQUIRED INT64 ts_event (TIMESTAMP(NANOS,true));
+            REQUIRED DOUBLE open;
+            REQUIRED DOUBLE high;
+            REQUIRED DOUBLE low;
+            REQUIRED DOUBLE close;
+            OPTIONAL INT64 symbol_id;
+        }";
+        let schema = Arc::new(parse_message_type(schema).unwrap());
+        let props = Arc::new(WriterProperties::builder().build());
+        let mut writer = SerializedFileWriter::new(File::create(path).unwrap(), schema, props).unwrap();
+        let mut group = writer.next_row_group().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column.typed::<Int64Type>().write_batch(stamps, None, None).unwrap();
+        column.close().unwrap();
+        for _ in 0..4 {
+            let mut column = group.next_column().unwrap().unwrap();
+            column.typed::<DoubleType>().write_batch(closes, None, None).unwrap();
+            column.close().unwrap();
+        }
+        let mut column = group.next_column().unwrap().unwrap();
+        column.typed::<Int64Type>().write_batch(&[], Some(&vec![0; stamps.len()]), None).unwrap();
+        column.close().unwrap();
+        group.close().unwrap();
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_reads_nanosecond_minute_bars() {
+        let path = std::env::temp_dir().join(format!("tsv2_bars_{}.parquet", std::process::id()));
+        // 2024-03-04 14:00, 14:01 and 14:03 UTC
+        let start = 1_709_560_800_000_000_000_i64;
+        let minute = 60_000_000_000;
+        write_bars(&path, &[start, start + minute, start + 3 * minute], &[78.0, 78.1, 78.3]);
+
+        let options = OhlcOptions { utc_offset_minutes: -300, ..OhlcOptions::default() };
+        let series = read_ohlc_parquet(&path, &options).unwrap();
+        assert_eq!(series.resolution, Resolution::Intraday(1));
+        assert_eq!(series.start_date(), Date::new(2024, 3, 4));
+        let times: Vec<(u32, u32)> = series.bars.iter().map(|b| (b.timestamp.day, b.timestamp.minute)).collect();
+        assert_eq!(times, [(0, 540), (0, 541), (0, 543)]);
+        assert_eq!(series.bars[2].close, 78.3);
+        assert_eq!(series.gaps.len(), 1);
+
+        write_bars(&path, &[start, start], &[78.0, 78.0]);
+        let error = read_ohlc_parquet(&path, &options).unwrap_err().to_string();
+        assert!(error.contains("line 2: bars are not in time order"), "{}", error);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
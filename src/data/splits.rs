@@ -0,0 +1,123 @@
+//! Train/Validation/Test Splits
+//!
+//! Date ranges that divide a historical series for out-of-sample testing,
+//! from a config's `splits` section. Each range cuts the bars whose real
+//! date falls inside it (both ends inclusive) out of a path, keeping their
+//! calendar days, so a slice runs exactly as that stretch of the full path
+//! would. Ranges never overlap (the config is rejected otherwise).
+
+use super::{Date, DayMapping};
+use crate::config::{DateRangeConfig, SplitsConfig};
+use crate::engine::PricePath;
+use crate::error::SimError;
+use crate::prices::PricePoint;
+use std::fmt;
+
+/// Which part of the data a range is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    Train,
+    Validation,
+    Test,
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Split::Train => "train",
+            Split::Validation => "validation",
+            Split::Test => "test",
+        })
+    }
+}
+
+/// Inclusive range of real dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: Date,
+    pub end: Date,
+}
+
+impl DateRange {
+    /// Parse a `DateRangeConfig` (None if a date is malformed or the range is reversed)
+    pub fn from_config(config: &DateRangeConfig) -> Option<Self> {
+        let (start, end) = (Date::parse(&config.start)?, Date::parse(&config.end)?);
+        (start <= end).then_some(Self { start, end })
+    }
+
+    pub fn contains(&self, date: Date) -> bool {
+        self.start <= date && date <= self.end
+    }
+
+    /// The bars of `path` dated inside the range, by `mapping`
+    pub fn slice(&self, path: &PricePath, mapping: DayMapping) -> Result<PricePath, SimError> {
+        let inside = |bar: &&PricePoint| self.contains(mapping.date(bar.timestamp.day));
+        let bars: Vec<PricePoint> = path.bars().iter().filter(inside).copied().collect();
+        if bars.is_empty() {
+            return Err(SimError::Data(format!("no bars from {} to {}", self.start, self.end)));
+        }
+        PricePath::from_bars(bars)
+    }
+}
+
+/// The ranges of a config's `splits` section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataSplits {
+    pub train: DateRange,
+    pub validation: Option<DateRange>,
+    pub test: Option<DateRange>,
+}
+
+impl DataSplits {
+    /// Parse a `SplitsConfig` (validated with the config)
+    pub fn from_config(config: &SplitsConfig) -> Option<Self> {
+        let optional = |range: &Option<DateRangeConfig>| match range {
+            Some(range) => DateRange::from_config(range).map(Some),
+            None => Some(None),
+        };
+        Some(Self {
+            train: DateRange::from_config(&config.train)?,
+            validation: optional(&config.validation)?,
+            test: optional(&config.test)?,
+        })
+    }
+
+    /// Every declared range, train first
+    pub fn ranges(&self) -> Vec<(Split, DateRange)> {
+        let mut ranges = vec![(Split::Train, self.train)];
+        ranges.extend(self.validation.map(|range| (Split::Validation, range)));
+        ranges.extend(self.test.map(|range| (Split::Test, range)));
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_splits_slice_by_date() {
+        let yaml = "simulation: {days: 21, initial_price: 75.0, volatility: 0.3, seed: 1}\n\
+                    strategy: {strategy_type: straddle, entry_dte: 1}\n\
+                    splits:\n\
+                    \x20 train: {start: 2024-03-04, end: 2024-03-10}\n\
+                    \x20 test: {start: 2024-03-18, end: 2024-03-24}\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        let splits = DataSplits::from_config(config.splits.as_ref().unwrap()).unwrap();
+        assert_eq!(splits.ranges().iter().map(|(split, _)| *split).collect::<Vec<_>>(), [Split::Train, Split::Test]);
+
+        // Day 0 is Monday 2024-03-04: the train slice is the first week, the test slice the third
+        let path = PricePath::generate(&config).unwrap();
+        let mapping = DayMapping::starting_week_of(splits.train.start);
+        let train = splits.train.slice(&path, mapping).unwrap();
+        let test = splits.test.unwrap().slice(&path, mapping).unwrap();
+        assert!(train.bars().iter().all(|bar| bar.timestamp.day < 7));
+        assert!(test.bars().iter().all(|bar| (14..21).contains(&bar.timestamp.day)));
+        assert_eq!(test.bars()[0], *path.bars().iter().find(|bar| bar.timestamp.day == 14).unwrap());
+
+        assert!(Config::from_yaml_str(&yaml.replace("2024-03-18", "2024-03-08")).is_err());
+        let empty = DateRange::from_config(&DateRangeConfig { start: "2025-01-01".into(), end: "2025-01-02".into() });
+        assert!(empty.unwrap().slice(&path, mapping).is_err());
+    }
+}
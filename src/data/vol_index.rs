@@ -0,0 +1,171 @@
+//! Volatility Index Series from CSV
+//!
+//! Daily levels of a volatility index such as OVX (crude oil) or VIX, in
+//! index points (35.2 = 35.2% annualized). Either `date,level` rows or a
+//! file with a header, where the level is the `close` column (or `level`,
+//! `value`, `index`); CBOE's own history files (`DATE,OPEN,HIGH,LOW,CLOSE`
+//! with US-style `MM/DD/YYYY` dates) read as they are.
+//!
+//! Like implied vols, each bar sees the level of the latest date on or
+//! before it, so weekends and holidays carry the last close forward. The
+//! engine uses the level for `config.vol_index` entry filters and, when
+//! asked, as the IV to price at.
+
+use super::{Date, DayMapping};
+use crate::error::SimError;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Options for `parse_vol_index`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct VolIndexOptions {
+    /// Calendar days of the price data (e.g. `OhlcSeries::mapping`); defaults
+    /// to the week of the first date
+    pub mapping: Option<DayMapping>,
+}
+
+/// Index level by calendar day
+#[derive(Debug, Clone)]
+pub struct VolIndexSeries {
+    levels: BTreeMap<u32, f64>,
+    mapping: DayMapping,
+}
+
+impl VolIndexSeries {
+    /// Level as of `day`, or `None` before the first date
+    pub fn level(&self, day: u32) -> Option<f64> {
+        self.levels.range(..=day).next_back().map(|(_, &level)| level)
+    }
+
+    /// Number of dates
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Real dates of the calendar days
+    pub fn mapping(&self) -> DayMapping {
+        self.mapping
+    }
+}
+
+/// Index levels as ATM vols: the level in percent, times `scale`
+#[derive(Debug, Clone)]
+pub struct IndexVol {
+    pub series: std::sync::Arc<VolIndexSeries>,
+    pub scale: f64,
+}
+
+impl crate::pricing::AtmVolSource for IndexVol {
+    fn atm_vol(&self, day: u32, _dte: f64) -> Option<f64> {
+        self.series.level(day).map(|level| level / 100.0 * self.scale)
+    }
+}
+
+/// Read and parse a volatility index CSV file
+pub fn read_vol_index(path: impl AsRef<Path>, options: &VolIndexOptions) -> Result<VolIndexSeries, SimError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)?;
+    parse_vol_index(&text, options).map_err(|e| match e {
+        SimError::Data(reason) => SimError::Data(format!("{}: {}", path.display(), reason)),
+        other => other,
+    })
+}
+
+/// Parse volatility index CSV text
+///
+/// Dates before day 0 of `VolIndexOptions::mapping` are skipped.
+pub fn parse_vol_index(text: &str, options: &VolIndexOptions) -> Result<VolIndexSeries, SimError> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let Some((first_index, first_line)) = lines.next() else {
+        return Err(SimError::Data("no index levels".to_string()));
+    };
+    let first_fields: Vec<&str> = first_line.split(',').collect();
+    let (columns, first_row) = if parse_date(first_fields[0].trim()).is_some() {
+        ((0, 1), Some((first_index, first_line)))
+    } else {
+        let names: Vec<String> = first_fields.iter().map(|f| f.trim().to_ascii_lowercase()).collect();
+        let find = |candidates: &[&str]| names.iter().position(|name| candidates.contains(&name.as_str()));
+        let date = find(&["date", "time"]).ok_or_else(|| data_error(first_index + 1, "no date column"))?;
+        let level = find(&["close", "level", "value", "index"])
+            .ok_or_else(|| data_error(first_index + 1, "no close column"))?;
+        ((date, level), None)
+    };
+
+    let mut raw = Vec::new();
+    for (index, line) in first_row.into_iter().chain(lines) {
+        let line_number = index + 1;
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let (Some(date), Some(level)) = (fields.get(columns.0), fields.get(columns.1)) else {
+            return Err(data_error(line_number, "too few columns"));
+        };
+        let date = parse_date(date).ok_or_else(|| data_error(line_number, &format!("bad date {:?}", date)))?;
+        let level: f64 = level.parse().map_err(|_| data_error(line_number, "level is not a number"))?;
+        if !level.is_finite() || level <= 0.0 {
+            return Err(data_error(line_number, "level must be positive"));
+        }
+        raw.push((line_number, date, level));
+    }
+    let Some(first_date) = raw.iter().map(|&(_, date, _)| date).min() else {
+        return Err(SimError::Data("no index levels".to_string()));
+    };
+
+    let mapping = options.mapping.unwrap_or_else(|| DayMapping::starting_week_of(first_date));
+    let mut levels = BTreeMap::new();
+    for (line, date, level) in raw {
+        let Some(day) = mapping.day(date) else {
+            continue;
+        };
+        if levels.insert(day, level).is_some() {
+            return Err(data_error(line, &format!("repeats {}", date)));
+        }
+    }
+    Ok(VolIndexSeries { levels, mapping })
+}
+
+fn data_error(line: usize, reason: &str) -> SimError {
+    SimError::Data(format!("line {}: {}", line, reason))
+}
+
+/// ISO dates, or the `MM/DD/YYYY` of CBOE history files
+fn parse_date(text: &str) -> Option<Date> {
+    Date::parse(text).or_else(|| {
+        let mut parts = text.split('/');
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        let year = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Date::new(year, month, day)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cboe_history_carries_forward() {
+        // Friday and the following Tuesday
+        let csv = "DATE,OPEN,HIGH,LOW,CLOSE\n03/01/2024,33.1,34.0,32.8,33.5\n03/05/2024,36.0,38.2,35.9,37.4\n";
+        let series = parse_vol_index(csv, &VolIndexOptions::default()).unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series.level(3), None);
+        assert_eq!(series.level(4), Some(33.5));
+        assert_eq!(series.level(7), Some(33.5));
+        assert_eq!(series.level(8), Some(37.4));
+
+        let plain = parse_vol_index("2024-03-04,41.0\n", &VolIndexOptions::default()).unwrap();
+        assert_eq!(plain.level(0), Some(41.0));
+
+        let error = |csv: &str| parse_vol_index(csv, &VolIndexOptions::default()).unwrap_err().to_string();
+        assert!(error("").contains("no index levels"));
+        assert!(error("2024-03-04,0\n").contains("line 1: level must be positive"));
+        assert!(error("2024-03-04,30\n2024-03-04,31\n").contains("line 2: repeats"));
+        assert!(error("date,open\n").contains("no close column"));
+    }
+}
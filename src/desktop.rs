@@ -0,0 +1,917 @@
+//! Desktop App Commands
+//!
+//! The bodies of the Tauri app's commands (`src/tauri_main.rs` wraps each
+//! in a `#[tauri::command]` and turns its callbacks into window events), kept
+//! here so they build and are tested with the rest of the crate. The UI picks
+//! a preset and overrides fields of it (`SimulationConfig`); results come back
+//! in dollars with per-day series for the charts. `Jobs` holds the cancel
+//! tokens of running simulations and `History` the completed runs on disk.
+
+use crate::config::{Config, RollTriggerConfig, StrategyConfig, StrikeConfig, SweepParameterConfig};
+use crate::engine::{CancelToken, LegReport, LogEntry, PricePath, Progress, Simulator, StreamEvent};
+use crate::events::Side;
+use crate::montecarlo::{self, Sweep, SweepProgress};
+use crate::reporting::{daily_closes, presenter, Distribution};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What the UI sends to start a run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    pub days: usize,
+    pub initial_price: f64,
+    pub volatility: f64,
+    pub vrp: f64,
+    pub seed: u64,
+    /// Preset: "straddle" (1DTE short), "long_protection" or "combined"
+    pub strategy: String,
+    #[serde(default)]
+    pub enable_long_leg: bool,
+    /// Changes to the preset's strategy (the short leg when combined)
+    #[serde(flatten)]
+    pub overrides: StrategyOverrides,
+    /// Changes to the combined preset's long leg
+    #[serde(default)]
+    pub long_leg: StrategyOverrides,
+    /// Strike grid and roll behaviour
+    #[serde(default)]
+    pub strike_config: Option<StrikeConfig>,
+}
+
+/// Strategy fields the UI may set; anything left out keeps the preset's value
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StrategyOverrides {
+    pub strategy_type: Option<String>,
+    pub entry_dte: Option<u32>,
+    pub entry_time: Option<String>,
+    pub roll_time: Option<String>,
+    /// "ATM", "OTM" or a delta selection such as "delta_put_16"
+    pub strike_selection: Option<String>,
+    pub strike_offset: Option<f64>,
+    /// "short" or "long"
+    pub side: Option<String>,
+    /// Replaces the preset's triggers entirely
+    pub roll_triggers: Option<Vec<RollTriggerConfig>>,
+}
+
+impl StrategyOverrides {
+    fn apply(&self, strategy: &mut StrategyConfig) {
+        let set = |field: &mut String, value: &Option<String>| {
+            if let Some(value) = value {
+                field.clone_from(value);
+            }
+        };
+        set(&mut strategy.strategy_type, &self.strategy_type);
+        set(&mut strategy.entry_time, &self.entry_time);
+        set(&mut strategy.roll_time, &self.roll_time);
+        set(&mut strategy.strike_selection, &self.strike_selection);
+        set(&mut strategy.side, &self.side);
+        if let Some(dte) = self.entry_dte {
+            strategy.entry_dte = dte;
+        }
+        if let Some(offset) = self.strike_offset {
+            strategy.strike_offset = offset;
+        }
+        if let Some(triggers) = &self.roll_triggers {
+            strategy.roll_triggers.clone_from(triggers);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeEntry {
+    pub trade_type: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationResult {
+    pub net_pnl: f64,
+    pub position_count: u32,
+    pub win_rate: f64,
+    pub final_price: f64,
+    pub trades: Vec<TradeEntry>,
+    /// Per-side breakdown (a single strategy counts towards its own side)
+    pub short_pnl_per_day: f64,
+    pub long_pnl_per_day: f64,
+    pub total_pnl_per_day: f64,
+    pub short_positions: u32,
+    pub long_positions: u32,
+    pub short_win_rate: f64,
+    pub long_win_rate: f64,
+    /// One point per trading day, for the price and equity charts
+    pub daily: Vec<DailyPoint>,
+    /// One marker per closed position, in close order within each leg
+    pub positions: Vec<PositionMarker>,
+    /// Id in the run history, once saved there
+    #[serde(default)]
+    pub history_id: Option<u64>,
+}
+
+/// End-of-day price and cumulative P&L (realized plus unrealized, in dollars)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DailyPoint {
+    pub day: u32,
+    pub price: f64,
+    pub short_pnl: f64,
+    pub long_pnl: f64,
+    pub total_pnl: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PositionMarker {
+    pub leg: String,
+    pub position_id: u64,
+    pub side: Side,
+    pub entry_day: u32,
+    pub entry_price: f64,
+    pub close_day: u32,
+    pub close_price: f64,
+    /// Realized P&L, in dollars
+    pub pnl: f64,
+}
+
+/// Payload of `simulation://started`
+#[derive(Debug, Clone, Serialize)]
+pub struct StartedEvent {
+    pub job_id: u64,
+}
+
+/// Payload of `simulation://progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressEvent {
+    /// "single", "short" or "long"
+    pub leg: &'static str,
+    pub bars_processed: usize,
+    pub total_bars: usize,
+    pub price: f64,
+    /// Realized plus unrealized, in dollars
+    pub pnl: f64,
+    pub bars_per_sec: f64,
+    /// Estimated seconds left in this leg, once any bars have run
+    pub eta_seconds: Option<f64>,
+}
+
+/// Payload of `simulation://trade`
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeEvent {
+    pub leg: &'static str,
+    #[serde(flatten)]
+    pub trade: TradeEntry,
+}
+
+impl ProgressEvent {
+    /// `progress` of the `leg` running `config`, in dollars
+    pub fn new(leg: &'static str, config: &Config, progress: &Progress) -> Self {
+        Self {
+            leg,
+            bars_processed: progress.bars_processed,
+            total_bars: progress.total_bars,
+            price: progress.price,
+            pnl: (progress.realized_pnl + progress.unrealized_pnl) * config.simulation.contract_multiplier,
+            bars_per_sec: progress.bars_per_sec(),
+            eta_seconds: progress.eta().map(|eta| eta.as_secs_f64()),
+        }
+    }
+}
+
+impl TradeEvent {
+    pub fn new(leg: &'static str, entry: &LogEntry, config: &Config) -> Self {
+        Self { leg, trade: trade_entry(entry, config) }
+    }
+}
+
+/// A swept config field (dotted path, e.g. `simulation.volatility_risk_premium`)
+/// and its inclusive range
+#[derive(Debug, Deserialize)]
+pub struct SweepAxis {
+    pub path: String,
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SweptAxis {
+    pub path: String,
+    pub values: Vec<f64>,
+}
+
+/// Result of `run_sweep`
+#[derive(Debug, Serialize)]
+pub struct SweepGrid {
+    pub x: SweptAxis,
+    pub y: SweptAxis,
+    pub seeds: u64,
+    /// One row per `x` value, one cell per `y` value
+    pub cells: Vec<Vec<SweepCell>>,
+}
+
+/// P&L and max drawdown over a grid point's seeds, in dollars
+#[derive(Debug, Serialize)]
+pub struct SweepCell {
+    pub pnl: Distribution,
+    pub max_drawdown: Distribution,
+}
+
+/// Payload of `sweep://progress` and `seeds://progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepProgressEvent {
+    pub completed: usize,
+    pub total: usize,
+}
+
+impl From<SweepProgress> for SweepProgressEvent {
+    fn from(progress: SweepProgress) -> Self {
+        Self { completed: progress.completed, total: progress.total }
+    }
+}
+
+/// Result of `explore_seeds`, all in dollars
+#[derive(Debug, Serialize)]
+pub struct SeedExploration {
+    pub first_seed: u64,
+    /// One entry per seed, in seed order
+    pub runs: Vec<SeedRun>,
+    pub pnl: Option<Distribution>,
+    pub max_drawdown: Option<Distribution>,
+    /// Fraction of seeds that lost money
+    pub loss_probability: f64,
+    /// Terminal P&L counts over `HISTOGRAM_BINS` equal-width bins
+    pub pnl_histogram: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeedRun {
+    pub seed: u64,
+    pub pnl: f64,
+    pub max_drawdown: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistogramBin {
+    pub from: f64,
+    pub to: f64,
+    pub count: usize,
+}
+
+/// Most seeds one `explore_seeds` call may run
+pub const MAX_SEEDS: u64 = 10_000;
+
+/// Bins in `SeedExploration::pnl_histogram`
+const HISTOGRAM_BINS: usize = 20;
+
+/// Equal-width bins spanning `samples` (a single bin when they're all equal)
+fn histogram(samples: &[f64], bins: usize) -> Vec<HistogramBin> {
+    if samples.is_empty() {
+        return Vec::new();
+    }
+    let min = samples.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max <= min {
+        return vec![HistogramBin { from: min, to: max, count: samples.len() }];
+    }
+    let width = (max - min) / bins as f64;
+    let mut result: Vec<HistogramBin> = (0..bins)
+        .map(|i| HistogramBin { from: min + i as f64 * width, to: min + (i + 1) as f64 * width, count: 0 })
+        .collect();
+    for sample in samples {
+        let bin = (((sample - min) / width) as usize).min(bins - 1);
+        result[bin].count += 1;
+    }
+    result
+}
+
+/// Most values one sweep axis may have
+pub const MAX_AXIS_VALUES: usize = 50;
+
+/// Most simulations (points × seeds) one `run_sweep` call may run
+pub const MAX_SWEEP_RUNS: usize = 10_000;
+
+impl SweepAxis {
+    /// The range's values, expanded as a config's `sweep.parameters` are
+    fn values(&self) -> Result<Vec<f64>, String> {
+        let values = SweepParameterConfig {
+            path: self.path.clone(),
+            values: None,
+            start: Some(self.start),
+            end: Some(self.end),
+            step: Some(self.step),
+        }
+        .values()?;
+        if values.len() > MAX_AXIS_VALUES {
+            return Err(format!("{}: {} values is too many (at most {})", self.path, values.len(), MAX_AXIS_VALUES));
+        }
+        Ok(values)
+    }
+}
+
+/// Bars between progress events (about a trading week at 10 minutes)
+pub const PROGRESS_BARS: usize = 690;
+
+/// Cancel tokens of the runs in progress, by job id
+#[derive(Debug, Default)]
+pub struct Jobs {
+    last_id: AtomicU64,
+    running: Mutex<HashMap<u64, CancelToken>>,
+}
+
+impl Jobs {
+    pub fn start(&self) -> (u64, CancelToken) {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let cancel = CancelToken::new();
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).insert(id, cancel.clone());
+        (id, cancel)
+    }
+
+    pub fn finish(&self, id: u64) {
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+    }
+
+    /// Whether `id` was still running
+    pub fn cancel(&self, id: u64) -> bool {
+        let running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        running.get(&id).map(CancelToken::cancel).is_some()
+    }
+}
+
+/// Result of `compare_strategies`
+#[derive(Debug, Serialize)]
+pub struct Comparison {
+    pub first: SimulationResult,
+    pub second: SimulationResult,
+    /// Days both runs reported, in order
+    pub daily: Vec<ComparisonPoint>,
+}
+
+/// Cumulative P&L of both runs at one day's close, in dollars
+#[derive(Debug, Serialize)]
+pub struct ComparisonPoint {
+    pub day: u32,
+    pub price: f64,
+    pub first_pnl: f64,
+    pub second_pnl: f64,
+    /// `second_pnl - first_pnl`
+    pub difference: f64,
+}
+
+/// Completed runs on disk plus the id counter
+#[derive(Debug)]
+pub struct History {
+    dir: PathBuf,
+    last_id: AtomicU64,
+}
+
+/// What `list_history` returns for each run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    pub strategy: String,
+    pub days: usize,
+    pub seed: u64,
+    pub net_pnl: f64,
+    pub position_count: u32,
+}
+
+/// A saved run: the resolved config and everything the UI was shown
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoredRun {
+    #[serde(flatten)]
+    pub entry: HistoryEntry,
+    pub config: Config,
+    pub result: SimulationResult,
+}
+
+/// `StoredRun` as written, borrowing the run instead of copying it
+#[derive(Serialize)]
+struct SavedRun<'a> {
+    #[serde(flatten)]
+    entry: HistoryEntry,
+    config: &'a Config,
+    result: &'a SimulationResult,
+}
+
+impl History {
+    /// Use `dir` (created if missing), continuing ids after the runs in it
+    pub fn open(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        let last_id = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok()?.path().file_stem()?.to_str()?.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        Ok(Self { dir, last_id: AtomicU64::new(last_id) })
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Store a finished run, returning its id
+    pub fn save(&self, strategy: &str, config: &Config, result: &mut SimulationResult) -> std::io::Result<u64> {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+        result.history_id = Some(id);
+        let entry = HistoryEntry {
+            id,
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+            strategy: strategy.to_string(),
+            days: config.simulation.days,
+            seed: config.simulation.seed,
+            net_pnl: result.net_pnl,
+            position_count: result.position_count,
+        };
+        let json = serde_json::to_vec(&SavedRun { entry, config, result }).map_err(std::io::Error::other)?;
+        // Write then rename, so a crash never leaves a half-written run behind
+        let partial = self.dir.join(format!("{}.json.partial", id));
+        std::fs::write(&partial, json)?;
+        std::fs::rename(&partial, self.path(id))?;
+        Ok(id)
+    }
+
+    /// Every stored run, newest first (unreadable files are skipped)
+    pub fn list(&self) -> std::io::Result<Vec<HistoryEntry>> {
+        let mut entries: Vec<HistoryEntry> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                path.extension().filter(|ext| *ext == "json")?;
+                serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+            })
+            .collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.id));
+        Ok(entries)
+    }
+
+    pub fn get(&self, id: u64) -> Result<StoredRun, String> {
+        let json = std::fs::read(self.path(id)).map_err(|e| format!("No saved run {}: {}", id, e))?;
+        serde_json::from_slice(&json).map_err(|e| format!("Saved run {} is unreadable: {}", id, e))
+    }
+}
+
+/// Totals over some of a run's legs, in dollars
+#[derive(Debug, Default)]
+struct LegTotals {
+    pnl: f64,
+    positions: u32,
+    closed: usize,
+    wins: usize,
+}
+
+impl LegTotals {
+    /// Sum the legs matching `include`
+    fn of<'a>(legs: &'a [LegReport], include: impl Fn(&'a LegReport) -> bool) -> Self {
+        legs.iter().filter(|leg| include(leg)).fold(Self::default(), |mut totals, leg| {
+            totals.pnl += leg.report.net_pnl() * leg.config.simulation.contract_multiplier;
+            totals.positions += leg.report.pnl_summary.position_count;
+            totals.closed += leg.report.trade_records.len();
+            totals.wins += leg.report.trade_records.iter().filter(|t| t.is_win()).count();
+            totals
+        })
+    }
+
+    /// Percentage of closed trades that won
+    fn win_rate(&self) -> f64 {
+        if self.closed > 0 {
+            self.wins as f64 / self.closed as f64 * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Run `config` over a two-parameter grid, `seeds` runs per point, calling
+/// `on_progress` as each simulation finishes
+pub fn run_sweep<F>(
+    config: &SimulationConfig,
+    x: SweepAxis,
+    y: SweepAxis,
+    seeds: Option<u64>,
+    on_progress: F,
+) -> Result<SweepGrid, String>
+where
+    F: Fn(SweepProgress) + Sync,
+{
+    let seeds = seeds.unwrap_or(1).max(1);
+    if seeds > MAX_SEEDS {
+        return Err(format!("seeds must be between 1 and {}", MAX_SEEDS));
+    }
+    let base = create_config_from_ui(config).map_err(|e| format!("Invalid configuration: {}", e))?;
+    let (x_values, y_values) = (x.values()?, y.values()?);
+    let sweep = Sweep::new(&base)
+        .parameter(&x.path, x_values.clone())
+        .parameter(&y.path, y_values.clone())
+        .seeds(seeds);
+    if sweep.run_count().is_none_or(|runs| runs > MAX_SWEEP_RUNS) {
+        return Err(format!("a sweep may run at most {} simulations (points × seeds)", MAX_SWEEP_RUNS));
+    }
+    let summaries = sweep.run_with_progress(on_progress).map_err(|e| format!("Sweep failed: {}", e))?.summaries();
+
+    let mut points = summaries.into_iter();
+    let cells = x_values
+        .iter()
+        .map(|_| {
+            points
+                .by_ref()
+                .take(y_values.len())
+                .map(|point| SweepCell { pnl: point.pnl, max_drawdown: point.max_drawdown })
+                .collect()
+        })
+        .collect();
+    Ok(SweepGrid {
+        x: SweptAxis { path: x.path, values: x_values },
+        y: SweptAxis { path: y.path, values: y_values },
+        seeds,
+        cells,
+    })
+}
+
+/// Run `config` over `seeds` consecutive seeds (from its own seed), in
+/// parallel, for the P&L and drawdown distributions
+pub fn explore_seeds<F>(config: &SimulationConfig, seeds: u64, on_progress: F) -> Result<SeedExploration, String>
+where
+    F: Fn(SweepProgress) + Sync,
+{
+    if seeds == 0 || seeds > MAX_SEEDS {
+        return Err(format!("seeds must be between 1 and {}", MAX_SEEDS));
+    }
+    let config = create_config_from_ui(config).map_err(|e| format!("Invalid configuration: {}", e))?;
+    let first_seed = config.simulation.seed;
+    let result = montecarlo::run_with_progress(&config, first_seed, seeds, on_progress)
+        .map_err(|e| format!("Simulation failed: {}", e))?;
+
+    let pnl: Vec<f64> = result.runs.iter().map(|run| run.pnl).collect();
+    Ok(SeedExploration {
+        first_seed,
+        runs: result
+            .runs
+            .iter()
+            .map(|run| SeedRun { seed: run.seed, pnl: run.pnl, max_drawdown: run.max_drawdown })
+            .collect(),
+        loss_probability: result.loss_probability(),
+        pnl: result.pnl,
+        max_drawdown: result.max_drawdown,
+        pnl_histogram: histogram(&pnl, HISTOGRAM_BINS),
+    })
+}
+
+/// Open a YAML config file (validated, as the CLI loads it)
+pub fn load_config(path: &str) -> Result<Config, String> {
+    Config::from_file(path).map_err(|e| format!("Failed to load {}: {}", path, e))
+}
+
+/// Write a config as YAML, refusing one the CLI couldn't load back
+pub fn save_config(path: &str, config: &Config) -> Result<(), String> {
+    config.validate().map_err(|e| format!("Invalid configuration: {}", e))?;
+    config.save_to_file(path).map_err(|e| format!("Failed to save {}: {}", path, e))
+}
+
+/// Build the full `Config` for the selected preset with every UI field applied
+pub fn create_config_from_ui(config: &SimulationConfig) -> Result<Config, String> {
+    // Create appropriate config based on strategy selection
+    let cfg = match config.strategy.as_str() {
+        "long_protection" => create_long_protection_config(config),
+        "combined" => create_combined_config(config),
+        _ => create_straddle_config(config),
+    };
+    cfg.validate().map_err(|e| e.to_string())?;
+    Ok(cfg)
+}
+
+fn create_straddle_config(config: &SimulationConfig) -> Config {
+    let mut cfg = Config::default_1dte_straddle();
+    apply_ui_parameters(&mut cfg, config);
+    cfg
+}
+
+fn create_long_protection_config(config: &SimulationConfig) -> Config {
+    let mut cfg = Config::default_1dte_straddle();
+    cfg.strategy = long_protection_strategy();
+    apply_ui_parameters(&mut cfg, config);
+    cfg
+}
+
+fn create_combined_config(config: &SimulationConfig) -> Config {
+    let mut cfg = Config::default_1dte_straddle();
+    apply_ui_parameters(&mut cfg, config);
+    cfg.short_leg = Some(cfg.strategy.clone());
+    if config.enable_long_leg {
+        let mut long_leg = long_protection_strategy();
+        config.long_leg.apply(&mut long_leg);
+        cfg.long_leg = Some(long_leg);
+    }
+    cfg
+}
+
+/// Copy the UI's simulation parameters and strategy changes onto a config
+fn apply_ui_parameters(cfg: &mut Config, config: &SimulationConfig) {
+    cfg.simulation.days = config.days;
+    cfg.simulation.initial_price = config.initial_price;
+    cfg.simulation.volatility = config.volatility;
+    cfg.simulation.volatility_risk_premium = config.vrp;
+    cfg.simulation.seed = config.seed;
+    config.overrides.apply(&mut cfg.strategy);
+    if let Some(strike_config) = &config.strike_config {
+        cfg.strike_config = strike_config.clone();
+    }
+}
+
+/// 70 DTE long OTM straddle, rolled at 28 DTE
+fn long_protection_strategy() -> StrategyConfig {
+    let mut strategy = Config::default_1dte_straddle().strategy;
+    strategy.entry_dte = 70;
+    strategy.strike_selection = "OTM".to_string();
+    strategy.strike_offset = 3.0;
+    strategy.side = "long".to_string();
+    strategy.roll_triggers = vec![RollTriggerConfig {
+        trigger_type: "dte".to_string(),
+        value: 28.0,
+        legs: "both".to_string(),
+    }];
+    strategy
+}
+
+/// Generate `config`'s price path and run it, passing each leg's stream events to `on_event`
+pub fn run_simulation_with_config<F>(
+    config: &Config,
+    cancel: &CancelToken,
+    on_event: F,
+) -> Result<SimulationResult, String>
+where
+    F: FnMut(&'static str, &Config, StreamEvent),
+{
+    let path = PricePath::generate(config).map_err(|e| e.to_string())?;
+    run_on_path(config, &path, cancel, on_event)
+}
+
+/// Run `config` over an existing price path and summarize it for the UI
+pub fn run_on_path<F>(
+    config: &Config,
+    path: &PricePath,
+    cancel: &CancelToken,
+    mut on_event: F,
+) -> Result<SimulationResult, String>
+where
+    F: FnMut(&'static str, &Config, StreamEvent),
+{
+    // Combined configs run each enabled leg on the shared price path
+    let legs: Vec<LegReport> = if config.short_leg.is_some() || config.long_leg.is_some() {
+        [("short", &config.short_leg), ("long", &config.long_leg)]
+            .into_iter()
+            .filter_map(|(name, leg)| leg.as_ref().filter(|l| l.enabled).map(|l| (name, l)))
+            .map(|(name, leg)| {
+                let mut leg_config = config.clone();
+                leg_config.strategy = leg.clone();
+                let report = Simulator::with_path(leg_config.clone(), path.clone())
+                    .and_then(|simulator| {
+                        simulator.run_streaming_cancellable(PROGRESS_BARS, cancel, |event| {
+                            on_event(name, &leg_config, event)
+                        })
+                    })
+                    .map_err(|e| e.to_string())?;
+                Ok(LegReport { name, config: leg_config, report })
+            })
+            .collect::<Result<_, String>>()?
+    } else {
+        let report = Simulator::with_path(config.clone(), path.clone())
+            .and_then(|simulator| {
+                simulator.run_streaming_cancellable(PROGRESS_BARS, cancel, |event| on_event("single", config, event))
+            })
+            .map_err(|e| e.to_string())?;
+        vec![LegReport { name: "single", config: config.clone(), report }]
+    };
+    if legs.is_empty() {
+        return Err("no enabled legs".to_string());
+    }
+    let all = LegTotals::of(&legs, |_| true);
+    let short = LegTotals::of(&legs, |leg| leg.config.strategy.side() == Side::Short);
+    let long = LegTotals::of(&legs, |leg| leg.config.strategy.side() == Side::Long);
+    let days = config.simulation.days.max(1) as f64;
+
+    let trades = legs
+        .iter()
+        .flat_map(|leg| leg.report.log.iter().map(move |entry| trade_entry(entry, &leg.config)))
+        .collect();
+    let (daily, positions) = chart_series(&legs);
+
+    Ok(SimulationResult {
+        net_pnl: all.pnl,
+        position_count: all.positions,
+        win_rate: all.win_rate(),
+        final_price: legs[0].report.final_price.unwrap_or(config.simulation.initial_price),
+        trades,
+        short_pnl_per_day: short.pnl / days,
+        long_pnl_per_day: long.pnl / days,
+        total_pnl_per_day: all.pnl / days,
+        short_positions: short.positions,
+        long_positions: long.positions,
+        short_win_rate: short.win_rate(),
+        long_win_rate: long.win_rate(),
+        daily,
+        positions,
+        history_id: None,
+    })
+}
+
+/// Run two configs over one price path (generated from the first config's
+/// seed and market parameters) and line up their daily P&L
+pub fn compare_strategies(first: &SimulationConfig, second: &SimulationConfig) -> Result<Comparison, String> {
+    let first_config = create_config_from_ui(first).map_err(|e| format!("Invalid first configuration: {}", e))?;
+    let second_config = create_config_from_ui(second).map_err(|e| format!("Invalid second configuration: {}", e))?;
+    if first_config.simulation.days != second_config.simulation.days
+        || first_config.simulation.intraday_resolution_minutes != second_config.simulation.intraday_resolution_minutes
+    {
+        return Err("Both configurations must simulate the same days at the same resolution".to_string());
+    }
+    let path = PricePath::generate(&first_config).map_err(|e| format!("Simulation failed: {}", e))?;
+    let cancel = CancelToken::new();
+    let run = |config: &Config| {
+        run_on_path(config, &path, &cancel, |_, _, _| {}).map_err(|e| format!("Simulation failed: {}", e))
+    };
+    let (first, second) = (run(&first_config)?, run(&second_config)?);
+
+    let second_by_day: HashMap<u32, f64> = second.daily.iter().map(|p| (p.day, p.total_pnl)).collect();
+    let daily = first
+        .daily
+        .iter()
+        .filter_map(|point| {
+            let second_pnl = *second_by_day.get(&point.day)?;
+            Some(ComparisonPoint {
+                day: point.day,
+                price: point.price,
+                first_pnl: point.total_pnl,
+                second_pnl,
+                difference: second_pnl - point.total_pnl,
+            })
+        })
+        .collect();
+    Ok(Comparison { first, second, daily })
+}
+
+/// Daily points (legs share one price path, so their days line up) and position markers
+fn chart_series(legs: &[LegReport]) -> (Vec<DailyPoint>, Vec<PositionMarker>) {
+    let mut daily: BTreeMap<u32, DailyPoint> = BTreeMap::new();
+    let mut positions = Vec::new();
+    for leg in legs {
+        let multiplier = leg.config.simulation.contract_multiplier;
+        for close in daily_closes(&leg.report.equity_series) {
+            let point = daily.entry(close.timestamp.day).or_default();
+            let pnl = close.equity() * multiplier;
+            point.day = close.timestamp.day;
+            point.price = close.price;
+            if leg.config.strategy.side() == Side::Long {
+                point.long_pnl += pnl;
+            } else {
+                point.short_pnl += pnl;
+            }
+            point.total_pnl += pnl;
+        }
+        positions.extend(leg.report.trade_records.iter().map(|trade| PositionMarker {
+            leg: leg.name.to_string(),
+            position_id: trade.position_id,
+            side: trade.side,
+            entry_day: trade.entry.day,
+            entry_price: trade.entry_price,
+            close_day: trade.close.day,
+            close_price: trade.close_price,
+            pnl: trade.pnl * multiplier,
+        }));
+    }
+    (daily.into_values().collect(), positions)
+}
+
+fn trade_entry(entry: &LogEntry, config: &Config) -> TradeEntry {
+    TradeEntry {
+        trade_type: match entry {
+            LogEntry::Opened { .. } => "open",
+            LogEntry::LegRolled { .. } => "leg_roll",
+            LogEntry::Closed { .. } => "close",
+            LogEntry::Rejected { .. } => "rejected",
+        }
+        .to_string(),
+        message: presenter::entry_line(entry, config).trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ui_config(strategy: &str) -> SimulationConfig {
+        SimulationConfig {
+            days: 5,
+            initial_price: 75.0,
+            volatility: 0.3,
+            vrp: 0.05,
+            seed: 42,
+            strategy: strategy.to_string(),
+            enable_long_leg: true,
+            overrides: StrategyOverrides::default(),
+            long_leg: StrategyOverrides::default(),
+            strike_config: None,
+        }
+    }
+
+    fn axis(path: &str, start: f64, end: f64, step: f64) -> SweepAxis {
+        SweepAxis { path: path.to_string(), start, end, step }
+    }
+
+    #[test]
+    fn test_presets_take_ui_fields() {
+        let mut ui = ui_config("combined");
+        ui.overrides.entry_dte = Some(2);
+        ui.long_leg.strike_offset = Some(5.0);
+        let config = create_config_from_ui(&ui).unwrap();
+        assert_eq!(config.simulation.days, 5);
+        assert_eq!(config.short_leg.as_ref().unwrap().entry_dte, 2);
+        assert_eq!(config.long_leg.as_ref().unwrap().strike_offset, 5.0);
+        assert_eq!(create_config_from_ui(&ui_config("long_protection")).unwrap().strategy.entry_dte, 70);
+    }
+
+    #[test]
+    fn test_run_reports_daily_points_and_splits_legs() {
+        let config = create_config_from_ui(&ui_config("combined")).unwrap();
+        let mut events = 0;
+        let result = run_simulation_with_config(&config, &CancelToken::new(), |_, _, _| events += 1).unwrap();
+        assert!(events > 0);
+        assert!(!result.daily.is_empty());
+        assert!((result.short_pnl_per_day + result.long_pnl_per_day - result.total_pnl_per_day).abs() < 1e-6);
+        let last = result.daily.last().unwrap();
+        assert!((last.total_pnl - result.net_pnl).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sweep_grid_shape_and_limits() {
+        let ui = ui_config("straddle");
+        let x = axis("simulation.volatility_risk_premium", 0.0, 0.1, 0.05);
+        let y = axis("strategy.entry_dte", 1.0, 2.0, 1.0);
+        let grid = run_sweep(&ui, x, y, Some(2), |_| {}).unwrap();
+        assert_eq!(grid.x.values.len(), 3);
+        assert_eq!(grid.cells.len(), 3);
+        assert!(grid.cells.iter().all(|row| row.len() == 2));
+
+        let wide = axis("strategy.entry_dte", 1.0, 100.0, 1.0);
+        assert!(run_sweep(&ui, wide, axis("strategy.entry_dte", 1.0, 1.0, 1.0), None, |_| {}).is_err());
+        let bad_step = axis("strategy.entry_dte", 1.0, 2.0, 0.0);
+        assert!(run_sweep(&ui, bad_step, axis("strategy.entry_dte", 1.0, 1.0, 1.0), None, |_| {}).is_err());
+        let one = || axis("strategy.entry_dte", 1.0, 1.0, 1.0);
+        assert!(run_sweep(&ui, axis("strategy.entry_dte", 0.0, 1e20, 1.0), one(), None, |_| {}).is_err());
+        assert!(run_sweep(&ui, one(), one(), Some(MAX_SEEDS + 1), |_| {}).is_err());
+        // 50 × 50 points over 10 seeds is within each cap but over the run limit
+        let (x, y) = (axis("strategy.entry_dte", 1.0, 50.0, 1.0), axis("strategy.strike_offset", 1.0, 50.0, 1.0));
+        assert!(run_sweep(&ui, x, y, Some(10), |_| {}).unwrap_err().contains("at most"));
+    }
+
+    #[test]
+    fn test_explore_seeds_bounds_and_histogram() {
+        let ui = ui_config("straddle");
+        assert!(explore_seeds(&ui, 0, |_| {}).is_err());
+        assert!(explore_seeds(&ui, MAX_SEEDS + 1, |_| {}).is_err());
+        let exploration = explore_seeds(&ui, 4, |_| {}).unwrap();
+        assert_eq!(exploration.runs.len(), 4);
+        assert_eq!(exploration.pnl_histogram.iter().map(|bin| bin.count).sum::<usize>(), 4);
+
+        assert_eq!(histogram(&[1.0, 1.0], 5).len(), 1);
+        let bins = histogram(&[0.0, 0.5, 10.0], 10);
+        assert_eq!((bins[0].count, bins[9].count), (2, 1));
+    }
+
+    #[test]
+    fn test_compare_needs_matching_days() {
+        let (first, mut second) = (ui_config("straddle"), ui_config("long_protection"));
+        let comparison = compare_strategies(&first, &second).unwrap();
+        assert_eq!(comparison.daily.len(), comparison.first.daily.len());
+        second.days = 6;
+        assert!(compare_strategies(&first, &second).is_err());
+    }
+
+    #[test]
+    fn test_jobs_cancel_only_running() {
+        let jobs = Jobs::default();
+        let (id, cancel) = jobs.start();
+        assert!(jobs.cancel(id));
+        assert!(cancel.is_cancelled());
+        jobs.finish(id);
+        assert!(!jobs.cancel(id));
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tsv2_history_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let history = History::open(dir.clone()).unwrap();
+        let config = create_config_from_ui(&ui_config("straddle")).unwrap();
+        let mut result = run_simulation_with_config(&config, &CancelToken::new(), |_, _, _| {}).unwrap();
+        let id = history.save("straddle", &config, &mut result).unwrap();
+        assert_eq!(result.history_id, Some(id));
+        assert_eq!(history.list().unwrap()[0].id, id);
+        assert_eq!(history.get(id).unwrap().result.net_pnl, result.net_pnl);
+
+        // Reopening continues the ids
+        assert_eq!(History::open(dir.clone()).unwrap().save("straddle", &config, &mut result).unwrap(), id + 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,350 @@
+//! Programmatic Simulation Builder
+//!
+//! Typed alternative to writing YAML: pick a price model, strategy and roll
+//! triggers in code and get a validated `Config` (or a ready `Simulator`).
+//! YAML is just another way to seed the builder via `from_config`.
+
+use super::{Simulator, StrikeSelection};
+use crate::calendar::TimeOfDay;
+use crate::config::{Config, RollTriggerConfig, StrategyConfig};
+use crate::error::SimError;
+use crate::events::Side;
+use crate::prices::RngKind;
+use std::path::Path;
+
+/// Underlying price process
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceModel {
+    /// Geometric Brownian motion with annual drift and volatility
+    Gbm {
+        initial_price: f64,
+        drift: f64,
+        volatility: f64,
+    },
+}
+
+impl PriceModel {
+    /// GBM starting at `initial_price`
+    pub fn gbm(initial_price: f64, drift: f64, volatility: f64) -> Self {
+        PriceModel::Gbm { initial_price, drift, volatility }
+    }
+}
+
+/// Straddle/strangle strategy parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategySpec {
+    pub side: Side,
+    pub entry_dte: u32,
+    pub entry_time: TimeOfDay,
+    pub roll_time: TimeOfDay,
+    pub strikes: StrikeSelection,
+}
+
+impl StrategySpec {
+    /// ATM straddle entered at 15:00 and rolled at 14:00
+    pub fn straddle(side: Side, entry_dte: u32) -> Self {
+        Self {
+            side,
+            entry_dte,
+            entry_time: TimeOfDay::hm(15, 0),
+            roll_time: TimeOfDay::hm(14, 0),
+            strikes: StrikeSelection::Atm,
+        }
+    }
+
+    pub fn entry_time(mut self, hour: u16, minute: u16) -> Self {
+        self.entry_time = TimeOfDay::hm(hour, minute);
+        self
+    }
+
+    pub fn roll_time(mut self, hour: u16, minute: u16) -> Self {
+        self.roll_time = TimeOfDay::hm(hour, minute);
+        self
+    }
+
+    pub fn strikes(mut self, strikes: StrikeSelection) -> Self {
+        self.strikes = strikes;
+        self
+    }
+}
+
+/// Which legs a trigger applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerLegs {
+    Both,
+    Put,
+    Call,
+}
+
+/// Roll trigger condition
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerKind {
+    /// Roll at this hour on expiration day
+    Time { hour: f64 },
+    /// Roll when remaining DTE falls to this value
+    Dte { days: f64 },
+    /// Roll at this fraction of max profit (e.g. 0.5)
+    ProfitTarget { fraction: f64 },
+    /// Roll when the loss reaches this multiple of the entry premium
+    StopLoss { multiple: f64 },
+}
+
+/// A roll trigger and the legs it applies to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollTrigger {
+    pub kind: TriggerKind,
+    pub legs: TriggerLegs,
+}
+
+impl RollTrigger {
+    pub fn time(hour: f64) -> Self {
+        Self { kind: TriggerKind::Time { hour }, legs: TriggerLegs::Both }
+    }
+
+    pub fn dte(days: f64) -> Self {
+        Self { kind: TriggerKind::Dte { days }, legs: TriggerLegs::Both }
+    }
+
+    pub fn profit_target(fraction: f64) -> Self {
+        Self { kind: TriggerKind::ProfitTarget { fraction }, legs: TriggerLegs::Both }
+    }
+
+    pub fn stop_loss(multiple: f64) -> Self {
+        Self { kind: TriggerKind::StopLoss { multiple }, legs: TriggerLegs::Both }
+    }
+
+    /// Restrict the trigger to one leg
+    pub fn legs(mut self, legs: TriggerLegs) -> Self {
+        self.legs = legs;
+        self
+    }
+
+    fn to_config(self) -> RollTriggerConfig {
+        let (trigger_type, value) = match self.kind {
+            TriggerKind::Time { hour } => ("time", hour),
+            TriggerKind::Dte { days } => ("dte", days),
+            TriggerKind::ProfitTarget { fraction } => ("profit_target", fraction),
+            TriggerKind::StopLoss { multiple } => ("stop_loss", multiple),
+        };
+        let legs = match self.legs {
+            TriggerLegs::Both => "both",
+            TriggerLegs::Put => "put",
+            TriggerLegs::Call => "call",
+        };
+        RollTriggerConfig {
+            trigger_type: trigger_type.to_string(),
+            value,
+            legs: legs.to_string(),
+        }
+    }
+}
+
+/// Builder for a simulation, starting from the default 1DTE straddle
+#[derive(Debug, Clone)]
+pub struct SimulationBuilder {
+    config: Config,
+}
+
+impl Default for SimulationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimulationBuilder {
+    pub fn new() -> Self {
+        Self::from_config(Config::default_1dte_straddle())
+    }
+
+    /// Start from an existing (e.g. YAML-loaded) configuration
+    pub fn from_config(config: Config) -> Self {
+        Self { config }
+    }
+
+    /// Start from a YAML file
+    pub fn from_yaml_file<P: AsRef<Path>>(path: P) -> Result<Self, SimError> {
+        Ok(Self::from_config(Config::from_file(path)?))
+    }
+
+    pub fn price_model(mut self, model: PriceModel) -> Self {
+        match model {
+            PriceModel::Gbm { initial_price, drift, volatility } => {
+                self.config.simulation.initial_price = initial_price;
+                self.config.simulation.drift = drift;
+                self.config.simulation.volatility = volatility;
+            }
+        }
+        self
+    }
+
+    pub fn strategy(mut self, spec: StrategySpec) -> Self {
+        let strategy = &mut self.config.strategy;
+        strategy.side = match spec.side {
+            Side::Long => "long",
+            Side::Short => "short",
+        }
+        .to_string();
+        strategy.entry_dte = spec.entry_dte;
+        strategy.entry_time = spec.entry_time.to_string();
+        strategy.roll_time = spec.roll_time.to_string();
+        match spec.strikes {
+            StrikeSelection::Atm => {
+                strategy.strategy_type = "straddle".to_string();
+                strategy.strike_selection = "ATM".to_string();
+                strategy.strike_offset = 0.0;
+            }
+            StrikeSelection::Otm { offset } => {
+                strategy.strategy_type = "strangle".to_string();
+                strategy.strike_selection = "OTM".to_string();
+                strategy.strike_offset = offset;
+            }
+            selection @ (StrikeSelection::Delta { .. } | StrikeSelection::Premium { .. }) => {
+                strategy.strategy_type = "strangle".to_string();
+                strategy.strike_selection = selection.to_config();
+                strategy.strike_offset = 0.0;
+            }
+        }
+        self
+    }
+
+    pub fn triggers<I: IntoIterator<Item = RollTrigger>>(mut self, triggers: I) -> Self {
+        self.config.strategy.roll_triggers = triggers.into_iter().map(RollTrigger::to_config).collect();
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.simulation.seed = seed;
+        self
+    }
+
+    /// RNG for the price path (see `prices` for the seeding scheme)
+    pub fn rng(mut self, rng: RngKind) -> Self {
+        self.config.simulation.rng = match rng {
+            RngKind::Std => "std",
+            RngKind::Xoshiro => "xoshiro",
+        }
+        .to_string();
+        self
+    }
+
+    pub fn days(mut self, days: usize) -> Self {
+        self.config.simulation.days = days;
+        self
+    }
+
+    /// Added to realized volatility for option pricing
+    pub fn volatility_risk_premium(mut self, vrp: f64) -> Self {
+        self.config.simulation.volatility_risk_premium = vrp;
+        self
+    }
+
+    pub fn risk_free_rate(mut self, rate: f64) -> Self {
+        self.config.simulation.risk_free_rate = rate;
+        self
+    }
+
+    pub fn contract_multiplier(mut self, multiplier: f64) -> Self {
+        self.config.simulation.contract_multiplier = multiplier;
+        self
+    }
+
+    /// Enable (or replace) the short leg of a combined strategy
+    pub fn short_leg(mut self, spec: StrategySpec, triggers: &[RollTrigger]) -> Self {
+        self.config.short_leg = Some(self.leg_config(spec, triggers));
+        self
+    }
+
+    /// Enable (or replace) the long leg of a combined strategy
+    pub fn long_leg(mut self, spec: StrategySpec, triggers: &[RollTrigger]) -> Self {
+        self.config.long_leg = Some(self.leg_config(spec, triggers));
+        self
+    }
+
+    fn leg_config(&self, spec: StrategySpec, triggers: &[RollTrigger]) -> StrategyConfig {
+        Self::from_config(self.config.clone())
+            .strategy(spec)
+            .triggers(triggers.iter().copied())
+            .config
+            .strategy
+    }
+
+    /// Validate and return the configuration
+    pub fn build_config(self) -> Result<Config, SimError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+
+    /// Validate and return a simulator ready to step
+    pub fn build(self) -> Result<Simulator, SimError> {
+        Simulator::new(self.build_config()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigError;
+    use crate::engine::{CloseCause, LogEntry, SimulationEngine, SimulationReport};
+
+    #[test]
+    fn test_builder_sets_config() {
+        let config = SimulationBuilder::new()
+            .price_model(PriceModel::gbm(62.0, 0.0, 0.25))
+            .strategy(
+                StrategySpec::straddle(Side::Long, 70)
+                    .entry_time(9, 30)
+                    .strikes(StrikeSelection::Otm { offset: 3.0 }),
+            )
+            .seed(7)
+            .build_config()
+            .unwrap();
+
+        assert_eq!(config.simulation.initial_price, 62.0);
+        assert_eq!(config.simulation.seed, 7);
+        assert_eq!(config.strategy.side, "long");
+        assert_eq!(config.strategy.entry_time, "09:30");
+        assert_eq!(config.strategy.strike_selection, "OTM");
+    }
+
+    #[test]
+    fn test_builder_triggers_change_the_trades() {
+        let run = |triggers: &[RollTrigger]| {
+            SimulationBuilder::new().triggers(triggers.iter().copied()).build().unwrap().finish().unwrap()
+        };
+        let scheduled = run(&[RollTrigger::time(14.0)]);
+        let target = run(&[RollTrigger::profit_target(0.01), RollTrigger::time(14.0)]);
+        let stop = run(&[RollTrigger::stop_loss(0.01), RollTrigger::time(14.0)]);
+
+        // Tight targets and stops close positions before their scheduled roll
+        for report in [&target, &stop] {
+            assert!(report.trade_records.len() > scheduled.trade_records.len());
+            assert_ne!(report.net_pnl(), scheduled.net_pnl());
+        }
+        // Scheduled rolls settle on expiration day; the triggers close before it
+        let early = |report: &SimulationReport| -> Vec<f64> {
+            let early_close = |entry: &LogEntry| match entry {
+                LogEntry::Closed { cause: CloseCause::Roll, pnl, .. } => Some(*pnl),
+                _ => None,
+            };
+            report.log.iter().filter_map(early_close).collect()
+        };
+        assert!(early(&scheduled).is_empty());
+        assert!(!early(&target).is_empty() && early(&target).iter().all(|pnl| *pnl > 0.0));
+        assert!(!early(&stop).is_empty() && early(&stop).iter().all(|pnl| *pnl < 0.0));
+    }
+
+    #[test]
+    fn test_builder_validates() {
+        let result = SimulationBuilder::new()
+            .price_model(PriceModel::gbm(75.0, 0.0, -0.1))
+            .build_config();
+        assert!(matches!(result, Err(SimError::Config(ConfigError::Validation(_)))));
+    }
+
+    #[test]
+    fn test_default_builder_matches_default_config() {
+        let built = SimulationBuilder::new().build().unwrap().finish().unwrap();
+        let default = SimulationEngine::run(&Config::default_1dte_straddle()).unwrap();
+        assert_eq!(built.net_pnl(), default.net_pnl());
+    }
+}
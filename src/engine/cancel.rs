@@ -0,0 +1,83 @@
+//! Cooperative Cancellation
+//!
+//! A `CancelToken` is shared between whoever may cancel a run (a web job, a
+//! UI button) and the simulator, which checks it between bars and stops with
+//! `SimError::Cancelled`. Nothing is interrupted mid-bar, so a cancelled run
+//! never leaves half-applied state behind.
+
+use super::{LegReport, PricePath, SimulationEngine, SimulationReport, Simulator};
+use crate::config::Config;
+use crate::error::SimError;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag asking a running simulation to stop
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Simulator {
+    /// Run to completion unless `cancel` is set, checked before every bar
+    pub fn run_cancellable(mut self, cancel: &CancelToken) -> Result<SimulationReport, SimError> {
+        loop {
+            if cancel.is_cancelled() {
+                return Err(SimError::Cancelled);
+            }
+            if self.step()?.is_none() {
+                return self.finish();
+            }
+        }
+    }
+}
+
+impl SimulationEngine {
+    /// `run_legs` that stops with `SimError::Cancelled` once `cancel` is set
+    pub fn run_legs_cancellable(config: &Config, cancel: &CancelToken) -> Result<Vec<LegReport>, SimError> {
+        let path = PricePath::generate(config)?;
+        [("short", &config.short_leg), ("long", &config.long_leg)]
+            .into_iter()
+            .filter_map(|(name, leg)| leg.as_ref().filter(|l| l.enabled).map(|l| (name, l)))
+            .map(|(name, leg)| {
+                let mut leg_config = config.clone();
+                leg_config.strategy = leg.clone();
+                let report = Simulator::with_path(leg_config.clone(), path.clone())?.run_cancellable(cancel)?;
+                Ok(LegReport { name, config: leg_config, report })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uncancelled_run_matches_batch() {
+        let config = Config::default_1dte_straddle();
+        let report = Simulator::new(config.clone()).unwrap().run_cancellable(&CancelToken::new()).unwrap();
+        assert_eq!(report.digest(), SimulationEngine::run(&config).unwrap().digest());
+    }
+
+    #[test]
+    fn test_cancelled_run_stops() {
+        let cancel = CancelToken::new();
+        let mut simulator = Simulator::new(Config::default_1dte_straddle()).unwrap();
+        let flag = cancel.clone();
+        simulator.on_bar(move |_, _| flag.cancel());
+        assert!(matches!(simulator.run_cancellable(&cancel), Err(SimError::Cancelled)));
+        assert!(cancel.is_cancelled());
+    }
+}
@@ -0,0 +1,185 @@
+//! Report Digests
+//!
+//! A stable 64-bit FNV-1a hash of a run's trade log, closed trades and P&L
+//! summary. Floats are hashed by bit pattern, so any numerical change shows
+//! up. `config/golden_digests.txt` records the digests of the reference
+//! configs; regenerate an entry with `cargo run -- <config.yaml> --digest`.
+
+use super::{CloseCause, LogEntry, SimulationReport};
+use crate::calendar::intraday::Timestamp;
+use crate::events::{OptionType, Side};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a, which unlike `DefaultHasher` is stable across Rust releases
+struct Fnv(u64);
+
+impl Fnv {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.u64(value.to_bits());
+    }
+
+    fn timestamp(&mut self, ts: Timestamp) {
+        self.u64(ts.total_minutes());
+    }
+}
+
+impl SimulationReport {
+    /// Hex digest of the trade log, closed trades and P&L summary
+    pub fn digest(&self) -> String {
+        let mut h = Fnv(FNV_OFFSET);
+
+        for entry in &self.log {
+            match entry {
+                LogEntry::Opened {
+                    timestamp,
+                    position_id,
+                    price,
+                    legs,
+                    premium,
+                    greeks,
+                    rolled,
+                    same_strikes,
+                    ..
+                } => {
+                    h.bytes(b"open");
+                    h.timestamp(*timestamp);
+                    h.u64(*position_id);
+                    h.f64(*price);
+                    for leg in legs {
+                        h.f64(leg.strike);
+                    }
+                    h.f64(*premium);
+                    for value in [greeks.delta, greeks.gamma, greeks.theta, greeks.vega, greeks.rho] {
+                        h.f64(value);
+                    }
+                    h.bytes(&[*rolled as u8, *same_strikes as u8]);
+                }
+                LogEntry::LegRolled { timestamp, position_id, price, option_type, old_strike, new_strike, pnl } => {
+                    h.bytes(b"leg_roll");
+                    h.timestamp(*timestamp);
+                    h.u64(*position_id);
+                    h.bytes(&[(*option_type == OptionType::Call) as u8]);
+                    for value in [*price, *old_strike, *new_strike, *pnl] {
+                        h.f64(value);
+                    }
+                }
+                LogEntry::Closed { timestamp, position_id, price, pnl, cause } => {
+                    h.bytes(b"close");
+                    h.timestamp(*timestamp);
+                    h.u64(*position_id);
+                    h.f64(*price);
+                    h.f64(*pnl);
+                    h.bytes(&[match cause {
+                        CloseCause::Roll => 0,
+                        CloseCause::Expiration => 1,
+                        CloseCause::KillSwitch => 2,
+                        CloseCause::DailyLoss => 3,
+                    }]);
+                }
+                LogEntry::Rejected { timestamp, price, rolled, reason } => {
+                    h.bytes(b"reject");
+                    h.timestamp(*timestamp);
+                    h.f64(*price);
+                    h.bytes(&[*rolled as u8]);
+                    h.bytes(reason.as_bytes());
+                }
+            }
+        }
+
+        for trade in &self.trade_records {
+            h.bytes(b"trade");
+            h.u64(trade.position_id);
+            h.bytes(&[matches!(trade.side, Side::Long) as u8]);
+            h.timestamp(trade.entry);
+            h.timestamp(trade.close);
+            for value in [
+                trade.entry_price,
+                trade.close_price,
+                trade.entry_premium,
+                trade.mid_value.unwrap_or(f64::NAN),
+                trade.close_value,
+                trade.pnl,
+                trade.implied_vol,
+                trade.realized_vol.unwrap_or(f64::NAN),
+            ] {
+                h.f64(value);
+            }
+        }
+
+        let pnl = &self.pnl_summary;
+        h.bytes(b"summary");
+        h.u64(u64::from(pnl.position_count));
+        for value in [
+            pnl.total_premium_collected,
+            pnl.total_premium_paid,
+            pnl.realized_pnl,
+            pnl.unrealized_pnl,
+            self.final_price.unwrap_or(f64::NAN),
+            self.implied_vol,
+        ] {
+            h.f64(value);
+        }
+        h.u64(self.total_bars as u64);
+
+        format!("{:016x}", h.0)
+    }
+}
+
+/// Hex FNV-1a hash of `bytes`
+pub(crate) fn fnv_hex(bytes: &[u8]) -> String {
+    let mut h = Fnv(FNV_OFFSET);
+    h.bytes(bytes);
+    format!("{:016x}", h.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::engine::SimulationEngine;
+
+    #[test]
+    fn test_digest_is_sensitive_to_results() {
+        let config = Config::default_1dte_straddle();
+        let digest = SimulationEngine::run(&config).unwrap().digest();
+        assert_eq!(digest.len(), 16);
+        assert_eq!(digest, SimulationEngine::run(&config).unwrap().digest());
+
+        let mut other = config.clone();
+        other.simulation.seed += 1;
+        assert_ne!(digest, SimulationEngine::run(&other).unwrap().digest());
+    }
+
+    #[test]
+    fn test_golden_digests() {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let golden = std::fs::read_to_string(format!("{}/config/golden_digests.txt", root)).unwrap();
+        let entries: Vec<(&str, &str)> = golden
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let mut fields = line.split_whitespace();
+                (fields.next().unwrap(), fields.next().expect("digest after config path"))
+            })
+            .collect();
+        assert!(!entries.is_empty());
+
+        for (path, expected) in entries {
+            let config = Config::from_file(format!("{}/{}", root, path)).unwrap();
+            let actual = SimulationEngine::run(&config).unwrap().digest();
+            assert_eq!(actual, expected, "{} changed; if intended, update config/golden_digests.txt", path);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Simulation Hooks
+//!
+//! Callbacks registered on a `Simulator` that run after each bar with a
+//! read-only view of the state, for custom analytics and monitoring
+//! without touching the main loop.
+
+use super::{BarOutcome, PositionTracking, SimulatorState};
+use crate::reporting::TradeRecord;
+
+type BarHook = Box<dyn FnMut(&SimulatorState<'_>, &BarOutcome) + Send>;
+type OpenHook = Box<dyn FnMut(&SimulatorState<'_>, &PositionTracking) + Send>;
+type RollHook = Box<dyn FnMut(&SimulatorState<'_>, &TradeRecord, &PositionTracking) + Send>;
+type CloseHook = Box<dyn FnMut(&SimulatorState<'_>, &TradeRecord) + Send>;
+
+/// Registered callbacks, called in registration order
+#[derive(Default)]
+pub struct Hooks {
+    pub(super) on_bar: Vec<BarHook>,
+    pub(super) on_position_opened: Vec<OpenHook>,
+    pub(super) on_roll: Vec<RollHook>,
+    pub(super) on_close: Vec<CloseHook>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.on_bar.is_empty()
+            && self.on_position_opened.is_empty()
+            && self.on_roll.is_empty()
+            && self.on_close.is_empty()
+    }
+
+    /// Fire the hooks for one processed bar. On a roll the order is
+    /// `on_close`, `on_roll`, `on_position_opened`, then `on_bar`.
+    pub(super) fn dispatch(&mut self, state: &SimulatorState<'_>, outcome: &BarOutcome) {
+        if let Some(trade) = &outcome.closed {
+            for hook in &mut self.on_close {
+                hook(state, trade);
+            }
+        }
+        if let Some(position) = state.position.filter(|_| outcome.opened.is_some()) {
+            if let Some(trade) = &outcome.closed {
+                for hook in &mut self.on_roll {
+                    hook(state, trade, position);
+                }
+            }
+            for hook in &mut self.on_position_opened {
+                hook(state, position);
+            }
+        }
+        for hook in &mut self.on_bar {
+            hook(state, outcome);
+        }
+    }
+}
+
+impl std::fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hooks")
+            .field("on_bar", &self.on_bar.len())
+            .field("on_position_opened", &self.on_position_opened.len())
+            .field("on_roll", &self.on_roll.len())
+            .field("on_close", &self.on_close.len())
+            .finish()
+    }
+}
@@ -0,0 +1,174 @@
+//! Position Lifecycle
+//!
+//! The simulator's book moves through explicit states instead of an
+//! `Option<PositionTracking>` that is taken and put back every bar:
+//!
+//! ```text
+//! PendingEntry → Open → Managing → PendingClose → Closed → PendingEntry
+//!                  ↑                                 │
+//!                  └──────────── roll ───────────────┘
+//! ```
+//!
+//! A position is `Open` on the bar it fills and `Managing` from the next bar
+//! on, while its roll and loss rules are checked. When one is due it is
+//! `PendingClose` until its close is recorded, then `Closed`; a roll reopens
+//! straight from `Closed` on the same bar, otherwise the book waits in
+//! `PendingEntry` from the next bar. Any other move is an error.
+
+use super::PositionTracking;
+use crate::calendar::intraday::Timestamp;
+use crate::error::SimError;
+use serde::Serialize;
+
+/// Where the book is in a position's life
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleState {
+    /// Flat, waiting for an entry
+    #[default]
+    PendingEntry,
+    /// Opened on the current bar
+    Open,
+    /// Held from an earlier bar
+    Managing,
+    /// A roll or liquidation is due and the close is being priced
+    PendingClose,
+    /// Closed on the current bar
+    Closed,
+}
+
+impl LifecycleState {
+    /// Name in reports (e.g. "pending_close")
+    pub fn name(&self) -> &'static str {
+        match self {
+            LifecycleState::PendingEntry => "pending_entry",
+            LifecycleState::Open => "open",
+            LifecycleState::Managing => "managing",
+            LifecycleState::PendingClose => "pending_close",
+            LifecycleState::Closed => "closed",
+        }
+    }
+}
+
+/// One change of lifecycle state
+#[derive(Debug, Clone, Copy, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LifecycleTransition {
+    pub timestamp: Timestamp,
+    /// The position the change is about (none when going back to waiting)
+    pub position_id: Option<u64>,
+    pub from: LifecycleState,
+    pub to: LifecycleState,
+}
+
+/// The book's state, its position and every transition so far
+#[derive(Debug, Default)]
+pub(super) struct Lifecycle {
+    state: LifecycleState,
+    position: Option<PositionTracking>,
+    transitions: Vec<LifecycleTransition>,
+}
+
+impl Lifecycle {
+    pub(super) fn state(&self) -> LifecycleState {
+        self.state
+    }
+
+    /// The position, from `Open` until it is closed
+    pub(super) fn position(&self) -> Option<&PositionTracking> {
+        self.position.as_ref()
+    }
+
+    pub(super) fn position_mut(&mut self) -> Option<&mut PositionTracking> {
+        self.position.as_mut()
+    }
+
+    pub(super) fn into_transitions(self) -> Vec<LifecycleTransition> {
+        self.transitions
+    }
+
+    /// Age the states of the last bar: an opened position is now managed and
+    /// a closed one leaves the book waiting
+    pub(super) fn begin_bar(&mut self, timestamp: Timestamp) {
+        match self.state {
+            LifecycleState::Open => self.enter(LifecycleState::Managing, timestamp),
+            LifecycleState::Closed => self.enter(LifecycleState::PendingEntry, timestamp),
+            _ => {}
+        }
+    }
+
+    /// Hold `position`, opened at `timestamp`
+    pub(super) fn open(&mut self, position: PositionTracking, timestamp: Timestamp) -> Result<(), SimError> {
+        self.expect(&[LifecycleState::PendingEntry, LifecycleState::Closed], LifecycleState::Open)?;
+        self.position = Some(position);
+        self.enter(LifecycleState::Open, timestamp);
+        Ok(())
+    }
+
+    /// Mark the position for closing and return it for pricing the close
+    pub(super) fn begin_close(&mut self, timestamp: Timestamp) -> Result<&PositionTracking, SimError> {
+        self.expect(&[LifecycleState::Open, LifecycleState::Managing], LifecycleState::PendingClose)?;
+        self.enter(LifecycleState::PendingClose, timestamp);
+        Ok(self.position.as_ref().expect("an open or managed book holds a position"))
+    }
+
+    /// Record the close and hand the position back
+    pub(super) fn close(&mut self, timestamp: Timestamp) -> Result<PositionTracking, SimError> {
+        self.expect(&[LifecycleState::PendingClose], LifecycleState::Closed)?;
+        self.enter(LifecycleState::Closed, timestamp);
+        Ok(self.position.take().expect("a pending close holds a position"))
+    }
+
+    fn expect(&self, from: &[LifecycleState], to: LifecycleState) -> Result<(), SimError> {
+        if from.contains(&self.state) {
+            return Ok(());
+        }
+        Err(SimError::Execution(format!("position lifecycle cannot go from {:?} to {:?}", self.state, to)))
+    }
+
+    fn enter(&mut self, to: LifecycleState, timestamp: Timestamp) {
+        let position_id = self.position.as_ref().map(|pos| pos.position_id.0);
+        self.transitions.push(LifecycleTransition { timestamp, position_id, from: self.state, to });
+        self.state = to;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::engine::Simulator;
+
+    #[test]
+    fn test_lifecycle_follows_positions_through_the_run() {
+        let report = Simulator::new(Config::default_1dte_straddle()).unwrap().finish().unwrap();
+        let transitions = &report.lifecycle;
+        assert_eq!(transitions[0].from, LifecycleState::PendingEntry);
+
+        // Each state only moves where the machine allows
+        use LifecycleState::*;
+        for pair in transitions.windows(2) {
+            assert_eq!(pair[0].to, pair[1].from);
+            assert!(pair[0].timestamp <= pair[1].timestamp);
+        }
+        for transition in transitions {
+            let allowed = match transition.from {
+                PendingEntry => [Open].as_slice(),
+                Open => &[Managing, PendingClose],
+                Managing => &[PendingClose],
+                PendingClose => &[Closed],
+                Closed => &[Open, PendingEntry],
+            };
+            assert!(allowed.contains(&transition.to), "{:?}", transition);
+        }
+        let closes = transitions.iter().filter(|t| t.to == Closed).count();
+        assert_eq!(closes, report.trade_records.len());
+
+        // Closing a book that holds nothing is refused
+        let mut lifecycle = Lifecycle::default();
+        assert!(lifecycle.begin_close(Timestamp::new(0, 600)).is_err());
+        assert!(lifecycle.close(Timestamp::new(0, 600)).is_err());
+        assert_eq!(lifecycle.state(), PendingEntry);
+    }
+}
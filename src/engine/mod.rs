@@ -0,0 +1,840 @@
+//! Simulation Engine
+//!
+//! Runs a single-leg strategy over a generated intraday price path
+//! (23/5 /CL calendar) and collects trades, marks and risk along the way.
+//! Every frontend (CLI, combined runner, web server, Tauri) goes through
+//! `SimulationEngine` so they all produce the same numbers.
+
+use crate::calendar::{self, intraday::{TradingCalendar, Timestamp}, TimeOfDay};
+use crate::config::Config;
+use crate::data::OptionChains;
+use crate::error::SimError;
+use crate::events::{Event, EventStore, LegId, OptionContract, OptionType, PositionId, Side};
+use crate::pricing::{Greeks, Valuation, ValuationCache, VolSurface};
+use crate::reporting::{blotter::Fill, EquityPoint, RiskSummary, TradeRecord};
+use crate::risk::{self, Exposure, PinnedLeg, PortfolioGreeks, RiskSnapshot};
+use serde::Serialize;
+
+mod builder;
+mod cancel;
+mod digest;
+mod hooks;
+mod lifecycle;
+mod path;
+mod portfolio;
+mod simulator;
+mod stream;
+mod strikes;
+
+pub(crate) use digest::fnv_hex;
+pub use builder::{PriceModel, RollTrigger, SimulationBuilder, StrategySpec, TriggerKind, TriggerLegs};
+pub use cancel::CancelToken;
+pub use hooks::Hooks;
+pub use lifecycle::{LifecycleState, LifecycleTransition};
+pub use path::PricePath;
+pub use portfolio::{PortfolioPoint, PortfolioReport, ProductReport};
+pub use simulator::{BarOutcome, MissedEntry, RollCheck, Simulator, SimulatorState};
+pub use stream::{Progress, SimulationStream, StreamEvent};
+pub use strikes::{roll_strike, RollDestination, StrikeMarket, StrikeSelection};
+pub(crate) use stream::{per_second, remaining};
+
+/// Position tracking with P&L (intraday version)
+#[derive(Debug, Clone)]
+pub struct PositionTracking {
+    pub position_id: PositionId,
+    pub entry_timestamp: Timestamp,
+    pub expiration_day: u32,
+    pub entry_price: f64,
+    /// Legs in the order they were opened (put before call for straddles)
+    pub legs: Vec<LegState>,
+    /// Implied volatility the position was priced at
+    pub implied_vol: f64,
+    /// Worst loss at expiration per unit, `None` when unlimited
+    pub max_loss: Option<f64>,
+    /// Margin charged at entry per unit (zero for long positions)
+    pub initial_margin: f64,
+    /// Labels of the rules that opened or adjusted it
+    pub tags: Vec<String>,
+    /// P&L per unit locked in by legs rolled out of it
+    pub rolled_pnl: f64,
+}
+
+/// One leg of an open position
+#[derive(Debug, Clone)]
+pub struct LegState {
+    pub leg_id: LegId,
+    /// Strike, type, side, expiry and quantity
+    pub contract: OptionContract,
+    /// Premium per contract at entry
+    pub entry_premium: f64,
+    /// Greeks per contract at entry
+    pub greeks: Greeks,
+}
+
+impl LegState {
+    pub fn strike(&self) -> f64 {
+        self.contract.strike
+    }
+
+    pub fn is_call(&self) -> bool {
+        self.contract.option_type == OptionType::Call
+    }
+
+    pub fn is_short(&self) -> bool {
+        self.contract.side == Side::Short
+    }
+}
+
+impl PositionTracking {
+    /// Value of the position with each leg worth `values[i]` per contract, per unit
+    pub fn value_of(&self, values: &[f64]) -> f64 {
+        self.legs.iter().zip(values).map(|(leg, value)| value * leg.contract.quantity).sum()
+    }
+
+    /// Premium of the position at entry, per unit
+    pub fn entry_value(&self) -> f64 {
+        self.legs.iter().map(|leg| leg.entry_premium * leg.contract.quantity).sum()
+    }
+
+    /// Side the position was opened on (its legs share it)
+    pub fn side(&self) -> Side {
+        self.legs.first().map_or(Side::Short, |leg| leg.contract.side)
+    }
+
+    /// Signed P&L per unit with each leg worth `values[i]` per contract,
+    /// including legs already rolled out
+    pub fn pnl(&self, values: &[f64]) -> f64 {
+        self.side().pnl(self.entry_value(), self.value_of(values)) + self.rolled_pnl
+    }
+
+    /// Strike of the first leg of `option_type`
+    pub fn strike(&self, option_type: OptionType) -> Option<f64> {
+        self.legs.iter().find(|leg| leg.contract.option_type == option_type).map(LegState::strike)
+    }
+
+    /// Net Greeks of all legs at their quantities and sides, from per-contract `greeks`
+    fn net_greeks(&self, greeks: impl IntoIterator<Item = Greeks>) -> Greeks {
+        let legs: Vec<(Greeks, bool)> = self
+            .legs
+            .iter()
+            .zip(greeks)
+            .map(|(leg, greeks)| (greeks.scaled(leg.contract.quantity), leg.is_short()))
+            .collect();
+        risk::net_greeks(&legs)
+    }
+}
+
+/// Track P&L summary
+#[derive(Debug, Default, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PnLSummary {
+    pub total_premium_collected: f64,
+    pub total_premium_paid: f64,
+    pub position_count: u32,
+    /// P&L of closed positions, per unit
+    pub realized_pnl: f64,
+    /// Mark-to-market P&L of positions still open at the end, per unit
+    pub unrealized_pnl: f64,
+}
+
+/// Why a position was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum CloseCause {
+    /// Closed early by a roll trigger
+    Roll,
+    /// Held to expiration and settled at intrinsic value
+    Expiration,
+    /// Liquidated by the drawdown kill switch
+    KillSwitch,
+    /// Liquidated by the daily loss limit
+    DailyLoss,
+}
+
+/// One trip of the drawdown kill switch
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct KillSwitchTrip {
+    pub timestamp: Timestamp,
+    /// Drawdown from peak equity that tripped it (dollars)
+    pub drawdown: f64,
+    /// First session (see `Timestamp::session_day`) new positions may open again
+    pub resume_day: u32,
+}
+
+/// A session trading stopped at the daily loss limit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DailyHalt {
+    pub timestamp: Timestamp,
+    /// Loss since the start of the session when it halted (dollars)
+    pub loss: f64,
+}
+
+/// One leg of a position in its `LogEntry::Opened`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OpenedLeg {
+    pub option_type: OptionType,
+    pub strike: f64,
+    /// Contracts
+    pub quantity: f64,
+}
+
+/// One trade log entry; formatting is left to `reporting::presenter`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogEntry {
+    Opened {
+        timestamp: Timestamp,
+        position_id: u64,
+        /// Underlying price at entry
+        price: f64,
+        legs: Vec<OpenedLeg>,
+        /// Net premium per unit: positive when collected, negative when paid
+        premium: f64,
+        /// Sum of the legs' Greeks
+        greeks: Greeks,
+        /// Worst loss at expiration per unit, `None` when unlimited
+        max_loss: Option<f64>,
+        /// Margin charged at entry per unit
+        initial_margin: f64,
+        /// Labels of the rule that opened it
+        tags: Vec<String>,
+        /// Opened by rolling the previous position
+        rolled: bool,
+        /// Rolled into the previous position's strikes
+        same_strikes: bool,
+    },
+    /// One leg of an open position moved to a new strike
+    LegRolled {
+        timestamp: Timestamp,
+        position_id: u64,
+        /// Underlying price at the roll
+        price: f64,
+        option_type: OptionType,
+        old_strike: f64,
+        new_strike: f64,
+        /// P&L per unit realized on the old leg
+        pnl: f64,
+    },
+    Closed {
+        timestamp: Timestamp,
+        position_id: u64,
+        /// Underlying price at close
+        price: f64,
+        /// Realized P&L per unit
+        pnl: f64,
+        cause: CloseCause,
+    },
+    /// A new position was blocked by a risk limit
+    Rejected {
+        timestamp: Timestamp,
+        /// Underlying price at the attempt
+        price: f64,
+        /// Blocked the second half of a roll
+        rolled: bool,
+        reason: String,
+    },
+}
+
+impl LogEntry {
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            LogEntry::Opened { timestamp, .. }
+            | LogEntry::LegRolled { timestamp, .. }
+            | LogEntry::Closed { timestamp, .. }
+            | LogEntry::Rejected { timestamp, .. } => *timestamp,
+        }
+    }
+}
+
+/// Everything a single run produces
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SimulationReport {
+    pub pnl_summary: PnLSummary,
+    pub trade_records: Vec<TradeRecord>,
+    pub equity_series: Vec<EquityPoint>,
+    pub risk_summary: RiskSummary,
+    pub final_price: Option<f64>,
+    /// Number of price bars simulated
+    pub total_bars: usize,
+    /// Volatility used for option pricing (realized + VRP)
+    pub implied_vol: f64,
+    /// Opens, closes and rejected entries in the order they happened
+    pub log: Vec<LogEntry>,
+    /// Every leg bought or sold, for a broker-style blotter
+    pub fills: Vec<Fill>,
+    /// Trips of `config.kill_switch`, in order
+    pub kill_switches: Vec<KillSwitchTrip>,
+    /// Days halted by `config.daily_loss`, in order
+    pub daily_halts: Vec<DailyHalt>,
+    /// Short legs settled under `config.pin_risk`, in order
+    pub pins: Vec<PinnedLeg>,
+    /// Every change of position lifecycle state, in order
+    pub lifecycle: Vec<LifecycleTransition>,
+}
+
+impl SimulationReport {
+    /// Realized plus unrealized P&L, per unit
+    pub fn net_pnl(&self) -> f64 {
+        self.pnl_summary.realized_pnl + self.pnl_summary.unrealized_pnl
+    }
+
+    /// Percentage of closed trades with positive P&L
+    pub fn win_rate(&self) -> f64 {
+        if self.trade_records.is_empty() {
+            return 0.0;
+        }
+        let wins = self.trade_records.iter().filter(|t| t.is_win()).count();
+        wins as f64 / self.trade_records.len() as f64 * 100.0
+    }
+}
+
+/// Report for one leg of a combined (short_leg/long_leg) config
+#[derive(Debug)]
+pub struct LegReport {
+    /// "short" or "long"
+    pub name: &'static str,
+    /// The config the leg ran with (`strategy` replaced by the leg)
+    pub config: Config,
+    pub report: SimulationReport,
+}
+
+/// Shared simulation engine
+pub struct SimulationEngine;
+
+impl SimulationEngine {
+    /// Run `config.strategy` over the configured price path
+    pub fn run(config: &Config) -> Result<SimulationReport, SimError> {
+        Simulator::new(config.clone())?.finish()
+    }
+
+    /// Run each enabled `short_leg`/`long_leg` over the same price path
+    pub fn run_legs(config: &Config) -> Result<Vec<LegReport>, SimError> {
+        Self::run_legs_cancellable(config, &CancelToken::new())
+    }
+}
+
+/// Valuations of each leg of an open position at one bar, per contract
+///
+/// Legs quoted in `quotes` at this bar are valued at the quote's mid.
+#[allow(clippy::too_many_arguments)]
+fn value_legs(
+    cache: &mut ValuationCache,
+    calendar: &TradingCalendar,
+    pos: &PositionTracking,
+    timestamp: &Timestamp,
+    current_price: f64,
+    config: &Config,
+    vols: &VolSurface,
+    quotes: Option<&OptionChains>,
+) -> Vec<Valuation> {
+    // Marks use the calendar's DTE so an open position is worth its entry premium at entry
+    let unit = config.dte_unit();
+    let time_to_expiry = unit.years(unit.remaining(calendar, timestamp, pos.expiration_day));
+    let rate = config.simulation.risk_free_rate;
+    let expiry = pos.expiration_day;
+    let atm_vol = vols.atm_vol(timestamp.day, calendar.calculate_dte(timestamp, expiry));
+    let mut value = |strike: f64, is_call: bool| {
+        let vol = vols.smile().vol(atm_vol, current_price, strike);
+        match quotes.and_then(|chains| chains.quote(timestamp, expiry, strike, is_call)) {
+            Some(quote) => quote.valuation(current_price, time_to_expiry, rate, vol),
+            None => cache.value(expiry, strike, is_call, current_price, time_to_expiry, rate, vol),
+        }
+    };
+    pos.legs.iter().map(|leg| value(leg.strike(), leg.is_call())).collect()
+}
+
+/// Current (unsigned) value of all legs at their quantities, per unit
+#[allow(clippy::too_many_arguments)]
+fn position_value(
+    cache: &mut ValuationCache,
+    calendar: &TradingCalendar,
+    pos: &PositionTracking,
+    timestamp: &Timestamp,
+    current_price: f64,
+    config: &Config,
+    vols: &VolSurface,
+    quotes: Option<&OptionChains>,
+) -> f64 {
+    let legs = value_legs(cache, calendar, pos, timestamp, current_price, config, vols, quotes);
+    pos.value_of(&legs.iter().map(|leg| leg.price).collect::<Vec<_>>())
+}
+
+/// Margin, notional and net Greeks of the open position (all zero when flat)
+#[allow(clippy::too_many_arguments)]
+fn risk_snapshot(
+    cache: &mut ValuationCache,
+    calendar: &TradingCalendar,
+    pos: Option<&PositionTracking>,
+    timestamp: &Timestamp,
+    current_price: f64,
+    config: &Config,
+    vols: &VolSurface,
+    quotes: Option<&OptionChains>,
+) -> RiskSnapshot {
+    let multiplier = config.simulation.contract_multiplier;
+    let mut snapshot = RiskSnapshot {
+        timestamp: *timestamp,
+        margin: 0.0,
+        notional: 0.0,
+        net_delta: 0.0,
+        net_gamma: 0.0,
+        net_theta: 0.0,
+        net_vega: 0.0,
+    };
+    let Some(pos) = pos else {
+        return snapshot;
+    };
+
+    let values = value_legs(cache, calendar, pos, timestamp, current_price, config, vols, quotes);
+    let net = pos.net_greeks(values.iter().map(|value| value.greeks));
+
+    if pos.legs.iter().any(LegState::is_short) {
+        let prices: Vec<f64> = values.iter().map(|value| value.price).collect();
+        snapshot.margin = short_margin(&pos.legs, &prices, current_price, config) * multiplier;
+    }
+    let contracts: f64 = pos.legs.iter().map(|leg| leg.contract.quantity).sum();
+    snapshot.notional = contracts * current_price * multiplier;
+    let greeks = PortfolioGreeks::from_net(&net, multiplier);
+    snapshot.net_delta = greeks.delta;
+    snapshot.net_gamma = greeks.gamma;
+    snapshot.net_theta = greeks.theta;
+    snapshot.net_vega = greeks.vega;
+    snapshot
+}
+
+/// Margin of the short `legs` valued at `prices`, per unit
+///
+/// A short put and short call opened together are margined as a straddle;
+/// any other short leg is charged as a naked option.
+fn short_margin(legs: &[LegState], prices: &[f64], current_price: f64, config: &Config) -> f64 {
+    let rates = &config.margin;
+    match (legs, prices) {
+        ([put, call], [put_price, call_price])
+            if put.is_short() && call.is_short() && !put.is_call() && call.is_call() =>
+        {
+            risk::short_legs_margin(
+                current_price,
+                put.strike(),
+                call.strike(),
+                *put_price,
+                *call_price,
+                (put.contract.quantity, call.contract.quantity),
+                rates,
+            )
+        }
+        _ => legs
+            .iter()
+            .zip(prices)
+            .filter(|(leg, _)| leg.is_short())
+            .map(|(leg, price)| {
+                let margin = risk::short_option_margin(current_price, leg.strike(), *price, leg.is_call(), rates);
+                leg.contract.quantity * margin
+            })
+            .sum(),
+    }
+}
+
+/// Signed mark-to-market P&L of an open position, per unit
+#[allow(clippy::too_many_arguments)]
+fn mark_to_market(
+    cache: &mut ValuationCache,
+    calendar: &TradingCalendar,
+    pos: &PositionTracking,
+    timestamp: &Timestamp,
+    current_price: f64,
+    config: &Config,
+    vols: &VolSurface,
+    quotes: Option<&OptionChains>,
+) -> f64 {
+    let current_value = position_value(cache, calendar, pos, timestamp, current_price, config, vols, quotes);
+    pos.side().pnl(pos.entry_value(), current_value) + pos.rolled_pnl
+}
+
+/// Calculate fractional days to expiration
+fn calculate_fractional_dte(current: &Timestamp, expiration_day: u32) -> f64 {
+    if current.day >= expiration_day {
+        return 0.0;
+    }
+    // Approximate: each day is 1.0, each minute is 1/138 (for 23-hour trading day at 10-min bars)
+    let days_remaining = (expiration_day - current.day) as f64;
+    let minutes_fraction = (138.0 - current.minute as f64) / 138.0;
+    days_remaining - 1.0 + minutes_fraction
+}
+
+/// Calculate intrinsic value at expiration
+fn calculate_intrinsic(underlying: f64, strike: f64, is_call: bool) -> f64 {
+    if is_call {
+        (underlying - strike).max(0.0)
+    } else {
+        (strike - underlying).max(0.0)
+    }
+}
+
+/// One leg of a position about to be opened
+#[derive(Debug, Clone, Copy)]
+struct LegQuote {
+    option_type: OptionType,
+    strike: f64,
+    premium: f64,
+    greeks: Greeks,
+    /// Contracts, the strategy's unless resized
+    quantity: f64,
+}
+
+/// Expiry and legs of a position about to be opened
+#[derive(Debug, Clone)]
+struct EntryQuote {
+    expiration_day: u32,
+    legs: Vec<LegQuote>,
+    /// At-the-money vol the legs were priced around
+    implied_vol: f64,
+}
+
+impl EntryQuote {
+    /// Scale every leg by `factor`, to whole contracts (at least one) unless `fractional`
+    fn resize(&mut self, factor: f64, fractional: bool) {
+        for leg in &mut self.legs {
+            let sized = leg.quantity * factor;
+            leg.quantity = if fractional { sized } else { sized.round().max(1.0) };
+        }
+    }
+
+    /// What the position would add to the book, opened at `current_price`
+    fn exposure(&self, config: &Config, current_price: f64) -> Exposure {
+        let multiplier = config.simulation.contract_multiplier;
+        let is_short = config.strategy.side() == Side::Short;
+        let legs: Vec<(Greeks, bool)> =
+            self.legs.iter().map(|leg| (leg.greeks.scaled(leg.quantity), is_short)).collect();
+        let net = risk::net_greeks(&legs);
+        let premium: f64 = self.legs.iter().map(|leg| leg.premium * leg.quantity).sum();
+        let contracts: f64 = self.legs.iter().map(|leg| leg.quantity).sum();
+        Exposure {
+            positions: 1,
+            short_premium: if is_short { premium * multiplier } else { 0.0 },
+            notional: contracts * current_price * multiplier,
+            greeks: PortfolioGreeks::from_net(&net, multiplier),
+        }
+    }
+}
+
+/// Price a new position with the product's pricing model (or at the mid of
+/// legs quoted in `quotes` at `bar`) without opening it
+#[allow(clippy::too_many_arguments)]
+fn quote_entry(
+    config: &Config,
+    entry_day: u32,
+    current_price: f64,
+    rolled_from: Option<&PositionTracking>,
+    vols: &VolSurface,
+    quotes: Option<&OptionChains>,
+    bar: &Timestamp,
+) -> Result<EntryQuote, SimError> {
+    // Calculate expiration day based on entry_dte config
+    let unit = config.dte_unit();
+    let exchange = calendar::Calendar::with_holidays(config.holidays());
+    let expiration_day = unit.expiration_day(&exchange, entry_day, config.strategy.entry_dte);
+    // Priced from the bar to the (holiday-adjusted) expiration, as marks are, so entries and marks agree
+    let time_to_expiry = unit.years(unit.remaining(&config.trading_calendar(), bar, expiration_day));
+
+    // Determine strikes: the entry rule, or the roll destination when replacing a position
+    let ladder = config.strike_config.ladder(current_price)?;
+    let model = config.pricing_model();
+    let implied_vol = vols.atm_vol(entry_day, f64::from(config.strategy.entry_dte));
+    let market = StrikeMarket {
+        model,
+        underlying: current_price,
+        time_to_expiry,
+        risk_free_rate: config.simulation.risk_free_rate,
+        implied_vol,
+        smile: vols.smile(),
+    };
+    let selection = config.strike_selection();
+    let (put_strike, call_strike) = match rolled_from {
+        Some(pos) => strikes::roll(config.roll_destination(), selection, &pos.legs, &ladder, &market),
+        None => strikes::select(selection, &ladder, &market),
+    };
+
+    // Price each leg with IMPLIED volatility, or at the mid where the chain quotes it
+    let strategy = &config.strategy;
+    let legs = [
+        (OptionType::Put, put_strike, strategy.put_quantity),
+        (OptionType::Call, call_strike, strategy.call_quantity),
+    ]
+    .into_iter()
+    .map(|(option_type, strike, quantity)| {
+        quote_leg(&market, quotes, bar, expiration_day, option_type, strike, quantity)
+    })
+    .collect::<Result<_, _>>()?;
+
+    Ok(EntryQuote { expiration_day, legs, implied_vol })
+}
+
+/// Price one leg in `market`, at the mid where `quotes` lists it at `bar`
+fn quote_leg(
+    market: &StrikeMarket,
+    quotes: Option<&OptionChains>,
+    bar: &Timestamp,
+    expiration_day: u32,
+    option_type: OptionType,
+    strike: f64,
+    quantity: f64,
+) -> Result<LegQuote, SimError> {
+    let StrikeMarket { model, underlying, time_to_expiry, risk_free_rate, .. } = *market;
+    let is_call = option_type == OptionType::Call;
+    let vol = market.vol(strike);
+    let (premium, greeks) = match quotes.and_then(|quotes| quotes.quote(bar, expiration_day, strike, is_call)) {
+        Some(quote) => {
+            let valuation = quote.valuation(underlying, time_to_expiry, risk_free_rate, vol);
+            (valuation.price, valuation.greeks)
+        }
+        None => (
+            model.try_price(underlying, strike, time_to_expiry, risk_free_rate, vol, is_call)?,
+            model.greeks(underlying, strike, time_to_expiry, risk_free_rate, vol, is_call),
+        ),
+    };
+    Ok(LegQuote { option_type, strike, premium, greeks, quantity })
+}
+
+/// Open the position priced by `quote_entry`
+#[allow(clippy::too_many_arguments)]
+fn open_position(
+    event_store: &mut EventStore,
+    pnl: &mut PnLSummary,
+    config: &Config,
+    current_price: f64,
+    entry: EntryQuote,
+    bar: &Timestamp,
+    tags: &[String],
+) -> Result<PositionTracking, SimError> {
+    let entry_day = bar.day;
+    let EntryQuote { expiration_day, legs, implied_vol } = entry;
+    let position_id = event_store.next_position_id();
+
+    // Determine side
+    let side = config.strategy.side();
+    let legs: Vec<LegState> = legs
+        .into_iter()
+        .map(|leg| LegState {
+            leg_id: event_store.next_leg_id(),
+            contract: OptionContract {
+                underlying_price: current_price,
+                strike: leg.strike,
+                option_type: leg.option_type,
+                side,
+                expiration_day,
+                quantity: leg.quantity,
+            },
+            entry_premium: leg.premium,
+            greeks: leg.greeks,
+        })
+        .collect();
+
+    // Risk per trade: what the structure can lose and what it ties up
+    let max_loss = risk::max_loss(&legs.iter().map(|leg| (leg.contract, leg.entry_premium)).collect::<Vec<_>>());
+    let premiums: Vec<f64> = legs.iter().map(|leg| leg.entry_premium).collect();
+    let initial_margin =
+        if side == Side::Short { short_margin(&legs, &premiums, current_price, config) } else { 0.0 };
+
+    // Stamped with the bar it fills on, which can be after the configured time
+    let event = Event::PositionOpened {
+        position_id,
+        timestamp: (entry_day, TimeOfDay::from_minutes(bar.minute as u16)),
+        legs: legs
+            .iter()
+            .map(|leg| {
+                let signed = if side == Side::Long { -leg.entry_premium } else { leg.entry_premium };
+                (leg.leg_id, leg.contract, signed)
+            })
+            .collect(),
+        max_loss,
+        initial_margin,
+        tags: tags.to_vec(),
+    };
+    event_store.append(event)?;
+
+    let position = PositionTracking {
+        position_id,
+        entry_timestamp: *bar,
+        expiration_day,
+        entry_price: current_price,
+        legs,
+        implied_vol,
+        max_loss,
+        initial_margin,
+        tags: tags.to_vec(),
+        rolled_pnl: 0.0,
+    };
+
+    pnl.position_count += 1;
+    if side == Side::Short {
+        pnl.total_premium_collected += position.entry_value();
+    } else {
+        pnl.total_premium_paid += position.entry_value();
+    }
+    Ok(position)
+}
+
+/// Log entry for a newly opened position
+fn opened_entry(
+    pos: &PositionTracking,
+    timestamp: Timestamp,
+    config: &Config,
+    rolled: bool,
+    same_strikes: bool,
+) -> LogEntry {
+    let total = pos.entry_value();
+    let legs: Vec<(Greeks, bool)> =
+        pos.legs.iter().map(|leg| (leg.greeks.scaled(leg.contract.quantity), false)).collect();
+    LogEntry::Opened {
+        timestamp,
+        position_id: pos.position_id.0,
+        price: pos.entry_price,
+        legs: pos
+            .legs
+            .iter()
+            .map(|leg| OpenedLeg {
+                option_type: leg.contract.option_type,
+                strike: leg.strike(),
+                quantity: leg.contract.quantity,
+            })
+            .collect(),
+        premium: if config.strategy.side() == Side::Long { -total } else { total },
+        greeks: risk::net_greeks(&legs),
+        max_loss: pos.max_loss,
+        initial_margin: pos.initial_margin,
+        tags: pos.tags.clone(),
+        rolled,
+        same_strikes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_is_deterministic() {
+        let config = Config::default_1dte_straddle();
+        let a = SimulationEngine::run(&config).unwrap();
+        let b = SimulationEngine::run(&config).unwrap();
+        assert_eq!(a.pnl_summary.position_count, b.pnl_summary.position_count);
+        assert_eq!(a.net_pnl(), b.net_pnl());
+        assert_eq!(a.equity_series.len(), a.total_bars);
+        assert!(a.pnl_summary.position_count > 0);
+    }
+
+    #[test]
+    fn test_log_matches_trades() {
+        let report = SimulationEngine::run(&Config::default_1dte_straddle()).unwrap();
+        let opens = report.log.iter().filter(|e| matches!(e, LogEntry::Opened { .. })).count();
+        let closes = report.log.iter().filter(|e| matches!(e, LogEntry::Closed { .. })).count();
+        assert_eq!(opens as u32, report.pnl_summary.position_count);
+        assert_eq!(closes, report.trade_records.len());
+    }
+
+    #[test]
+    fn test_run_legs_share_price_path() {
+        let mut config = Config::default_1dte_straddle();
+        let mut long_leg = config.strategy.clone();
+        long_leg.side = "long".to_string();
+        config.short_leg = Some(config.strategy.clone());
+        config.long_leg = Some(long_leg);
+
+        let legs = SimulationEngine::run_legs(&config).unwrap();
+        assert_eq!(legs.len(), 2);
+        assert_eq!(legs[0].name, "short");
+        assert_eq!(legs[0].report.final_price, legs[1].report.final_price);
+        // Mirror-image legs net to zero
+        assert!((legs[0].report.net_pnl() + legs[1].report.net_pnl()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_leg_quantities_scale_pnl_and_fills() {
+        let base = SimulationEngine::run(&Config::default_1dte_straddle()).unwrap();
+        let mut config = Config::default_1dte_straddle();
+        config.strategy.put_quantity = 2.5;
+        config.strategy.call_quantity = 2.5;
+        assert!(matches!(SimulationEngine::run(&config), Err(SimError::Config(_))));
+
+        config.strategy.fractional_quantity = true;
+        let sized = SimulationEngine::run(&config).unwrap();
+        assert!((sized.net_pnl() - 2.5 * base.net_pnl()).abs() < 1e-6);
+        assert!(sized.fills.iter().all(|fill| fill.quantity == 2.5));
+        let opened = sized.log.iter().find_map(|entry| match entry {
+            LogEntry::Opened { legs, .. } => Some(legs),
+            _ => None,
+        });
+        let types: Vec<OptionType> = opened.unwrap().iter().map(|leg| leg.option_type).collect();
+        assert_eq!(types, [OptionType::Put, OptionType::Call]);
+        assert!(opened.unwrap().iter().all(|leg| leg.quantity == 2.5));
+        let margin = |report: &SimulationReport| report.risk_summary.peak_margin.map_or(0.0, |peak| peak.value);
+        assert!((margin(&sized) - 2.5 * margin(&base)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_unpaired_short_legs_are_margined_naked() {
+        let mut sim = Simulator::new(Config::default_1dte_straddle()).unwrap();
+        let pos = loop {
+            sim.step().unwrap().unwrap();
+            if let Some(pos) = sim.state().position {
+                break pos.clone();
+            }
+        };
+        let config = Config::default_1dte_straddle();
+        let pair = short_margin(&pos.legs, &[1.0, 1.2], 75.0, &config);
+        let strike = pos.legs[0].strike();
+        assert_eq!(pair, risk::short_straddle_margin(75.0, strike, pos.legs[1].strike(), 1.0, 1.2, &config.margin));
+
+        let mut single = pos.clone();
+        single.legs.truncate(1);
+        let naked = short_margin(&single.legs, &[1.0], 75.0, &config);
+        assert_eq!(naked, risk::short_option_margin(75.0, strike, 1.0, false, &config.margin));
+        assert_eq!(single.entry_value(), pos.legs[0].entry_premium);
+
+        // The short straddle's risk at entry: naked calls, margined at the entry premiums
+        let premiums: Vec<f64> = pos.legs.iter().map(|leg| leg.entry_premium).collect();
+        assert_eq!(pos.max_loss, None);
+        assert_eq!(pos.initial_margin, short_margin(&pos.legs, &premiums, pos.entry_price, &config));
+    }
+
+    #[test]
+    fn test_spx_prices_spot_options_with_black_scholes() {
+        use crate::pricing::{BlackScholes, PricingModel};
+
+        let root = env!("CARGO_MANIFEST_DIR");
+        let config = Config::from_file(format!("{}/config/spx_index.yaml", root)).unwrap();
+        assert_eq!(config.pricing_model(), PricingModel::BlackScholes { dividend_yield: 0.013 });
+        assert_eq!(config.simulation.contract_multiplier, 100.0);
+        assert_eq!(config.trading_calendar().expiration_minute(), 16 * 60 + 15);
+
+        let mut sim = Simulator::new(config.clone()).unwrap();
+        let pos = loop {
+            sim.step().unwrap().unwrap();
+            if let Some(pos) = sim.state().position {
+                break pos.clone();
+            }
+        };
+        let (rate, vol) = (config.simulation.risk_free_rate, pos.implied_vol);
+        for leg in &pos.legs {
+            let expected =
+                BlackScholes::price(pos.entry_price, leg.strike(), 1.0 / 252.0, rate, 0.013, vol, leg.is_call());
+            assert_eq!(leg.entry_premium, expected);
+        }
+        // Marked on the expiration day against the 16:15 expiry
+        let before_expiry = Timestamp::new(pos.expiration_day, 16 * 60);
+        assert_eq!(config.trading_calendar().calculate_dte(&before_expiry, pos.expiration_day), 15.0 / 1440.0);
+    }
+
+    #[test]
+    fn test_malformed_time_is_an_error() {
+        let mut config = Config::default_1dte_straddle();
+        config.strategy.roll_time = "2pm".to_string();
+        assert!(matches!(SimulationEngine::run(&config), Err(SimError::Config(_))));
+    }
+}
@@ -0,0 +1,295 @@
+//! Shared Price Paths
+//!
+//! Strategy variants compared "on the same market" should see literally the
+//! same bars, not bars regenerated from a seed that happens to match. A
+//! `PricePath` is generated once from the `simulation` section and shared by
+//! reference count across every run that uses it. With the `mmap` feature a
+//! path can also be a memory-mapped historical bar file, read in place.
+//!
+//! Any path can be saved as a small text file (`# tsv2 price path v1`, free
+//! `#` comment lines, then `day,time,price` rows) and loaded back bit for
+//! bit, to archive an interesting path, attach it to a bug report or rerun
+//! it under other strategies.
+
+use super::{SimulationEngine, SimulationReport, Simulator};
+use crate::calendar::intraday::{Timestamp, TradingCalendar};
+use crate::calendar::TimeOfDay;
+use crate::config::Config;
+use crate::error::SimError;
+#[cfg(feature = "mmap")]
+use crate::prices::mapped::MappedBars;
+use crate::prices::{PricePoint, RngKind, GBM};
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+/// First line of a saved path
+const PATH_FILE_MAGIC: &str = "# tsv2 price path v1";
+
+/// An intraday price path; cloning shares the bars
+#[derive(Debug, Clone)]
+pub struct PricePath {
+    bars: Bars,
+}
+
+#[derive(Debug, Clone)]
+enum Bars {
+    Owned(Arc<[PricePoint]>),
+    #[cfg(feature = "mmap")]
+    Mapped(Arc<MappedBars>),
+}
+
+impl PartialEq for PricePath {
+    fn eq(&self, other: &Self) -> bool {
+        self.bars() == other.bars()
+    }
+}
+
+impl PricePath {
+    /// Generate the path described by `config.simulation` (GBM from day 0, 09:00)
+    pub fn generate(config: &Config) -> Result<Self, SimError> {
+        config.validate()?;
+
+        let sim = &config.simulation;
+        let mut gbm = GBM::with_rng(
+            sim.initial_price,
+            sim.drift,
+            sim.volatility,
+            sim.seed,
+            RngKind::from_config(&sim.rng).unwrap_or_default(),
+        );
+        let start_day = 0; // Day 0 = Monday
+        let start_minute = 9 * 60; // 9:00 AM
+        let resolution = sim.intraday_resolution_minutes;
+        let bars =
+            gbm.generate_intraday_path(&TradingCalendar::new(), sim.days, resolution, start_day, start_minute);
+        Self::from_bars(bars).map_err(|_| {
+            SimError::Calendar(format!("no trading bars in {} days at {}-minute resolution", sim.days, resolution))
+        })
+    }
+
+    /// One path per config over the same bars, with returns correlated by
+    /// `correlation` (each config's own `generate` path when it is `None`)
+    ///
+    /// The configs must share `days`, resolution and `rng`; only price,
+    /// drift, volatility and seed may differ.
+    pub fn generate_correlated(configs: &[Config], correlation: Option<&[Vec<f64>]>) -> Result<Vec<Self>, SimError> {
+        let (Some(first), Some(correlation)) = (configs.first(), correlation) else {
+            return configs.iter().map(Self::generate).collect();
+        };
+        let factor = crate::prices::cholesky(correlation)
+            .filter(|factor| factor.len() == configs.len())
+            .ok_or_else(|| SimError::Data(format!("correlation is not a valid {0}x{0} matrix", configs.len())))?;
+        let sim = &first.simulation;
+        let rng = RngKind::from_config(&sim.rng).unwrap_or_default();
+        let mut generators: Vec<GBM> = configs
+            .iter()
+            .map(|c| {
+                let sim = &c.simulation;
+                GBM::with_rng(sim.initial_price, sim.drift, sim.volatility, sim.seed, rng)
+            })
+            .collect();
+        let resolution = sim.intraday_resolution_minutes;
+        let paths = GBM::generate_correlated_intraday_paths(
+            &mut generators,
+            &factor,
+            &TradingCalendar::new(),
+            sim.days,
+            resolution,
+            0,
+            9 * 60,
+        );
+        paths.into_iter().map(Self::from_bars).collect()
+    }
+
+    /// Wrap existing bars (must be non-empty and in time order)
+    pub fn from_bars(bars: Vec<PricePoint>) -> Result<Self, SimError> {
+        check_bars(&bars)?;
+        Ok(Self { bars: Bars::Owned(bars.into()) })
+    }
+
+    /// Use a mapped bar file as the path, without copying it
+    #[cfg(feature = "mmap")]
+    pub fn mapped(bars: MappedBars) -> Result<Self, SimError> {
+        check_bars(bars.bars())?;
+        Ok(Self { bars: Bars::Mapped(Arc::new(bars)) })
+    }
+
+    pub fn bars(&self) -> &[PricePoint] {
+        match &self.bars {
+            Bars::Owned(bars) => bars,
+            #[cfg(feature = "mmap")]
+            Bars::Mapped(bars) => bars.bars(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bars().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bars().is_empty()
+    }
+
+    /// The path as text, with each line of `note` as a comment (e.g. the
+    /// settings it was generated from)
+    pub fn to_text(&self, note: &str) -> String {
+        let mut text = format!("{}\n", PATH_FILE_MAGIC);
+        for line in note.lines() {
+            let _ = writeln!(text, "# {}", line);
+        }
+        text.push_str("day,time,price\n");
+        for bar in self.bars() {
+            // `{}` prints the shortest text that parses back to the same f64
+            let _ = writeln!(text, "{},{},{}", bar.timestamp.day, bar.timestamp.format_time(), bar.price);
+        }
+        text
+    }
+
+    /// Parse text written by `to_text`
+    pub fn parse(text: &str) -> Result<Self, SimError> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, line)| line.trim()) != Some(PATH_FILE_MAGIC) {
+            return Err(SimError::Data(format!("not a saved price path (expected {:?} first)", PATH_FILE_MAGIC)));
+        }
+        let mut bars = Vec::new();
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line == "day,time,price" {
+                continue;
+            }
+            let invalid = |reason: &str| SimError::Data(format!("line {}: {}", index + 1, reason));
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [day, time, price] = fields[..] else {
+                return Err(invalid("expected day,time,price"));
+            };
+            let day = day.parse().map_err(|_| invalid("day is not a whole number"))?;
+            let time = TimeOfDay::parse(time).map_err(|e| invalid(&e.to_string()))?;
+            let price = price.parse().map_err(|_| invalid("price is not a number"))?;
+            bars.push(PricePoint { timestamp: Timestamp::new(day, u32::from(time.minutes())), price });
+        }
+        Self::from_bars(bars)
+    }
+
+    /// Save the path to a file (see `to_text`)
+    pub fn save(&self, path: impl AsRef<Path>, note: &str) -> Result<(), SimError> {
+        std::fs::write(path, self.to_text(note))?;
+        Ok(())
+    }
+
+    /// Load a path saved with `save`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SimError> {
+        let path = path.as_ref();
+        Self::parse(&std::fs::read_to_string(path)?).map_err(|e| match e {
+            SimError::Data(reason) => SimError::Data(format!("{}: {}", path.display(), reason)),
+            other => other,
+        })
+    }
+
+    /// True if `text` starts like a saved path
+    pub fn is_saved_path(text: &str) -> bool {
+        text.lines().next().is_some_and(|line| line.trim() == PATH_FILE_MAGIC)
+    }
+}
+
+/// Paths must be non-empty, in time order and have usable prices
+fn check_bars(bars: &[PricePoint]) -> Result<(), SimError> {
+    if bars.is_empty() {
+        return Err(SimError::Calendar("price path has no bars".to_string()));
+    }
+    if bars.windows(2).any(|w| w[1].timestamp <= w[0].timestamp) {
+        return Err(SimError::Calendar("price path bars are not in time order".to_string()));
+    }
+    if let Some(bar) = bars.iter().find(|b| !(b.price.is_finite() && b.price > 0.0)) {
+        return Err(SimError::Data(format!("bad price {} at {}", bar.price, bar.timestamp.format())));
+    }
+    Ok(())
+}
+
+impl SimulationEngine {
+    /// Run `config.strategy` over an existing path instead of generating one
+    pub fn run_on_path(config: &Config, path: &PricePath) -> Result<SimulationReport, SimError> {
+        Simulator::with_path(config.clone(), path.clone())?.finish()
+    }
+
+    /// Run each strategy variant over one path generated from the first
+    /// variant's `simulation` section
+    pub fn run_variants(variants: &[Config]) -> Result<Vec<SimulationReport>, SimError> {
+        let Some(first) = variants.first() else {
+            return Ok(Vec::new());
+        };
+        let path = PricePath::generate(first)?;
+        variants.iter().map(|config| Self::run_on_path(config, &path)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_path_matches_generated_run() {
+        let config = Config::default_1dte_straddle();
+        let path = PricePath::generate(&config).unwrap();
+        let shared = SimulationEngine::run_on_path(&config, &path).unwrap();
+        let own = SimulationEngine::run(&config).unwrap();
+        assert_eq!(shared.digest(), own.digest());
+    }
+
+    #[test]
+    fn test_variants_see_the_same_bars() {
+        let base = Config::default_1dte_straddle();
+        // A different seed on the second variant must not change its market
+        let mut other = base.clone();
+        other.simulation.seed += 7;
+        other.strategy.side = "long".to_string();
+
+        let reports = SimulationEngine::run_variants(&[base.clone(), other]).unwrap();
+        let prices = |r: &SimulationReport| r.equity_series.iter().map(|p| p.price).collect::<Vec<_>>();
+        assert_eq!(prices(&reports[0]), prices(&reports[1]));
+        assert_eq!(reports[0].digest(), SimulationEngine::run(&base).unwrap().digest());
+        assert!(SimulationEngine::run_variants(&[]).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_path_runs_like_generated() {
+        let config = Config::default_1dte_straddle();
+        let generated = PricePath::generate(&config).unwrap();
+        let file = std::env::temp_dir().join(format!("tsv2_path_{}.bars", std::process::id()));
+        crate::prices::mapped::write_bars(&file, generated.bars()).unwrap();
+
+        let mapped = PricePath::mapped(MappedBars::open(&file).unwrap()).unwrap();
+        assert_eq!(mapped, generated);
+        let report = SimulationEngine::run_on_path(&config, &mapped).unwrap();
+        assert_eq!(report.digest(), SimulationEngine::run(&config).unwrap().digest());
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn test_saved_path_reloads_bit_for_bit() {
+        let config = Config::default_1dte_straddle();
+        let generated = PricePath::generate(&config).unwrap();
+        let text = generated.to_text("seed 42\n30 days");
+        assert!(PricePath::is_saved_path(&text));
+        assert!(text.starts_with("# tsv2 price path v1\n# seed 42\n# 30 days\nday,time,price\n0,09:00,"));
+
+        let reloaded = PricePath::parse(&text).unwrap();
+        assert_eq!(reloaded, generated);
+        let report = SimulationEngine::run_on_path(&config, &reloaded).unwrap();
+        assert_eq!(report.digest(), SimulationEngine::run(&config).unwrap().digest());
+
+        assert!(PricePath::parse("day,time,price\n0,09:00,75\n").unwrap_err().to_string().contains("not a saved"));
+        let bad = "# tsv2 price path v1\nday,time,price\n0,9am,75\n";
+        assert!(PricePath::parse(bad).unwrap_err().to_string().contains("line 3"));
+    }
+
+    #[test]
+    fn test_from_bars_rejects_bad_paths() {
+        assert!(PricePath::from_bars(Vec::new()).is_err());
+        let path = PricePath::generate(&Config::default_1dte_straddle()).unwrap();
+        let mut reversed = path.bars().to_vec();
+        reversed.reverse();
+        assert!(PricePath::from_bars(reversed).is_err());
+    }
+}
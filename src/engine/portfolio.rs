@@ -0,0 +1,234 @@
+//! Multi-Product Portfolios
+//!
+//! Runs every product of a config's `portfolio` section bar by bar in
+//! lockstep, each on its own (optionally correlated) path with its own
+//! contract specs and strategy, and sums them into one equity curve and one
+//! risk report in the account currency (`config.fx`, dollars by default),
+//! converting each product's P&L at that bar's FX rate. Loss rules and risk
+//! limits apply to each product separately, in its own currency.
+
+use super::{PricePath, SimulationEngine, SimulationReport, Simulator};
+use crate::calendar::intraday::Timestamp;
+use crate::config::{Config, ConfigError, ContractRegistry};
+use crate::data::FxRates;
+use crate::error::SimError;
+use crate::reporting::RiskSummary;
+use crate::risk::RiskSnapshot;
+use serde::Serialize;
+
+/// Portfolio mark at one bar, in the account currency
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PortfolioPoint {
+    pub timestamp: Timestamp,
+    pub realized: f64,
+    pub unrealized: f64,
+}
+
+impl PortfolioPoint {
+    pub fn equity(&self) -> f64 {
+        self.realized + self.unrealized
+    }
+}
+
+/// One product's run
+#[derive(Debug)]
+pub struct ProductReport {
+    /// Registry spelling of the symbol
+    pub symbol: String,
+    /// The config the product ran with
+    pub config: Config,
+    pub report: SimulationReport,
+    /// Currency the product is quoted in
+    pub currency: String,
+    /// Net P&L at the last bar in the account currency
+    pub account_pnl: f64,
+}
+
+/// Every product's run and their sum
+#[derive(Debug)]
+pub struct PortfolioReport {
+    pub products: Vec<ProductReport>,
+    pub equity_series: Vec<PortfolioPoint>,
+    /// Peaks of the summed margin, notional and net Greeks
+    pub risk_summary: RiskSummary,
+    /// Currency of the equity series, risk and `account_pnl`
+    pub account_currency: String,
+}
+
+impl PortfolioReport {
+    /// Realized plus unrealized P&L of all products at the last bar (account currency)
+    pub fn net_pnl(&self) -> f64 {
+        self.equity_series.last().map_or(0.0, PortfolioPoint::equity)
+    }
+
+    /// Largest fall of portfolio equity from a previous peak (account currency)
+    pub fn max_drawdown(&self) -> f64 {
+        let mut peak: f64 = 0.0;
+        let mut drawdown: f64 = 0.0;
+        for point in &self.equity_series {
+            peak = peak.max(point.equity());
+            drawdown = drawdown.max(peak - point.equity());
+        }
+        drawdown
+    }
+}
+
+impl SimulationEngine {
+    /// Run the products of `config.portfolio` with the built-in contract registry
+    pub fn run_portfolio(config: &Config) -> Result<PortfolioReport, SimError> {
+        Self::run_portfolio_with(config, ContractRegistry::builtin())
+    }
+
+    /// Run the products of `config.portfolio`, resolving symbols in `registry`
+    pub fn run_portfolio_with(config: &Config, registry: &ContractRegistry) -> Result<PortfolioReport, SimError> {
+        let configs = config.product_configs(registry)?;
+        if configs.is_empty() {
+            return Err(SimError::Execution("config has no portfolio products".to_string()));
+        }
+        let fx = match &config.fx {
+            Some(fx) => FxRates::from_config(fx).map_err(ConfigError::Validation)?,
+            None => FxRates::default(),
+        };
+        let currencies: Vec<String> = configs
+            .iter()
+            .map(|config| config.product.as_ref().map_or_else(|| "USD".to_string(), |p| p.currency.clone()))
+            .collect();
+        if let Some(missing) = currencies.iter().find(|currency| fx.rate(currency, 0).is_none()) {
+            let account = fx.account_currency();
+            let msg = format!("no fx rate from {} to account currency {}", missing, account);
+            return Err(ConfigError::Validation(msg).into());
+        }
+        let correlation = config.portfolio.as_ref().and_then(|p| p.correlation.as_deref());
+        let paths = PricePath::generate_correlated(&configs, correlation)?;
+        let mut simulators = configs
+            .iter()
+            .zip(paths)
+            .map(|(config, path)| Simulator::with_path(config.clone(), path))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut equity_series = Vec::new();
+        let mut risk_summary = RiskSummary::default();
+        let mut last_marks = vec![0.0; configs.len()];
+        loop {
+            let mut point: Option<PortfolioPoint> = None;
+            let mut risk: Option<RiskSnapshot> = None;
+            for (i, (simulator, config)) in simulators.iter_mut().zip(&configs).enumerate() {
+                let Some(mut outcome) = simulator.step()? else {
+                    continue;
+                };
+                let rate = fx.rate(&currencies[i], outcome.timestamp.day).unwrap_or(1.0);
+                let multiplier = config.simulation.contract_multiplier * rate;
+                last_marks[i] = outcome.equity.equity() * multiplier;
+                scale_risk(&mut outcome.risk, rate);
+                let mark = point.get_or_insert(PortfolioPoint {
+                    timestamp: outcome.timestamp,
+                    realized: 0.0,
+                    unrealized: 0.0,
+                });
+                mark.realized += outcome.equity.realized * multiplier;
+                mark.unrealized += outcome.equity.unrealized * multiplier;
+                match &mut risk {
+                    Some(total) => add_risk(total, &outcome.risk),
+                    None => risk = Some(outcome.risk),
+                }
+            }
+            let Some(point) = point else {
+                break;
+            };
+            equity_series.push(point);
+            if let Some(risk) = &risk {
+                risk_summary.observe(risk);
+            }
+        }
+
+        let products = simulators
+            .into_iter()
+            .zip(configs)
+            .zip(currencies.into_iter().zip(last_marks))
+            .map(|((simulator, config), (currency, account_pnl))| {
+                let symbol = config.product.as_ref().map_or_else(String::new, |p| p.symbol.clone());
+                Ok(ProductReport { symbol, report: simulator.finish()?, config, currency, account_pnl })
+            })
+            .collect::<Result<Vec<_>, SimError>>()?;
+        let account_currency = fx.account_currency().to_string();
+        Ok(PortfolioReport { products, equity_series, risk_summary, account_currency })
+    }
+}
+
+/// Convert a product's risk into the account currency at `rate`
+fn scale_risk(risk: &mut RiskSnapshot, rate: f64) {
+    risk.margin *= rate;
+    risk.notional *= rate;
+    risk.net_delta *= rate;
+    risk.net_gamma *= rate;
+    risk.net_theta *= rate;
+    risk.net_vega *= rate;
+}
+
+fn add_risk(total: &mut RiskSnapshot, risk: &RiskSnapshot) {
+    total.margin += risk.margin;
+    total.notional += risk.notional;
+    total.net_delta += risk.net_delta;
+    total.net_gamma += risk.net_gamma;
+    total.net_theta += risk.net_theta;
+    total.net_vega += risk.net_vega;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portfolio_sums_its_products() {
+        let yaml = "simulation: {days: 10, initial_price: 75.0, volatility: 0.3, seed: 7}\n\
+                    strategy: {strategy_type: straddle, entry_dte: 1}\n\
+                    portfolio:\n\
+                    \x20 products:\n\
+                    \x20   - symbol: cl\n\
+                    \x20     initial_price: 75.0\n\
+                    \x20     volatility: 0.3\n\
+                    \x20     strategy: {strategy_type: straddle, entry_dte: 1}\n\
+                    \x20   - symbol: es\n\
+                    \x20     initial_price: 5000.0\n\
+                    \x20     volatility: 0.15\n\
+                    \x20     strategy: {strategy_type: strangle, entry_dte: 2}\n\
+                    \x20 correlation: [[1.0, 0.4], [0.4, 1.0]]\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        let report = SimulationEngine::run_portfolio(&config).unwrap();
+        let symbols: Vec<&str> = report.products.iter().map(|p| p.symbol.as_str()).collect();
+        assert_eq!(symbols, ["/CL", "/ES"]);
+        assert_eq!(report.products[1].config.simulation.contract_multiplier, 50.0);
+
+        // The first product's path is its standalone path, and the total is the sum in dollars
+        let alone = SimulationEngine::run(&report.products[0].config).unwrap();
+        assert_eq!(alone.net_pnl(), report.products[0].report.net_pnl());
+        let dollars = |p: &ProductReport| p.report.net_pnl() * p.config.simulation.contract_multiplier;
+        let sum: f64 = report.products.iter().map(dollars).sum();
+        assert!((report.net_pnl() - sum).abs() < 1e-6);
+        assert!(report.risk_summary.peak_notional.is_some());
+
+        let singular = yaml.replace("0.4], [0.4", "1.0], [1.0");
+        assert!(Config::from_yaml_str(&singular).is_err());
+    }
+
+    #[test]
+    fn test_products_convert_to_the_account_currency() {
+        let yaml = "simulation: {days: 5, initial_price: 75.0, volatility: 0.3, seed: 3}\n\
+                    strategy: &straddle {strategy_type: straddle, entry_dte: 1}\n\
+                    portfolio:\n\
+                    \x20 products:\n\
+                    \x20   - {symbol: cl, initial_price: 75.0, volatility: 0.3, strategy: *straddle}\n\
+                    \x20   - {symbol: fesx, initial_price: 5000.0, volatility: 0.2, strategy: *straddle}\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        assert!(SimulationEngine::run_portfolio(&config).is_err());
+
+        let config = Config::from_yaml_str(&format!("{}fx:\n  rates: {{EUR: {{rate: 1.1}}}}\n", yaml)).unwrap();
+        let report = SimulationEngine::run_portfolio(&config).unwrap();
+        let euro = &report.products[1];
+        assert_eq!(euro.currency, "EUR");
+        assert!((euro.account_pnl - euro.report.net_pnl() * 10.0 * 1.1).abs() < 1e-6);
+        let sum: f64 = report.products.iter().map(|p| p.account_pnl).sum();
+        assert!((report.net_pnl() - sum).abs() < 1e-6);
+    }
+}
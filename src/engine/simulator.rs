@@ -0,0 +1,1603 @@
+//! Step-wise Simulator
+//!
+//! Drives a simulation one price bar at a time so callers can inspect the
+//! state between bars (interactive and streaming frontends). The batch
+//! `SimulationEngine::run` is just `step()` until exhausted, then `finish()`.
+
+use super::hooks::Hooks;
+use super::lifecycle::{Lifecycle, LifecycleState};
+use super::{
+    calculate_fractional_dte, calculate_intrinsic, mark_to_market, open_position, opened_entry, position_value,
+    quote_entry, quote_leg, risk_snapshot, strikes, value_legs, CloseCause, DailyHalt, KillSwitchTrip, LegState,
+    LogEntry, PnLSummary, PositionTracking, PricePath, RollDestination, SimulationReport, StrikeMarket,
+};
+use crate::calendar::intraday::{Timestamp, TradingCalendar};
+use crate::calendar::{DteUnit, TimeOfDay};
+use crate::config::{Config, ConfigError};
+use crate::data::{ImpliedVolSeries, IndexVol, OptionChains, VolIndexSeries};
+use crate::error::SimError;
+use crate::events::{CloseReason, Event, EventStore, OptionContract, OptionType, PositionId, Side};
+use crate::prices::{PricePoint, RealizedIv, RealizedVol};
+use crate::pricing::{ValuationCache, VolSurface};
+use crate::reporting::{self, blotter, EquityPoint, RiskSummary, TradeRecord};
+use crate::risk::{LimitAction, PinRisk, PinnedLeg, RiskLimits, RiskSnapshot};
+use crate::triggers::{self, RollDecision, RollReason, TriggerMark};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Arc;
+
+/// What happened on a single bar
+#[derive(Debug, Clone)]
+pub struct BarOutcome {
+    /// Index of the bar in the price path
+    pub bar_index: usize,
+    pub timestamp: Timestamp,
+    pub price: f64,
+    /// Trade closed on this bar (a roll closes and reopens on the same bar)
+    pub closed: Option<TradeRecord>,
+    /// Position opened on this bar
+    pub opened: Option<PositionId>,
+    /// Mark-to-market after the bar
+    pub equity: EquityPoint,
+    /// Margin and Greeks after the bar
+    pub risk: RiskSnapshot,
+}
+
+/// The scheduled roll rule evaluated for the open position at one bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct RollCheck {
+    pub timestamp: Timestamp,
+    /// Fractional DTE at `timestamp`, in the configured DTE unit
+    pub dte: f64,
+    /// What the rule compares, e.g. "14:00 on expiration day 4"
+    pub rule: String,
+    /// Whether the schedule rolls (or expires) the position at `timestamp`
+    pub due: bool,
+}
+
+impl RollCheck {
+    fn evaluate(
+        config: &Config,
+        calendar: &TradingCalendar,
+        roll_time: u32,
+        pos: &PositionTracking,
+        timestamp: Timestamp,
+    ) -> Self {
+        let dte = match config.dte_unit() {
+            DteUnit::Trading => calculate_fractional_dte(&timestamp, pos.expiration_day),
+            unit => unit.remaining(calendar, &timestamp, pos.expiration_day),
+        };
+        let mark = TriggerMark { timestamp, price: 0.0, dte, unrealized_pnl: 0.0 };
+        let (rule, due) = triggers::schedule(config, pos, roll_time, &mark);
+        Self { timestamp, dte, rule, due }
+    }
+}
+
+/// What entries do when a session has no bar at the entry time (an early
+/// close or a gap in the data)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissedEntry {
+    /// Enter at the first bar after the entry time, however late
+    #[default]
+    NextBar,
+    /// Only enter within one bar of the entry time, otherwise wait a day
+    SkipDay,
+    /// Enter at the first bar of the next session instead
+    Open,
+}
+
+impl MissedEntry {
+    /// Parse `strategy.missed_entry`
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "next_bar" => Some(MissedEntry::NextBar),
+            "skip_day" => Some(MissedEntry::SkipDay),
+            "open" => Some(MissedEntry::Open),
+            _ => None,
+        }
+    }
+}
+
+/// Read-only view of the simulator between bars
+#[derive(Debug)]
+pub struct SimulatorState<'a> {
+    /// Bars processed so far
+    pub bars_processed: usize,
+    pub total_bars: usize,
+    /// Currently open position, if any
+    pub position: Option<&'a PositionTracking>,
+    /// Where the book is in the position's life
+    pub lifecycle: LifecycleState,
+    /// Running totals; `unrealized_pnl` is only filled in by `finish()`
+    pub pnl_summary: &'a PnLSummary,
+    /// Mark of the most recent bar
+    pub last_equity: Option<&'a EquityPoint>,
+    pub trade_records: &'a [TradeRecord],
+    /// Opens, closes and rejected entries so far
+    pub log: &'a [LogEntry],
+    /// IV of the most recent bar
+    pub implied_vol: f64,
+}
+
+/// Bar-by-bar simulation of `config.strategy`
+pub struct Simulator {
+    config: Config,
+    calendar: TradingCalendar,
+    event_store: EventStore,
+    price_bars: PricePath,
+    next_bar: usize,
+    /// First bar after `simulation.warmup_days`; earlier bars open nothing
+    trading_start: usize,
+    entry_time: u32,
+    roll_time: u32,
+    /// Session of the latest bar and whether it had a bar at the entry time
+    entry_window: Option<(u32, bool)>,
+    implied_vol: f64,
+    /// IV of the most recent bar (differs from `implied_vol` with a series)
+    current_vol: f64,
+    bars_per_year: f64,
+    /// The position, if any, and where it is in its life
+    lifecycle: Lifecycle,
+    pnl_summary: PnLSummary,
+    trade_records: Vec<TradeRecord>,
+    equity_series: Vec<EquityPoint>,
+    risk_summary: RiskSummary,
+    log: Vec<LogEntry>,
+    hooks: Hooks,
+    /// Leg valuations shared by marks and risk within a bar
+    valuation: ValuationCache,
+    /// Observed option quotes that replace the model where they exist
+    quotes: Option<Arc<OptionChains>>,
+    /// Where every IV comes from: the configured surface under any series or index levels
+    vols: VolSurface,
+    /// Volatility index levels for `config.vol_index`
+    vol_index: Option<Arc<VolIndexSeries>>,
+    /// Rolling realized vol of the path for `config.realized_vol`
+    realized_vol: Option<Arc<RealizedVol>>,
+    /// Caps on net Greeks from `config.risk_limits`
+    limits: Option<RiskLimits>,
+    /// Session the last entry was blocked by a limit in, until one opens
+    blocked_on: Option<u32>,
+    /// Highest equity since the start or the last kill switch trip, per unit
+    equity_peak: f64,
+    /// No entries before this session after a kill switch trip
+    resume_day: Option<u32>,
+    kill_switches: Vec<KillSwitchTrip>,
+    /// Session of the latest bar and equity before it opened, per unit
+    day_open: Option<(u32, f64)>,
+    /// Session trading was halted in by the daily loss limit
+    halted_on: Option<u32>,
+    daily_halts: Vec<DailyHalt>,
+    /// Settlement of short strikes near the money from `config.pin_risk`
+    pin_risk: Option<PinRisk>,
+    /// Draws of `PinMode::Random`, seeded from the simulation seed
+    pin_rng: StdRng,
+    pins: Vec<PinnedLeg>,
+    /// Bar of the last `Event::RiskSnapshot`, for `simulation.risk_snapshot_minutes`
+    last_risk_event: Option<Timestamp>,
+}
+
+/// Why the book is being closed out ahead of its roll
+#[derive(Debug, Clone, Copy)]
+enum Liquidation {
+    KillSwitch { drawdown: f64, stand_down_days: u32 },
+    DailyLoss { loss: f64 },
+}
+
+/// The bar being processed and what its session allows
+#[derive(Debug, Clone, Copy)]
+struct Bar {
+    index: usize,
+    timestamp: Timestamp,
+    price: f64,
+    /// Equity before the bar's session opened, per unit
+    day_open_equity: f64,
+    /// A flat book enters on this bar
+    entry_bar: bool,
+    /// The risk limits let an entry be tried on this bar
+    limits_retry: bool,
+}
+
+/// What the close and entry phases did on one bar
+#[derive(Debug, Default)]
+struct BarActions {
+    closed: Option<TradeRecord>,
+    opened: Option<PositionId>,
+    /// An entry was blocked by a risk limit
+    rejected: bool,
+    /// A loss rule closed the book
+    liquidated: bool,
+}
+
+impl Simulator {
+    /// Validate the config, generate the price path and set up an empty book
+    pub fn new(config: Config) -> Result<Self, SimError> {
+        let path = PricePath::generate(&config)?;
+        Self::with_path(config, path)
+    }
+
+    /// Validate the config and set up an empty book over an existing path
+    ///
+    /// `config.simulation` still sets implied vol, rates and bar length, but
+    /// its seed and GBM parameters are not used to generate anything.
+    pub fn with_path(config: Config, price_bars: PricePath) -> Result<Self, SimError> {
+        config.validate()?;
+
+        // Parse times from config
+        let entry_time = u32::from(TimeOfDay::parse(&config.strategy.entry_time)?.minutes());
+        let roll_time = u32::from(TimeOfDay::parse(&config.strategy.roll_time)?.minutes());
+
+        let calendar = config.trading_calendar();
+        let resolution = config.simulation.intraday_resolution_minutes;
+
+        // Implied volatility for option pricing (volatility + VRP unless `vol_surface` says otherwise)
+        let mut vols = config.vol_surface()?;
+        let implied_vol = vols.atm_vol(0, f64::from(config.strategy.entry_dte));
+        let realized_vol = config.realized_vol.as_ref().map(|rules| {
+            let realized = Arc::new(RealizedVol::from_bars(price_bars.bars(), rules.estimator(), rules.window_days));
+            if rules.implied_vol == "realized" {
+                let premium = config.simulation.volatility_risk_premium;
+                vols.fall_back_to(Arc::new(RealizedIv { realized: Arc::clone(&realized), premium }));
+            }
+            realized
+        });
+        // GBM steps are calendar time, so annualize per-bar returns the same way
+        let bars_per_year = 365.25 * 24.0 * 60.0 / resolution as f64;
+
+        // Warm-up sessions see their bars but open nothing
+        let mut sessions = Vec::new();
+        let warmup = config.simulation.warmup_days as usize;
+        let trading_start = price_bars
+            .bars()
+            .iter()
+            .position(|bar| {
+                let session = bar.timestamp.session_day();
+                if sessions.last() != Some(&session) {
+                    sessions.push(session);
+                }
+                sessions.len() > warmup
+            })
+            .unwrap_or(price_bars.len());
+        if warmup > 0 && trading_start >= price_bars.len() {
+            return Err(SimError::Config(ConfigError::Validation(format!(
+                "simulation.warmup_days ({}) leaves no trading in the path's {} sessions",
+                warmup,
+                sessions.len()
+            ))));
+        }
+
+        let mut event_store = EventStore::new();
+        if let Some(first) = price_bars.bars().first() {
+            event_store.append(Event::RunStarted {
+                timestamp: (first.timestamp.day, TimeOfDay::from_minutes(first.timestamp.minute as u16)),
+                seed: config.simulation.seed,
+                seed_generated: config.simulation.seed_generated,
+            })?;
+        }
+
+        Ok(Self {
+            calendar,
+            event_store,
+            equity_series: Vec::with_capacity(price_bars.len()),
+            price_bars,
+            next_bar: 0,
+            trading_start,
+            entry_time,
+            roll_time,
+            entry_window: None,
+            implied_vol,
+            current_vol: implied_vol,
+            bars_per_year,
+            lifecycle: Lifecycle::default(),
+            pnl_summary: PnLSummary::default(),
+            trade_records: Vec::new(),
+            risk_summary: RiskSummary::default(),
+            log: Vec::new(),
+            hooks: Hooks::default(),
+            valuation: ValuationCache::with_model(config.pricing_model())
+                .with_tolerance(config.simulation.valuation_tolerance),
+            quotes: None,
+            vols,
+            vol_index: None,
+            realized_vol,
+            limits: config.risk_limits.as_ref().and_then(RiskLimits::from_config),
+            blocked_on: None,
+            equity_peak: 0.0,
+            resume_day: None,
+            kill_switches: Vec::new(),
+            day_open: None,
+            halted_on: None,
+            daily_halts: Vec::new(),
+            pin_risk: config.pin_risk.as_ref().and_then(PinRisk::from_config),
+            pin_rng: StdRng::seed_from_u64(config.simulation.seed),
+            pins: Vec::new(),
+            last_risk_event: None,
+            config,
+        })
+    }
+
+    /// Events recorded so far, in time order
+    pub fn events(&self) -> &[Event] {
+        self.event_store.all_events()
+    }
+
+    /// Read-only view of the current state
+    pub fn state(&self) -> SimulatorState<'_> {
+        SimulatorState {
+            bars_processed: self.next_bar,
+            total_bars: self.price_bars.len(),
+            position: self.lifecycle.position(),
+            lifecycle: self.lifecycle.state(),
+            pnl_summary: &self.pnl_summary,
+            last_equity: self.equity_series.last(),
+            trade_records: &self.trade_records,
+            log: &self.log,
+            implied_vol: self.current_vol,
+        }
+    }
+
+    /// Time of the bar the next `step()` processes
+    pub fn next_timestamp(&self) -> Option<Timestamp> {
+        self.price_bars.bars().get(self.next_bar).map(|bar| bar.timestamp)
+    }
+
+    /// The scheduled roll rule for the open position at the next bar (before
+    /// P&L triggers and loss rules, which can also close it), or `None` when
+    /// flat or finished
+    pub fn pending_roll(&self) -> Option<RollCheck> {
+        let pos = self.lifecycle.position()?;
+        Some(RollCheck::evaluate(&self.config, &self.calendar, self.roll_time, pos, self.next_timestamp()?))
+    }
+
+    /// Attach `tag` to the open position, recording it in the event log; the
+    /// position's trade record carries it when it closes
+    pub fn tag_position(&mut self, tag: &str) -> Result<(), SimError> {
+        if !crate::config::is_tag(tag) {
+            return Err(SimError::Execution(format!("invalid tag {:?}", tag)));
+        }
+        let last_bar = self.next_bar.checked_sub(1).and_then(|bar| self.price_bars.bars().get(bar));
+        let (Some(pos), Some(bar)) = (self.lifecycle.position_mut(), last_bar) else {
+            return Err(SimError::Execution(format!("no open position to tag {:?}", tag)));
+        };
+        if pos.tags.iter().any(|existing| existing == tag) {
+            return Ok(());
+        }
+        self.event_store.append(Event::PositionTagged {
+            position_id: pos.position_id,
+            timestamp: (bar.timestamp.day, TimeOfDay::from_minutes(bar.timestamp.minute as u16)),
+            tag: tag.to_string(),
+        })?;
+        pos.tags.push(tag.to_string());
+        Ok(())
+    }
+
+    /// Price entries, marks and closes from `chains` where they quote a leg
+    ///
+    /// The chains must use the price path's calendar days (see
+    /// `data::ChainOptions::mapping`); unquoted legs keep Black-76 prices.
+    pub fn use_option_chains(&mut self, chains: Arc<OptionChains>) -> &mut Self {
+        self.quotes = Some(chains);
+        self
+    }
+
+    /// Price at the IVs of `series` instead of `volatility + volatility_risk_premium`
+    ///
+    /// Like option chains, the series must use the price path's calendar
+    /// days; bars before its first date keep the constant IV.
+    pub fn use_implied_vols(&mut self, series: Arc<ImpliedVolSeries>) -> &mut Self {
+        self.vols.prefer(series);
+        self
+    }
+
+    /// Apply the `config.vol_index` entry filters and pricing to the levels of `series`
+    ///
+    /// Calendar days must match the price path, as for implied vols. While a
+    /// filter is set, bars before the first level open nothing, and a roll
+    /// that falls outside the filters closes without reopening. Without a
+    /// series, `config.vol_index` has no effect.
+    pub fn use_vol_index(&mut self, series: Arc<VolIndexSeries>) -> &mut Self {
+        if let Some(rules) = self.config.vol_index.as_ref().filter(|rules| rules.implied_vol == "index") {
+            self.vols.fall_back_to(Arc::new(IndexVol { series: Arc::clone(&series), scale: rules.iv_scale }));
+        }
+        self.vol_index = Some(series);
+        self
+    }
+
+    /// Call `hook` after every bar
+    pub fn on_bar<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: FnMut(&SimulatorState<'_>, &BarOutcome) + Send + 'static,
+    {
+        self.hooks.on_bar.push(Box::new(hook));
+        self
+    }
+
+    /// Call `hook` whenever a position is opened (including the new leg of a roll)
+    pub fn on_position_opened<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: FnMut(&SimulatorState<'_>, &PositionTracking) + Send + 'static,
+    {
+        self.hooks.on_position_opened.push(Box::new(hook));
+        self
+    }
+
+    /// Call `hook` when a position is closed and replaced on the same bar
+    pub fn on_roll<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: FnMut(&SimulatorState<'_>, &TradeRecord, &PositionTracking) + Send + 'static,
+    {
+        self.hooks.on_roll.push(Box::new(hook));
+        self
+    }
+
+    /// Call `hook` whenever a position is closed
+    pub fn on_close<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: FnMut(&SimulatorState<'_>, &TradeRecord) + Send + 'static,
+    {
+        self.hooks.on_close.push(Box::new(hook));
+        self
+    }
+
+    /// True once every bar has been processed
+    pub fn is_finished(&self) -> bool {
+        self.next_bar >= self.price_bars.len()
+    }
+
+    /// Process the next bar; `Ok(None)` once the price path is exhausted
+    pub fn step(&mut self) -> Result<Option<BarOutcome>, SimError> {
+        let index = self.next_bar;
+        let Some(&point) = self.price_bars.bars().get(index) else {
+            return Ok(None);
+        };
+        self.next_bar += 1;
+
+        let bar = self.start_bar(index, point.timestamp, point.price);
+        let mut actions = BarActions::default();
+
+        // Check for roll triggers
+        self.lifecycle.begin_bar(bar.timestamp);
+        self.manage(&bar, &mut actions)?;
+
+        // Open new position at entry time if none exists
+        let stood_down = actions.liquidated
+            || bar.index < self.trading_start
+            || self.resume_day.is_some_and(|session| bar.timestamp.session_day() < session)
+            || self.halted_on == Some(bar.timestamp.session_day());
+        if self.lifecycle.position().is_none() && bar.entry_bar && !actions.rejected && !stood_down {
+            self.enter(&bar, None, &mut actions)?;
+        }
+
+        let (equity, risk) = self.mark(&bar)?;
+        let outcome = BarOutcome {
+            bar_index: bar.index,
+            timestamp: bar.timestamp,
+            price: bar.price,
+            closed: actions.closed,
+            opened: actions.opened,
+            equity,
+            risk,
+        };
+        if !self.hooks.is_empty() {
+            let mut hooks = std::mem::take(&mut self.hooks);
+            hooks.dispatch(&self.state(), &outcome);
+            self.hooks = hooks;
+        }
+        Ok(Some(outcome))
+    }
+
+    /// Update the session bookkeeping for a new bar and say what it allows
+    fn start_bar(&mut self, index: usize, timestamp: Timestamp, price: f64) -> Bar {
+        let config = &self.config;
+        let entry_time = self.entry_time;
+        // A rejected entry waits for the next session; a delayed one tries again every bar
+        let limits_retry = match (self.limits, self.blocked_on) {
+            (Some(limits), Some(session)) => limits.action == LimitAction::Delay || session != timestamp.session_day(),
+            _ => true,
+        };
+        // Sessions open the evening before, so the loss limit resets at the session break, not at midnight
+        let session = timestamp.session_day();
+        if self.day_open.is_none_or(|(day, _)| day != session) {
+            self.day_open = Some((session, self.equity_series.last().map_or(0.0, |e| e.equity())));
+        }
+        let day_open_equity = self.day_open.map_or(0.0, |(_, equity)| equity);
+        // Whether a flat book enters on this bar, given the bars the session has had
+        let in_window =
+            (entry_time..entry_time + config.simulation.intraday_resolution_minutes).contains(&timestamp.minute);
+        let missed_last = self.entry_window.is_some_and(|(day, seen)| day != session && !seen);
+        self.entry_window = match self.entry_window {
+            Some((day, seen)) if day == session => Some((day, seen || in_window)),
+            _ => Some((session, in_window)),
+        };
+        let entry_bar = match config.missed_entry() {
+            MissedEntry::NextBar => timestamp.minute >= entry_time,
+            MissedEntry::SkipDay => in_window,
+            MissedEntry::Open => in_window || missed_last,
+        };
+        Bar { index, timestamp, price, day_open_equity, entry_bar, limits_retry }
+    }
+
+    /// Whether the vol index and realized vol filters let a position open on `day`
+    fn entry_allowed(&self, day: u32) -> bool {
+        let index_allows = match self.config.vol_index.as_ref().zip(self.vol_index.as_deref()) {
+            Some((rules, series)) if rules.filters_entries() => {
+                series.level(day).is_some_and(|level| rules.allows_entry(level))
+            }
+            _ => true,
+        };
+        let realized_allows = match self.config.realized_vol.as_ref().zip(self.realized_vol.as_deref()) {
+            Some((rules, realized)) if rules.filters_entries() => {
+                realized.vol(day).is_some_and(|vol| rules.allows_entry(vol))
+            }
+            _ => true,
+        };
+        index_allows && realized_allows
+    }
+
+    /// Quantity multiplier from `realized_vol.target_vol`
+    fn size(&self, day: u32) -> f64 {
+        let rules = self.config.realized_vol.as_ref().zip(self.realized_vol.as_deref());
+        rules.map_or(1.0, |(rules, realized)| rules.size(realized.vol(day)))
+    }
+
+    /// Close the open position when a roll trigger fires or a loss rule trips,
+    /// and reopen it on a roll
+    fn manage(&mut self, bar: &Bar, actions: &mut BarActions) -> Result<(), SimError> {
+        let Simulator { config, calendar, lifecycle, pnl_summary, valuation, quotes, vols, equity_peak, .. } = self;
+        let Some(pos) = lifecycle.position() else {
+            return Ok(());
+        };
+        let (timestamp, current_price) = (bar.timestamp, bar.price);
+        let quotes = quotes.as_deref();
+
+        // Roll triggers and loss rules all read the position at this bar's mark
+        let unrealized = mark_to_market(valuation, calendar, pos, &timestamp, current_price, config, vols, quotes);
+        let dte = RollCheck::evaluate(config, calendar, self.roll_time, pos, timestamp).dte;
+        let mark = TriggerMark { timestamp, price: current_price, dte, unrealized_pnl: unrealized - pos.rolled_pnl };
+        let decision = triggers::evaluate_triggers(pos, config, self.roll_time, &mark);
+        let should_roll = !matches!(decision, RollDecision::Hold);
+        // A put or call trigger moves that leg alone within the position's expiry;
+        // the schedule, and anything at expiration, rolls the whole position
+        let expiring = timestamp >= Timestamp::new(pos.expiration_day, self.roll_time);
+        let leg_roll = match decision {
+            RollDecision::RollPut { reason } => Some((OptionType::Put, reason)),
+            RollDecision::RollCall { reason } => Some((OptionType::Call, reason)),
+            _ => None,
+        }
+        .filter(|(_, reason)| !reason.is_scheduled() && !expiring);
+
+        // Drawdown from peak and loss on the day, against the loss rules
+        let liquidation = if config.kill_switch.is_some() || config.daily_loss.is_some() {
+            let equity = pnl_summary.realized_pnl + unrealized;
+            let multiplier = config.simulation.contract_multiplier;
+            let drawdown = (*equity_peak - equity) * multiplier;
+            let loss = (bar.day_open_equity - equity) * multiplier;
+            match (&config.kill_switch, &config.daily_loss) {
+                (Some(switch), _) if drawdown > switch.max_drawdown => {
+                    Some(Liquidation::KillSwitch { drawdown, stand_down_days: switch.stand_down_days })
+                }
+                (_, Some(limit)) if loss > limit.max_loss => Some(Liquidation::DailyLoss { loss }),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        if !should_roll && liquidation.is_none() {
+            return Ok(());
+        }
+        if let (Some((option_type, reason)), None) = (leg_roll, &liquidation) {
+            return self.roll_leg(bar, option_type, &reason);
+        }
+
+        let pos = self.close(bar, liquidation, actions)?;
+        // Open new position at roll time, unless a loss rule closed it
+        if !actions.liquidated {
+            self.enter(bar, Some(&pos), actions)?;
+        }
+        Ok(())
+    }
+
+    /// Move the open position's `option_type` leg to its roll destination,
+    /// closing the old leg at its mark and keeping the position's expiry
+    fn roll_leg(&mut self, bar: &Bar, option_type: OptionType, reason: &RollReason) -> Result<(), SimError> {
+        let Simulator { config, calendar, event_store, lifecycle, pnl_summary, log, valuation, quotes, vols, .. } =
+            self;
+        let (timestamp, current_price) = (bar.timestamp, bar.price);
+        let quotes = quotes.as_deref();
+        let Some(pos) = lifecycle.position_mut() else {
+            return Ok(());
+        };
+        let Some(index) = pos.legs.iter().position(|leg| leg.contract.option_type == option_type) else {
+            return Ok(());
+        };
+
+        // Out of the old leg at its mark, into the new one in the market the marks use
+        let marks = value_legs(valuation, calendar, pos, &timestamp, current_price, config, vols, quotes);
+        let close_premium = marks[index].price;
+        let unit = config.dte_unit();
+        let expiry = pos.expiration_day;
+        let market = StrikeMarket {
+            model: config.pricing_model(),
+            underlying: current_price,
+            time_to_expiry: unit.years(unit.remaining(calendar, &timestamp, expiry)),
+            risk_free_rate: config.simulation.risk_free_rate,
+            implied_vol: vols.atm_vol(timestamp.day, calendar.calculate_dte(&timestamp, expiry)),
+            smile: vols.smile(),
+        };
+        let ladder = config.strike_config.ladder(current_price)?;
+        let old = pos.legs[index].clone();
+        let strike = strikes::roll_leg(config.roll_destination(), config.strike_selection(), &old, &ladder, &market);
+        let quantity = old.contract.quantity;
+        let new = quote_leg(&market, quotes, &timestamp, expiry, option_type, strike, quantity)?;
+        let new_contract = OptionContract { underlying_price: current_price, strike, ..old.contract };
+
+        event_store.append(Event::LegRolled {
+            position_id: pos.position_id,
+            leg_id: old.leg_id,
+            timestamp: (timestamp.day, TimeOfDay::from_minutes(timestamp.minute as u16)),
+            old_contract: old.contract,
+            close_premium,
+            new_contract,
+            open_premium: new.premium,
+            trigger: reason.trigger(),
+        })?;
+        let side = old.contract.side;
+        let pnl = side.pnl(old.entry_premium * quantity, close_premium * quantity);
+        if side == Side::Long {
+            pnl_summary.total_premium_collected += close_premium * quantity;
+            pnl_summary.total_premium_paid += new.premium * quantity;
+        } else {
+            pnl_summary.total_premium_paid += close_premium * quantity;
+            pnl_summary.total_premium_collected += new.premium * quantity;
+        }
+        log.push(LogEntry::LegRolled {
+            timestamp,
+            position_id: pos.position_id.0,
+            price: current_price,
+            option_type,
+            old_strike: old.strike(),
+            new_strike: strike,
+            pnl,
+        });
+        pos.rolled_pnl += pnl;
+        pos.legs[index] = LegState { contract: new_contract, entry_premium: new.premium, greeks: new.greeks, ..old };
+        Ok(())
+    }
+
+    /// Close the open position at this bar's price (its model value, or its
+    /// settlement on expiration) and hand it back
+    fn close(
+        &mut self,
+        bar: &Bar,
+        liquidation: Option<Liquidation>,
+        actions: &mut BarActions,
+    ) -> Result<PositionTracking, SimError> {
+        let Simulator {
+            config,
+            calendar,
+            event_store,
+            price_bars,
+            lifecycle,
+            pnl_summary,
+            trade_records,
+            log,
+            valuation,
+            quotes,
+            vols,
+            equity_peak,
+            resume_day,
+            kill_switches,
+            halted_on,
+            daily_halts,
+            pin_risk,
+            pin_rng,
+            pins,
+            ..
+        } = self;
+        let (timestamp, current_price) = (bar.timestamp, bar.price);
+        let quotes = quotes.as_deref();
+        let pos = lifecycle.begin_close(timestamp)?;
+        // Held to the roll time on expiration day, the position settles
+        let expired = timestamp >= Timestamp::new(pos.expiration_day, self.roll_time);
+
+        let closes: Vec<f64> = if !expired {
+            // Early close: at the bar's mark, the value the triggers and the equity curve see
+            let values = value_legs(valuation, calendar, pos, &timestamp, current_price, config, vols, quotes);
+            values.iter().map(|value| value.price).collect()
+        } else {
+            // Expiration: intrinsic value, unless a short strike is pinned
+            let legs: Vec<(f64, bool)> = pos.legs.iter().map(|leg| (leg.strike(), leg.is_call())).collect();
+            let settled = match pin_risk.filter(|_| config.strategy.side() == Side::Short) {
+                Some(pin) => pin.settle(current_price, &legs, pin_rng),
+                None => vec![None; legs.len()],
+            };
+            legs.iter()
+                .zip(settled)
+                .map(|(&(strike, is_call), pinned)| {
+                    let intrinsic = calculate_intrinsic(current_price, strike, is_call);
+                    let Some((outcome, value)) = pinned else {
+                        return intrinsic;
+                    };
+                    pins.push(PinnedLeg {
+                        timestamp,
+                        strike,
+                        is_call,
+                        settlement: current_price,
+                        outcome,
+                        value,
+                        intrinsic,
+                    });
+                    value
+                })
+                .collect()
+        };
+
+        // Same signed P&L as the marks and the profit-target trigger
+        let position_pnl = pos.pnl(&closes);
+        pnl_summary.realized_pnl += position_pnl;
+        let bars = price_bars.bars();
+        let close_bar = bars.partition_point(|p| p.timestamp <= timestamp);
+        let entry_bar = bars.partition_point(|p| p.timestamp < pos.entry_timestamp);
+        let life_bars = &bars[entry_bar..close_bar];
+        let life_prices: Vec<f64> = life_bars.iter().map(|p| p.price).collect();
+        let mid_value = life_bars.get(life_bars.len() / 2).map(|mid| {
+            let (timestamp, price) = (&mid.timestamp, mid.price);
+            position_value(valuation, calendar, pos, timestamp, price, config, vols, quotes)
+        });
+        trade_records.push(TradeRecord {
+            position_id: pos.position_id.0,
+            side: pos.side(),
+            entry: pos.entry_timestamp,
+            close: timestamp,
+            entry_price: pos.entry_price,
+            close_price: current_price,
+            entry_premium: pos.entry_value(),
+            mid_value,
+            close_value: pos.value_of(&closes),
+            pnl: position_pnl,
+            implied_vol: pos.implied_vol,
+            realized_vol: reporting::realized_volatility(&life_prices, self.bars_per_year),
+            max_loss: pos.max_loss,
+            initial_margin: pos.initial_margin,
+            tags: pos.tags.clone(),
+        });
+        actions.closed = trade_records.last().cloned();
+        valuation.clear();
+
+        // Track close value
+        if pos.side() == Side::Long {
+            pnl_summary.total_premium_collected += pos.value_of(&closes);
+        } else {
+            pnl_summary.total_premium_paid += pos.value_of(&closes);
+        }
+
+        log.push(LogEntry::Closed {
+            timestamp,
+            position_id: pos.position_id.0,
+            price: current_price,
+            pnl: position_pnl,
+            cause: match liquidation {
+                Some(Liquidation::KillSwitch { .. }) => CloseCause::KillSwitch,
+                Some(Liquidation::DailyLoss { .. }) => CloseCause::DailyLoss,
+                None if expired => CloseCause::Expiration,
+                None => CloseCause::Roll,
+            },
+        });
+
+        let close_event = Event::PositionClosed {
+            position_id: pos.position_id,
+            timestamp: (timestamp.day, TimeOfDay::from_minutes(timestamp.minute as u16)),
+            close_premiums: pos.legs.iter().map(|leg| leg.leg_id).zip(closes.iter().copied()).collect(),
+            reason: if liquidation.is_some() { CloseReason::StopLoss } else { CloseReason::Expiration },
+        };
+        event_store.append(close_event)?;
+        let pos = lifecycle.close(timestamp)?;
+
+        match liquidation {
+            // Stand down, and measure drawdown from here on
+            Some(Liquidation::KillSwitch { drawdown, stand_down_days }) => {
+                let resume = resume_session(price_bars.bars(), timestamp, stand_down_days);
+                kill_switches.push(KillSwitchTrip { timestamp, drawdown, resume_day: resume });
+                *resume_day = Some(resume);
+                *equity_peak = pnl_summary.realized_pnl;
+            }
+            Some(Liquidation::DailyLoss { loss }) => {
+                daily_halts.push(DailyHalt { timestamp, loss });
+                *halted_on = Some(timestamp.session_day());
+            }
+            None => {}
+        }
+        actions.liquidated = liquidation.is_some();
+        Ok(pos)
+    }
+
+    /// Open a position at this bar, replacing `rolled_from` on a roll, unless
+    /// the entry filters or risk limits keep it out
+    fn enter(
+        &mut self,
+        bar: &Bar,
+        rolled_from: Option<&PositionTracking>,
+        actions: &mut BarActions,
+    ) -> Result<(), SimError> {
+        let (timestamp, current_price) = (bar.timestamp, bar.price);
+        if !self.entry_allowed(timestamp.day) || !bar.limits_retry {
+            return Ok(());
+        }
+        let size = self.size(timestamp.day);
+        let Simulator { config, event_store, lifecycle, pnl_summary, log, quotes, vols, limits, blocked_on, .. } = self;
+        let quotes = quotes.as_deref();
+        let mut entry = quote_entry(config, timestamp.day, current_price, rolled_from, vols, quotes, &timestamp)?;
+        entry.resize(size, config.strategy.fractional_quantity);
+        // The book is flat whenever a position opens, so the new position's exposure is the book's
+        let breach = limits.as_ref().and_then(|limits| limits.breach(&entry.exposure(config, current_price)));
+        if let Some(reason) = breach {
+            let rolled_from = rolled_from.map(|pos| pos.position_id);
+            reject(event_store, log, blocked_on, timestamp, current_price, rolled_from, reason)?;
+            actions.rejected = true;
+            return Ok(());
+        }
+
+        let tags = match rolled_from {
+            Some(_) => &config.strategy.roll_tags,
+            None => &config.strategy.entry_tags,
+        };
+        let pos = open_position(event_store, pnl_summary, config, current_price, entry, &timestamp, tags)?;
+        let same_strikes = rolled_from.is_some() && config.roll_destination() == RollDestination::SameStrikes;
+        log.push(opened_entry(&pos, timestamp, config, rolled_from.is_some(), same_strikes));
+
+        *blocked_on = None;
+        actions.opened = Some(pos.position_id);
+        lifecycle.open(pos, timestamp)
+    }
+
+    /// Record mark-to-market for the equity curve and the book's risk
+    fn mark(&mut self, bar: &Bar) -> Result<(EquityPoint, RiskSnapshot), SimError> {
+        let Simulator {
+            config,
+            calendar,
+            event_store,
+            lifecycle,
+            pnl_summary,
+            equity_series,
+            risk_summary,
+            valuation,
+            quotes,
+            vols,
+            current_vol,
+            equity_peak,
+            last_risk_event,
+            ..
+        } = self;
+        let (timestamp, current_price) = (bar.timestamp, bar.price);
+        let quotes = quotes.as_deref();
+
+        let mark_dte = lifecycle.position().map_or(f64::from(config.strategy.entry_dte), |pos| {
+            calendar.calculate_dte(&timestamp, pos.expiration_day)
+        });
+        *current_vol = vols.atm_vol(timestamp.day, mark_dte);
+        let unrealized = lifecycle
+            .position()
+            .map(|pos| mark_to_market(valuation, calendar, pos, &timestamp, current_price, config, vols, quotes))
+            .unwrap_or(0.0);
+        let equity = EquityPoint {
+            timestamp,
+            price: current_price,
+            realized: pnl_summary.realized_pnl,
+            unrealized,
+        };
+        equity_series.push(equity);
+        *equity_peak = equity_peak.max(pnl_summary.realized_pnl + unrealized);
+        let risk =
+            risk_snapshot(valuation, calendar, lifecycle.position(), &timestamp, current_price, config, vols, quotes);
+        risk_summary.observe(&risk);
+
+        // Periodic risk in the event log, on the first bar at least the cadence after the last
+        let cadence = config.simulation.risk_snapshot_minutes.map(u64::from);
+        let since_last = |last: Timestamp| timestamp.total_minutes() - last.total_minutes();
+        if cadence.is_some_and(|cadence| last_risk_event.is_none_or(|last| since_last(last) >= cadence)) {
+            let multiplier = config.simulation.contract_multiplier;
+            event_store.append(Event::RiskSnapshot {
+                timestamp: (timestamp.day, TimeOfDay::from_minutes(timestamp.minute as u16)),
+                position_id: lifecycle.position().map(|pos| pos.position_id),
+                margin: risk.margin,
+                net_delta: risk.net_delta,
+                net_gamma: risk.net_gamma,
+                net_theta: risk.net_theta,
+                net_vega: risk.net_vega,
+                unrealized_pnl: unrealized * multiplier,
+            })?;
+            *last_risk_event = Some(timestamp);
+        }
+        Ok((equity, risk))
+    }
+
+    /// Run any remaining bars and build the report
+    pub fn finish(mut self) -> Result<SimulationReport, SimError> {
+        while self.step()?.is_some() {}
+
+        // Open positions are marked at the final bar rather than counted at entry premium
+        self.pnl_summary.unrealized_pnl = self.equity_series.last().map(|p| p.unrealized).unwrap_or(0.0);
+
+        Ok(SimulationReport {
+            final_price: self.price_bars.bars().last().map(|p| p.price),
+            pnl_summary: self.pnl_summary,
+            trade_records: self.trade_records,
+            equity_series: self.equity_series,
+            risk_summary: self.risk_summary,
+            total_bars: self.price_bars.len(),
+            implied_vol: self.implied_vol,
+            log: self.log,
+            fills: blotter::fills(self.event_store.all_events()),
+            kill_switches: self.kill_switches,
+            daily_halts: self.daily_halts,
+            pins: self.pins,
+            lifecycle: self.lifecycle.into_transitions(),
+        })
+    }
+}
+
+/// Session entries resume in after a kill switch trip at `timestamp`: the
+/// `stand_down`th of the path's sessions after the trip's, so weekends and
+/// holidays don't count (past the path's end, the sessions are just counted on)
+fn resume_session(bars: &[PricePoint], timestamp: Timestamp, stand_down: u32) -> u32 {
+    let mut session = timestamp.session_day();
+    let start = bars.partition_point(|bar| bar.timestamp <= timestamp);
+    let mut later = bars[start..].iter().map(|bar| bar.timestamp.session_day());
+    for _ in 0..stand_down {
+        session = later.find(|&next| next > session).unwrap_or(session + 1);
+    }
+    session
+}
+
+/// Record an entry blocked by a risk limit, once per session it stays blocked
+fn reject(
+    event_store: &mut EventStore,
+    log: &mut Vec<LogEntry>,
+    blocked_on: &mut Option<u32>,
+    timestamp: Timestamp,
+    price: f64,
+    rolled_from: Option<PositionId>,
+    reason: String,
+) -> Result<(), SimError> {
+    if *blocked_on == Some(timestamp.session_day()) {
+        return Ok(());
+    }
+    *blocked_on = Some(timestamp.session_day());
+    event_store.append(Event::EntryRejected {
+        timestamp: (timestamp.day, TimeOfDay::from_minutes(timestamp.minute as u16)),
+        rolled_from,
+        reason: reason.clone(),
+    })?;
+    log.push(LogEntry::Rejected { timestamp, price, rolled: rolled_from.is_some(), reason });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SimulationEngine;
+
+    #[test]
+    fn test_stepping_matches_batch_run() {
+        let config = Config::default_1dte_straddle();
+        let batch = SimulationEngine::run(&config).unwrap();
+
+        let mut sim = Simulator::new(config).unwrap();
+        let mut steps = 0;
+        let mut closes = 0;
+        while let Some(outcome) = sim.step().unwrap() {
+            assert_eq!(outcome.bar_index, steps);
+            steps += 1;
+            closes += outcome.closed.is_some() as usize;
+        }
+        assert!(sim.is_finished());
+        assert_eq!(steps, batch.total_bars);
+        assert_eq!(closes, batch.trade_records.len());
+
+        let report = sim.finish().unwrap();
+        assert_eq!(report.net_pnl(), batch.net_pnl());
+    }
+
+    #[test]
+    fn test_state_between_bars() {
+        let mut sim = Simulator::new(Config::default_1dte_straddle()).unwrap();
+        assert_eq!(sim.state().bars_processed, 0);
+        assert!(sim.state().position.is_none());
+
+        // Step until the first entry
+        let opened = loop {
+            let outcome = sim.step().unwrap().expect("position opened before the path ends");
+            if let Some(id) = outcome.opened {
+                break id;
+            }
+        };
+        let state = sim.state();
+        assert_eq!(state.position.map(|p| p.position_id), Some(opened));
+        assert_eq!(state.pnl_summary.position_count, 1);
+        assert!(state.last_equity.is_some());
+    }
+
+    #[test]
+    fn test_pending_roll_predicts_the_next_bar() {
+        let mut simulator = Simulator::new(Config::default_1dte_straddle()).unwrap();
+        assert!(simulator.pending_roll().is_none());
+        let mut checked = 0;
+        loop {
+            let pending = simulator.pending_roll();
+            let Some(outcome) = simulator.step().unwrap() else {
+                break;
+            };
+            if let Some(check) = pending {
+                assert_eq!(check.timestamp, outcome.timestamp);
+                assert_eq!(check.due, outcome.closed.is_some());
+                checked += 1;
+            }
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_risk_reuses_marks_from_the_same_bar() {
+        let mut sim = Simulator::new(Config::default_1dte_straddle()).unwrap();
+        while sim.step().unwrap().is_some() {}
+        // Marks value both legs on each bar, then risk finds them cached
+        assert!(sim.valuation.hits() > 0);
+        assert!(sim.valuation.hits() * 2 >= sim.valuation.misses());
+    }
+
+    #[test]
+    fn test_valuation_tolerance_reuses_marks_across_bars() {
+        let run = |tolerance: f64| {
+            let mut config = Config::default_1dte_straddle();
+            config.simulation.valuation_tolerance = tolerance;
+            let mut sim = Simulator::new(config).unwrap();
+            while sim.step().unwrap().is_some() {}
+            let (updates, misses) = (sim.valuation.updates(), sim.valuation.misses());
+            (sim.finish().unwrap(), updates, misses)
+        };
+        let (exact, _, exact_misses) = run(0.0);
+        let (report, updates, misses) = run(0.01);
+        // Bars close to the last full evaluation update it instead of repeating it
+        assert!(updates > exact_misses / 4);
+        assert_eq!(updates + misses, exact_misses);
+        assert_eq!(report.trade_records.len(), exact.trade_records.len());
+        assert!((report.net_pnl() - exact.net_pnl()).abs() < 1e-6);
+
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.valuation_tolerance = 0.5;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_entries_are_priced_like_their_first_mark() {
+        use crate::config::HolidaysConfig;
+
+        // Trading-day DTE, with and without a holiday pulling the first expiration in a day
+        let holiday = HolidaysConfig { days: vec![2], expiration_adjustment: "preceding".to_string() };
+        for holidays in [None, Some(holiday)] {
+            let mut config = Config::default_1dte_straddle();
+            config.strategy.entry_dte = 2;
+            config.holidays = holidays;
+            let mut sim = Simulator::new(config).unwrap();
+            let outcome = loop {
+                let outcome = sim.step().unwrap().expect("position opened before the path ends");
+                if outcome.opened.is_some() {
+                    break outcome;
+                }
+            };
+            let pos = sim.lifecycle.position().unwrap();
+            let mut cache = ValuationCache::with_model(sim.config.pricing_model());
+            let (calendar, vols) = (&sim.calendar, &sim.vols);
+            let (timestamp, price) = (outcome.timestamp, outcome.price);
+            let mark = mark_to_market(&mut cache, calendar, pos, &timestamp, price, &sim.config, vols, None);
+            assert!(mark.abs() < 1e-9, "fresh position marked at {}", mark);
+        }
+    }
+
+    #[test]
+    fn test_quoted_legs_replace_model_premiums() {
+        use crate::data::{parse_option_chains, ChainOptions, Date, DayMapping};
+
+        let first_open = |sim: &mut Simulator| loop {
+            let outcome = sim.step().unwrap().expect("position opened before the path ends");
+            if outcome.opened.is_some() {
+                break sim.state().position.cloned().unwrap();
+            }
+        };
+        let model = first_open(&mut Simulator::new(Config::default_1dte_straddle()).unwrap());
+
+        let mapping = DayMapping::starting_week_of(Date::new(2024, 3, 4).unwrap());
+        let csv = format!(
+            "{},{},{},P,1.00,1.10\n",
+            mapping.date(model.entry_timestamp.day),
+            mapping.date(model.expiration_day),
+            model.legs[0].strike()
+        );
+        let options = ChainOptions { snapshot_time: TimeOfDay::hm(0, 0), mapping: Some(mapping) };
+        let chains = parse_option_chains(&csv, &options).unwrap();
+
+        let mut sim = Simulator::new(Config::default_1dte_straddle()).unwrap();
+        sim.use_option_chains(Arc::new(chains));
+        let quoted = first_open(&mut sim);
+        assert!((quoted.legs[0].entry_premium - 1.05).abs() < 1e-12);
+        // The call has no quote, so it keeps its Black-76 premium
+        assert_eq!(quoted.legs[1].entry_premium, model.legs[1].entry_premium);
+        // Marks use the same quote, so a fresh position is flat
+        assert_eq!(sim.state().last_equity.map(|e| e.unrealized), Some(0.0));
+    }
+
+    #[test]
+    fn test_implied_vol_series_replaces_constant_iv() {
+        use crate::data::{parse_implied_vols, IvOptions};
+
+        let config = Config::default_1dte_straddle();
+        let model = SimulationEngine::run(&config).unwrap();
+
+        // Vol doubles from the second calendar day on
+        let series = parse_implied_vols("2024-03-05,0.70\n", &IvOptions::default()).unwrap();
+        let mut sim = Simulator::new(config).unwrap();
+        sim.use_implied_vols(Arc::new(series));
+        let report = sim.finish().unwrap();
+
+        assert_eq!(report.trade_records.len(), model.trade_records.len());
+        for (quoted, base) in report.trade_records.iter().zip(&model.trade_records) {
+            let expected_vol = if quoted.entry.day == 0 { model.implied_vol } else { 0.70 };
+            assert_eq!(quoted.implied_vol, expected_vol);
+            if quoted.entry.day > 0 {
+                assert!(quoted.entry_premium > base.entry_premium);
+            }
+        }
+    }
+
+    #[test]
+    fn test_vol_index_filters_entries_and_sets_iv() {
+        use crate::config::VolIndexConfig;
+        use crate::data::{parse_vol_index, VolIndexOptions};
+
+        let mut config = Config::default_1dte_straddle();
+        config.vol_index = Some(VolIndexConfig {
+            min_entry: Some(35.0),
+            max_entry: None,
+            implied_vol: "index".to_string(),
+            iv_scale: 1.0,
+        });
+        // Too quiet on day 0, selling from day 2, quiet again from day 7
+        let csv = "2024-03-04,30.0\n2024-03-06,40.0\n2024-03-11,20.0\n";
+        let series = parse_vol_index(csv, &VolIndexOptions::default()).unwrap();
+        let mut sim = Simulator::new(config.clone()).unwrap();
+        sim.use_vol_index(Arc::new(series));
+        let report = sim.finish().unwrap();
+
+        assert!(!report.trade_records.is_empty());
+        for trade in &report.trade_records {
+            assert!((2..7).contains(&trade.entry.day), "entered on day {}", trade.entry.day);
+            assert!((trade.implied_vol - 0.40).abs() < 1e-12);
+        }
+        // Without levels the rules do nothing
+        let unfiltered = Simulator::new(config).unwrap().finish().unwrap();
+        assert!(unfiltered.trade_records.len() > report.trade_records.len());
+    }
+
+    #[test]
+    fn test_vol_surface_skew_prices_puts_over_calls() {
+        use crate::config::VolSurfaceConfig;
+
+        let mut config = Config::default_1dte_straddle();
+        config.strategy.strike_selection = "OTM".to_string();
+        config.strategy.strike_offset = 2.0;
+        let first_legs = |config: Config| {
+            let mut sim = Simulator::new(config).unwrap();
+            loop {
+                sim.step().unwrap().unwrap();
+                if let Some(pos) = sim.state().position {
+                    return (pos.legs[0].entry_premium, pos.legs[1].entry_premium, pos.implied_vol);
+                }
+            }
+        };
+        let (flat_put, flat_call, flat_vol) = first_legs(config.clone());
+        config.vol_surface = Some(VolSurfaceConfig { term: Vec::new(), skew: -0.5, curvature: 0.0 });
+        let (put, call, vol) = first_legs(config);
+
+        // Same ATM vol, with the put strike priced richer and the call cheaper
+        assert_eq!(vol, flat_vol);
+        assert!(put > flat_put && call < flat_call);
+    }
+
+    #[test]
+    fn test_risk_snapshot_events_follow_the_cadence() {
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.days = 3;
+        let snapshots = |config: Config| {
+            let mut sim = Simulator::new(config).unwrap();
+            while sim.step().unwrap().is_some() {}
+            let bars = sim.state().total_bars;
+            let snapshots: Vec<_> = sim
+                .events()
+                .iter()
+                .filter_map(|event| match event {
+                    Event::RiskSnapshot { timestamp, position_id, net_delta, .. } => {
+                        Some((Timestamp::new(timestamp.0, u32::from(timestamp.1.minutes())), *position_id, *net_delta))
+                    }
+                    _ => None,
+                })
+                .collect();
+            (bars, snapshots)
+        };
+        assert!(snapshots(config.clone()).1.is_empty());
+
+        config.simulation.risk_snapshot_minutes = Some(60);
+        let (bars, hourly) = snapshots(config);
+        assert!(hourly.len() > 1 && hourly.len() * 6 <= bars + 6);
+        for pair in hourly.windows(2) {
+            assert!(pair[1].0.total_minutes() - pair[0].0.total_minutes() >= 60);
+        }
+        // Flat snapshots carry no position and no risk
+        assert!(hourly.iter().any(|(_, position, _)| position.is_some()));
+        assert!(hourly.iter().filter(|(_, position, _)| position.is_none()).all(|(_, _, delta)| *delta == 0.0));
+    }
+
+    #[test]
+    fn test_warmup_days_open_nothing() {
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.days = 10;
+        let baseline = Simulator::new(config.clone()).unwrap().finish().unwrap();
+        config.simulation.warmup_days = 4;
+        let report = Simulator::new(config.clone()).unwrap().finish().unwrap();
+
+        // The path starts mid-session on day 0, which counts as the first of the four
+        let first_session = report.equity_series[0].timestamp.session_day();
+        assert!(!report.trade_records.is_empty());
+        assert!(report.trade_records.iter().all(|trade| trade.entry.session_day() >= first_session + 4));
+        assert!(baseline.trade_records.iter().any(|trade| trade.entry.session_day() < first_session + 4));
+        assert_eq!(report.equity_series.len(), baseline.equity_series.len());
+
+        // Warm-up counts the path's sessions, not its calendar days
+        let mut sessions: Vec<_> = baseline.equity_series.iter().map(|point| point.timestamp.session_day()).collect();
+        sessions.dedup();
+        config.simulation.warmup_days = sessions.len() as u32 - 1;
+        assert!(Simulator::new(config.clone()).is_ok());
+        config.simulation.warmup_days = sessions.len() as u32;
+        assert!(Simulator::new(config).is_err());
+    }
+
+    #[test]
+    fn test_realized_vol_filters_sizes_and_prices_entries() {
+        use crate::config::RealizedVolConfig;
+
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.days = 12;
+        let rules = RealizedVolConfig {
+            estimator: "ewma".to_string(),
+            window_days: 5,
+            lambda: 0.94,
+            min_entry: Some(0.01),
+            max_entry: None,
+            target_vol: None,
+            implied_vol: "config".to_string(),
+        };
+        config.realized_vol = Some(rules.clone());
+        let path = PricePath::generate(&config).unwrap();
+        let realized = RealizedVol::from_bars(path.bars(), rules.estimator(), 5);
+        let filtered = Simulator::with_path(config.clone(), path.clone()).unwrap().finish().unwrap();
+
+        // Nothing opens before the window has filled
+        assert!(!filtered.trade_records.is_empty());
+        assert!(filtered.trade_records.iter().all(|trade| realized.vol(trade.entry.day).is_some()));
+
+        // Twice the realized vol as the target doubles each leg, and the estimate plus VRP prices it
+        let vrp = config.simulation.volatility_risk_premium;
+        let target = realized.vol(11).unwrap() * 2.0;
+        config.realized_vol =
+            Some(RealizedVolConfig { target_vol: Some(target), implied_vol: "realized".to_string(), ..rules });
+        let mut sim = Simulator::with_path(config, path).unwrap();
+        let pos = loop {
+            sim.step().unwrap().unwrap();
+            if let Some(pos) = sim.state().position {
+                break pos.clone();
+            }
+        };
+        let estimate = realized.vol(pos.entry_timestamp.day).unwrap();
+        assert_eq!(pos.implied_vol, estimate + vrp);
+        let expected = (target / estimate).round().max(1.0);
+        assert!(expected > 1.0 && pos.legs.iter().all(|leg| leg.contract.quantity == expected));
+    }
+
+    #[test]
+    fn test_risk_limits_block_entries() {
+        use crate::config::RiskLimitsConfig;
+
+        let mut config = Config::default_1dte_straddle();
+        let multiplier = config.simulation.contract_multiplier;
+        let open_deltas = |log: &[LogEntry]| -> Vec<f64> {
+            let deltas = log.iter().filter_map(|entry| match entry {
+                LogEntry::Opened { greeks, .. } => Some((greeks.delta * multiplier).abs()),
+                _ => None,
+            });
+            deltas.collect()
+        };
+        let mut unlimited = open_deltas(&SimulationEngine::run(&config).unwrap().log);
+        unlimited.sort_by(f64::total_cmp);
+        let cap = unlimited[unlimited.len() / 2];
+
+        let limits = |action: &str| RiskLimitsConfig {
+            max_positions: None,
+            max_short_premium: None,
+            max_notional: None,
+            max_delta: Some(cap),
+            max_gamma: None,
+            max_theta: None,
+            max_vega: None,
+            action: action.to_string(),
+        };
+        config.risk_limits = Some(limits("reject"));
+        let mut sim = Simulator::new(config.clone()).unwrap();
+        while sim.step().unwrap().is_some() {}
+        let rejections = sim.event_store.all_events().iter().filter(|e| matches!(e, Event::EntryRejected { .. }));
+        let logged = sim.log.iter().filter(|e| matches!(e, LogEntry::Rejected { .. })).count();
+        assert!(logged > 0);
+        assert_eq!(rejections.count(), logged);
+        let rejected = open_deltas(&sim.log);
+        assert!(rejected.iter().all(|delta| *delta <= cap));
+
+        // A notional cap below one position's notional blocks everything
+        let mut capped = Config::default_1dte_straddle();
+        capped.risk_limits = Some(RiskLimitsConfig { max_delta: None, max_notional: Some(1000.0), ..limits("reject") });
+        let report = SimulationEngine::run(&capped).unwrap();
+        assert_eq!(report.pnl_summary.position_count, 0);
+        assert!(matches!(&report.log[0], LogEntry::Rejected { reason, .. } if reason.starts_with("notional")));
+        // Logged once per session, not once per calendar day
+        let sessions: Vec<u32> = report
+            .log
+            .iter()
+            .filter(|entry| matches!(entry, LogEntry::Rejected { .. }))
+            .map(|entry| entry.timestamp().session_day())
+            .collect();
+        let mut distinct = sessions.clone();
+        distinct.dedup();
+        assert_eq!(distinct, sessions);
+
+        // Delaying retries on later bars, so it opens at least as often
+        config.risk_limits = Some(limits("delay"));
+        let delayed = open_deltas(&SimulationEngine::run(&config).unwrap().log);
+        assert!(delayed.iter().all(|delta| *delta <= cap));
+        assert!(delayed.len() >= rejected.len());
+    }
+
+    #[test]
+    fn test_kill_switch_liquidates_and_stands_down() {
+        use crate::config::KillSwitchConfig;
+
+        let mut config = Config::default_1dte_straddle();
+        config.kill_switch = Some(KillSwitchConfig { max_drawdown: 500.0, stand_down_days: 2 });
+        let report = SimulationEngine::run(&config).unwrap();
+
+        assert!(!report.kill_switches.is_empty());
+        let liquidations: Vec<Timestamp> = report
+            .log
+            .iter()
+            .filter_map(|entry| match entry {
+                LogEntry::Closed { timestamp, cause: CloseCause::KillSwitch, .. } => Some(*timestamp),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(liquidations, report.kill_switches.iter().map(|trip| trip.timestamp).collect::<Vec<_>>());
+        for trip in &report.kill_switches {
+            assert!(trip.drawdown > 500.0);
+            assert!(trip.resume_day >= trip.timestamp.session_day() + 2);
+            let early = report.log.iter().filter(|entry| {
+                let at = entry.timestamp();
+                matches!(entry, LogEntry::Opened { .. }) && at >= trip.timestamp && at.session_day() < trip.resume_day
+            });
+            assert_eq!(early.count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_stand_down_counts_sessions_not_calendar_days() {
+        use crate::prices::PricePoint;
+
+        // Friday (day 4) afternoon, then Sunday evening (Monday's session) and Tuesday
+        let bar = |day, minute| PricePoint { timestamp: Timestamp::new(day, minute), price: 75.0 };
+        let bars = [bar(4, 600), bar(4, 900), bar(6, 1140), bar(7, 600), bar(8, 600)];
+        let trip = Timestamp::new(4, 600);
+        assert_eq!(resume_session(&bars, trip, 0), 4);
+        assert_eq!(resume_session(&bars, trip, 1), 7);
+        assert_eq!(resume_session(&bars, trip, 2), 8);
+        // Past the end of the path
+        assert_eq!(resume_session(&bars, trip, 4), 10);
+    }
+
+    #[test]
+    fn test_daily_loss_limit_halts_for_the_day() {
+        use crate::config::DailyLossConfig;
+
+        let mut config = Config::default_1dte_straddle();
+        config.daily_loss = Some(DailyLossConfig { max_loss: 300.0 });
+        let report = SimulationEngine::run(&config).unwrap();
+
+        assert!(!report.daily_halts.is_empty());
+        let closes = report.log.iter().filter(|e| matches!(e, LogEntry::Closed { cause: CloseCause::DailyLoss, .. }));
+        assert_eq!(closes.count(), report.daily_halts.len());
+        for halt in &report.daily_halts {
+            assert!(halt.loss > 300.0);
+            // Nothing opens again until the next session
+            let reopened = report.log.iter().any(|entry| {
+                let at = entry.timestamp();
+                matches!(entry, LogEntry::Opened { .. })
+                    && at >= halt.timestamp
+                    && at.session_day() == halt.timestamp.session_day()
+            });
+            assert!(!reopened);
+        }
+        let mut days: Vec<u32> = report.daily_halts.iter().map(|halt| halt.timestamp.session_day()).collect();
+        days.dedup();
+        assert_eq!(days.len(), report.daily_halts.len());
+    }
+
+    #[test]
+    fn test_daily_loss_halt_before_midnight_holds_through_the_session() {
+        use crate::config::DailyLossConfig;
+
+        let mut config = Config::default_1dte_straddle();
+        config.daily_loss = Some(DailyLossConfig { max_loss: 300.0 });
+        let report = SimulationEngine::run(&config).unwrap();
+
+        // Breaches after the 18:00 session break belong to the next day's session
+        let evening: Vec<_> = report.daily_halts.iter().filter(|halt| halt.timestamp.minute >= 18 * 60).collect();
+        assert!(!evening.is_empty());
+        for halt in evening {
+            let next_morning = report.log.iter().any(|entry| {
+                let at = entry.timestamp();
+                matches!(entry, LogEntry::Opened { .. }) && at.day == halt.timestamp.day + 1 && at.minute < 18 * 60
+            });
+            assert!(!next_morning, "reopened the morning after {:?}", halt.timestamp);
+        }
+    }
+
+    #[test]
+    fn test_pin_risk_settles_short_strikes_near_the_money() {
+        use crate::config::PinRiskConfig;
+        use crate::risk::PinOutcome;
+
+        let mut config = Config::default_1dte_straddle();
+        let clean = SimulationEngine::run(&config).unwrap();
+        assert!(clean.pins.is_empty());
+        config.pin_risk = Some(PinRiskConfig { band: 0.5, mode: "worst_case".to_string() });
+        let worst = SimulationEngine::run(&config).unwrap();
+
+        assert!(!worst.pins.is_empty());
+        assert!(worst.pins.iter().all(|pin| (pin.settlement - pin.strike).abs() <= 0.5));
+        assert!(worst.pins.iter().all(|pin| pin.value >= pin.intrinsic));
+        assert!(worst.pins.iter().any(|pin| pin.outcome == PinOutcome::Assigned && pin.value > pin.intrinsic));
+        assert!(worst.net_pnl() < clean.net_pnl());
+
+        // Random settlement is reproducible from the seed
+        config.pin_risk = Some(PinRiskConfig { band: 0.5, mode: "random".to_string() });
+        let random = SimulationEngine::run(&config).unwrap();
+        assert_eq!(random.pins.len(), worst.pins.len());
+        assert_eq!(random.net_pnl(), SimulationEngine::run(&config).unwrap().net_pnl());
+    }
+
+    #[test]
+    fn test_hooks_fire_for_every_event() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let bars = Arc::new(AtomicUsize::new(0));
+        let opens = Arc::new(AtomicUsize::new(0));
+        let rolls = Arc::new(AtomicUsize::new(0));
+        let closes = Arc::new(AtomicUsize::new(0));
+
+        let mut sim = Simulator::new(Config::default_1dte_straddle()).unwrap();
+        let counter = bars.clone();
+        sim.on_bar(move |state, bar| {
+            assert_eq!(state.bars_processed, bar.bar_index + 1);
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let counter = opens.clone();
+        sim.on_position_opened(move |state, position| {
+            assert_eq!(state.position.map(|p| p.position_id), Some(position.position_id));
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let counter = rolls.clone();
+        sim.on_roll(move |_, closed, opened| {
+            assert!(opened.position_id.0 > closed.position_id);
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let counter = closes.clone();
+        sim.on_close(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        let report = sim.finish().unwrap();
+        assert_eq!(bars.load(Ordering::Relaxed), report.total_bars);
+        assert_eq!(opens.load(Ordering::Relaxed) as u32, report.pnl_summary.position_count);
+        assert_eq!(closes.load(Ordering::Relaxed), report.trade_records.len());
+        // Every 1DTE close rolls straight into a new position
+        assert_eq!(rolls.load(Ordering::Relaxed), report.trade_records.len());
+    }
+
+    #[test]
+    fn test_tags_follow_positions_into_trade_records() {
+        let mut config = Config::default_1dte_straddle();
+        config.strategy.entry_tags = vec!["weekly_entry".to_string()];
+        config.strategy.roll_tags = vec!["defensive_roll".to_string()];
+        let mut sim = Simulator::new(config.clone()).unwrap();
+        assert!(sim.tag_position("post_event").is_err());
+        while sim.state().position.is_none() {
+            sim.step().unwrap();
+        }
+        assert!(sim.tag_position("post event").is_err());
+        sim.tag_position("post_event").unwrap();
+        while sim.step().unwrap().is_some() {}
+        let report = sim.finish().unwrap();
+
+        assert_eq!(report.trade_records[0].tags, ["weekly_entry", "post_event"]);
+        assert_eq!(report.trade_records[1].tags, ["defensive_roll"]);
+        let csv = reporting::csv_export::trades_csv(&report.trade_records, 1000.0);
+        assert!(csv.lines().nth(1).unwrap().ends_with(",weekly_entry;post_event"));
+
+        config.strategy.entry_tags = vec!["weekly,entry".to_string()];
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_missed_entry_window_follows_the_policy() {
+        let mut config = Config::default_1dte_straddle();
+        let path = PricePath::generate(&config).unwrap();
+        let first_entry = |config: &Config, gap_end: u32| {
+            // Day 0 has no bars from 14:30 until `gap_end`
+            let kept = path.bars().iter().filter(|bar| {
+                let time = bar.timestamp;
+                time.day != 0 || !(870..gap_end).contains(&time.minute)
+            });
+            let path = PricePath::from_bars(kept.copied().collect()).unwrap();
+            let mut sim = Simulator::with_path(config.clone(), path).unwrap();
+            while sim.state().position.is_none() {
+                sim.step().unwrap();
+            }
+            let entry = sim.state().position.unwrap().entry_timestamp;
+            (entry.day, entry.minute)
+        };
+        // An early close that reopens for the evening session, then one that stays shut until midnight
+        let expected =
+            [("next_bar", (0, 1080), (1, 900)), ("skip_day", (1, 900), (1, 900)), ("open", (0, 1080), (1, 0))];
+        for (policy, early_close, closed_evening) in expected {
+            config.strategy.missed_entry = policy.to_string();
+            assert_eq!(first_entry(&config, 1080), early_close, "{}", policy);
+            assert_eq!(first_entry(&config, 1440), closed_evening, "{}", policy);
+        }
+        config.strategy.missed_entry = "later".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_leg_triggers_roll_one_leg_in_place() {
+        use crate::config::RollTriggerConfig;
+
+        let mut config = Config::default_1dte_straddle();
+        let trigger = |trigger_type: &str, value: f64, legs: &str| RollTriggerConfig {
+            trigger_type: trigger_type.to_string(),
+            value,
+            legs: legs.to_string(),
+        };
+        config.strategy.roll_triggers = vec![trigger("time", 14.0, "both"), trigger("price_move", 0.5, "put")];
+        let report = SimulationEngine::run(&config).unwrap();
+
+        // The put recenters at the money; the position and its call carry on
+        let rolls: Vec<&LogEntry> = report.log.iter().filter(|e| matches!(e, LogEntry::LegRolled { .. })).collect();
+        assert!(!rolls.is_empty());
+        for roll in &rolls {
+            let LogEntry::LegRolled { price, option_type, new_strike, .. } = roll else { unreachable!() };
+            assert_eq!(*option_type, OptionType::Put);
+            assert_eq!(*new_strike, config.strike_config.ladder(*price).unwrap().nearest(*price));
+        }
+        let opens = report.log.iter().filter(|e| matches!(e, LogEntry::Opened { .. })).count();
+        assert_eq!(opens as u32, report.pnl_summary.position_count);
+
+        // Legs rolled out still count toward their position's P&L and the blotter
+        let traded: f64 = report.trade_records.iter().map(|trade| trade.pnl).sum();
+        assert!((traded - report.pnl_summary.realized_pnl).abs() < 1e-9);
+        assert_eq!(report.fills.len(), 2 * (opens + report.trade_records.len() + rolls.len()));
+    }
+}
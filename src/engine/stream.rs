@@ -0,0 +1,247 @@
+//! Streaming Simulation Output
+//!
+//! Emits progress and trade events while a simulation runs, so the web
+//! server and Tauri app can show partial results of multi-year intraday runs
+//! instead of blocking until the report is ready. `run_streaming` hands events
+//! to a callback on the current thread; `SimulationEngine::spawn` runs on a
+//! worker thread and delivers them over a channel. Progress events carry the
+//! wall-clock time so far, from which frontends show throughput and an ETA.
+//! `run_streaming_cancellable` also checks a `CancelToken` before every bar.
+
+use super::{CancelToken, LogEntry, SimulationEngine, SimulationReport, Simulator};
+use crate::calendar::intraday::Timestamp;
+use crate::config::Config;
+use crate::error::SimError;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How far a running simulation has got
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub bars_processed: usize,
+    pub total_bars: usize,
+    /// Timestamp and underlying price of the latest bar
+    pub timestamp: Timestamp,
+    pub price: f64,
+    /// Running P&L per unit
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    /// Wall-clock time since the run started
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Completed fraction in [0, 1]
+    pub fn fraction(&self) -> f64 {
+        if self.total_bars == 0 {
+            return 1.0;
+        }
+        self.bars_processed as f64 / self.total_bars as f64
+    }
+
+    /// Bars simulated per second of wall-clock time so far
+    pub fn bars_per_sec(&self) -> f64 {
+        per_second(self.bars_processed, self.elapsed)
+    }
+
+    /// Estimated time to finish at the current rate (None before any bars)
+    pub fn eta(&self) -> Option<Duration> {
+        remaining(self.bars_processed, self.total_bars, self.elapsed)
+    }
+}
+
+/// Items completed per second of `elapsed`
+pub(crate) fn per_second(done: usize, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        done as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/// Time left for `total - done` items at the average rate so far
+pub(crate) fn remaining(done: usize, total: usize, elapsed: Duration) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+    let left = total.saturating_sub(done) as f64;
+    Some(elapsed.mul_f64(left / done as f64))
+}
+
+/// Incremental output of a running simulation
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// Sent every `progress_every` bars and after the last bar
+    Progress(Progress),
+    /// A position was opened or closed
+    Trade(LogEntry),
+}
+
+impl Simulator {
+    /// Run to completion, passing trades and periodic progress to `emit`
+    pub fn run_streaming<F>(self, progress_every: usize, emit: F) -> Result<SimulationReport, SimError>
+    where
+        F: FnMut(StreamEvent),
+    {
+        self.run_streaming_cancellable(progress_every, &CancelToken::new(), emit)
+    }
+
+    /// `run_streaming` that stops with `SimError::Cancelled` once `cancel` is set
+    pub fn run_streaming_cancellable<F>(
+        mut self,
+        progress_every: usize,
+        cancel: &CancelToken,
+        mut emit: F,
+    ) -> Result<SimulationReport, SimError>
+    where
+        F: FnMut(StreamEvent),
+    {
+        let progress_every = progress_every.max(1);
+        let started = Instant::now();
+        let mut emitted = 0;
+        loop {
+            if cancel.is_cancelled() {
+                return Err(SimError::Cancelled);
+            }
+            let Some(outcome) = self.step()? else {
+                break;
+            };
+            let finished = self.is_finished();
+            let state = self.state();
+            for entry in &state.log[emitted..] {
+                emit(StreamEvent::Trade(entry.clone()));
+            }
+            emitted = state.log.len();
+
+            if finished || state.bars_processed.is_multiple_of(progress_every) {
+                emit(StreamEvent::Progress(Progress {
+                    bars_processed: state.bars_processed,
+                    total_bars: state.total_bars,
+                    timestamp: outcome.timestamp,
+                    price: outcome.price,
+                    realized_pnl: outcome.equity.realized,
+                    unrealized_pnl: outcome.equity.unrealized,
+                    elapsed: started.elapsed(),
+                }));
+            }
+        }
+        self.finish()
+    }
+}
+
+/// A simulation running on a worker thread; iterate it for events
+#[derive(Debug)]
+pub struct SimulationStream {
+    events: Receiver<StreamEvent>,
+    handle: JoinHandle<Result<SimulationReport, SimError>>,
+}
+
+impl SimulationStream {
+    /// Wait for the run to end (dropping unread events) and return the report
+    pub fn finish(self) -> Result<SimulationReport, SimError> {
+        drop(self.events);
+        self.handle
+            .join()
+            .map_err(|_| SimError::Execution("simulation thread panicked".to_string()))?
+    }
+}
+
+impl Iterator for SimulationStream {
+    type Item = StreamEvent;
+
+    fn next(&mut self) -> Option<StreamEvent> {
+        self.events.recv().ok()
+    }
+}
+
+impl SimulationEngine {
+    /// Run `config.strategy` on a worker thread, streaming events as it goes
+    ///
+    /// The config is validated before the thread starts, so bad input is
+    /// reported here rather than from `finish()`.
+    pub fn spawn(config: &Config, progress_every: usize) -> Result<SimulationStream, SimError> {
+        let simulator = Simulator::new(config.clone())?;
+        let (sender, events) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            // A dropped receiver just means nobody is listening any more
+            simulator.run_streaming(progress_every, |event| {
+                let _ = sender.send(event);
+            })
+        });
+        Ok(SimulationStream { events, handle })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_matches_batch_run() {
+        let config = Config::default_1dte_straddle();
+        let batch = SimulationEngine::run(&config).unwrap();
+
+        let mut stream = SimulationEngine::spawn(&config, 500).unwrap();
+        let events: Vec<StreamEvent> = stream.by_ref().collect();
+        let report = stream.finish().unwrap();
+
+        let trades: Vec<&LogEntry> = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::Trade(entry) => Some(entry),
+                StreamEvent::Progress(_) => None,
+            })
+            .collect();
+        assert_eq!(trades.len(), batch.log.len());
+        assert_eq!(report.net_pnl(), batch.net_pnl());
+
+        let Some(StreamEvent::Progress(last)) = events.last() else {
+            panic!("stream should end with a progress event");
+        };
+        assert_eq!(last.bars_processed, batch.total_bars);
+        assert_eq!(last.fraction(), 1.0);
+        assert_eq!(last.eta(), Some(Duration::ZERO));
+        let progress_count = events.iter().filter(|e| matches!(e, StreamEvent::Progress(_))).count();
+        assert_eq!(progress_count, batch.total_bars.div_ceil(500));
+    }
+
+    #[test]
+    fn test_rate_and_eta() {
+        let progress = Progress {
+            bars_processed: 250,
+            total_bars: 1000,
+            timestamp: Timestamp::new(0, 0),
+            price: 75.0,
+            realized_pnl: 0.0,
+            unrealized_pnl: 0.0,
+            elapsed: Duration::from_secs(2),
+        };
+        assert_eq!(progress.bars_per_sec(), 125.0);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(6)));
+        assert_eq!(remaining(0, 1000, Duration::from_secs(2)), None);
+    }
+
+    #[test]
+    fn test_streaming_run_cancels_between_bars() {
+        let cancel = CancelToken::new();
+        let simulator = Simulator::new(Config::default_1dte_straddle()).unwrap();
+        let mut progress_events = 0;
+        let result = simulator.run_streaming_cancellable(10, &cancel, |event| {
+            if matches!(event, StreamEvent::Progress(_)) {
+                progress_events += 1;
+                cancel.cancel();
+            }
+        });
+        assert!(matches!(result, Err(SimError::Cancelled)));
+        assert_eq!(progress_events, 1);
+    }
+
+    #[test]
+    fn test_spawn_rejects_bad_config() {
+        let mut config = Config::default_1dte_straddle();
+        config.strategy.roll_time = "2pm".to_string();
+        assert!(matches!(SimulationEngine::spawn(&config, 1), Err(SimError::Config(_))));
+    }
+}
@@ -0,0 +1,298 @@
+//! Strike Selection
+//!
+//! Where new positions and rolls put their strikes. Entries follow
+//! `strategy.strike_selection` (at the money, a fixed offset out, a target
+//! delta or a target premium); rolls follow `strike_config.roll_type`, which
+//! re-applies the entry rule, keeps the old strikes, or re-selects each leg at
+//! the delta it had when it opened. A leg rolled on its own moves the same
+//! way while the others stay where they are. Every choice lands on a listed
+//! strike of the ladder; delta and premium targets value the candidates in one batch
+//! with `Black76::ladder`.
+
+use super::LegState;
+use crate::events::OptionType;
+use crate::pricing::{LadderGreeks, PricingModel, Smile, StrikeLadder};
+
+/// Candidate strikes for delta and premium matching reach this many
+/// standard deviations either side of the money
+const CANDIDATE_DEVIATIONS: f64 = 4.0;
+
+/// How strikes are chosen at entry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StrikeSelection {
+    /// Both legs at the strike nearest the underlying
+    Atm,
+    /// Put `offset` below and call `offset` above ATM
+    Otm { offset: f64 },
+    /// Legs at these absolute deltas (e.g. 0.16), at the money when `None`
+    Delta { put: Option<f64>, call: Option<f64> },
+    /// Both legs at the strike whose premium is closest to `premium`
+    Premium { premium: f64 },
+}
+
+impl StrikeSelection {
+    /// Parse `strategy.strike_selection`: "ATM", "OTM" (`offset` points out),
+    /// "delta_16" for both legs, or "delta_put_16", "delta_call_16" and
+    /// "delta_put_16_call_10" per leg, or "premium_1.5" for both legs at a
+    /// target premium
+    pub fn from_config(value: &str, offset: f64) -> Option<Self> {
+        match value {
+            "ATM" => return Some(StrikeSelection::Atm),
+            "OTM" => return Some(StrikeSelection::Otm { offset }),
+            _ => {}
+        }
+        if let Some(premium) = value.strip_prefix("premium_") {
+            let premium = premium.parse::<f64>().ok().filter(|p| p.is_finite() && *p > 0.0)?;
+            return Some(StrikeSelection::Premium { premium });
+        }
+        let percent = |text: &str| text.parse::<f64>().ok().filter(|p| *p > 0.0 && *p < 100.0).map(|p| p / 100.0);
+        let rest = value.strip_prefix("delta_")?;
+        if let Some(both) = percent(rest) {
+            return Some(StrikeSelection::Delta { put: Some(both), call: Some(both) });
+        }
+        let (put, call) = match rest.strip_prefix("put_") {
+            Some(put) => match put.split_once("_call_") {
+                Some((put, call)) => (Some(percent(put)?), Some(percent(call)?)),
+                None => (Some(percent(put)?), None),
+            },
+            None => (None, Some(percent(rest.strip_prefix("call_")?)?)),
+        };
+        Some(StrikeSelection::Delta { put, call })
+    }
+
+    /// The `strategy.strike_selection` spelling
+    pub fn to_config(&self) -> String {
+        let percent = |delta: f64| (delta * 100.0 * 1e6).round() / 1e6;
+        match *self {
+            StrikeSelection::Atm | StrikeSelection::Delta { put: None, call: None } => "ATM".to_string(),
+            StrikeSelection::Otm { .. } => "OTM".to_string(),
+            StrikeSelection::Premium { premium } => format!("premium_{}", premium),
+            StrikeSelection::Delta { put: Some(put), call: Some(call) } if put == call => {
+                format!("delta_{}", percent(put))
+            }
+            StrikeSelection::Delta { put: Some(put), call: Some(call) } => {
+                format!("delta_put_{}_call_{}", percent(put), percent(call))
+            }
+            StrikeSelection::Delta { put: Some(put), call: None } => format!("delta_put_{}", percent(put)),
+            StrikeSelection::Delta { put: None, call: Some(call) } => format!("delta_call_{}", percent(call)),
+        }
+    }
+}
+
+/// Where a roll puts the new legs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RollDestination {
+    /// Select again with the entry rule at the new price
+    #[default]
+    Recenter,
+    /// Keep the strikes of the legs being rolled
+    SameStrikes,
+    /// Each leg at the strike whose delta matches the leg's delta at entry
+    Delta,
+}
+
+impl RollDestination {
+    /// Parse `strike_config.roll_type`
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "recenter" => Some(RollDestination::Recenter),
+            "same_strikes" => Some(RollDestination::SameStrikes),
+            "delta" => Some(RollDestination::Delta),
+            _ => None,
+        }
+    }
+}
+
+/// What candidate strikes are priced against when matching a delta
+#[derive(Debug, Clone, Copy)]
+pub struct StrikeMarket {
+    pub model: PricingModel,
+    pub underlying: f64,
+    pub time_to_expiry: f64,
+    pub risk_free_rate: f64,
+    /// At-the-money vol, moved strike by strike by `smile`
+    pub implied_vol: f64,
+    pub smile: Smile,
+}
+
+impl StrikeMarket {
+    /// Vol the surface gives `strike`
+    pub fn vol(&self, strike: f64) -> f64 {
+        self.smile.vol(self.implied_vol, self.underlying, strike)
+    }
+
+    /// Listed strikes within four standard deviations of the money, valued
+    /// together on the batch ladder
+    pub fn ladder(&self, ladder: &StrikeLadder, is_call: bool) -> LadderGreeks {
+        let atm = ladder.nearest(self.underlying);
+        let width = CANDIDATE_DEVIATIONS * self.implied_vol * self.time_to_expiry.max(0.0).sqrt();
+        let low = (self.underlying * (-width).exp()).min(atm);
+        let high = (self.underlying * width.exp()).max(atm);
+        let strikes: Vec<f64> = ladder.between(low, high).collect();
+        let vols: Vec<f64> = strikes.iter().map(|&strike| self.vol(strike)).collect();
+        self.model.ladder(self.underlying, &strikes, self.time_to_expiry, self.risk_free_rate, &vols, is_call)
+    }
+
+    /// Listed strike whose absolute delta is closest to `target`
+    pub fn strike_for_delta(&self, ladder: &StrikeLadder, target: f64, is_call: bool) -> f64 {
+        self.ladder(ladder, is_call).strike_for_delta(target).unwrap_or_else(|| ladder.nearest(self.underlying))
+    }
+
+    /// Listed strike whose premium is closest to `target`
+    pub fn strike_for_premium(&self, ladder: &StrikeLadder, target: f64, is_call: bool) -> f64 {
+        self.ladder(ladder, is_call).strike_for_premium(target).unwrap_or_else(|| ladder.nearest(self.underlying))
+    }
+}
+
+/// Put and call strikes for a new position
+pub fn select(selection: StrikeSelection, ladder: &StrikeLadder, market: &StrikeMarket) -> (f64, f64) {
+    let atm = ladder.nearest(market.underlying);
+    match selection {
+        StrikeSelection::Atm => (atm, atm),
+        StrikeSelection::Otm { offset } => (ladder.nearest(atm - offset), ladder.nearest(atm + offset)),
+        StrikeSelection::Delta { put, call } => (
+            put.map_or(atm, |delta| market.strike_for_delta(ladder, delta, false)),
+            call.map_or(atm, |delta| market.strike_for_delta(ladder, delta, true)),
+        ),
+        StrikeSelection::Premium { premium } => (
+            market.strike_for_premium(ladder, premium, false),
+            market.strike_for_premium(ladder, premium, true),
+        ),
+    }
+}
+
+/// Strike a rolled `leg` moves to, given the strike a fresh entry would use
+pub fn roll_strike(
+    destination: RollDestination,
+    leg: &LegState,
+    fresh: f64,
+    ladder: &StrikeLadder,
+    market: &StrikeMarket,
+) -> f64 {
+    match destination {
+        RollDestination::Recenter => fresh,
+        RollDestination::SameStrikes => leg.strike(),
+        // An expired leg's delta is 0 or 1 and says nothing about where it sat
+        RollDestination::Delta if leg.greeks.delta.abs() > 0.0 && leg.greeks.delta.abs() < 1.0 => {
+            market.strike_for_delta(ladder, leg.greeks.delta.abs(), leg.is_call())
+        }
+        RollDestination::Delta => fresh,
+    }
+}
+
+/// Strike `leg` moves to when it rolls on its own
+///
+/// The position's other legs keep their strikes, which is what
+/// `same_strikes` asks of a roll, so the rolled leg itself recenters as it
+/// would under `recenter`.
+pub fn roll_leg(
+    destination: RollDestination,
+    selection: StrikeSelection,
+    leg: &LegState,
+    ladder: &StrikeLadder,
+    market: &StrikeMarket,
+) -> f64 {
+    let (put, call) = select(selection, ladder, market);
+    let fresh = if leg.is_call() { call } else { put };
+    match destination {
+        RollDestination::SameStrikes => fresh,
+        destination => roll_strike(destination, leg, fresh, ladder, market),
+    }
+}
+
+/// Put and call strikes for the position that replaces `legs`
+pub fn roll(
+    destination: RollDestination,
+    selection: StrikeSelection,
+    legs: &[LegState],
+    ladder: &StrikeLadder,
+    market: &StrikeMarket,
+) -> (f64, f64) {
+    let (put, call) = select(selection, ladder, market);
+    let rolled = |option_type: OptionType, fresh: f64| {
+        legs.iter()
+            .find(|leg| leg.contract.option_type == option_type)
+            .map_or(fresh, |leg| roll_strike(destination, leg, fresh, ladder, market))
+    };
+    (rolled(OptionType::Put, put), rolled(OptionType::Call, call))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{LegId, OptionContract, Side};
+
+    #[test]
+    fn test_strike_selection_round_trips_through_config() {
+        let strangle = StrikeSelection::from_config("delta_16", 0.0).unwrap();
+        assert_eq!(strangle, StrikeSelection::Delta { put: Some(0.16), call: Some(0.16) });
+        assert_eq!(StrikeSelection::from_config("delta_put_16", 0.0).unwrap().to_config(), "delta_put_16");
+        let split = StrikeSelection::Delta { put: Some(0.25), call: Some(0.1) };
+        assert_eq!(StrikeSelection::from_config(&split.to_config(), 0.0), Some(split));
+        assert_eq!(StrikeSelection::from_config("OTM", 2.0), Some(StrikeSelection::Otm { offset: 2.0 }));
+        for bad in ["delta_0", "delta_put_", "delta_call_101", "gamma_16", "atm"] {
+            assert_eq!(StrikeSelection::from_config(bad, 0.0), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_delta_selection_moves_out_of_the_money() {
+        let ladder = StrikeLadder::uniform(0.25);
+        let market = StrikeMarket {
+            model: PricingModel::Black76,
+            underlying: 75.0,
+            time_to_expiry: 30.0 / 365.0,
+            risk_free_rate: 0.05,
+            implied_vol: 0.35,
+            smile: Smile::default(),
+        };
+        let (put, call) = select(StrikeSelection::Delta { put: Some(0.16), call: Some(0.16) }, &ladder, &market);
+        assert!(put < 75.0 && call > 75.0);
+        let delta = |strike, is_call| market.model.greeks(75.0, strike, 30.0 / 365.0, 0.05, 0.35, is_call).delta.abs();
+        assert!((delta(call, true) - 0.16).abs() < (delta(call + 0.25, true) - 0.16).abs());
+        assert!((delta(call, true) - 0.16).abs() < (delta(call - 0.25, true) - 0.16).abs());
+        assert!((delta(put, false) - 0.16).abs() < 0.01);
+
+        // Premium targets land on the listed strike priced closest to them
+        let selection = StrikeSelection::from_config("premium_0.8", 0.0).unwrap();
+        assert_eq!(selection.to_config(), "premium_0.8");
+        let (put, call) = select(selection, &ladder, &market);
+        let price = |strike, is_call| market.model.price(75.0, strike, 30.0 / 365.0, 0.05, 0.35, is_call);
+        assert!(put < 75.0 && call > 75.0);
+        for (strike, is_call) in [(put, false), (call, true)] {
+            let error = |strike| (price(strike, is_call) - 0.8_f64).abs();
+            assert!(error(strike) <= error(strike + 0.25) && error(strike) <= error(strike - 0.25));
+        }
+    }
+
+    #[test]
+    fn test_single_leg_rolls_follow_the_destination() {
+        let ladder = StrikeLadder::uniform(0.25);
+        let market = StrikeMarket {
+            model: PricingModel::Black76,
+            underlying: 75.0,
+            time_to_expiry: 30.0 / 365.0,
+            risk_free_rate: 0.05,
+            implied_vol: 0.35,
+            smile: Smile::default(),
+        };
+        let put = LegState {
+            leg_id: LegId(1),
+            contract: OptionContract {
+                underlying_price: 72.0,
+                strike: 70.0,
+                option_type: OptionType::Put,
+                side: Side::Short,
+                expiration_day: 30,
+                quantity: 1.0,
+            },
+            entry_premium: 0.6,
+            greeks: market.model.greeks(75.0, 70.0, 30.0 / 365.0, 0.05, 0.35, false),
+        };
+        let rolled = |destination| roll_leg(destination, StrikeSelection::Atm, &put, &ladder, &market);
+        // The other legs keep their strikes, so same_strikes recenters the rolled one
+        assert_eq!(rolled(RollDestination::Recenter), 75.0);
+        assert_eq!(rolled(RollDestination::SameStrikes), 75.0);
+        assert_eq!(rolled(RollDestination::Delta), 70.0);
+    }
+}
@@ -0,0 +1,40 @@
+//! Crate-wide Error Type
+//!
+//! `SimError` covers every way a simulation can fail: bad configuration,
+//! malformed times, pricing inputs, unreadable market data and engine
+//! execution problems.
+
+use crate::config::ConfigError;
+use thiserror::Error;
+
+/// Any failure while configuring or running a simulation
+#[derive(Debug, Error)]
+pub enum SimError {
+    /// Configuration could not be loaded or failed validation
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    /// A time string was not a valid "HH:MM"
+    #[error("invalid time {0:?}: expected HH:MM between 00:00 and 23:59")]
+    InvalidTime(String),
+    /// Option pricing inputs or output were not usable
+    #[error("pricing error: {0}")]
+    Pricing(String),
+    /// The calendar produced no usable trading bars or dates
+    #[error("calendar error: {0}")]
+    Calendar(String),
+    /// Market data files were malformed or inconsistent
+    #[error("data error: {0}")]
+    Data(String),
+    /// A data file could not be read or written
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The simulation loop hit an inconsistent state
+    #[error("execution error: {0}")]
+    Execution(String),
+    /// The run was stopped through its `CancelToken`
+    #[error("simulation cancelled")]
+    Cancelled,
+}
+
+/// Result alias for fallible simulator operations
+pub type SimResult<T> = Result<T, SimError>;
@@ -1,9 +1,13 @@
 //! Event Types for Trading Simulator V2
 //! 
 //! All state changes are recorded as immutable events.
-//! The current state is derived by replaying events in order.
+//! The current state is derived by replaying events in order, so the store
+//! only accepts events in time order that fit the positions it has seen.
 
 use crate::calendar::{Day, TimeOfDay};
+use crate::error::SimError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 /// Unique identifier for a position
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -14,27 +18,44 @@ pub struct PositionId(pub u64);
 pub struct LegId(pub u64);
 
 /// Option type (Put or Call)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
 pub enum OptionType {
     Put,
     Call,
 }
 
 /// Side of a trade (Long or Short)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
 pub enum Side {
     Long,
     Short,
 }
 
+impl Side {
+    /// Signed P&L of premium opened at `entry_value` and now worth `value`
+    /// (positive is a profit on either side)
+    pub fn pnl(self, entry_value: f64, value: f64) -> f64 {
+        match self {
+            Side::Long => value - entry_value,
+            Side::Short => entry_value - value,
+        }
+    }
+}
+
 /// Represents a single option contract specification
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct OptionContract {
     pub underlying_price: f64,
     pub strike: f64,
     pub option_type: OptionType,
     pub side: Side,
     pub expiration_day: Day,
+    /// Contracts (fractional for index-style products)
+    pub quantity: f64,
 }
 
 /// All possible events in the trading system
@@ -45,6 +66,12 @@ pub enum Event {
         position_id: PositionId,
         timestamp: (Day, TimeOfDay),
         legs: Vec<(LegId, OptionContract, f64)>, // (leg_id, contract, premium_received/paid)
+        /// Worst loss at expiration per unit of underlying, `None` when unlimited
+        max_loss: Option<f64>,
+        /// Margin charged at entry per unit of underlying
+        initial_margin: f64,
+        /// Labels of the rule that opened it (e.g. "weekly_entry")
+        tags: Vec<String>,
     },
     
     /// A position was fully closed
@@ -72,6 +99,13 @@ pub enum Event {
         trigger: RollTrigger,
     },
     
+    /// A label was attached to an open position after it opened
+    PositionTagged {
+        position_id: PositionId,
+        timestamp: (Day, TimeOfDay),
+        tag: String,
+    },
+
     /// A roll was attempted but rejected (for audit/debugging)
     RollRejected {
         position_id: PositionId,
@@ -79,6 +113,35 @@ pub enum Event {
         timestamp: (Day, TimeOfDay),
         reason: String,
     },
+
+    /// The run began; its seed reproduces a generated price path
+    RunStarted {
+        timestamp: (Day, TimeOfDay),
+        seed: u64,
+        /// Whether the seed was drawn from entropy rather than configured
+        seed_generated: bool,
+    },
+
+    /// A new position was blocked before it opened (e.g. by a risk limit)
+    EntryRejected {
+        timestamp: (Day, TimeOfDay),
+        /// The position just closed, when the entry was the second half of a roll
+        rolled_from: Option<PositionId>,
+        reason: String,
+    },
+
+    /// Book risk at a bar, every `simulation.risk_snapshot_minutes` (all in dollars)
+    RiskSnapshot {
+        timestamp: (Day, TimeOfDay),
+        /// The open position, if any
+        position_id: Option<PositionId>,
+        margin: f64,
+        net_delta: f64,
+        net_gamma: f64,
+        net_theta: f64,
+        net_vega: f64,
+        unrealized_pnl: f64,
+    },
 }
 
 /// Reason a position was closed
@@ -115,16 +178,25 @@ impl Event {
             Event::PositionClosed { timestamp, .. } => *timestamp,
             Event::LegRolled { timestamp, .. } => *timestamp,
             Event::RollRejected { timestamp, .. } => *timestamp,
+            Event::PositionTagged { timestamp, .. } => *timestamp,
+            Event::EntryRejected { timestamp, .. } => *timestamp,
+            Event::RunStarted { timestamp, .. } => *timestamp,
+            Event::RiskSnapshot { timestamp, .. } => *timestamp,
         }
     }
     
-    /// Get the position ID associated with this event
-    pub fn position_id(&self) -> PositionId {
+    /// Get the position ID associated with this event (none for the start
+    /// of a run; a rejected entry only has one if it was rolling out of a position)
+    pub fn position_id(&self) -> Option<PositionId> {
         match self {
-            Event::PositionOpened { position_id, .. } => *position_id,
-            Event::PositionClosed { position_id, .. } => *position_id,
-            Event::LegRolled { position_id, .. } => *position_id,
-            Event::RollRejected { position_id, .. } => *position_id,
+            Event::PositionOpened { position_id, .. } => Some(*position_id),
+            Event::PositionClosed { position_id, .. } => Some(*position_id),
+            Event::LegRolled { position_id, .. } => Some(*position_id),
+            Event::RollRejected { position_id, .. } => Some(*position_id),
+            Event::PositionTagged { position_id, .. } => Some(*position_id),
+            Event::EntryRejected { rolled_from, .. } => *rolled_from,
+            Event::RunStarted { .. } => None,
+            Event::RiskSnapshot { position_id, .. } => *position_id,
         }
     }
 }
@@ -135,6 +207,9 @@ pub struct EventStore {
     events: Vec<Event>,
     next_position_id: u64,
     next_leg_id: u64,
+    /// Legs of the positions opened and not yet closed
+    open: HashMap<PositionId, Vec<LegId>>,
+    closed: HashSet<PositionId>,
 }
 
 impl EventStore {
@@ -143,19 +218,89 @@ impl EventStore {
             events: Vec::new(),
             next_position_id: 1,
             next_leg_id: 1,
+            open: HashMap::new(),
+            closed: HashSet::new(),
         }
     }
     
     /// Append an event to the store
-    pub fn append(&mut self, event: Event) {
+    ///
+    /// Timestamps must not go backwards, positions open once and close once,
+    /// and rolls and closes must name legs of an open position.
+    pub fn append(&mut self, event: Event) -> Result<(), SimError> {
+        self.check(&event).map_err(|reason| {
+            let (day, time) = event.timestamp();
+            SimError::Execution(format!("rejected event at day {} {}: {}", day, time, reason))
+        })?;
+        match &event {
+            Event::PositionOpened { position_id, legs, .. } => {
+                self.open.insert(*position_id, legs.iter().map(|(leg_id, _, _)| *leg_id).collect());
+            }
+            Event::PositionClosed { position_id, .. } => {
+                self.open.remove(position_id);
+                self.closed.insert(*position_id);
+            }
+            _ => {}
+        }
         self.events.push(event);
+        Ok(())
+    }
+
+    /// Why `event` cannot follow the events already stored
+    fn check(&self, event: &Event) -> Result<(), String> {
+        if let Some(last) = self.events.last() {
+            let (day, time) = last.timestamp();
+            if event.timestamp() < (day, time) {
+                return Err(format!("earlier than the previous event at day {} {}", day, time));
+            }
+        }
+        let open_legs = |position_id: &PositionId| {
+            self.open.get(position_id).ok_or_else(|| format!("position {} is not open", position_id.0))
+        };
+        let unknown_leg = |position_id: &PositionId, leg_id: &LegId| {
+            format!("leg {} is not part of position {}", leg_id.0, position_id.0)
+        };
+        match event {
+            Event::PositionOpened { position_id, legs, .. } => {
+                if self.open.contains_key(position_id) || self.closed.contains(position_id) {
+                    return Err(format!("position {} was already opened", position_id.0));
+                }
+                if legs.iter().any(|(_, contract, premium)| !premium.is_finite() || !contract.strike.is_finite()) {
+                    return Err(format!("position {} has a non-finite strike or premium", position_id.0));
+                }
+            }
+            Event::PositionClosed { position_id, close_premiums, .. } => {
+                let legs = open_legs(position_id)?;
+                for (leg_id, premium) in close_premiums {
+                    if !legs.contains(leg_id) {
+                        return Err(unknown_leg(position_id, leg_id));
+                    }
+                    if !premium.is_finite() {
+                        return Err(format!("leg {} closes at a non-finite premium", leg_id.0));
+                    }
+                }
+            }
+            Event::LegRolled { position_id, leg_id, .. } | Event::RollRejected { position_id, leg_id, .. } => {
+                if !open_legs(position_id)?.contains(leg_id) {
+                    return Err(unknown_leg(position_id, leg_id));
+                }
+            }
+            Event::PositionTagged { position_id, .. } => {
+                open_legs(position_id)?;
+            }
+            Event::RiskSnapshot { position_id: Some(position_id), .. } => {
+                open_legs(position_id)?;
+            }
+            Event::RunStarted { .. } | Event::EntryRejected { .. } | Event::RiskSnapshot { .. } => {}
+        }
+        Ok(())
     }
     
     /// Get all events for a specific position
     pub fn events_for_position(&self, position_id: PositionId) -> Vec<&Event> {
         self.events
             .iter()
-            .filter(|e| e.position_id() == position_id)
+            .filter(|e| e.position_id() == Some(position_id))
             .collect()
     }
     
@@ -197,11 +342,58 @@ mod tests {
         
         let event = Event::PositionOpened {
             position_id: pos_id,
-            timestamp: (0, 0),
+            timestamp: (0, TimeOfDay::default()),
             legs: vec![],
+            max_loss: Some(0.0),
+            initial_margin: 0.0,
+            tags: vec![],
         };
         
-        store.append(event);
+        store.append(event).unwrap();
         assert_eq!(store.all_events().len(), 1);
     }
+
+    #[test]
+    fn test_event_store_rejects_inconsistent_events() {
+        let mut store = EventStore::new();
+        let position_id = store.next_position_id();
+        let leg_id = store.next_leg_id();
+        let contract = OptionContract {
+            underlying_price: 75.0,
+            strike: 75.0,
+            option_type: OptionType::Put,
+            side: Side::Short,
+            expiration_day: 1,
+            quantity: 1.0,
+        };
+        let at = |minutes| (0, TimeOfDay::from_minutes(minutes));
+        let opened = |minutes| Event::PositionOpened {
+            position_id,
+            timestamp: at(minutes),
+            legs: vec![(leg_id, contract, 1.5)],
+            max_loss: None,
+            initial_margin: 16.5,
+            tags: vec!["weekly_entry".to_string()],
+        };
+        let closed = |minutes, leg_id| Event::PositionClosed {
+            position_id,
+            timestamp: at(minutes),
+            close_premiums: vec![(leg_id, 0.5)],
+            reason: CloseReason::Expiration,
+        };
+
+        // Closing before opening, then out of order, then a leg the position never had
+        assert!(store.append(closed(960, leg_id)).is_err());
+        store.append(opened(900)).unwrap();
+        assert!(store.append(closed(840, leg_id)).is_err());
+        assert!(store.append(closed(960, LegId(99))).is_err());
+        store.append(closed(960, leg_id)).unwrap();
+
+        // A closed position stays closed
+        assert!(store.append(closed(1000, leg_id)).is_err());
+        assert!(store.append(opened(1000)).is_err());
+        let tag = Event::PositionTagged { position_id, timestamp: at(1000), tag: "post_event".to_string() };
+        assert!(store.append(tag).is_err());
+        assert_eq!(store.all_events().len(), 2);
+    }
 }
@@ -0,0 +1,158 @@
+//! C ABI for the Pricing Module
+//!
+//! Exposes the Black-76 pricer, Greeks and implied volatility to C/C++/C#
+//! tools so they can check their numbers against the simulator's. Build the
+//! shared library with `cargo build --release --lib --features ffi`; the
+//! matching declarations are in `include/trading_simulator_v2.h`.
+//!
+//! Every function returns a status code and writes its result through an
+//! out pointer, which is left untouched on failure.
+
+use crate::pricing::{Black76, Greeks};
+
+/// Success
+pub const TSV2_OK: i32 = 0;
+/// An input was out of range (non-positive price/strike, bad volatility, ...)
+pub const TSV2_INVALID_INPUT: i32 = 1;
+/// The out pointer was null
+pub const TSV2_NULL_POINTER: i32 = 2;
+/// Implied volatility did not converge
+pub const TSV2_NO_CONVERGENCE: i32 = 3;
+
+/// Greeks in C layout (theta per day, vega per vol point)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tsv2Greeks {
+    pub delta: f64,
+    pub gamma: f64,
+    pub theta: f64,
+    pub vega: f64,
+    pub rho: f64,
+}
+
+impl From<Greeks> for Tsv2Greeks {
+    fn from(g: Greeks) -> Self {
+        Self { delta: g.delta, gamma: g.gamma, theta: g.theta, vega: g.vega, rho: g.rho }
+    }
+}
+
+/// Black-76 price of a European futures option
+///
+/// # Safety
+/// `out` must be null or valid for writing one `f64`.
+#[no_mangle]
+pub unsafe extern "C" fn tsv2_black76_price(
+    futures_price: f64,
+    strike: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    volatility: f64,
+    is_call: bool,
+    out: *mut f64,
+) -> i32 {
+    if out.is_null() {
+        return TSV2_NULL_POINTER;
+    }
+    match Black76::try_price(futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call) {
+        Ok(price) => {
+            *out = price;
+            TSV2_OK
+        }
+        Err(_) => TSV2_INVALID_INPUT,
+    }
+}
+
+/// Black-76 Greeks of a European futures option
+///
+/// # Safety
+/// `out` must be null or valid for writing one `Tsv2Greeks`.
+#[no_mangle]
+pub unsafe extern "C" fn tsv2_black76_greeks(
+    futures_price: f64,
+    strike: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    volatility: f64,
+    is_call: bool,
+    out: *mut Tsv2Greeks,
+) -> i32 {
+    if out.is_null() {
+        return TSV2_NULL_POINTER;
+    }
+    // Same input checks as pricing
+    if Black76::try_price(futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call).is_err() {
+        return TSV2_INVALID_INPUT;
+    }
+    let greeks = Black76::greeks(futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call);
+    *out = greeks.into();
+    TSV2_OK
+}
+
+/// Black-76 implied volatility from an option price
+///
+/// # Safety
+/// `out` must be null or valid for writing one `f64`.
+#[no_mangle]
+pub unsafe extern "C" fn tsv2_black76_implied_vol(
+    option_price: f64,
+    futures_price: f64,
+    strike: f64,
+    time_to_expiry: f64,
+    risk_free_rate: f64,
+    is_call: bool,
+    out: *mut f64,
+) -> i32 {
+    if out.is_null() {
+        return TSV2_NULL_POINTER;
+    }
+    let valid = [option_price, futures_price, strike, time_to_expiry].iter().all(|x| x.is_finite() && *x > 0.0)
+        && risk_free_rate.is_finite();
+    if !valid {
+        return TSV2_INVALID_INPUT;
+    }
+    match Black76::implied_volatility(option_price, futures_price, strike, time_to_expiry, risk_free_rate, is_call) {
+        Some(vol) => {
+            *out = vol;
+            TSV2_OK
+        }
+        None => TSV2_NO_CONVERGENCE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_matches_rust_pricer() {
+        let mut price = 0.0;
+        let status = unsafe { tsv2_black76_price(75.0, 75.0, 0.25, 0.05, 0.40, true, &mut price) };
+        assert_eq!(status, TSV2_OK);
+        assert_eq!(price, Black76::price(75.0, 75.0, 0.25, 0.05, 0.40, true));
+
+        let mut greeks = Tsv2Greeks::default();
+        let status = unsafe { tsv2_black76_greeks(75.0, 75.0, 0.25, 0.05, 0.40, false, &mut greeks) };
+        assert_eq!(status, TSV2_OK);
+        assert_eq!(greeks, Black76::greeks(75.0, 75.0, 0.25, 0.05, 0.40, false).into());
+
+        let mut vol = 0.0;
+        let status = unsafe { tsv2_black76_implied_vol(price, 75.0, 75.0, 0.25, 0.05, true, &mut vol) };
+        assert_eq!(status, TSV2_OK);
+        assert!((vol - 0.40).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        let mut out = -1.0;
+        assert_eq!(unsafe { tsv2_black76_price(-75.0, 75.0, 0.25, 0.05, 0.4, true, &mut out) }, TSV2_INVALID_INPUT);
+        assert_eq!(out, -1.0);
+        assert_eq!(
+            unsafe { tsv2_black76_price(75.0, 75.0, 0.25, 0.05, 0.4, true, std::ptr::null_mut()) },
+            TSV2_NULL_POINTER
+        );
+        assert_eq!(
+            unsafe { tsv2_black76_implied_vol(0.0, 75.0, 75.0, 0.25, 0.05, true, &mut out) },
+            TSV2_INVALID_INPUT
+        );
+    }
+}
@@ -0,0 +1,37 @@
+//! Trading Simulator V2 - Library
+//!
+//! Synthetic /CL options simulator: calendars, configuration, event store,
+//! price generation, historical data loaders, Black-76 pricing, roll
+//! triggers and the simulation engine. The CLI, combined runner, web server
+//! and Tauri app (through `desktop`) are thin frontends over this crate; with
+//! the `wasm` feature (and no default features) it also builds for
+//! `wasm32-unknown-unknown`.
+//!
+//! ```no_run
+//! use trading_simulator_v2::{config::Config, engine::SimulationEngine};
+//!
+//! let config = Config::from_file("config/straddle_1dte.yaml").unwrap();
+//! let report = SimulationEngine::run(&config).unwrap();
+//! println!("Net P&L: {:.2}", report.net_pnl());
+//! ```
+
+pub mod calendar;
+pub mod config;
+pub mod data;
+#[cfg(feature = "desktop")]
+pub mod desktop;
+pub mod engine;
+pub mod error;
+pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod montecarlo;
+pub mod prices;
+pub mod pricing;
+pub mod reporting;
+pub mod risk;
+pub mod triggers;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use error::{SimError, SimResult};
@@ -1,7 +1,7 @@
-//! Trading Simulator V2 - Intraday Version (10-minute resolution)
+//! Trading Simulator V2 - Intraday Version
 //!
 //! Supports:
-//! - 10-minute granularity (matching V1)
+//! - Intraday bars, 10-minute by default (`simulation.intraday_resolution_minutes`)
 //! - 23/5 trading calendar (/CL futures)
 //! - Intraday roll triggers (profit targets, DTE, time-based)
 //! - Fractional DTE calculation
@@ -10,60 +10,164 @@
 //! Usage:
 //!   cargo run -- config/straddle_1dte.yaml
 //!   cargo run -- config/long_protection.yaml
-
-mod calendar;
-mod config;
-mod events;
-mod prices;
-mod pricing;
-mod triggers;
-
-use calendar::intraday::{TradingCalendar, Timestamp};
-use config::Config;
-use events::{CloseReason, Event, EventStore, LegId, OptionContract, OptionType, PositionId, Side};
-use prices::{GBM, PricePoint};
-use pricing::{Black76, Greeks};
+//!   cargo run --features charts -- config/straddle_1dte.yaml --charts out/
+//!   cargo run --features parquet-export -- config/straddle_1dte.yaml --parquet out/
+//!   cargo run -- config/straddle_1dte.yaml --seeds 100
+//!   cargo run --release -- montecarlo config/straddle_1dte.yaml --seeds 1000 --confidence 0.95
+//!   cargo run --features results-db -- montecarlo config/straddle_1dte.yaml --db results.sqlite --study baseline
+//!   cargo run -- config/my_product.yaml --contracts config/my_contracts.yaml
+//!   cargo run -- config/straddle_1dte.yaml --digest
+//!   cargo run -- config/straddle_1dte.yaml --progress
+//!   cargo run -- config/straddle_1dte.yaml --step bar
+//!   cargo run -- config/straddle_1dte.yaml --bars data/cl_1min.bars
+//!   cargo run -- config/straddle_1dte.yaml --bars data/cl_daily.csv
+//!   cargo run --features parquet-import -- config/straddle_1dte.yaml --bars data/cl_1min.parquet
+//!   cargo run -- config/straddle_1dte.yaml --bars data/cl_daily.csv --chains data/cl_chains.csv
+//!   cargo run -- config/straddle_1dte.yaml --bars data/cl_daily.csv --iv data/cl_iv.csv
+//!   cargo run -- config/straddle_1dte.yaml --bars data/cl_daily.csv --vol-index data/ovx_history.csv
+//!   cargo run --features market-data -- config/my_fetch.yaml --fetch
+//!   cargo run -- config/straddle_1dte.yaml --save-path out/seed42.path.csv
+//!   cargo run -- config/long_protection.yaml --bars out/seed42.path.csv
+//!   cargo run -- config/straddle_1dte.yaml --bars data/cl_daily.csv --blotter out/blotter.csv --fee 2.5
+
+use trading_simulator_v2::config::{Config, ContractRegistry};
+use trading_simulator_v2::engine::{PricePath, SimulationReport, Simulator, StreamEvent};
+use trading_simulator_v2::data;
+use trading_simulator_v2::montecarlo;
+use trading_simulator_v2::reporting::{self, presenter, BreakdownPeriod, EquityPoint, TradeRecord};
+use trading_simulator_v2::SimError;
 use std::env;
+use std::sync::Arc;
+use std::io::Write;
+
+/// Default bars between progress updates (about a trading week at 10 minutes)
+const DEFAULT_PROGRESS_BARS: usize = 690;
 
-/// Parse time string "HH:MM" to minutes from midnight
-fn parse_time(time_str: &str) -> u32 {
-    let parts: Vec<&str> = time_str.split(':').collect();
-    let hours: u32 = parts[0].parse().unwrap_or(14);
-    let minutes: u32 = parts[1].parse().unwrap_or(0);
-    hours * 60 + minutes
+/// Seeds a `montecarlo` study runs unless `--seeds` says otherwise
+const DEFAULT_MONTE_CARLO_SEEDS: u64 = 1000;
+
+/// Command-line arguments:
+/// `[montecarlo] <config.yaml> [--confidence <level>] [--resamples <n>]
+/// [--charts <dir>] [--chart-format svg|png] [--parquet <dir>] [--seeds <n>] [--digest]
+/// [--progress] [--progress-every <bars>] [--step [bar|day]] [--bars <file>] [--chains <file>] [--iv <file>]
+/// [--vol-index <file>] [--fetch] [--save-path <file>] [--blotter <file>] [--fee <per contract>] [--contracts <file>]
+/// [--db <file>] [--study <name>]`
+#[derive(Debug, Default)]
+struct CliArgs {
+    config_path: Option<String>,
+    /// Directory to write price/equity charts into (requires the `charts` feature)
+    chart_dir: Option<String>,
+    /// Image format for charts: "svg" (default) or "png"
+    chart_format: Option<String>,
+    /// Directory to write Parquet exports into (requires the `parquet-export` feature)
+    parquet_dir: Option<String>,
+    /// Run the config over this many consecutive seeds (starting at the config seed)
+    seeds: Option<u64>,
+    /// The `montecarlo` subcommand: a seed study with bootstrap confidence intervals
+    montecarlo: bool,
+    /// Confidence level of the `montecarlo` intervals (default 0.95)
+    confidence: Option<f64>,
+    /// Bootstrap resamples per interval (default 2000)
+    resamples: Option<usize>,
+    /// Print the report digest (see `config/golden_digests.txt`)
+    digest: bool,
+    /// Draw a progress bar on stderr every this many bars (seed studies update per seed)
+    progress_every: Option<usize>,
+    /// Pause after every bar or day and print the book until told to run on
+    step: Option<StepUnit>,
+    /// Bars to run over instead of a generated path: a saved path (see
+    /// `--save-path`), OHLC bars (`.csv` or `.parquet`) or a memory-mapped
+    /// bar file
+    bars_path: Option<String>,
+    /// Option chain snapshots (CSV, see `data::chains`) to price legs from
+    chains_path: Option<String>,
+    /// Implied volatility by date (CSV, see `data::iv`) to price at
+    iv_path: Option<String>,
+    /// Volatility index levels (CSV, see `data::vol_index`) for `config.vol_index`
+    vol_index_path: Option<String>,
+    /// Run over bars from `config.market_data` (requires the `market-data` feature)
+    fetch: bool,
+    /// Save the path the run uses, to rerun later with `--bars`
+    save_path: Option<String>,
+    /// Write every fill as a broker-style blotter CSV (see `reporting::blotter`)
+    blotter_path: Option<String>,
+    /// Commission and fees per contract for the blotter, in dollars
+    fee_per_contract: Option<f64>,
+    /// Contract catalog (YAML) added to the built-in one, for `symbol` in configs
+    contracts_path: Option<String>,
+    /// SQLite database to record runs in (requires the `results-db` feature)
+    db_path: Option<String>,
+    /// Study the runs are recorded under (default: the config file's name)
+    study: Option<String>,
 }
 
-/// Position tracking with P&L (intraday version)
-#[derive(Debug)]
-struct PositionTracking {
-    position_id: PositionId,
-    entry_timestamp: Timestamp,
-    expiration_day: u32,
-    entry_price: f64,
-    put_strike: f64,
-    call_strike: f64,
-    put_entry_premium: f64,
-    call_entry_premium: f64,
-    put_greeks: Greeks,
-    call_greeks: Greeks,
+/// How far `--step` advances per keypress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepUnit {
+    Bar,
+    Day,
 }
 
-/// Track P&L summary
-#[derive(Debug, Default)]
-struct PnLSummary {
-    total_premium_collected: f64,
-    total_premium_paid: f64,
-    position_count: u32,
+fn parse_args() -> Result<CliArgs, String> {
+    let mut cli = CliArgs::default();
+    let mut args = env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--charts" => cli.chart_dir = args.next(),
+            "--chart-format" => cli.chart_format = args.next(),
+            "--parquet" => cli.parquet_dir = args.next(),
+            "montecarlo" => cli.montecarlo = true,
+            "--seeds" => match args.next().map(|n| n.parse::<u64>()) {
+                Some(Ok(count)) => cli.seeds = Some(count),
+                _ => return Err("--seeds needs a whole number of seeds, e.g. --seeds 100".to_string()),
+            },
+            "--confidence" => cli.confidence = args.next().and_then(|n| n.parse().ok()),
+            "--resamples" => cli.resamples = args.next().and_then(|n| n.parse().ok()),
+            "--digest" => cli.digest = true,
+            "--bars" => cli.bars_path = args.next(),
+            "--chains" => cli.chains_path = args.next(),
+            "--iv" => cli.iv_path = args.next(),
+            "--vol-index" => cli.vol_index_path = args.next(),
+            "--fetch" => cli.fetch = true,
+            "--save-path" => cli.save_path = args.next(),
+            "--blotter" => cli.blotter_path = args.next(),
+            "--fee" => cli.fee_per_contract = args.next().and_then(|n| n.parse().ok()),
+            "--contracts" => cli.contracts_path = args.next(),
+            "--db" => cli.db_path = args.next(),
+            "--study" => cli.study = args.next(),
+            "--step" => {
+                cli.step = match args.next_if(|unit| unit == "bar" || unit == "day").as_deref() {
+                    Some("bar") => Some(StepUnit::Bar),
+                    _ => Some(StepUnit::Day),
+                }
+            }
+            "--progress" => cli.progress_every = cli.progress_every.or(Some(DEFAULT_PROGRESS_BARS)),
+            "--progress-every" => {
+                cli.progress_every = args.next().and_then(|n| n.parse().ok()).or(Some(DEFAULT_PROGRESS_BARS))
+            }
+            _ => cli.config_path = Some(arg),
+        }
+    }
+    Ok(cli)
 }
 
 fn main() {
-    println!("Trading Simulator V2 - Intraday Version (10-minute resolution)\n");
+    println!("Trading Simulator V2 - Intraday Version\n");
+
+    let cli = match parse_args() {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("✗ {}", e);
+            eprintln!("Usage: cargo run -- [montecarlo] <config.yaml> [--seeds <n>] [options]");
+            std::process::exit(2);
+        }
+    };
 
     // Load configuration from file or use default
-    let config = match env::args().nth(1) {
+    let config = match cli.config_path.clone() {
         Some(path) => {
             println!("Loading configuration from: {}", path);
-            match Config::from_file(&path) {
+            match Config::from_file_with(&path, &contract_registry(&cli)) {
                 Ok(cfg) => {
                     println!("✓ Configuration loaded successfully\n");
                     cfg
@@ -82,417 +186,487 @@ fn main() {
         }
     };
 
-    // Parse times from config
-    let entry_time = parse_time(&config.strategy.entry_time);
-    let roll_time = parse_time(&config.strategy.roll_time);
-
-    // Setup trading calendar and price generator
-    let calendar = TradingCalendar::new();
-    let mut event_store = EventStore::new();
-
-    // Generate intraday price path
-    let start_day = 0; // Day 0 = Monday
-    let start_minute = 9 * 60; // 9:00 AM
-    
-    let mut gbm = GBM::new(
-        config.simulation.initial_price,
-        config.simulation.drift,
-        config.simulation.volatility,
-        config.simulation.seed,
-    );
-    
-    let resolution = config.simulation.intraday_resolution_minutes;
-    let price_bars = gbm.generate_intraday_path(
-        &calendar,
-        config.simulation.days,
-        resolution,
-        start_day,
-        start_minute,
-    );
-
-    // Calculate implied volatility for option pricing
-    let realized_vol = config.simulation.volatility;
-    let implied_vol = realized_vol + config.simulation.volatility_risk_premium;
-    
-    // Print configuration
-    println!("Simulation Parameters:");
-    println!("  Days: {}", config.simulation.days);
-    println!("  Resolution: {} minutes", config.simulation.intraday_resolution_minutes);
-    println!("  Total bars: {}", price_bars.len());
-    println!("  Initial price: ${:.2}", config.simulation.initial_price);
-    println!("  Drift (μ): {:.2}%", config.simulation.drift * 100.0);
-    println!("  Realized volatility: {:.0}%", realized_vol * 100.0);
-    println!("  Volatility Risk Premium: {:.1}%", config.simulation.volatility_risk_premium * 100.0);
-    println!("  Implied volatility: {:.0}% (for option pricing)", implied_vol * 100.0);
-    println!("  Risk-free rate: {:.1}%", config.simulation.risk_free_rate * 100.0);
-    println!("  Seed: {}", config.simulation.seed);
-    println!();
-    println!("Strategy: {} ({} DTE)", config.strategy.strategy_type, config.strategy.entry_dte);
-    println!("  Side: {} ({})", 
-        config.strategy.side,
-        if config.strategy.side == "long" { "pay premium" } else { "collect premium" }
-    );
-    println!("  Entry time: {}", config.strategy.entry_time);
-    println!("  Roll time: {}", config.strategy.roll_time);
-    println!("  Strike selection: {}", config.strategy.strike_selection);
-    println!("  Strike tick size: ${:.2}", config.strike_config.tick_size);
-    println!("  Roll type: {}", config.strike_config.roll_type);
-    if config.strategy.strike_offset > 0.0 {
-        println!("  Strike offset: {} points", config.strategy.strike_offset);
+    if config.simulation.seed_generated {
+        let seed = config.simulation.seed;
+        println!("*** Random seed {} (set `simulation.seed: {}` to reproduce this run) ***\n", seed, seed);
     }
-    println!();
 
-    // Track active position
-    let mut active_position: Option<PositionTracking> = None;
-    let mut pnl_summary = PnLSummary::default();
-
-    // Run simulation bar by bar
-    for price_point in &price_bars {
-        let current_price = price_point.price;
-        let timestamp = price_point.timestamp;
-        let date_str = format_timestamp(&timestamp);
-
-        // Check for roll triggers
-        if let Some(pos) = active_position.take() {
-            // Calculate fractional DTE
-            let fractional_dte = calculate_fractional_dte(&timestamp, pos.expiration_day);
-            
-            // Check if we should roll (DTE threshold or time-based)
-            let should_roll = if config.strategy.entry_dte == 1 {
-                // For 1DTE: roll at roll_time on expiration day
-                timestamp.day == pos.expiration_day && timestamp.minute >= roll_time
-            } else {
-                // For longer DTE: roll when DTE <= 28
-                fractional_dte <= 28.0
-            };
-            
-            if should_roll {
-                // Close current position
-                let (put_close, call_close) = if fractional_dte > 0.0 {
-                    // Early close: use Black76 to include time value
-                    let time_to_expiry = fractional_dte / 252.0;
-                    let put = Black76::price(
-                        current_price, pos.put_strike, time_to_expiry,
-                        config.simulation.risk_free_rate, implied_vol, false
-                    );
-                    let call = Black76::price(
-                        current_price, pos.call_strike, time_to_expiry,
-                        config.simulation.risk_free_rate, implied_vol, true
-                    );
-                    (put, call)
-                } else {
-                    // Expiration: use intrinsic value only
-                    let put = calculate_intrinsic(current_price, pos.put_strike, false);
-                    let call = calculate_intrinsic(current_price, pos.call_strike, true);
-                    (put, call)
-                };
-                
-                // Calculate P&L based on position side
-                let is_long = config.strategy.side == "long";
-                let position_pnl = if is_long {
-                    // Long: Close Value - Entry Premium
-                    (put_close + call_close) - (pos.put_entry_premium + pos.call_entry_premium)
-                } else {
-                    // Short: Entry Premium - Close Value
-                    (pos.put_entry_premium + pos.call_entry_premium) - (put_close + call_close)
-                };
-                let position_pnl_dollars = position_pnl * config.simulation.contract_multiplier;
-                
-                // Track close value
-                if is_long {
-                    pnl_summary.total_premium_collected += put_close + call_close;
-                } else {
-                    pnl_summary.total_premium_paid += put_close + call_close;
-                }
-                
-                let reason_str = if fractional_dte <= 0.0 { "Expiration" } else { "Roll" };
-                print!("{} | Price ${:.2} | ", date_str, current_price);
-                println!(
-                    "CLOSED position {} at {} | P&L: ${:.0} ({})",
-                    pos.position_id.0,
-                    &config.strategy.roll_time,
-                    position_pnl_dollars,
-                    reason_str
-                );
-                
-                let close_event = Event::PositionClosed {
-                    position_id: pos.position_id,
-                    timestamp: (timestamp.day, timestamp.minute as u16),
-                    close_premiums: vec![
-                        (LegId(pos.position_id.0 * 2 - 1), put_close),
-                        (LegId(pos.position_id.0 * 2), call_close),
-                    ],
-                    reason: CloseReason::Expiration,
-                };
-                event_store.append(close_event);
-                
-                // Open new position at roll time
-                let use_same_strikes = config.strike_config.roll_type == "same_strikes";
-                let new_pos = open_position_with_pricing(
-                    &calendar,
-                    &mut event_store,
-                    &mut pnl_summary,
-                    &config,
-                    timestamp.day,
-                    roll_time,
-                    current_price,
-                    if use_same_strikes {
-                        Some((pos.put_strike, pos.call_strike))
-                    } else {
-                        None
-                    },
-                    implied_vol,
-                );
-                let new_total = new_pos.put_entry_premium + new_pos.call_entry_premium;
-                let new_total_dollars = new_total * config.simulation.contract_multiplier;
-                let new_display_premium = if is_long { -new_total } else { new_total };
-                let new_display_premium_dollars = if is_long { -new_total_dollars } else { new_total_dollars };
-                let roll_type_str = if use_same_strikes { " (same strikes)" } else { "" };
-                println!(
-                    "  -> OPENED position {} at {} | Strikes: Put ${:.2} Call ${:.2} | ${:.2} per barrel (${:.0} total){}",
-                    new_pos.position_id.0,
-                    &config.strategy.roll_time,
-                    new_pos.put_strike,
-                    new_pos.call_strike,
-                    new_display_premium,
-                    new_display_premium_dollars,
-                    roll_type_str
-                );
-                print_greeks(&new_pos);
-                
-                active_position = Some(new_pos);
-                continue;
-            } else {
-                // No roll triggered, keep position
-                active_position = Some(pos);
-            }
+    if cli.montecarlo {
+        if let Err(e) = run_monte_carlo_study(&config, &cli) {
+            eprintln!("✗ Monte Carlo study failed: {}", e);
+            std::process::exit(1);
         }
+        return;
+    }
 
-        // Open new position at entry time if none exists
-        if active_position.is_none() && timestamp.minute >= entry_time {
-            let pos = open_position_with_pricing(
-                &calendar,
-                &mut event_store,
-                &mut pnl_summary,
-                &config,
-                timestamp.day,
-                entry_time,
-                current_price,
-                None,
-                implied_vol,
-            );
-
-            let is_long = config.strategy.side == "long";
-            let total_premium = pos.put_entry_premium + pos.call_entry_premium;
-            let total_premium_dollars = total_premium * config.simulation.contract_multiplier;
-            let display_premium = if is_long { -total_premium } else { total_premium };
-            let display_premium_dollars = if is_long { -total_premium_dollars } else { total_premium_dollars };
-            
-            print!("{} | Price ${:.2} | ", date_str, current_price);
-            println!(
-                "OPENED position {} at {} | Strikes: Put ${:.2} Call ${:.2} | ${:.2} per barrel (${:.0} total)",
-                pos.position_id.0,
-                &config.strategy.entry_time,
-                pos.put_strike,
-                pos.call_strike,
-                display_premium,
-                display_premium_dollars
-            );
-            print_greeks(&pos);
-
-            active_position = Some(pos);
+    if let Some(count) = cli.seeds {
+        if let Err(e) = run_seed_study(&config, count, &cli) {
+            eprintln!("✗ Seed study failed: {}", e);
+            std::process::exit(1);
         }
+        return;
     }
 
-    // Final summary
-    println!("\n{}", "=".repeat(60));
-    println!("SIMULATION SUMMARY");
-    println!("{}", "=".repeat(60));
-    println!("Total positions opened: {}", pnl_summary.position_count);
-    println!(
-        "Total premium collected: ${:.2} per barrel (${:.0} total)",
-        pnl_summary.total_premium_collected,
-        pnl_summary.total_premium_collected * config.simulation.contract_multiplier
-    );
-    println!(
-        "Total premium paid: ${:.2} per barrel (${:.0} total)",
-        pnl_summary.total_premium_paid,
-        pnl_summary.total_premium_paid * config.simulation.contract_multiplier
-    );
-    let net_pnl = pnl_summary.total_premium_collected - pnl_summary.total_premium_paid;
-    println!(
-        "Net P&L: ${:.2} per barrel (${:.0} total)",
-        net_pnl,
-        net_pnl * config.simulation.contract_multiplier
-    );
-    println!(
-        "Contract multiplier: {} barrels",
-        config.simulation.contract_multiplier as u32
-    );
-    if let Some(last_point) = price_bars.last() {
-        println!("Final underlying price: ${:.2}", last_point.price);
+    let (output, mapping) = match run_simulation(&config, &cli) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("✗ Simulation failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+    print!("{}", presenter::render(&config, &output));
+
+    if let Some(period) = BreakdownPeriod::from_config(&config.reporting.breakdown) {
+        let rows = reporting::periodic_breakdown(&output.trade_records, period);
+        reporting::print_breakdown(&rows, period, config.simulation.contract_multiplier);
+    }
+
+    if let Some(report) = reporting::volatility_report(&output.trade_records) {
+        reporting::print_volatility_report(&report, config.simulation.contract_multiplier);
+    }
+
+    if let Some(report) = reporting::premium_decay_report(&output.trade_records) {
+        reporting::print_premium_decay_report(&report);
+    }
+
+    reporting::print_risk_summary(&output.risk_summary, output.net_pnl() * config.simulation.contract_multiplier);
+
+    if let Some(result) = reporting::benchmark(&output.equity_series) {
+        reporting::print_benchmark(&result, config.simulation.contract_multiplier);
+    }
+
+    if let Some(dir) = &cli.chart_dir {
+        let format = cli.chart_format.as_deref().unwrap_or("svg");
+        write_charts(dir, format, &output.equity_series, &output.trade_records, config.simulation.contract_multiplier);
+    }
+
+    if let Some(dir) = &cli.parquet_dir {
+        write_parquet(dir, &output.equity_series, &output.trade_records);
+    }
+
+    if let Some(file) = &cli.blotter_path {
+        write_blotter(file, &config, &cli, mapping, &output);
+    }
+
+    if let Some(db) = &cli.db_path {
+        record_run(db, &config, &cli, &output);
+    }
+
+    if cli.digest {
+        println!("\nDigest: {}", output.digest());
     }
 }
 
-/// Calculate fractional days to expiration
-fn calculate_fractional_dte(current: &Timestamp, expiration_day: u32) -> f64 {
-    if current.day >= expiration_day {
-        return 0.0;
+/// The built-in contract catalog plus `--contracts`, if given
+fn contract_registry(cli: &CliArgs) -> ContractRegistry {
+    let mut registry = ContractRegistry::builtin().clone();
+    if let Some(path) = &cli.contracts_path {
+        match ContractRegistry::from_file(path) {
+            Ok(extra) => {
+                println!("Read {} contract(s) from {}", extra.len(), path);
+                registry.extend(extra);
+            }
+            Err(e) => eprintln!("✗ Failed to load contracts: {}", e),
+        }
     }
-    // Approximate: each day is 1.0, each minute is 1/138 (for 23-hour trading day at 10-min bars)
-    let days_remaining = (expiration_day - current.day) as f64;
-    let minutes_fraction = (138.0 - current.minute as f64) / 138.0;
-    days_remaining - 1.0 + minutes_fraction
+    registry
 }
 
-/// Format timestamp as human-readable string
-fn format_timestamp(ts: &Timestamp) -> String {
-    let hours = ts.minute / 60;
-    let mins = ts.minute % 60;
-    let weekday = match ts.day % 7 {
-        0 => "Mon", 1 => "Tue", 2 => "Wed", 3 => "Thu",
-        4 => "Fri", 5 => "Sat", 6 => "Sun", _ => "???",
+/// Run the simulation, drawing a progress bar on stderr if requested; also
+/// returns the real dates of the bars when they came from a data file
+fn run_simulation(config: &Config, cli: &CliArgs) -> Result<(SimulationReport, Option<data::DayMapping>), SimError> {
+    let (path, mapping) = match cli.bars_path.as_deref() {
+        Some(path) => load_bars(path, &ohlc_options(config))?,
+        None if cli.fetch => {
+            let series = fetch_bars(config)?;
+            (series.price_path()?, Some(series.mapping))
+        }
+        None => (PricePath::generate(config)?, None),
     };
-    let week = ts.day / 7;
-    format!("Day {} ({} W{}) {:02}:{:02}", ts.day, weekday, week, hours, mins)
+    if let Some(file) = &cli.save_path {
+        path.save(file, &path_note(config, cli))?;
+        println!("Saved {} bars to {} (rerun with --bars {})\n", path.len(), file, file);
+    }
+    let mut simulator = Simulator::with_path(config.clone(), path)?;
+    if let Some(path) = &cli.chains_path {
+        let options = data::ChainOptions { mapping, ..data::ChainOptions::default() };
+        let chains = data::read_option_chains(path, &options)?;
+        println!("Read {} option quotes from {}\n", chains.len(), path);
+        simulator.use_option_chains(Arc::new(chains));
+    }
+    if let Some(path) = &cli.iv_path {
+        let series = data::read_implied_vols(path, &data::IvOptions { mapping })?;
+        println!("Read implied vols for {} date(s) from {}\n", series.len(), path);
+        simulator.use_implied_vols(Arc::new(series));
+    }
+    match (&cli.vol_index_path, &config.vol_index) {
+        (Some(path), _) => {
+            let series = data::read_vol_index(path, &data::VolIndexOptions { mapping })?;
+            println!("Read volatility index levels for {} date(s) from {}\n", series.len(), path);
+            simulator.use_vol_index(Arc::new(series));
+        }
+        (None, Some(_)) => return Err(SimError::Data("config.vol_index needs levels from --vol-index".to_string())),
+        (None, None) => {}
+    }
+    if let Some(unit) = cli.step {
+        step_through(&mut simulator, config, unit)?;
+    }
+    let Some(every) = cli.progress_every else {
+        return Ok((simulator.finish()?, mapping));
+    };
+    let report = simulator.run_streaming(every, |event| {
+        if let StreamEvent::Progress(p) = event {
+            draw_progress(&presenter::progress_bar(p.fraction(), p.bars_per_sec(), "bars", p.eta()));
+        }
+    });
+    eprintln!();
+    Ok((report?, mapping))
 }
 
-/// Calculate intrinsic value at expiration
-fn calculate_intrinsic(underlying: f64, strike: f64, is_call: bool) -> f64 {
-    if is_call {
-        (underlying - strike).max(0.0)
-    } else {
-        (strike - underlying).max(0.0)
+/// Advance one bar or day per Enter, printing what opened and closed and
+/// the book after it; `c` (or the end of input) runs the rest unattended
+fn step_through(simulator: &mut Simulator, config: &Config, unit: StepUnit) -> Result<(), SimError> {
+    let unit_name = if unit == StepUnit::Bar { "bar" } else { "day" };
+    println!("Stepping one {} at a time: Enter for the next, c to run to the end\n", unit_name);
+    let mut logged = 0;
+    loop {
+        let mut last = None;
+        while let Some(outcome) = simulator.step()? {
+            let day = outcome.timestamp.day;
+            last = Some(outcome);
+            if unit == StepUnit::Bar || simulator.next_timestamp().is_none_or(|next| next.day != day) {
+                break;
+            }
+        }
+        let Some(outcome) = last else {
+            return Ok(());
+        };
+        let state = simulator.state();
+        for entry in &state.log[logged..] {
+            println!("{}", presenter::entry_line(entry, config));
+        }
+        logged = state.log.len();
+        print!("{}", presenter::step_view(&state, &outcome, simulator.pending_roll().as_ref(), config));
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 || line.trim() == "c" {
+            println!();
+            return Ok(());
+        }
     }
 }
 
-/// Open a position with Black-76 pricing
-fn open_position_with_pricing(
-    calendar: &TradingCalendar,
-    event_store: &mut EventStore,
-    pnl: &mut PnLSummary,
-    config: &Config,
-    entry_day: u32,
-    entry_time: u32,
-    current_price: f64,
-    strike_override: Option<(f64, f64)>,
-    implied_vol: f64,
-) -> PositionTracking {
-    // Calculate expiration day based on entry_dte config
-    let mut expiration_day = entry_day;
-    let mut trading_days_count = 0;
-    let calendar_old = calendar::Calendar::new();
-    while trading_days_count < config.strategy.entry_dte {
-        expiration_day = calendar_old.next_trading_day(expiration_day);
-        trading_days_count += 1;
-    }
-    let time_to_expiry = config.strategy.entry_dte as f64 / 252.0;
-
-    let position_id = event_store.next_position_id();
-    let put_leg_id = event_store.next_leg_id();
-    let call_leg_id = event_store.next_leg_id();
-
-    // Determine strikes
-    let (put_strike, call_strike) = if let Some((put, call)) = strike_override {
-        (put, call)
+#[cfg(feature = "parquet-import")]
+fn read_parquet_bars(path: &str, options: &data::OhlcOptions) -> Result<data::OhlcSeries, SimError> {
+    data::parquet_import::read_ohlc_parquet(path, options)
+}
+
+#[cfg(not(feature = "parquet-import"))]
+fn read_parquet_bars(_path: &str, _options: &data::OhlcOptions) -> Result<data::OhlcSeries, SimError> {
+    Err(SimError::Data("Parquet bar files require building with `--features parquet-import`".to_string()))
+}
+
+/// Where the bars of a saved path came from
+fn path_note(config: &Config, cli: &CliArgs) -> String {
+    let sim = &config.simulation;
+    match (&cli.bars_path, cli.fetch) {
+        (Some(path), _) => format!("bars from {}", path),
+        (None, true) => format!("bars fetched for {}", config.market_data.as_ref().map_or("?", |m| &m.symbol)),
+        (None, false) => format!(
+            "generated: seed {}, {} days, {}-minute bars, initial price {}, drift {}, volatility {}, rng {}",
+            sim.seed, sim.days, sim.intraday_resolution_minutes, sim.initial_price, sim.drift, sim.volatility, sim.rng
+        ),
+    }
+}
+
+/// Load bars: a saved path (see `PricePath::save`), closes of OHLC bars
+/// (see `data::ohlc` and `data::parquet_import`) or a mapped bar file (see
+/// `prices::mapped`), with the OHLC file's real dates
+fn load_bars(path: &str, options: &data::OhlcOptions) -> Result<(PricePath, Option<data::DayMapping>), SimError> {
+    let mut head = [0u8; 32];
+    let read = std::io::Read::read(&mut std::fs::File::open(path)?, &mut head)?;
+    if PricePath::is_saved_path(&String::from_utf8_lossy(&head[..read])) {
+        let saved = PricePath::load(path)?;
+        println!("Loaded saved path of {} bars from {}\n", saved.len(), path);
+        return Ok((saved, None));
+    }
+    let series = if path.ends_with(".csv") {
+        data::read_ohlc_csv(path, options)?
+    } else if path.ends_with(".parquet") {
+        read_parquet_bars(path, options)?
     } else {
-        match config.strategy.strike_selection.as_str() {
-            "OTM" => {
-                let offset = config.strategy.strike_offset;
-                let atm = config.strike_config.round_to_strike(current_price);
-                let put = config.strike_config.round_to_strike(atm - offset);
-                let call = config.strike_config.round_to_strike(atm + offset);
-                (put, call)
-            }
-            _ => {
-                let atm = config.strike_config.round_to_strike(current_price);
-                (atm, atm)
-            }
-        }
+        return Ok((map_bars(path)?, None));
     };
+    print_series_summary(&series, path);
+    Ok((series.price_path()?, Some(series.mapping)))
+}
+
+fn print_series_summary(series: &data::OhlcSeries, source: &str) {
+    let start = series.start_date().map_or_else(String::new, |date| format!(" from {}", date));
+    println!("Read {} bars{} ({:?}) from {}", series.bars.len(), start, series.resolution, source);
+    if !series.gaps.is_empty() {
+        let missing: usize = series.gaps.iter().map(|gap| gap.missing).sum();
+        let largest = series.gaps.iter().map(|gap| gap.missing).max().unwrap_or(0);
+        println!("  {} gap(s), {} bar(s) missing (largest {})", series.gaps.len(), missing, largest);
+    }
+    let quality = &series.quality;
+    if !quality.is_clean() {
+        println!(
+            "  Data quality: {} of {} rows with bad prices, {} out of order, {} off-session; {} dropped, {} filled",
+            quality.bad_prices, quality.rows, quality.out_of_order, quality.off_session, quality.dropped, quality.filled
+        );
+    }
+    println!();
+}
+
+/// Import policies from `config.data_quality` (validated with the config)
+fn ohlc_options(config: &Config) -> data::OhlcOptions {
+    data::OhlcOptions::from_config(&config.data_quality).unwrap_or_default()
+}
 
-    // Price using Black-76 with IMPLIED volatility
-    let put_premium = Black76::price(
-        current_price, put_strike, time_to_expiry,
-        config.simulation.risk_free_rate, implied_vol, false
-    );
-    let call_premium = Black76::price(
-        current_price, call_strike, time_to_expiry,
-        config.simulation.risk_free_rate, implied_vol, true
-    );
-
-    // Calculate Greeks
-    let put_greeks = Black76::greeks(
-        current_price, put_strike, time_to_expiry,
-        config.simulation.risk_free_rate, implied_vol, false
-    );
-    let call_greeks = Black76::greeks(
-        current_price, call_strike, time_to_expiry,
-        config.simulation.risk_free_rate, implied_vol, true
-    );
-
-    // Determine side
-    let side = if config.strategy.side == "long" { Side::Long } else { Side::Short };
-    
-    let put_contract = OptionContract {
-        underlying_price: current_price,
-        strike: put_strike,
-        option_type: OptionType::Put,
-        side,
-        expiration_day,
+/// Fetch (or read cached) bars from `config.market_data` (see `data::fetch`)
+#[cfg(feature = "market-data")]
+fn fetch_bars(config: &Config) -> Result<data::OhlcSeries, SimError> {
+    let Some(market_data) = &config.market_data else {
+        return Err(SimError::Data("--fetch needs a market_data section in the config".to_string()));
     };
+    let series = data::fetch::fetch_ohlc(market_data, &ohlc_options(config))?;
+    print_series_summary(&series, &format!("{} (cached in {})", market_data.symbol, market_data.cache_dir));
+    Ok(series)
+}
+
+#[cfg(not(feature = "market-data"))]
+fn fetch_bars(_config: &Config) -> Result<data::OhlcSeries, SimError> {
+    Err(SimError::Data("--fetch requires building with `--features market-data`".to_string()))
+}
 
-    let call_contract = OptionContract {
-        underlying_price: current_price,
-        strike: call_strike,
-        option_type: OptionType::Call,
-        side,
-        expiration_day,
+#[cfg(feature = "mmap")]
+fn map_bars(path: &str) -> Result<PricePath, SimError> {
+    let bars = trading_simulator_v2::prices::mapped::MappedBars::open(path)?;
+    println!("Mapped {} bars from {}\n", bars.len(), path);
+    PricePath::mapped(bars)
+}
+
+#[cfg(not(feature = "mmap"))]
+fn map_bars(_path: &str) -> Result<PricePath, SimError> {
+    Err(SimError::Data("historical bar files require building with `--features mmap`".to_string()))
+}
+
+/// Write the run's fills as a broker-style blotter; generated paths start on 2024-01-01
+fn write_blotter(
+    file: &str,
+    config: &Config,
+    cli: &CliArgs,
+    mapping: Option<data::DayMapping>,
+    report: &SimulationReport,
+) {
+    let symbol = config.product.as_ref().map_or("", |p| p.symbol.trim().trim_start_matches('/'));
+    let options = reporting::blotter::BlotterOptions {
+        symbol: if symbol.is_empty() { "CL" } else { symbol }.to_string(),
+        mapping: mapping.unwrap_or_else(|| data::DayMapping::starting_week_of(data::Date::new(2024, 1, 1).unwrap())),
+        fee_per_contract: cli.fee_per_contract.unwrap_or(0.0),
     };
+    match std::fs::write(file, reporting::blotter::blotter_csv(&report.fills, &options)) {
+        Ok(()) => println!("\nWrote {} fills to {}", report.fills.len(), file),
+        Err(e) => eprintln!("✗ Failed to write blotter: {}", e),
+    }
+}
 
-    let put_premium_signed = if side == Side::Long { -put_premium } else { put_premium };
-    let call_premium_signed = if side == Side::Long { -call_premium } else { call_premium };
-    
-    let event = Event::PositionOpened {
-        position_id,
-        timestamp: (entry_day, entry_time as u16),
-        legs: vec![
-            (put_leg_id, put_contract, put_premium_signed),
-            (call_leg_id, call_contract, call_premium_signed),
-        ],
+/// Redraw the progress line in place on stderr
+fn draw_progress(line: &str) {
+    let mut stderr = std::io::stderr().lock();
+    let _ = write!(stderr, "\r{}", line);
+    let _ = stderr.flush();
+}
+
+/// Run the config over `count` consecutive seeds and report the distribution
+fn run_seed_study(config: &Config, count: u64, cli: &CliArgs) -> Result<(), SimError> {
+    let result = run_study_seeds(config, count, cli, cli.progress_every.is_some())?;
+    reporting::print_seed_distribution(&result.runs);
+    Ok(())
+}
+
+/// Run `count` seeds from the config's own, drawing a progress bar if
+/// `progress`; with `--db` the study resumes from, and records into, the
+/// results database
+fn run_study_seeds(
+    config: &Config,
+    count: u64,
+    cli: &CliArgs,
+    progress: bool,
+) -> Result<montecarlo::MonteCarloResult, SimError> {
+    let first_seed = config.simulation.seed;
+    if first_seed.checked_add(count).is_none() {
+        return Err(SimError::Execution(format!("{} seeds from seed {} run past the largest seed", count, first_seed)));
+    }
+    println!("Running {} seeds starting at {}...", count, first_seed);
+    let on_progress = |p: montecarlo::SweepProgress| {
+        if progress {
+            draw_progress(&presenter::progress_bar(p.fraction(), p.runs_per_sec(), "runs", p.eta()));
+        }
+    };
+    let result = match &cli.db_path {
+        Some(db) => run_recorded_seeds(db, config, count, cli, on_progress),
+        None => montecarlo::run_with_progress(config, first_seed, count, on_progress).map(|result| (result, 0)),
     };
-    event_store.append(event);
+    if progress {
+        eprintln!();
+    }
+    let (result, resumed) = result?;
+    if let Some(db) = &cli.db_path {
+        let new = result.runs.len() - resumed;
+        println!("✓ {} new run(s) recorded in {}, {} reused", new, db, resumed);
+    }
+    Ok(result)
+}
 
-    pnl.position_count += 1;
-    if side == Side::Short {
-        pnl.total_premium_collected += put_premium + call_premium;
-    } else {
-        pnl.total_premium_paid += put_premium + call_premium;
+/// The `montecarlo` subcommand: the seed distribution plus bootstrap
+/// confidence intervals, so one seed's result is not taken as the answer
+fn run_monte_carlo_study(config: &Config, cli: &CliArgs) -> Result<(), SimError> {
+    let defaults = montecarlo::BootstrapOptions::default();
+    let options = montecarlo::BootstrapOptions {
+        resamples: cli.resamples.unwrap_or(defaults.resamples),
+        level: cli.confidence.unwrap_or(defaults.level),
+        seed: config.simulation.seed,
+    };
+    let count = cli.seeds.unwrap_or(DEFAULT_MONTE_CARLO_SEEDS);
+    let result = run_study_seeds(config, count, cli, true)?;
+    let intervals = result.confidence_intervals(&options)?;
+    reporting::print_seed_distribution(&result.runs);
+
+    println!("\n{}", "=".repeat(60));
+    println!("BOOTSTRAP {:.0}% CONFIDENCE INTERVALS ({} resamples)", options.level * 100.0, options.resamples);
+    println!("{}", "=".repeat(60));
+    println!("{:<16} {:>12} {:>12} {:>12}", "Metric", "Estimate", "Lower", "Upper");
+    let rows = [
+        ("Mean P&L ($)", intervals.mean_pnl, 1.0, 0),
+        ("Mean Sharpe", intervals.mean_sharpe, 1.0, 2),
+        ("Mean max DD ($)", intervals.mean_max_drawdown, 1.0, 0),
+        ("P(loss) (%)", intervals.loss_probability, 100.0, 1),
+    ];
+    for (name, interval, scale, digits) in rows {
+        if let Some(ci) = interval {
+            let (estimate, lower, upper) = (ci.estimate * scale, ci.lower * scale, ci.upper * scale);
+            println!("{:<16} {:>12.*} {:>12.*} {:>12.*}", name, digits, estimate, digits, lower, digits, upper);
+        }
+    }
+    if intervals.mean_pnl.is_some_and(|ci| ci.contains(0.0)) {
+        println!("The mean P&L interval includes zero: the edge is not distinguishable from luck at this level.");
+    }
+    Ok(())
+}
+
+/// `--study`, or the config file's name
+#[cfg(feature = "results-db")]
+fn study_name(cli: &CliArgs) -> String {
+    cli.study.clone().unwrap_or_else(|| {
+        let path = cli.config_path.as_deref().unwrap_or("default");
+        std::path::Path::new(path).file_stem().map_or_else(|| path.to_string(), |s| s.to_string_lossy().into_owned())
+    })
+}
+
+/// Record the run, and the files it wrote, in the results database
+#[cfg(feature = "results-db")]
+fn record_run(db: &str, config: &Config, cli: &CliArgs, output: &SimulationReport) {
+    let artifacts: Vec<String> =
+        [&cli.chart_dir, &cli.parquet_dir, &cli.blotter_path, &cli.save_path].into_iter().flatten().cloned().collect();
+    let result = montecarlo::seed_result(config, config.simulation.seed, output);
+    let recorded = montecarlo::ResultsStore::open(db).and_then(|store| {
+        let study = store.study(&study_name(cli), "run")?;
+        store.record(study, config, &[], &result, &artifacts)
+    });
+    match recorded {
+        Ok(true) => println!("✓ Run recorded in {} (study {})", db, study_name(cli)),
+        Ok(false) => println!("Run already recorded in {}", db),
+        Err(e) => eprintln!("✗ Failed to record run: {}", e),
     }
+}
+
+#[cfg(not(feature = "results-db"))]
+fn record_run(_db: &str, _config: &Config, _cli: &CliArgs, _output: &SimulationReport) {
+    eprintln!("✗ Recording runs requires building with `--features results-db`");
+}
+
+/// Run the seeds as study `--study`, reusing the ones the database already
+/// has and recording the rest as they finish; also returns how many were reused
+#[cfg(feature = "results-db")]
+fn run_recorded_seeds(
+    db: &str,
+    config: &Config,
+    count: u64,
+    cli: &CliArgs,
+    on_progress: impl Fn(montecarlo::SweepProgress) + Sync,
+) -> Result<(montecarlo::MonteCarloResult, usize), SimError> {
+    let mut store = montecarlo::ResultsStore::open(db)?;
+    store.run_monte_carlo(&study_name(cli), config, config.simulation.seed, count, on_progress)
+}
+
+#[cfg(not(feature = "results-db"))]
+fn run_recorded_seeds(
+    _db: &str,
+    _config: &Config,
+    _count: u64,
+    _cli: &CliArgs,
+    _on_progress: impl Fn(montecarlo::SweepProgress) + Sync,
+) -> Result<(montecarlo::MonteCarloResult, usize), SimError> {
+    Err(SimError::Execution("Recording runs requires building with `--features results-db`".to_string()))
+}
 
-    PositionTracking {
-        position_id,
-        entry_timestamp: Timestamp::new(entry_day, entry_time),
-        expiration_day,
-        entry_price: current_price,
-        put_strike,
-        call_strike,
-        put_entry_premium: put_premium,
-        call_entry_premium: call_premium,
-        put_greeks,
-        call_greeks,
+/// Export the per-bar marks and trade table as Parquet files into `dir`
+#[cfg(feature = "parquet-export")]
+fn write_parquet(dir: &str, series: &[EquityPoint], trades: &[TradeRecord]) {
+    let dir = std::path::Path::new(dir);
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        eprintln!("✗ Failed to create Parquet directory: {}", e);
+        return;
+    }
+    let equity_path = dir.join("equity.parquet");
+    let trades_path = dir.join("trades.parquet");
+    let result = reporting::parquet_export::write_equity_parquet(&equity_path, series)
+        .and_then(|_| reporting::parquet_export::write_trades_parquet(&trades_path, trades));
+    match result {
+        Ok(()) => println!("\nParquet written to {} and {}", equity_path.display(), trades_path.display()),
+        Err(e) => eprintln!("✗ Failed to write Parquet: {}", e),
+    }
+}
+
+#[cfg(not(feature = "parquet-export"))]
+fn write_parquet(_dir: &str, _series: &[EquityPoint], _trades: &[TradeRecord]) {
+    eprintln!("✗ Parquet output requires building with `--features parquet-export`");
+}
+
+/// Render price and equity charts into `dir`
+#[cfg(feature = "charts")]
+fn write_charts(
+    dir: &str,
+    format: &str,
+    series: &[EquityPoint],
+    trades: &[TradeRecord],
+    contract_multiplier: f64,
+) {
+    let dir = std::path::Path::new(dir);
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        eprintln!("✗ Failed to create chart directory: {}", e);
+        return;
+    }
+    let price_path = dir.join(format!("price.{}", format));
+    let equity_path = dir.join(format!("equity.{}", format));
+    let result = reporting::charts::render_price_chart(&price_path, series, trades)
+        .and_then(|_| reporting::charts::render_equity_chart(&equity_path, series, contract_multiplier));
+    match result {
+        Ok(()) => println!("\nCharts written to {} and {}", price_path.display(), equity_path.display()),
+        Err(e) => eprintln!("✗ Failed to render charts: {}", e),
     }
 }
 
-/// Print Greeks for a position
-fn print_greeks(pos: &PositionTracking) {
-    let total_delta = pos.put_greeks.delta + pos.call_greeks.delta;
-    let total_gamma = pos.put_greeks.gamma + pos.call_greeks.gamma;
-    let total_theta = pos.put_greeks.theta + pos.call_greeks.theta;
-    let total_vega = pos.put_greeks.vega + pos.call_greeks.vega;
-
-    println!(
-        "      Greeks: δ={:.3} γ={:.4} θ={:.3}/day ν={:.3}",
-        total_delta, total_gamma, total_theta, total_vega
-    );
+#[cfg(not(feature = "charts"))]
+fn write_charts(
+    _dir: &str,
+    _format: &str,
+    _series: &[EquityPoint],
+    _trades: &[TradeRecord],
+    _contract_multiplier: f64,
+) {
+    eprintln!("✗ Chart output requires building with `--features charts`");
 }
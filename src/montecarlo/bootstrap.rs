@@ -0,0 +1,149 @@
+//! Bootstrap Confidence Intervals
+//!
+//! Percentile bootstrap over per-seed results: resample the seeds with
+//! replacement, recompute the statistic on each resample, and take the middle
+//! `level` of the resampled values as the interval. Resampling draws from its
+//! own seeded RNG, so intervals are as reproducible as the runs themselves.
+
+use super::MonteCarloResult;
+use crate::config::ConfigError;
+use crate::error::SimError;
+use crate::reporting::percentile;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+/// How intervals are computed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BootstrapOptions {
+    /// Resamples drawn per statistic
+    pub resamples: usize,
+    /// Confidence level in (0, 1), e.g. 0.95
+    pub level: f64,
+    /// Seed of the resampling RNG
+    pub seed: u64,
+}
+
+impl Default for BootstrapOptions {
+    fn default() -> Self {
+        Self { resamples: 2000, level: 0.95, seed: 0 }
+    }
+}
+
+impl BootstrapOptions {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.resamples == 0 {
+            return Err(ConfigError::Validation("bootstrap needs at least one resample".to_string()));
+        }
+        if !(self.level > 0.0 && self.level < 1.0) {
+            return Err(ConfigError::Validation(format!("confidence level must be in (0, 1), got {}", self.level)));
+        }
+        Ok(())
+    }
+}
+
+/// A statistic with its bootstrap interval
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ConfidenceInterval {
+    /// The statistic on the full sample
+    pub estimate: f64,
+    pub lower: f64,
+    pub upper: f64,
+    pub level: f64,
+}
+
+impl ConfidenceInterval {
+    pub fn contains(&self, value: f64) -> bool {
+        (self.lower..=self.upper).contains(&value)
+    }
+}
+
+/// Interval of `statistic` over `samples`, or `None` for an empty sample
+pub fn bootstrap<F>(samples: &[f64], statistic: F, options: &BootstrapOptions) -> Option<ConfidenceInterval>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    if samples.is_empty() {
+        return None;
+    }
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut resample = vec![0.0; samples.len()];
+    let mut estimates: Vec<f64> = (0..options.resamples.max(1))
+        .map(|_| {
+            for value in resample.iter_mut() {
+                *value = samples[rng.gen_range(0..samples.len())];
+            }
+            statistic(&resample)
+        })
+        .collect();
+    estimates.sort_by(f64::total_cmp);
+    let tail = (1.0 - options.level) / 2.0;
+    Some(ConfidenceInterval {
+        estimate: statistic(samples),
+        lower: percentile(&estimates, tail),
+        upper: percentile(&estimates, 1.0 - tail),
+        level: options.level,
+    })
+}
+
+/// Bootstrap intervals of a Monte Carlo study's headline numbers (dollars)
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StudyIntervals {
+    pub seeds: usize,
+    pub mean_pnl: Option<ConfidenceInterval>,
+    /// Mean of the per-seed Sharpe ratios (seeds without one are left out)
+    pub mean_sharpe: Option<ConfidenceInterval>,
+    pub mean_max_drawdown: Option<ConfidenceInterval>,
+    /// Fraction of seeds that lost money
+    pub loss_probability: Option<ConfidenceInterval>,
+}
+
+impl MonteCarloResult {
+    /// Bootstrap intervals of mean P&L, Sharpe, max drawdown and probability of loss
+    pub fn confidence_intervals(&self, options: &BootstrapOptions) -> Result<StudyIntervals, SimError> {
+        options.validate()?;
+        let pnl: Vec<f64> = self.runs.iter().map(|r| r.pnl).collect();
+        let drawdown: Vec<f64> = self.runs.iter().map(|r| r.max_drawdown).collect();
+        let sharpe: Vec<f64> = self.runs.iter().filter_map(|r| r.sharpe).collect();
+        let loss_share = |sample: &[f64]| sample.iter().filter(|&&p| p < 0.0).count() as f64 / sample.len() as f64;
+        Ok(StudyIntervals {
+            seeds: self.runs.len(),
+            mean_pnl: bootstrap(&pnl, mean, options),
+            mean_sharpe: bootstrap(&sharpe, mean, options),
+            mean_max_drawdown: bootstrap(&drawdown, mean, options),
+            loss_probability: bootstrap(&pnl, loss_share, options),
+        })
+    }
+}
+
+fn mean(sample: &[f64]) -> f64 {
+    sample.iter().sum::<f64>() / sample.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intervals_bracket_the_estimate_and_narrow_with_more_seeds() {
+        let options = BootstrapOptions { resamples: 500, ..BootstrapOptions::default() };
+        let samples: Vec<f64> = (0..200).map(|i| ((i * 37) % 101) as f64 - 40.0).collect();
+        let wide = bootstrap(&samples[..20], mean, &options).unwrap();
+        let narrow = bootstrap(&samples, mean, &options).unwrap();
+        assert!(wide.contains(wide.estimate) && narrow.contains(narrow.estimate));
+        assert!(narrow.upper - narrow.lower < wide.upper - wide.lower);
+        assert_eq!(bootstrap(&samples, mean, &options), Some(narrow));
+        assert!(bootstrap(&[], mean, &options).is_none());
+
+        let mut config = crate::config::Config::default_1dte_straddle();
+        config.simulation.days = 10;
+        let study = super::super::run(&config, 1, 12).unwrap();
+        let intervals = study.confidence_intervals(&options).unwrap();
+        assert_eq!(intervals.seeds, 12);
+        assert_eq!(intervals.loss_probability.unwrap().estimate, study.loss_probability());
+        assert!(intervals.mean_pnl.unwrap().contains(study.pnl.as_ref().unwrap().mean));
+        assert!(study.confidence_intervals(&BootstrapOptions { level: 1.0, ..options }).is_err());
+    }
+}
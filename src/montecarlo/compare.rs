@@ -0,0 +1,252 @@
+//! Strategy Comparison
+//!
+//! Runs several configs on identical markets: for each seed one price path
+//! is generated from the first config's `simulation` section and every
+//! config runs on it (their own market fields are not used for the path), so
+//! differences come from the strategies alone. Results line up per seed and
+//! per day, which gives head-to-head numbers a set of separate runs cannot:
+//! how often one strategy beats another on the same path, whose drawdown is
+//! shallower, and how correlated their daily P&L is.
+
+use super::{seed_result, SweepProgress};
+use crate::config::{Config, ConfigError};
+use crate::engine::{PricePath, Simulator};
+use crate::error::SimError;
+use crate::reporting::{self, Distribution, SeedResult};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// One strategy's runs, in seed order
+#[derive(Debug, Clone)]
+pub struct StrategyRuns {
+    pub name: String,
+    pub runs: Vec<SeedResult>,
+    /// Day-over-day change of end-of-day equity per seed, keyed by day (dollars)
+    pub daily_pnl: Vec<BTreeMap<u32, f64>>,
+}
+
+/// Cross-seed statistics of one strategy (dollars)
+#[derive(Debug, Clone)]
+pub struct StrategySummary {
+    pub name: String,
+    pub pnl: Distribution,
+    pub max_drawdown: Distribution,
+    /// Mean Sharpe ratio of the seeds that have one
+    pub mean_sharpe: Option<f64>,
+    /// Fraction of seeds with positive P&L
+    pub win_rate: f64,
+}
+
+/// Head-to-head numbers of strategy `a` against strategy `b`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PairComparison {
+    pub a: usize,
+    pub b: usize,
+    /// Correlation of daily P&L over every seed's days
+    pub daily_pnl_correlation: Option<f64>,
+    /// Mean of `a`'s P&L less `b`'s, per seed (dollars)
+    pub mean_pnl_difference: f64,
+    /// Fraction of seeds where `a` made more than `b`
+    pub pnl_wins: f64,
+    /// Fraction of seeds where `a`'s max drawdown was smaller than `b`'s
+    pub shallower_drawdown: f64,
+}
+
+/// One strategy's result and daily P&L on one seed
+type StrategySeed = (SeedResult, BTreeMap<u32, f64>);
+
+/// Every strategy's runs on the shared seeds
+#[derive(Debug, Clone)]
+pub struct ComparisonResult {
+    pub seeds: Vec<u64>,
+    pub strategies: Vec<StrategyRuns>,
+}
+
+/// Run each named config on the paths of seeds `first_seed..first_seed + count`
+pub fn compare(configs: &[(String, Config)], first_seed: u64, count: u64) -> Result<ComparisonResult, SimError> {
+    compare_with_progress(configs, first_seed, count, |_| {})
+}
+
+/// `compare`, calling `on_progress` as each simulation finishes
+pub fn compare_with_progress<F>(
+    configs: &[(String, Config)],
+    first_seed: u64,
+    count: u64,
+    on_progress: F,
+) -> Result<ComparisonResult, SimError>
+where
+    F: Fn(SweepProgress) + Sync,
+{
+    let Some((_, market)) = configs.first() else {
+        return Err(ConfigError::Validation("nothing to compare".to_string()).into());
+    };
+    for (_, config) in configs {
+        config.validate()?;
+    }
+    let seeds: Vec<u64> = (first_seed..first_seed + count).collect();
+    let started = Instant::now();
+    let completed = AtomicUsize::new(0);
+    let total = seeds.len() * configs.len();
+
+    // One path per seed, shared by every config
+    let run_seed = |&seed: &u64| -> Result<Vec<StrategySeed>, SimError> {
+        let mut path_config = market.clone();
+        path_config.simulation.seed = seed;
+        let path = PricePath::generate(&path_config)?;
+        configs
+            .iter()
+            .map(|(_, config)| {
+                let mut seed_config = config.clone();
+                seed_config.simulation.seed = seed;
+                let report = Simulator::with_path(seed_config, path.clone())?.finish()?;
+                on_progress(SweepProgress {
+                    completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                    total,
+                    elapsed: started.elapsed(),
+                });
+                let multiplier = config.simulation.contract_multiplier;
+                Ok((seed_result(config, seed, &report), daily_pnl(&report.equity_series, multiplier)))
+            })
+            .collect()
+    };
+
+    #[cfg(feature = "parallel")]
+    let per_seed = seeds.par_iter().map(run_seed).collect::<Result<Vec<_>, _>>()?;
+    #[cfg(not(feature = "parallel"))]
+    let per_seed = seeds.iter().map(run_seed).collect::<Result<Vec<_>, _>>()?;
+
+    let mut strategies: Vec<StrategyRuns> = configs
+        .iter()
+        .map(|(name, _)| StrategyRuns { name: name.clone(), runs: Vec::new(), daily_pnl: Vec::new() })
+        .collect();
+    for results in per_seed {
+        for (strategy, (result, daily)) in strategies.iter_mut().zip(results) {
+            strategy.runs.push(result);
+            strategy.daily_pnl.push(daily);
+        }
+    }
+    Ok(ComparisonResult { seeds, strategies })
+}
+
+/// Change of end-of-day equity by day, the first day against the start
+fn daily_pnl(series: &[reporting::EquityPoint], multiplier: f64) -> BTreeMap<u32, f64> {
+    let mut previous = 0.0;
+    reporting::daily_closes(series)
+        .iter()
+        .map(|close| {
+            let change = close.equity() - std::mem::replace(&mut previous, close.equity());
+            (close.timestamp.day, change * multiplier)
+        })
+        .collect()
+}
+
+impl ComparisonResult {
+    /// Per-strategy distributions, in input order
+    pub fn summaries(&self) -> Vec<StrategySummary> {
+        self.strategies
+            .iter()
+            .filter_map(|strategy| {
+                let pnl: Vec<f64> = strategy.runs.iter().map(|r| r.pnl).collect();
+                let drawdown: Vec<f64> = strategy.runs.iter().map(|r| r.max_drawdown).collect();
+                let sharpes: Vec<f64> = strategy.runs.iter().filter_map(|r| r.sharpe).collect();
+                Some(StrategySummary {
+                    name: strategy.name.clone(),
+                    pnl: Distribution::from_samples(&pnl)?,
+                    max_drawdown: Distribution::from_samples(&drawdown)?,
+                    mean_sharpe: (!sharpes.is_empty()).then(|| sharpes.iter().sum::<f64>() / sharpes.len() as f64),
+                    win_rate: pnl.iter().filter(|&&p| p > 0.0).count() as f64 / pnl.len() as f64,
+                })
+            })
+            .collect()
+    }
+
+    /// Every pair of strategies, each once (`a` before `b` in input order)
+    pub fn pairs(&self) -> Vec<PairComparison> {
+        let n = self.strategies.len();
+        let seeds = self.seeds.len().max(1) as f64;
+        let mut pairs = Vec::new();
+        for a in 0..n {
+            for b in a + 1..n {
+                let (runs_a, runs_b) = (&self.strategies[a].runs, &self.strategies[b].runs);
+                let both = || runs_a.iter().zip(runs_b);
+                // Daily P&L of the days both have, over every seed
+                let (mut xs, mut ys) = (Vec::new(), Vec::new());
+                for (days_a, days_b) in self.strategies[a].daily_pnl.iter().zip(&self.strategies[b].daily_pnl) {
+                    for (day, &x) in days_a {
+                        if let Some(&y) = days_b.get(day) {
+                            xs.push(x);
+                            ys.push(y);
+                        }
+                    }
+                }
+                pairs.push(PairComparison {
+                    a,
+                    b,
+                    daily_pnl_correlation: reporting::correlation(&xs, &ys),
+                    mean_pnl_difference: both().map(|(x, y)| x.pnl - y.pnl).sum::<f64>() / seeds,
+                    pnl_wins: both().filter(|(x, y)| x.pnl > y.pnl).count() as f64 / seeds,
+                    shallower_drawdown: both().filter(|(x, y)| x.max_drawdown < y.max_drawdown).count() as f64 / seeds,
+                });
+            }
+        }
+        pairs
+    }
+
+    /// One row per seed with every strategy's P&L, max drawdown and Sharpe side by side
+    pub fn table_csv(&self) -> String {
+        let mut csv = "seed".to_string();
+        for strategy in &self.strategies {
+            let _ = write!(csv, ",{0}_pnl,{0}_max_drawdown,{0}_sharpe", strategy.name);
+        }
+        csv.push('\n');
+        for (i, seed) in self.seeds.iter().enumerate() {
+            let _ = write!(csv, "{}", seed);
+            for strategy in &self.strategies {
+                let run = &strategy.runs[i];
+                let sharpe = run.sharpe.map_or_else(String::new, |s| s.to_string());
+                let _ = write!(csv, ",{},{},{}", run.pnl, run.max_drawdown, sharpe);
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategies_share_paths_and_line_up() {
+        let mut short = Config::default_1dte_straddle();
+        short.simulation.days = 10;
+        let mut long = short.clone();
+        long.strategy.side = "long".to_string();
+        // Ignored for the path: both trade the first config's paths
+        long.simulation.drift = 0.5;
+        let configs = vec![("short".to_string(), short.clone()), ("long".to_string(), long.clone())];
+        let result = compare(&configs, 5, 4).unwrap();
+        assert_eq!(result.seeds, vec![5, 6, 7, 8]);
+        assert_eq!(result.strategies[0].runs[1].pnl, super::super::run_seed(&short, 6).unwrap().pnl);
+
+        // Opposite sides of the same straddle on the same path mirror each other
+        let pair = result.pairs()[0];
+        assert!(pair.daily_pnl_correlation.unwrap() < -0.99);
+        let (a, b) = (&result.strategies[0].runs, &result.strategies[1].runs);
+        assert!(a.iter().zip(b).all(|(x, y)| (x.pnl + y.pnl).abs() < 1e-6));
+        assert_eq!(pair.pnl_wins, a.iter().zip(b).filter(|(x, y)| x.pnl > y.pnl).count() as f64 / 4.0);
+        let days = &result.strategies[0].daily_pnl[0];
+        assert!((days.values().sum::<f64>() - a[0].pnl).abs() < 1e-6);
+
+        let csv = result.table_csv();
+        assert!(csv.starts_with("seed,short_pnl,short_max_drawdown,short_sharpe,long_pnl,"));
+        assert_eq!(csv.lines().count(), 5);
+        assert_eq!(result.summaries().len(), 2);
+        assert!(compare(&[], 1, 1).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Out-of-Sample Holdout
+//!
+//! Parameter selection on historical bars with the later data held out: the
+//! sweep runs only on the bars of the `train` split, the best point by the
+//! sweep's objective is picked from those runs, and only then is that point
+//! run on each split (train, validation, test) for the final numbers. Nothing
+//! outside the training range can influence which parameters are chosen.
+
+use super::{run_seed_on_path, PointSummary, Sweep, SweepProgress, SweepResult};
+use crate::data::{DataSplits, DateRange, DayMapping, Split};
+use crate::engine::PricePath;
+use crate::error::SimError;
+use crate::reporting::SeedResult;
+use std::fmt::Write as _;
+
+/// The chosen point's run on one split
+#[derive(Debug, Clone)]
+pub struct SplitResult {
+    pub split: Split,
+    pub range: DateRange,
+    /// Bars in the split
+    pub bars: usize,
+    pub result: SeedResult,
+}
+
+/// A sweep on the training split and the winner's out-of-sample runs
+#[derive(Debug, Clone)]
+pub struct HoldoutResult {
+    /// Every point, run on the training bars only
+    pub training: SweepResult,
+    /// The best training point (`None` when no point has a score)
+    pub best: Option<PointSummary>,
+    /// The best point on every split, train first (empty without a best point)
+    pub splits: Vec<SplitResult>,
+}
+
+/// Select parameters on the train split of `path` (real dates by `mapping`)
+/// and report the selection on every split
+pub fn holdout(
+    sweep: &Sweep,
+    path: &PricePath,
+    mapping: DayMapping,
+    splits: &DataSplits,
+) -> Result<HoldoutResult, SimError> {
+    holdout_with_progress(sweep, path, mapping, splits, |_| {})
+}
+
+/// `holdout`, calling `on_progress` as each training run finishes
+pub fn holdout_with_progress<F>(
+    sweep: &Sweep,
+    path: &PricePath,
+    mapping: DayMapping,
+    splits: &DataSplits,
+    on_progress: F,
+) -> Result<HoldoutResult, SimError>
+where
+    F: Fn(SweepProgress) + Sync,
+{
+    let training = sweep.clone().on_path(splits.train.slice(path, mapping)?).run_with_progress(on_progress)?;
+    let best = training.ranked().into_iter().next().filter(|summary| summary.score.is_some());
+
+    let mut results = Vec::new();
+    if let Some(best) = &best {
+        let config = sweep.point_config(&best.values)?;
+        for (split, range) in splits.ranges() {
+            let bars = range.slice(path, mapping)?;
+            let count = bars.len();
+            let result = run_seed_on_path(&config, config.simulation.seed, bars)?;
+            results.push(SplitResult { split, range, bars: count, result });
+        }
+    }
+    Ok(HoldoutResult { training, best, splits: results })
+}
+
+impl HoldoutResult {
+    /// One row per split for the chosen point (dollars)
+    pub fn splits_csv(&self) -> String {
+        let mut csv = String::from("split,start,end,bars,pnl,max_drawdown,sharpe\n");
+        for split in &self.splits {
+            let result = &split.result;
+            let sharpe = result.sharpe.map_or_else(String::new, |s| s.to_string());
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{}",
+                split.split, split.range.start, split.range.end, split.bars, result.pnl, result.max_drawdown, sharpe
+            );
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::data::Date;
+
+    #[test]
+    fn test_selection_sees_only_the_training_bars() {
+        let yaml = "simulation: {days: 21, initial_price: 75.0, volatility: 0.3, seed: 5}\n\
+                    strategy: {strategy_type: straddle, entry_dte: 1}\n\
+                    splits:\n\
+                    \x20 train: {start: 2024-03-04, end: 2024-03-10}\n\
+                    \x20 validation: {start: 2024-03-11, end: 2024-03-17}\n\
+                    \x20 test: {start: 2024-03-18, end: 2024-03-24}\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        let splits = DataSplits::from_config(config.splits.as_ref().unwrap()).unwrap();
+        let mapping = DayMapping::starting_week_of(Date::new(2024, 3, 4).unwrap());
+        let path = PricePath::generate(&config).unwrap();
+        let sweep = Sweep::new(&config).parameter("simulation.volatility_risk_premium", vec![0.0, 0.1]);
+
+        let result = holdout(&sweep, &path, mapping, &splits).unwrap();
+        assert_eq!(result.training.rows.len(), 2);
+        assert_eq!(result.best.as_ref().unwrap().values, [0.1]);
+        assert_eq!(result.splits.len(), 3);
+        assert_eq!(result.splits[0].result.pnl, result.training.rows[1].result.pnl);
+
+        // Changing the held-out bars cannot change the selection or its training numbers
+        let mut bars = path.bars().to_vec();
+        bars.iter_mut().filter(|bar| bar.timestamp.day >= 7).for_each(|bar| bar.price *= 1.5);
+        let changed = holdout(&sweep, &PricePath::from_bars(bars).unwrap(), mapping, &splits).unwrap();
+        assert_eq!(changed.best.unwrap().values, [0.1]);
+        assert_eq!(changed.splits[0].result.pnl, result.splits[0].result.pnl);
+        assert_eq!(result.splits_csv().lines().count(), 4);
+    }
+}
@@ -0,0 +1,215 @@
+//! Monte Carlo Runner
+//!
+//! Runs one config over many consecutive seeds and aggregates terminal P&L
+//! and drawdown distributions. Seeds run in parallel with rayon (the
+//! `parallel` feature); each seed is an independent, reproducible run and
+//! results are ordered by seed, so the thread count never changes the output.
+//! `run_with_progress` reports each finished seed with throughput and an ETA.
+//! `Sweep` runs a grid of parameter values the same way, ranked by an
+//! `Objective`; `Optimizer` searches one within a run budget, `holdout`
+//! selects parameters on a historical training split, `compare` runs
+//! several configs on shared paths, and
+//! `MonteCarloResult::confidence_intervals` bootstraps the headline numbers.
+
+use crate::config::Config;
+use crate::engine::{self, PricePath, SimulationEngine, SimulationReport, Simulator};
+use crate::error::SimError;
+use crate::reporting::{self, Distribution, SeedResult};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+mod bootstrap;
+mod compare;
+mod holdout;
+mod objective;
+mod optimize;
+#[cfg(feature = "results-db")]
+mod store;
+mod sweep;
+
+pub use bootstrap::{bootstrap, BootstrapOptions, ConfidenceInterval, StudyIntervals};
+pub use compare::{compare, compare_with_progress, ComparisonResult, PairComparison, StrategyRuns, StrategySummary};
+pub use holdout::{holdout, holdout_with_progress, HoldoutResult, SplitResult};
+pub use objective::{Aggregate, Metric, Objective};
+pub use optimize::{Candidate, OptimizeResult, Optimizer};
+#[cfg(feature = "results-db")]
+pub use store::{ResultsStore, StoredRun, StudyInfo};
+pub use sweep::{Parameter, PointSummary, Sweep, SweepResult, SweepRow};
+
+/// Per-seed results and their distributions (dollars)
+#[derive(Debug, Clone)]
+pub struct MonteCarloResult {
+    /// One entry per seed, in seed order
+    pub runs: Vec<SeedResult>,
+    pub pnl: Option<Distribution>,
+    pub max_drawdown: Option<Distribution>,
+}
+
+impl MonteCarloResult {
+    /// Distributions of `runs` (kept in the given order)
+    pub fn from_runs(runs: Vec<SeedResult>) -> Self {
+        let pnl: Vec<f64> = runs.iter().map(|r| r.pnl).collect();
+        let drawdown: Vec<f64> = runs.iter().map(|r| r.max_drawdown).collect();
+        Self { pnl: Distribution::from_samples(&pnl), max_drawdown: Distribution::from_samples(&drawdown), runs }
+    }
+
+    /// Fraction of seeds that lost money
+    pub fn loss_probability(&self) -> f64 {
+        if self.runs.is_empty() {
+            return 0.0;
+        }
+        self.runs.iter().filter(|r| r.pnl < 0.0).count() as f64 / self.runs.len() as f64
+    }
+}
+
+/// How far a sweep over seeds has got
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepProgress {
+    pub completed: usize,
+    pub total: usize,
+    /// Wall-clock time since the sweep started
+    pub elapsed: Duration,
+}
+
+impl SweepProgress {
+    /// Completed fraction in [0, 1]
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.completed as f64 / self.total as f64
+    }
+
+    /// Runs finished per second so far
+    pub fn runs_per_sec(&self) -> f64 {
+        engine::per_second(self.completed, self.elapsed)
+    }
+
+    /// Estimated time to finish at the current rate
+    pub fn eta(&self) -> Option<Duration> {
+        engine::remaining(self.completed, self.total, self.elapsed)
+    }
+}
+
+/// Run `config` with seeds `first_seed..first_seed + count`
+pub fn run(config: &Config, first_seed: u64, count: u64) -> Result<MonteCarloResult, SimError> {
+    run_with_progress(config, first_seed, count, |_| {})
+}
+
+/// `run`, calling `on_progress` as each seed finishes (from worker threads,
+/// in completion order)
+pub fn run_with_progress<F>(config: &Config, first_seed: u64, count: u64, on_progress: F) -> Result<MonteCarloResult, SimError>
+where
+    F: Fn(SweepProgress) + Sync,
+{
+    let seeds: Vec<u64> = (first_seed..first_seed + count).collect();
+    Ok(MonteCarloResult::from_runs(run_seeds(config, &seeds, on_progress)?))
+}
+
+/// Run `config` on each of `seeds`; results in the same order
+pub(crate) fn run_seeds<F>(config: &Config, seeds: &[u64], on_progress: F) -> Result<Vec<SeedResult>, SimError>
+where
+    F: Fn(SweepProgress) + Sync,
+{
+    let started = Instant::now();
+    let completed = AtomicUsize::new(0);
+    let run_one = |&seed: &u64| {
+        let result = run_seed(config, seed);
+        on_progress(SweepProgress {
+            completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+            total: seeds.len(),
+            elapsed: started.elapsed(),
+        });
+        result
+    };
+
+    #[cfg(feature = "parallel")]
+    let runs = seeds.par_iter().map(run_one).collect();
+    #[cfg(not(feature = "parallel"))]
+    let runs = seeds.iter().map(run_one).collect();
+    runs
+}
+
+/// A single seed; identical to running the config with `simulation.seed = seed`
+pub fn run_seed(config: &Config, seed: u64) -> Result<SeedResult, SimError> {
+    let mut seed_config = config.clone();
+    seed_config.simulation.seed = seed;
+    Ok(seed_result(config, seed, &SimulationEngine::run(&seed_config)?))
+}
+
+/// `run_seed` over a path already generated for that seed
+pub(crate) fn run_seed_on_path(config: &Config, seed: u64, path: PricePath) -> Result<SeedResult, SimError> {
+    let mut seed_config = config.clone();
+    seed_config.simulation.seed = seed;
+    Ok(seed_result(config, seed, &Simulator::with_path(seed_config, path)?.finish()?))
+}
+
+/// Headline metrics of a finished run of `config` (dollars)
+pub fn seed_result(config: &Config, seed: u64, output: &SimulationReport) -> SeedResult {
+    let multiplier = config.simulation.contract_multiplier;
+    SeedResult {
+        seed,
+        pnl: output.net_pnl() * multiplier,
+        max_drawdown: reporting::max_drawdown(&output.equity_series) * multiplier,
+        sharpe: reporting::sharpe_ratio(&output.equity_series),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn short_config() -> Config {
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.days = 20;
+        config
+    }
+
+    #[test]
+    fn test_runs_are_reproducible_per_seed() {
+        let config = short_config();
+        let result = run(&config, 10, 8).unwrap();
+        assert_eq!(result.runs.len(), 8);
+        for (i, r) in result.runs.iter().enumerate() {
+            assert_eq!(r.seed, 10 + i as u64);
+            assert_eq!(r.pnl, run_seed(&config, r.seed).unwrap().pnl);
+        }
+
+        let mut single = config.clone();
+        single.simulation.seed = 12;
+        let report = SimulationEngine::run(&single).unwrap();
+        assert_eq!(result.runs[2].pnl, report.net_pnl() * config.simulation.contract_multiplier);
+    }
+
+    #[test]
+    fn test_distributions() {
+        let result = run(&short_config(), 1, 5).unwrap();
+        let pnl = result.pnl.as_ref().unwrap();
+        assert_eq!(pnl.count, 5);
+        assert!(pnl.min <= pnl.median && pnl.median <= pnl.max);
+        assert!(result.max_drawdown.as_ref().unwrap().min >= 0.0);
+        assert!((0.0..=1.0).contains(&result.loss_probability()));
+    }
+
+    #[test]
+    fn test_progress_counts_every_seed() {
+        let seen = std::sync::Mutex::new(Vec::new());
+        run_with_progress(&short_config(), 1, 4, |p| seen.lock().unwrap().push(p)).unwrap();
+        let mut seen = seen.into_inner().unwrap();
+        seen.sort_by_key(|p| p.completed);
+        assert_eq!(seen.iter().map(|p| p.completed).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert!(seen.iter().all(|p| p.total == 4));
+        assert_eq!(seen[3].fraction(), 1.0);
+        assert_eq!(seen[3].eta(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_zero_seeds() {
+        let result = run(&short_config(), 1, 0).unwrap();
+        assert!(result.runs.is_empty());
+        assert!(result.pnl.is_none());
+    }
+}
@@ -0,0 +1,220 @@
+//! Study Objectives
+//!
+//! What a sweep or optimizer ranks parameter points by, from each point's
+//! runs: a metric to maximize (mean or median P&L, Sharpe, Sharpe less a
+//! drawdown penalty) or CVaR to minimize, optionally subject to a cap on the
+//! worst seed's max drawdown. Scores are oriented so higher is always better;
+//! points that break the cap score negative infinity and rank last.
+//!
+//! By default the metric pools every seed's run. For robustness it can be
+//! scored on each seed alone and aggregated by the median or the worst seed,
+//! so a point that only shines on a few lucky paths ranks below one that
+//! holds up on all of them.
+
+use crate::config::{Config, ConfigError, ObjectiveConfig};
+use crate::reporting::{Distribution, SeedResult};
+use std::fmt;
+
+/// The number an objective optimizes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    MeanPnl,
+    MedianPnl,
+    /// Mean of the per-seed Sharpe ratios
+    Sharpe,
+    /// Mean Sharpe less `penalty` per dollar of mean max drawdown
+    SharpeDrawdown { penalty: f64 },
+    /// Mean loss over the worst `level` fraction of seeds (minimized)
+    Cvar { level: f64 },
+}
+
+/// How the runs of several seeds combine into one score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregate {
+    /// The metric over all runs together
+    #[default]
+    Pooled,
+    /// Median of the per-seed scores
+    Median,
+    /// Lowest per-seed score
+    Worst,
+}
+
+impl Aggregate {
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "pooled" => Some(Self::Pooled),
+            "median" => Some(Self::Median),
+            "worst" => Some(Self::Worst),
+            _ => None,
+        }
+    }
+}
+
+/// A metric and the constraint points must meet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Objective {
+    pub metric: Metric,
+    /// Largest max drawdown any seed may have (dollars)
+    pub max_drawdown: Option<f64>,
+    pub aggregate: Aggregate,
+}
+
+impl Default for Objective {
+    /// Maximize mean P&L, unconstrained
+    fn default() -> Self {
+        Self::new(Metric::MeanPnl)
+    }
+}
+
+impl Objective {
+    pub fn new(metric: Metric) -> Self {
+        Self { metric, max_drawdown: None, aggregate: Aggregate::Pooled }
+    }
+
+    /// Parse an `ObjectiveConfig`
+    pub fn from_config(config: &ObjectiveConfig) -> Result<Self, ConfigError> {
+        let metric = match config.metric.as_str() {
+            "mean_pnl" => Metric::MeanPnl,
+            "median_pnl" => Metric::MedianPnl,
+            "sharpe" => Metric::Sharpe,
+            "sharpe_drawdown" => Metric::SharpeDrawdown { penalty: config.drawdown_penalty },
+            "cvar" => Metric::Cvar { level: config.cvar_level },
+            other => return Err(ConfigError::Validation(format!("unknown objective metric {}", other))),
+        };
+        let aggregate = Aggregate::from_config(&config.aggregate)
+            .ok_or_else(|| ConfigError::Validation(format!("unknown objective aggregate {}", config.aggregate)))?;
+        Ok(Self { metric, max_drawdown: config.max_drawdown, aggregate })
+    }
+
+    /// `config.sweep.objective`, or the default
+    pub fn of_study(config: &Config) -> Result<Self, ConfigError> {
+        match config.sweep.as_ref().and_then(|s| s.objective.as_ref()) {
+            Some(objective) => Self::from_config(objective),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Whether every run stays within the drawdown cap
+    pub fn feasible(&self, runs: &[SeedResult]) -> bool {
+        self.max_drawdown.is_none_or(|limit| runs.iter().all(|r| r.max_drawdown <= limit))
+    }
+
+    /// The metric itself (CVaR as a positive loss), or `None` without runs
+    /// or, for the Sharpe metrics, without any Sharpe ratio
+    pub fn value(&self, runs: &[SeedResult]) -> Option<f64> {
+        if runs.is_empty() {
+            return None;
+        }
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+        let mut pnl: Vec<f64> = runs.iter().map(|r| r.pnl).collect();
+        pnl.sort_by(f64::total_cmp);
+        let sharpe: Vec<f64> = runs.iter().filter_map(|r| r.sharpe).collect();
+        let drawdown: Vec<f64> = runs.iter().map(|r| r.max_drawdown).collect();
+        match self.metric {
+            Metric::MeanPnl => Some(mean(&pnl)),
+            Metric::MedianPnl => Distribution::from_samples(&pnl).map(|d| d.median),
+            _ if matches!(self.metric, Metric::Sharpe | Metric::SharpeDrawdown { .. }) && sharpe.is_empty() => None,
+            Metric::Sharpe => Some(mean(&sharpe)),
+            Metric::SharpeDrawdown { penalty } => Some(mean(&sharpe) - penalty * mean(&drawdown)),
+            Metric::Cvar { level } => {
+                let tail = ((pnl.len() as f64 * level).ceil() as usize).clamp(1, pnl.len());
+                Some(-mean(&pnl[..tail]))
+            }
+        }
+    }
+
+    /// Higher is better: the metric (negated for CVaR) combined over seeds
+    /// by `aggregate`, or negative infinity when it is undefined or the runs
+    /// break the drawdown cap
+    pub fn score(&self, runs: &[SeedResult]) -> f64 {
+        let mut scores = self.seed_scores(runs);
+        scores.sort_by(f64::total_cmp);
+        match self.aggregate {
+            Aggregate::Pooled => self.pooled_score(runs),
+            _ if scores.is_empty() => f64::NEG_INFINITY,
+            Aggregate::Median => {
+                let middle = scores.len() / 2;
+                if scores.len().is_multiple_of(2) {
+                    (scores[middle - 1] + scores[middle]) / 2.0
+                } else {
+                    scores[middle]
+                }
+            }
+            Aggregate::Worst => scores[0],
+        }
+    }
+
+    /// The pooled score of each run on its own, in run order
+    pub fn seed_scores(&self, runs: &[SeedResult]) -> Vec<f64> {
+        runs.iter().map(|run| self.pooled_score(std::slice::from_ref(run))).collect()
+    }
+
+    fn pooled_score(&self, runs: &[SeedResult]) -> f64 {
+        match self.value(runs) {
+            Some(value) if self.feasible(runs) => match self.metric {
+                Metric::Cvar { .. } => -value,
+                _ => value,
+            },
+            _ => f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.metric {
+            Metric::MeanPnl => write!(f, "maximize mean P&L")?,
+            Metric::MedianPnl => write!(f, "maximize median P&L")?,
+            Metric::Sharpe => write!(f, "maximize mean Sharpe")?,
+            Metric::SharpeDrawdown { penalty } => write!(f, "maximize mean Sharpe - {} x mean max drawdown", penalty)?,
+            Metric::Cvar { level } => write!(f, "minimize CVaR ({}% tail)", level * 100.0)?,
+        }
+        if let Some(limit) = self.max_drawdown {
+            write!(f, " subject to max drawdown <= ${:.0}", limit)?;
+        }
+        match self.aggregate {
+            Aggregate::Pooled => Ok(()),
+            Aggregate::Median => write!(f, ", median seed"),
+            Aggregate::Worst => write!(f, ", worst seed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runs(pnl: &[f64], drawdown: f64) -> Vec<SeedResult> {
+        pnl.iter()
+            .enumerate()
+            .map(|(seed, &pnl)| SeedResult { seed: seed as u64, pnl, max_drawdown: drawdown, sharpe: None })
+            .collect()
+    }
+
+    #[test]
+    fn test_metrics_and_drawdown_cap() {
+        let steady = runs(&[100.0, 120.0, 80.0, 90.0], 500.0);
+        let risky = runs(&[-900.0, 400.0, 500.0, 600.0], 1500.0);
+        let mean = Objective::default();
+        assert!(mean.score(&steady) < mean.score(&risky));
+        assert_eq!(Objective::new(Metric::MedianPnl).value(&steady), Some(95.0));
+
+        // The worst quarter of four seeds is the single worst one
+        let cvar = Objective::new(Metric::Cvar { level: 0.25 });
+        assert_eq!(cvar.value(&risky), Some(900.0));
+        assert_eq!(cvar.value(&steady), Some(-80.0));
+        assert!(cvar.score(&steady) > cvar.score(&risky));
+
+        let capped = Objective { max_drawdown: Some(1000.0), ..mean };
+        assert!(capped.feasible(&steady) && !capped.feasible(&risky));
+        assert_eq!(capped.score(&risky), f64::NEG_INFINITY);
+        assert_eq!(Objective::new(Metric::Sharpe).score(&steady), f64::NEG_INFINITY);
+
+        // Per seed, the risky point's one bad path decides its worst case
+        let worst = Objective { aggregate: Aggregate::Worst, ..mean };
+        assert_eq!((worst.score(&steady), worst.score(&risky)), (80.0, -900.0));
+        let median = Objective { aggregate: Aggregate::Median, ..mean };
+        assert_eq!(median.score(&risky), 450.0);
+    }
+}
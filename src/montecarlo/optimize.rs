@@ -0,0 +1,252 @@
+//! Strategy Parameter Optimizer
+//!
+//! Random search with successive halving over a sweep grid. Candidates are
+//! sampled from the grid without repeats and all run on the first
+//! `min_seeds` seeds; each round keeps the best one in `eta` by the sweep's
+//! objective and runs the survivors on `eta` times as many seeds (only the new seeds
+//! run, earlier results are kept) until one candidate is left. Weak
+//! parameters are dropped after a few cheap runs, so most of the budget goes
+//! to promising ones and the winner has the most seeds behind it.
+//!
+//! The whole schedule is planned up front to fit the run budget; sampling
+//! draws from its own RNG seeded with `simulation.seed`, so a search is as
+//! reproducible as a sweep.
+
+use super::{Metric, Objective, Sweep, SweepProgress};
+use crate::config::{Config, ConfigError, OptimizerConfig};
+use crate::error::SimError;
+use crate::reporting::SeedResult;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::time::Instant;
+
+/// Searches a sweep grid for the best parameters
+#[derive(Debug, Clone)]
+pub struct Optimizer {
+    sweep: Sweep,
+    settings: OptimizerConfig,
+}
+
+/// One sampled point and everything run on it
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Parameter values, in sweep order
+    pub values: Vec<f64>,
+    /// Runs in seed order
+    pub runs: Vec<SeedResult>,
+    /// Objective over `runs`
+    pub score: f64,
+    /// Rounds the candidate was run in (1 = dropped after the first)
+    pub rounds: usize,
+}
+
+/// Every candidate of a search, best first
+#[derive(Debug, Clone)]
+pub struct OptimizeResult {
+    /// Swept config paths (column names for `Candidate::values`)
+    pub parameters: Vec<String>,
+    /// Ordered by rounds survived, then score
+    pub candidates: Vec<Candidate>,
+    /// Simulations run
+    pub runs: usize,
+}
+
+/// Candidates and cumulative seeds of each round
+fn schedule(candidates: usize, min_seeds: u64, eta: usize) -> Vec<(usize, u64)> {
+    let mut rounds = vec![(candidates, min_seeds)];
+    while let Some(&(count, seeds)) = rounds.last() {
+        if count <= 1 || count / eta <= 1 {
+            break;
+        }
+        rounds.push((count / eta, seeds.saturating_mul(eta as u64)));
+    }
+    rounds
+}
+
+/// Simulations a schedule runs
+fn schedule_cost(rounds: &[(usize, u64)]) -> usize {
+    rounds
+        .iter()
+        .scan(0, |previous, &(count, seeds)| {
+            let new = seeds - std::mem::replace(previous, seeds);
+            Some(count.saturating_mul(new as usize))
+        })
+        .fold(0, usize::saturating_add)
+}
+
+impl Optimizer {
+    /// Search the grid of `sweep` with `settings`, ranking by the sweep's objective
+    pub fn new(sweep: Sweep, settings: &OptimizerConfig) -> Self {
+        Self { sweep, settings: settings.clone() }
+    }
+
+    /// The grid of `config.sweep` and its `optimizer` settings (or the defaults)
+    pub fn from_config(config: &Config) -> Result<Self, SimError> {
+        let settings = config.sweep.as_ref().and_then(|s| s.optimizer.clone()).unwrap_or_default();
+        Ok(Self::new(Sweep::from_config(config)?, &settings))
+    }
+
+    pub fn objective(&self) -> Objective {
+        self.sweep.objective()
+    }
+
+    /// The rounds the search will run: candidates and cumulative seeds of each
+    pub fn plan(&self) -> Result<Vec<(usize, u64)>, SimError> {
+        let grid = self.sweep.parameters().iter().fold(1usize, |n, p| n.saturating_mul(p.values.len()));
+        let (min_seeds, eta, budget) = (self.settings.min_seeds, self.settings.eta.max(2), self.settings.budget);
+        let candidates = match self.settings.candidates {
+            Some(candidates) => candidates.min(grid),
+            // Largest first round whose whole schedule fits the budget
+            None => (1..=grid.min(budget))
+                .rev()
+                .find(|&n| schedule_cost(&schedule(n, min_seeds, eta)) <= budget)
+                .unwrap_or(1),
+        };
+        let rounds = schedule(candidates, min_seeds, eta);
+        let cost = schedule_cost(&rounds);
+        if cost > budget {
+            let reason = format!("the search needs {} runs, over the budget of {}", cost, budget);
+            return Err(ConfigError::Validation(reason).into());
+        }
+        Ok(rounds)
+    }
+
+    /// Run the search
+    pub fn run(&self) -> Result<OptimizeResult, SimError> {
+        self.run_with_progress(|_| {})
+    }
+
+    /// `run`, calling `on_progress` as each simulation finishes (over the whole plan)
+    pub fn run_with_progress<F>(&self, on_progress: F) -> Result<OptimizeResult, SimError>
+    where
+        F: Fn(SweepProgress) + Sync,
+    {
+        let rounds = self.plan()?;
+        let total = schedule_cost(&rounds);
+        let first_seed = self.sweep.base().simulation.seed;
+        let mut candidates: Vec<Candidate> = self
+            .sample(rounds[0].0)
+            .into_iter()
+            .map(|values| Candidate { values, runs: Vec::new(), score: f64::NEG_INFINITY, rounds: 0 })
+            .collect();
+
+        let started = Instant::now();
+        let mut done = 0;
+        let mut seeds_run = 0;
+        let mut alive: Vec<usize> = (0..candidates.len()).collect();
+        for (round, &(count, seeds)) in rounds.iter().enumerate() {
+            // Keep the best `count` of the previous round
+            alive.sort_by(|&a, &b| candidates[b].score.total_cmp(&candidates[a].score).then(a.cmp(&b)));
+            alive.truncate(count);
+            alive.sort_unstable();
+
+            let points = alive.iter().map(|&i| candidates[i].values.clone()).collect();
+            let seed_range = first_seed + seeds_run..first_seed + seeds;
+            let rows = self.sweep.run_points(points, seed_range, |p| {
+                on_progress(SweepProgress { completed: done + p.completed, total, elapsed: started.elapsed() })
+            })?;
+            done += rows.len();
+            for row in rows {
+                candidates[alive[row.point]].runs.push(row.result);
+            }
+            for &i in &alive {
+                let candidate = &mut candidates[i];
+                candidate.score = self.sweep.objective().score(&candidate.runs);
+                candidate.rounds = round + 1;
+            }
+            seeds_run = seeds;
+        }
+
+        candidates.sort_by(|a, b| b.rounds.cmp(&a.rounds).then(b.score.total_cmp(&a.score)));
+        Ok(OptimizeResult { parameters: self.sweep.parameter_paths(), candidates, runs: done })
+    }
+
+    /// `count` distinct grid points: the whole grid when it is that small,
+    /// otherwise a seeded random sample (in grid order either way)
+    fn sample(&self, count: usize) -> Vec<Vec<f64>> {
+        let parameters = self.sweep.parameters();
+        let grid = parameters.iter().fold(1usize, |n, p| n.saturating_mul(p.values.len()));
+        if count >= grid {
+            return self.sweep.points();
+        }
+        let mut rng = StdRng::seed_from_u64(self.sweep.base().simulation.seed);
+        let mut picked = HashSet::new();
+        while picked.len() < count {
+            let indices: Vec<usize> = parameters.iter().map(|p| rng.gen_range(0..p.values.len())).collect();
+            picked.insert(indices);
+        }
+        let mut picked: Vec<Vec<usize>> = picked.into_iter().collect();
+        picked.sort_unstable();
+        picked.iter().map(|indices| indices.iter().zip(parameters).map(|(&i, p)| p.values[i]).collect()).collect()
+    }
+}
+
+impl OptimizeResult {
+    /// The winner: most rounds survived, then best score
+    pub fn best(&self) -> Option<&Candidate> {
+        self.candidates.first()
+    }
+
+    /// One row per candidate, best first (dollars)
+    pub fn csv(&self) -> String {
+        let mut csv =
+            format!("rank,{},rounds,seeds,score,mean_pnl,mean_max_drawdown,mean_sharpe\n", self.parameters.join(","));
+        for (rank, candidate) in self.candidates.iter().enumerate() {
+            let _ = write!(csv, "{},", rank + 1);
+            for value in &candidate.values {
+                let _ = write!(csv, "{},", value);
+            }
+            let runs = &candidate.runs;
+            let sharpe = Objective::new(Metric::Sharpe).value(runs);
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{}",
+                candidate.rounds,
+                runs.len(),
+                candidate.score,
+                Objective::default().value(runs).unwrap_or(0.0),
+                runs.iter().map(|r| r.max_drawdown).sum::<f64>() / runs.len().max(1) as f64,
+                sharpe.map_or_else(String::new, |s| s.to_string()),
+            );
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_fits_the_budget() {
+        assert_eq!(schedule(27, 2, 3), vec![(27, 2), (9, 6), (3, 18)]);
+        assert_eq!(schedule_cost(&schedule(27, 2, 3)), 27 * 2 + 9 * 4 + 3 * 12);
+        assert_eq!(schedule(1, 4, 3), vec![(1, 4)]);
+    }
+
+    #[test]
+    fn test_search_finds_the_best_point_within_budget() {
+        let yaml = "simulation: {days: 10, initial_price: 75.0, volatility: 0.3, seed: 3}\n\
+                    strategy: {strategy_type: straddle, entry_dte: 1}\n\
+                    sweep:\n  parameters:\n\
+                    \x20   - {path: simulation.volatility_risk_premium, start: 0.0, end: 0.1, step: 0.01}\n\
+                    \x20   - {path: simulation.volatility, values: [0.25, 0.3]}\n\
+                    \x20 optimizer: {budget: 40, min_seeds: 1, eta: 3}\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        let optimizer = Optimizer::from_config(&config).unwrap();
+        let plan = optimizer.plan().unwrap();
+        assert!(schedule_cost(&plan) <= 40 && plan[0].0 > 10);
+
+        let result = optimizer.run().unwrap();
+        assert_eq!(result.runs, schedule_cost(&plan));
+        let best = result.best().unwrap();
+        assert_eq!(best.rounds, plan.len());
+        assert_eq!(best.runs.len() as u64, plan.last().unwrap().1);
+        // Short straddles earn the premium, so the richest VRP (at the higher vol) wins
+        assert_eq!(best.values, [0.1, 0.3]);
+        assert_eq!(result.csv().lines().count(), plan[0].0 + 1);
+        assert_eq!(optimizer.run().unwrap().best().unwrap().values, best.values);
+    }
+}
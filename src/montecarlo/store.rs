@@ -0,0 +1,465 @@
+//! Study Results Database
+//!
+//! Every run of a sweep or Monte Carlo study in a SQLite file: the resolved
+//! config (stored once per `Config::fingerprint`), the seed, the swept
+//! parameter values, headline metrics and any artifact paths. A run is keyed
+//! by study, fingerprint and seed, so recording it again is a no-op and a
+//! study can be checked for runs it already has. Other tools can read the
+//! file directly; the tables are
+//!
+//! - `studies(id, name, kind, created_at)`
+//! - `configs(fingerprint, yaml)`
+//! - `runs(study_id, fingerprint, seed, parameters, pnl, max_drawdown, sharpe, artifacts)`
+//!
+//! `parameters` is `path=value` pairs joined by `;`, `artifacts` paths joined
+//! by `;`, and `created_at` Unix seconds.
+//!
+//! `run_sweep` and `run_monte_carlo` make studies resumable: runs the study
+//! already has are read back instead of rerun, and new runs are committed in
+//! batches as they finish, so a study stopped part way picks up where it left
+//! off when started again with the same name.
+
+use super::{run_seeds, MonteCarloResult, Sweep, SweepProgress, SweepResult, SweepRow};
+use crate::config::Config;
+use crate::error::SimError;
+use crate::reporting::SeedResult;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Runs committed per transaction by the resumable runners (a crash loses at most one batch)
+const RESUME_BATCH: usize = 256;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS studies (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        kind TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS configs (
+        fingerprint TEXT PRIMARY KEY,
+        yaml TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS runs (
+        study_id INTEGER NOT NULL REFERENCES studies(id),
+        fingerprint TEXT NOT NULL REFERENCES configs(fingerprint),
+        seed INTEGER NOT NULL,
+        parameters TEXT NOT NULL,
+        pnl REAL NOT NULL,
+        max_drawdown REAL NOT NULL,
+        sharpe REAL,
+        artifacts TEXT NOT NULL DEFAULT '',
+        PRIMARY KEY (study_id, fingerprint, seed)
+    );
+";
+
+/// A study and how many runs it has
+#[derive(Debug, Clone, PartialEq)]
+pub struct StudyInfo {
+    pub name: String,
+    /// "sweep", "montecarlo" or "run"
+    pub kind: String,
+    /// Unix seconds
+    pub created_at: i64,
+    pub runs: usize,
+}
+
+/// One stored run
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredRun {
+    pub fingerprint: String,
+    /// Swept config paths and their values (empty outside sweeps)
+    pub parameters: Vec<(String, f64)>,
+    pub result: SeedResult,
+    pub artifacts: Vec<String>,
+}
+
+/// A SQLite file of study runs
+pub struct ResultsStore {
+    conn: Connection,
+}
+
+impl ResultsStore {
+    /// Open (or create) the database at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SimError> {
+        Self::init(Connection::open(path).map_err(db_error)?)
+    }
+
+    /// A database that lives only as long as the value
+    pub fn in_memory() -> Result<Self, SimError> {
+        Self::init(Connection::open_in_memory().map_err(db_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, SimError> {
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        Ok(Self { conn })
+    }
+
+    /// The underlying connection, for queries the methods here do not cover
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Id of study `name`, created with `kind` if it does not exist yet
+    pub fn study(&self, name: &str, kind: &str) -> Result<i64, SimError> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs() as i64);
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO studies (name, kind, created_at) VALUES (?1, ?2, ?3)",
+                params![name, kind, now],
+            )
+            .map_err(db_error)?;
+        self.conn
+            .query_row("SELECT id FROM studies WHERE name = ?1", params![name], |row| row.get(0))
+            .map_err(db_error)
+    }
+
+    /// Every study, oldest first
+    pub fn studies(&self) -> Result<Vec<StudyInfo>, SimError> {
+        let mut statement = self
+            .conn
+            .prepare(
+                "SELECT s.name, s.kind, s.created_at, COUNT(r.seed) FROM studies s
+                 LEFT JOIN runs r ON r.study_id = s.id GROUP BY s.id ORDER BY s.id",
+            )
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(StudyInfo { name: row.get(0)?, kind: row.get(1)?, created_at: row.get(2)?, runs: row.get(3)? })
+            })
+            .map_err(db_error)?;
+        rows.collect::<Result<_, _>>().map_err(db_error)
+    }
+
+    /// Whether `study` already has `config` run on `seed`
+    pub fn contains(&self, study: i64, config: &Config, seed: u64) -> Result<bool, SimError> {
+        let fingerprint = config.fingerprint()?;
+        self.conn
+            .query_row(
+                "SELECT 1 FROM runs WHERE study_id = ?1 AND fingerprint = ?2 AND seed = ?3",
+                params![study, fingerprint, seed as i64],
+                |_| Ok(()),
+            )
+            .optional()
+            .map(|found| found.is_some())
+            .map_err(db_error)
+    }
+
+    /// Record one run of `config`; false if the study already had it
+    pub fn record(
+        &self,
+        study: i64,
+        config: &Config,
+        parameters: &[(String, f64)],
+        result: &SeedResult,
+        artifacts: &[String],
+    ) -> Result<bool, SimError> {
+        let fingerprint = config.fingerprint()?;
+        let mut resolved = config.clone();
+        resolved.simulation.seed = 0;
+        resolved.sweep = None;
+        let yaml = serde_yaml::to_string(&resolved).map_err(|e| SimError::Data(e.to_string()))?;
+        self.conn
+            .execute("INSERT OR IGNORE INTO configs (fingerprint, yaml) VALUES (?1, ?2)", params![fingerprint, yaml])
+            .map_err(db_error)?;
+        let parameters: Vec<String> = parameters.iter().map(|(path, value)| format!("{}={}", path, value)).collect();
+        let inserted = self
+            .conn
+            .execute(
+                "INSERT OR IGNORE INTO runs
+                 (study_id, fingerprint, seed, parameters, pnl, max_drawdown, sharpe, artifacts)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    study,
+                    fingerprint,
+                    result.seed as i64,
+                    parameters.join(";"),
+                    result.pnl,
+                    result.max_drawdown,
+                    result.sharpe,
+                    artifacts.join(";")
+                ],
+            )
+            .map_err(db_error)?;
+        Ok(inserted == 1)
+    }
+
+    /// Record every run of a sweep; returns how many were new
+    pub fn record_sweep(&mut self, study: &str, sweep: &Sweep, result: &SweepResult) -> Result<usize, SimError> {
+        let study = self.study(study, "sweep")?;
+        self.transaction(|store| {
+            let mut inserted = 0;
+            for row in &result.rows {
+                let parameters: Vec<(String, f64)> =
+                    result.parameters.iter().cloned().zip(row.values.iter().copied()).collect();
+                let config = parameters
+                    .iter()
+                    .try_fold(sweep.base().clone(), |config, (path, value)| config.with_override(path, *value))?;
+                inserted += usize::from(store.record(study, &config, &parameters, &row.result, &[])?);
+            }
+            Ok(inserted)
+        })
+    }
+
+    /// Record every seed of a Monte Carlo study; returns how many were new
+    pub fn record_monte_carlo(
+        &mut self,
+        study: &str,
+        config: &Config,
+        result: &MonteCarloResult,
+    ) -> Result<usize, SimError> {
+        let study = self.study(study, "montecarlo")?;
+        self.transaction(|store| {
+            let mut inserted = 0;
+            for run in &result.runs {
+                inserted += usize::from(store.record(study, config, &[], run, &[])?);
+            }
+            Ok(inserted)
+        })
+    }
+
+    /// Run `sweep` as `study`, skipping runs it already has and recording new
+    /// ones in batches as they finish; also returns how many runs were reused
+    pub fn run_sweep<F>(&mut self, study: &str, sweep: &Sweep, on_progress: F) -> Result<(SweepResult, usize), SimError>
+    where
+        F: Fn(SweepProgress) + Sync,
+    {
+        let study = self.study(study, "sweep")?;
+        let paths = sweep.parameter_paths();
+        let points = sweep.points();
+        let configs = points.iter().map(|values| sweep.point_config(values)).collect::<Result<Vec<_>, _>>()?;
+        let fingerprints = configs.iter().map(Config::fingerprint).collect::<Result<Vec<_>, _>>()?;
+        let completed = self.completed(study)?;
+
+        let mut results = HashMap::new();
+        let mut pending = Vec::new();
+        for (point, fingerprint) in fingerprints.into_iter().enumerate() {
+            for seed in sweep.seed_range() {
+                match completed.get(&(fingerprint.clone(), seed)) {
+                    Some(&result) => {
+                        results.insert((point, seed), result);
+                    }
+                    None => pending.push((point, seed)),
+                }
+            }
+        }
+
+        let (resumed, total) = (results.len(), results.len() + pending.len());
+        let started = Instant::now();
+        for batch in pending.chunks(RESUME_BATCH) {
+            let done = results.len();
+            let rows = sweep.run_jobs(points.clone(), batch.to_vec(), |p| {
+                on_progress(SweepProgress { completed: done + p.completed, total, elapsed: started.elapsed() })
+            })?;
+            self.transaction(|store| {
+                for row in &rows {
+                    let parameters: Vec<(String, f64)> =
+                        paths.iter().cloned().zip(row.values.iter().copied()).collect();
+                    store.record(study, &configs[row.point], &parameters, &row.result, &[])?;
+                }
+                Ok(())
+            })?;
+            results.extend(rows.into_iter().map(|row| ((row.point, row.result.seed), row.result)));
+        }
+
+        let rows = points
+            .iter()
+            .enumerate()
+            .flat_map(|(point, values)| {
+                let results = &results;
+                let row = move |seed| SweepRow { point, values: values.clone(), result: results[&(point, seed)] };
+                sweep.seed_range().map(row)
+            })
+            .collect();
+        Ok((SweepResult { parameters: paths, rows, objective: sweep.objective() }, resumed))
+    }
+
+    /// Run `config` on seeds `first_seed..first_seed + count` as `study`,
+    /// skipping seeds it already has and recording new ones in batches as
+    /// they finish; also returns how many runs were reused
+    pub fn run_monte_carlo<F>(
+        &mut self,
+        study: &str,
+        config: &Config,
+        first_seed: u64,
+        count: u64,
+        on_progress: F,
+    ) -> Result<(MonteCarloResult, usize), SimError>
+    where
+        F: Fn(SweepProgress) + Sync,
+    {
+        let study = self.study(study, "montecarlo")?;
+        let fingerprint = config.fingerprint()?;
+        let completed = self.completed(study)?;
+        let (mut runs, mut pending) = (Vec::new(), Vec::new());
+        for seed in first_seed..first_seed + count {
+            match completed.get(&(fingerprint.clone(), seed)) {
+                Some(&result) => runs.push(result),
+                None => pending.push(seed),
+            }
+        }
+
+        let (resumed, total) = (runs.len(), count as usize);
+        let started = Instant::now();
+        for batch in pending.chunks(RESUME_BATCH) {
+            let done = runs.len();
+            let results = run_seeds(config, batch, |p| {
+                on_progress(SweepProgress { completed: done + p.completed, total, elapsed: started.elapsed() })
+            })?;
+            self.transaction(|store| {
+                for result in &results {
+                    store.record(study, config, &[], result, &[])?;
+                }
+                Ok(())
+            })?;
+            runs.extend(results);
+        }
+        runs.sort_by_key(|r| r.seed);
+        Ok((MonteCarloResult::from_runs(runs), resumed))
+    }
+
+    /// Results `study` already has, by config fingerprint and seed
+    fn completed(&self, study: i64) -> Result<HashMap<(String, u64), SeedResult>, SimError> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT fingerprint, seed, pnl, max_drawdown, sharpe FROM runs WHERE study_id = ?1")
+            .map_err(db_error)?;
+        let rows = statement
+            .query_map(params![study], |row| {
+                let seed = row.get::<_, i64>(1)? as u64;
+                let result = SeedResult { seed, pnl: row.get(2)?, max_drawdown: row.get(3)?, sharpe: row.get(4)? };
+                Ok(((row.get(0)?, seed), result))
+            })
+            .map_err(db_error)?;
+        rows.collect::<Result<_, _>>().map_err(db_error)
+    }
+
+    /// Runs of `study`, by fingerprint then seed
+    pub fn runs(&self, study: &str) -> Result<Vec<StoredRun>, SimError> {
+        self.select_runs("WHERE s.name = ?1 ORDER BY r.fingerprint, r.seed", study)
+    }
+
+    /// The `limit` most profitable runs of `study`
+    pub fn best_runs(&self, study: &str, limit: usize) -> Result<Vec<StoredRun>, SimError> {
+        self.select_runs(&format!("WHERE s.name = ?1 ORDER BY r.pnl DESC LIMIT {}", limit), study)
+    }
+
+    /// The resolved config (seed 0) stored under `fingerprint`
+    pub fn config(&self, fingerprint: &str) -> Result<Option<Config>, SimError> {
+        let yaml: Option<String> = self
+            .conn
+            .query_row("SELECT yaml FROM configs WHERE fingerprint = ?1", params![fingerprint], |row| row.get(0))
+            .optional()
+            .map_err(db_error)?;
+        yaml.map(|yaml| Config::from_yaml_str(&yaml).map_err(SimError::from)).transpose()
+    }
+
+    fn select_runs(&self, filter: &str, study: &str) -> Result<Vec<StoredRun>, SimError> {
+        let sql = format!(
+            "SELECT r.fingerprint, r.seed, r.parameters, r.pnl, r.max_drawdown, r.sharpe, r.artifacts
+             FROM runs r JOIN studies s ON s.id = r.study_id {}",
+            filter
+        );
+        let mut statement = self.conn.prepare(&sql).map_err(db_error)?;
+        let rows = statement
+            .query_map(params![study], |row| {
+                let parameters: String = row.get(2)?;
+                let artifacts: String = row.get(6)?;
+                Ok(StoredRun {
+                    fingerprint: row.get(0)?,
+                    parameters: parameters
+                        .split(';')
+                        .filter_map(|pair| {
+                            let (path, value) = pair.split_once('=')?;
+                            Some((path.to_string(), value.parse().ok()?))
+                        })
+                        .collect(),
+                    result: SeedResult {
+                        seed: row.get::<_, i64>(1)? as u64,
+                        pnl: row.get(3)?,
+                        max_drawdown: row.get(4)?,
+                        sharpe: row.get(5)?,
+                    },
+                    artifacts: artifacts.split(';').filter(|a| !a.is_empty()).map(str::to_string).collect(),
+                })
+            })
+            .map_err(db_error)?;
+        rows.collect::<Result<_, _>>().map_err(db_error)
+    }
+
+    /// Run `f` in one transaction (much faster for many inserts)
+    fn transaction<T>(&mut self, f: impl FnOnce(&Self) -> Result<T, SimError>) -> Result<T, SimError> {
+        self.conn.execute_batch("BEGIN").map_err(db_error)?;
+        match f(self) {
+            Ok(value) => {
+                self.conn.execute_batch("COMMIT").map_err(db_error)?;
+                Ok(value)
+            }
+            Err(e) => {
+                let _ = self.conn.execute_batch("ROLLBACK");
+                Err(e)
+            }
+        }
+    }
+}
+
+fn db_error(e: rusqlite::Error) -> SimError {
+    SimError::Data(format!("results database: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sweep_runs_round_trip_and_deduplicate() {
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.days = 10;
+        let sweep = Sweep::new(&config).parameter("simulation.volatility_risk_premium", vec![0.0, 0.05]).seeds(2);
+        let result = sweep.run().unwrap();
+
+        let mut store = ResultsStore::in_memory().unwrap();
+        assert_eq!(store.record_sweep("vrp", &sweep, &result).unwrap(), 4);
+        assert_eq!(store.record_sweep("vrp", &sweep, &result).unwrap(), 0);
+        let studies = store.studies().unwrap();
+        assert_eq!((studies[0].name.as_str(), studies[0].kind.as_str(), studies[0].runs), ("vrp", "sweep", 4));
+
+        let runs = store.runs("vrp").unwrap();
+        assert_eq!(runs.len(), 4);
+        let best = &store.best_runs("vrp", 1).unwrap()[0];
+        assert_eq!(best.result.pnl, result.rows.iter().map(|r| r.result.pnl).fold(f64::MIN, f64::max));
+        assert_eq!(best.parameters, [("simulation.volatility_risk_premium".to_string(), 0.05)]);
+
+        let resolved = store.config(&best.fingerprint).unwrap().unwrap();
+        assert_eq!(resolved.simulation.volatility_risk_premium, 0.05);
+        let study = store.study("vrp", "sweep").unwrap();
+        let mut seeded = resolved.clone();
+        seeded.simulation.seed = 99;
+        assert!(store.contains(study, &seeded, best.result.seed).unwrap());
+        assert!(!store.contains(study, &seeded, 99).unwrap());
+    }
+
+    #[test]
+    fn test_studies_resume_where_they_stopped() {
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.days = 10;
+        let mut store = ResultsStore::in_memory().unwrap();
+
+        // A study stopped after three seeds reuses them and runs only the rest
+        let (_, resumed) = store.run_monte_carlo("mc", &config, 1, 3, |_| {}).unwrap();
+        assert_eq!(resumed, 0);
+        let (result, resumed) = store.run_monte_carlo("mc", &config, 1, 5, |_| {}).unwrap();
+        assert_eq!(resumed, 3);
+        assert_eq!(result.runs, crate::montecarlo::run(&config, 1, 5).unwrap().runs);
+
+        let sweep = Sweep::new(&config).parameter("simulation.volatility_risk_premium", vec![0.0, 0.05]);
+        store.run_sweep("grid", &sweep.clone().seeds(1), |_| {}).unwrap();
+        let (result, resumed) = store.run_sweep("grid", &sweep.clone().seeds(2), |_| {}).unwrap();
+        assert_eq!(resumed, 2);
+        let fresh = sweep.seeds(2).run().unwrap();
+        let key = |rows: &[SweepRow]| rows.iter().map(|r| (r.point, r.result)).collect::<Vec<_>>();
+        assert_eq!(key(&result.rows), key(&fresh.rows));
+    }
+}
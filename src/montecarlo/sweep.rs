@@ -0,0 +1,495 @@
+//! Parameter Sweeps
+//!
+//! Runs the cartesian grid of parameter values, each point over the same
+//! seeds (`base seed .. base seed + seeds`), so every point sees the same
+//! markets and differences come from the parameters alone. Runs are isolated
+//! (each builds its own simulator, event store and RNG) and execute on a
+//! rayon pool bounded by `threads`; the merged table is in grid order, so
+//! neither the thread count nor scheduling changes the results.
+//!
+//! Each distinct price path is generated once and shared by every point
+//! that would generate the same one, so sweeping strategy fields costs one
+//! path per seed; `on_path` runs every point once on a fixed (e.g.
+//! historical) path instead. Grids can come from a config's `sweep` section, and the
+//! results can be written as CSV tables (per run, or per point). Points are
+//! ranked by the sweep's `Objective` (`sweep.objective` in the config, mean
+//! P&L by default).
+
+use super::{run_seed_on_path, Aggregate, Objective, SweepProgress};
+use crate::config::{Config, ConfigError, SimulationConfig};
+use crate::engine::PricePath;
+use crate::error::SimError;
+use crate::reporting::{Distribution, SeedResult};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// One swept field and the values it takes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    /// Dotted config path, see `Config::with_override`
+    pub path: String,
+    pub values: Vec<f64>,
+}
+
+/// A grid of parameter values over a base config
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    base: Config,
+    parameters: Vec<Parameter>,
+    seeds: u64,
+    threads: Option<usize>,
+    objective: Objective,
+    /// Fixed path every point runs on, instead of generated ones
+    path: Option<PricePath>,
+}
+
+/// One run of the merged results table
+#[derive(Debug, Clone)]
+pub struct SweepRow {
+    /// Index of the grid point
+    pub point: usize,
+    /// Parameter values, in the order they were added
+    pub values: Vec<f64>,
+    pub result: SeedResult,
+}
+
+/// Every run of a sweep, ordered by grid point then seed
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    /// Swept config paths (column names for `SweepRow::values`)
+    pub parameters: Vec<String>,
+    pub rows: Vec<SweepRow>,
+    /// What `ranked` orders points by
+    pub objective: Objective,
+}
+
+/// Cross-seed statistics of one grid point
+#[derive(Debug, Clone, Serialize)]
+pub struct PointSummary {
+    pub point: usize,
+    pub values: Vec<f64>,
+    pub pnl: Distribution,
+    pub max_drawdown: Distribution,
+    /// Fraction of seeds with positive P&L
+    pub win_rate: f64,
+    /// Mean Sharpe ratio of the seeds that have one
+    pub mean_sharpe: Option<f64>,
+    /// The sweep's objective over the point's runs (higher is better;
+    /// `None` when undefined or the point breaks a constraint)
+    pub score: Option<f64>,
+    /// Median and lowest of the objective scored on each seed alone
+    pub median_seed_score: Option<f64>,
+    pub worst_seed_score: Option<f64>,
+    /// Made money on average but lost on most seeds: the mean rides on a few
+    /// lucky paths
+    pub lucky: bool,
+}
+
+/// Everything `PricePath::generate` reads, so equal keys mean identical paths
+type PathKey = (u64, u64, u64, u64, usize, u32, String);
+
+fn path_key(sim: &SimulationConfig, seed: u64) -> PathKey {
+    let (price, drift, vol) = (sim.initial_price.to_bits(), sim.drift.to_bits(), sim.volatility.to_bits());
+    (price, drift, vol, seed, sim.days, sim.intraday_resolution_minutes, sim.rng.clone())
+}
+
+impl Sweep {
+    /// Sweep over `base`, one seed per point until `seeds` is called
+    pub fn new(base: &Config) -> Self {
+        Self {
+            base: base.clone(),
+            parameters: Vec::new(),
+            seeds: 1,
+            threads: None,
+            objective: Objective::default(),
+            path: None,
+        }
+    }
+
+    /// The grid and seed count of `config.sweep`, over `config` itself
+    pub fn from_config(config: &Config) -> Result<Self, SimError> {
+        let Some(grid) = &config.sweep else {
+            return Err(ConfigError::Validation("the config has no sweep section".to_string()).into());
+        };
+        let mut sweep = Self::new(config).seeds(grid.seeds).rank_by(Objective::of_study(config)?);
+        for parameter in &grid.parameters {
+            sweep = sweep.parameter(&parameter.path, parameter.values().map_err(ConfigError::Validation)?);
+        }
+        Ok(sweep)
+    }
+
+    /// Add a swept field
+    pub fn parameter(mut self, path: &str, values: Vec<f64>) -> Self {
+        self.parameters.push(Parameter { path: path.to_string(), values });
+        self
+    }
+
+    /// Run every point over this many consecutive seeds
+    pub fn seeds(mut self, count: u64) -> Self {
+        self.seeds = count;
+        self
+    }
+
+    /// Cap the number of worker threads (default: rayon's global pool)
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads.max(1));
+        self
+    }
+
+    /// Run every point once on `path` instead of over generated paths (the
+    /// seed no longer changes the market, so only the base seed runs)
+    pub fn on_path(mut self, path: PricePath) -> Self {
+        self.path = Some(path);
+        self.seeds = 1;
+        self
+    }
+
+    /// Rank points by `objective` instead of mean P&L
+    pub fn rank_by(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Every combination of parameter values, last parameter varying fastest
+    pub fn points(&self) -> Vec<Vec<f64>> {
+        self.parameters.iter().fold(vec![Vec::new()], |points, parameter| {
+            points
+                .iter()
+                .flat_map(|point| {
+                    parameter.values.iter().map(move |&value| {
+                        let mut next = point.clone();
+                        next.push(value);
+                        next
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Total simulations the sweep will run, counted without building the
+    /// grid (`None` if it overflows `usize`)
+    pub fn run_count(&self) -> Option<usize> {
+        let seeds = usize::try_from(self.seeds).ok()?;
+        self.parameters.iter().try_fold(seeds, |runs, parameter| runs.checked_mul(parameter.values.len()))
+    }
+
+    /// Run the whole grid
+    pub fn run(&self) -> Result<SweepResult, SimError> {
+        self.run_with_progress(|_| {})
+    }
+
+    /// `run`, calling `on_progress` as each simulation finishes
+    pub fn run_with_progress<F>(&self, on_progress: F) -> Result<SweepResult, SimError>
+    where
+        F: Fn(SweepProgress) + Sync,
+    {
+        let rows = self.run_points(self.points(), self.seed_range(), on_progress)?;
+        Ok(SweepResult { parameters: self.parameter_paths(), rows, objective: self.objective })
+    }
+
+    pub(super) fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    pub(super) fn parameter_paths(&self) -> Vec<String> {
+        self.parameters.iter().map(|p| p.path.clone()).collect()
+    }
+
+    pub(super) fn base(&self) -> &Config {
+        &self.base
+    }
+
+    /// Seeds every point runs on
+    pub(super) fn seed_range(&self) -> Range<u64> {
+        let first_seed = self.base.simulation.seed;
+        first_seed..first_seed + self.seeds
+    }
+
+    /// The base config with `values` (in parameter order) applied
+    pub(super) fn point_config(&self, values: &[f64]) -> Result<Config, SimError> {
+        self.parameters
+            .iter()
+            .zip(values)
+            .try_fold(self.base.clone(), |config, (parameter, &value)| config.with_override(&parameter.path, value))
+            .map_err(SimError::from)
+    }
+
+    /// Run `points` (values in parameter order) over `seeds`; rows are in
+    /// point then seed order, `SweepRow::point` indexing into `points`
+    pub(super) fn run_points<F>(
+        &self,
+        points: Vec<Vec<f64>>,
+        seeds: Range<u64>,
+        on_progress: F,
+    ) -> Result<Vec<SweepRow>, SimError>
+    where
+        F: Fn(SweepProgress) + Sync,
+    {
+        let jobs = (0..points.len()).flat_map(|point| seeds.clone().map(move |seed| (point, seed))).collect();
+        self.run_jobs(points, jobs, on_progress)
+    }
+
+    /// Run each `(point, seed)` of `jobs` (points indexing into `points`);
+    /// rows are in job order
+    pub(super) fn run_jobs<F>(
+        &self,
+        points: Vec<Vec<f64>>,
+        jobs: Vec<(usize, u64)>,
+        on_progress: F,
+    ) -> Result<Vec<SweepRow>, SimError>
+    where
+        F: Fn(SweepProgress) + Sync,
+    {
+        // Build (and validate) every point's config before running anything
+        let configs = points
+            .into_iter()
+            .map(|values| self.point_config(&values).map(|config| (values, config)))
+            .collect::<Result<Vec<_>, SimError>>()?;
+
+        // One config per distinct path, in job order (none on a fixed path)
+        let mut path_jobs: Vec<(PathKey, Config)> = Vec::new();
+        let mut seen = HashMap::new();
+        for &(point, seed) in jobs.iter().filter(|_| self.path.is_none()) {
+            let key = path_key(&configs[point].1.simulation, seed);
+            if seen.insert(key.clone(), ()).is_none() {
+                let mut config = configs[point].1.clone();
+                config.simulation.seed = seed;
+                path_jobs.push((key, config));
+            }
+        }
+        let generate = |(key, config): &(PathKey, Config)| PricePath::generate(config).map(|path| (key.clone(), path));
+
+        let started = Instant::now();
+        let completed = AtomicUsize::new(0);
+        let run_one = |&(point, seed): &(usize, u64), paths: &HashMap<PathKey, PricePath>| {
+            let config = &configs[point].1;
+            let path = match &self.path {
+                Some(path) => path.clone(),
+                None => paths[&path_key(&config.simulation, seed)].clone(),
+            };
+            let result = run_seed_on_path(config, seed, path);
+            on_progress(SweepProgress {
+                completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                total: jobs.len(),
+                elapsed: started.elapsed(),
+            });
+            result.map(|result| SweepRow { point, values: configs[point].0.clone(), result })
+        };
+
+        #[cfg(feature = "parallel")]
+        let rows: Vec<SweepRow> = {
+            let run_all = || -> Result<Vec<_>, SimError> {
+                let paths = path_jobs.par_iter().map(generate).collect::<Result<HashMap<_, _>, _>>()?;
+                jobs.par_iter().map(|job| run_one(job, &paths)).collect()
+            };
+            match self.threads {
+                Some(threads) => rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(|e| SimError::Execution(format!("could not start sweep threads: {}", e)))?
+                    .install(run_all)?,
+                None => run_all()?,
+            }
+        };
+        #[cfg(not(feature = "parallel"))]
+        let rows = {
+            let paths = path_jobs.iter().map(generate).collect::<Result<HashMap<_, _>, _>>()?;
+            jobs.iter().map(|job| run_one(job, &paths)).collect::<Result<Vec<_>, _>>()?
+        };
+        Ok(rows)
+    }
+}
+
+impl SweepResult {
+    /// Per-point P&L and drawdown distributions, in grid order
+    pub fn summaries(&self) -> Vec<PointSummary> {
+        self.rows
+            .chunk_by(|a, b| a.point == b.point)
+            .filter_map(|runs| {
+                let pnl: Vec<f64> = runs.iter().map(|r| r.result.pnl).collect();
+                let drawdown: Vec<f64> = runs.iter().map(|r| r.result.max_drawdown).collect();
+                let sharpes: Vec<f64> = runs.iter().filter_map(|r| r.result.sharpe).collect();
+                let results: Vec<SeedResult> = runs.iter().map(|r| r.result).collect();
+                let score = self.objective.score(&results);
+                let per_seed = |aggregate| Objective { aggregate, ..self.objective }.score(&results);
+                let (median_seed, worst_seed) = (per_seed(Aggregate::Median), per_seed(Aggregate::Worst));
+                let pnl_distribution = Distribution::from_samples(&pnl)?;
+                Some(PointSummary {
+                    point: runs[0].point,
+                    values: runs[0].values.clone(),
+                    lucky: pnl.len() > 1 && pnl_distribution.mean > 0.0 && pnl_distribution.median <= 0.0,
+                    pnl: pnl_distribution,
+                    max_drawdown: Distribution::from_samples(&drawdown)?,
+                    win_rate: pnl.iter().filter(|&&p| p > 0.0).count() as f64 / pnl.len() as f64,
+                    mean_sharpe: (!sharpes.is_empty()).then(|| sharpes.iter().sum::<f64>() / sharpes.len() as f64),
+                    score: score.is_finite().then_some(score),
+                    median_seed_score: median_seed.is_finite().then_some(median_seed),
+                    worst_seed_score: worst_seed.is_finite().then_some(worst_seed),
+                })
+            })
+            .collect()
+    }
+
+    /// `summaries`, best score first (ties and unscored points in grid order)
+    pub fn ranked(&self) -> Vec<PointSummary> {
+        let mut summaries = self.summaries();
+        let key = |s: &PointSummary| s.score.unwrap_or(f64::NEG_INFINITY);
+        summaries.sort_by(|a, b| key(b).total_cmp(&key(a)).then(a.point.cmp(&b.point)));
+        summaries
+    }
+
+    /// One row per run: point, parameter values, seed and headline metrics (dollars)
+    pub fn runs_csv(&self) -> String {
+        let mut csv = format!("point,{},seed,pnl,max_drawdown,sharpe\n", self.parameters.join(","));
+        for row in &self.rows {
+            let _ = write!(csv, "{},", row.point);
+            for value in &row.values {
+                let _ = write!(csv, "{},", value);
+            }
+            let result = &row.result;
+            let sharpe = result.sharpe.map_or_else(String::new, |s| s.to_string());
+            let _ = writeln!(csv, "{},{},{},{}", result.seed, result.pnl, result.max_drawdown, sharpe);
+        }
+        csv
+    }
+
+    /// One row per grid point with its cross-seed statistics (dollars)
+    pub fn summary_csv(&self) -> String {
+        let mut csv = format!(
+            "point,{},runs,mean_pnl,median_pnl,p5_pnl,p95_pnl,win_rate,{}\n",
+            self.parameters.join(","),
+            "mean_max_drawdown,worst_max_drawdown,mean_sharpe,score,median_seed_score,worst_seed_score,lucky"
+        );
+        for summary in self.summaries() {
+            let _ = write!(csv, "{},", summary.point);
+            for value in &summary.values {
+                let _ = write!(csv, "{},", value);
+            }
+            let (pnl, drawdown) = (&summary.pnl, &summary.max_drawdown);
+            let sharpe = summary.mean_sharpe.map_or_else(String::new, |s| s.to_string());
+            let optional = |value: Option<f64>| value.map_or_else(String::new, |v| v.to_string());
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                pnl.count,
+                pnl.mean,
+                pnl.median,
+                pnl.p5,
+                pnl.p95,
+                summary.win_rate,
+                drawdown.mean,
+                drawdown.max,
+                sharpe,
+                optional(summary.score),
+                optional(summary.median_seed_score),
+                optional(summary.worst_seed_score),
+                summary.lucky
+            );
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::montecarlo::run_seed;
+
+    fn short_config() -> Config {
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.days = 10;
+        config
+    }
+
+    #[test]
+    fn test_grid_points() {
+        let sweep = Sweep::new(&short_config())
+            .parameter("simulation.volatility_risk_premium", vec![0.0, 0.05])
+            .parameter("strategy.entry_dte", vec![1.0, 2.0, 3.0])
+            .seeds(4);
+        let points = sweep.points();
+        assert_eq!(points.len(), 6);
+        assert_eq!(points[0], vec![0.0, 1.0]);
+        assert_eq!(points[5], vec![0.05, 3.0]);
+        assert_eq!(sweep.run_count(), Some(24));
+        let overflowing = Sweep::new(&short_config()).parameter("strategy.entry_dte", vec![1.0, 2.0]).seeds(u64::MAX);
+        assert_eq!(overflowing.run_count(), None);
+        assert_eq!(Sweep::new(&short_config()).points(), vec![Vec::<f64>::new()]);
+    }
+
+    #[test]
+    fn test_sweep_is_deterministic_and_matches_single_runs() {
+        let config = short_config();
+        let sweep = Sweep::new(&config).parameter("simulation.volatility_risk_premium", vec![0.0, 0.1]).seeds(3);
+        let a = sweep.clone().threads(1).run().unwrap();
+        let b = sweep.threads(3).run().unwrap();
+        assert_eq!(a.rows.len(), 6);
+        for (x, y) in a.rows.iter().zip(&b.rows) {
+            assert_eq!((x.point, x.result.seed, x.result.pnl), (y.point, y.result.seed, y.result.pnl));
+        }
+
+        // Row for point 1, seed 43 is the plain run with those settings
+        let single = config.with_override("simulation.volatility_risk_premium", 0.1).unwrap();
+        assert_eq!(a.rows[4].result.pnl, run_seed(&single, 43).unwrap().pnl);
+
+        let summaries = a.summaries();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[1].values, vec![0.1]);
+        assert_eq!(summaries[1].pnl.count, 3);
+    }
+
+    #[test]
+    fn test_config_grid_shares_paths_and_writes_tables() {
+        let yaml = "simulation: {days: 10, initial_price: 75.0, volatility: 0.3, seed: 7}\n\
+                    strategy: {strategy_type: straddle, entry_dte: 1}\n\
+                    sweep:\n  seeds: 2\n  parameters:\n\
+                    \x20   - {path: simulation.volatility_risk_premium, start: 0.0, end: 0.1, step: 0.05}\n\
+                    \x20   - {path: simulation.volatility, values: [0.3, 0.4]}\n";
+        let config = Config::from_yaml_str(yaml).unwrap();
+        let sweep = Sweep::from_config(&config).unwrap();
+        assert_eq!(sweep.points().len(), 6);
+        let result = sweep.run().unwrap();
+
+        // Shared or not, every run matches a run of its own
+        let point = config
+            .with_override("simulation.volatility_risk_premium", 0.05)
+            .and_then(|c| c.with_override("simulation.volatility", 0.4))
+            .unwrap();
+        let row = &result.rows[3 * 2 + 1];
+        assert_eq!((row.values.as_slice(), row.result.seed), ([0.05, 0.4].as_slice(), 8));
+        assert_eq!(row.result.pnl, run_seed(&point, 8).unwrap().pnl);
+
+        let runs = result.runs_csv();
+        assert!(runs.starts_with("point,simulation.volatility_risk_premium,simulation.volatility,seed,pnl,"));
+        assert_eq!(runs.lines().count(), 13);
+        let summary = result.summary_csv();
+        assert_eq!(summary.lines().count(), 7);
+        assert!(summary.lines().nth(1).unwrap().starts_with("0,0,0.3,2,"));
+
+        // Ranked by the default objective, mean P&L
+        let ranked = result.ranked();
+        assert_eq!(ranked[0].score, Some(ranked[0].pnl.mean));
+        assert!(ranked.windows(2).all(|w| w[0].pnl.mean >= w[1].pnl.mean));
+        // Per seed, mean P&L is each seed's own P&L
+        assert_eq!(ranked[0].worst_seed_score, Some(ranked[0].pnl.min));
+        assert!(ranked.iter().all(|s| !s.lucky || s.pnl.median <= 0.0));
+        assert!(Sweep::from_config(&short_config()).is_err());
+    }
+
+    #[test]
+    fn test_bad_parameter_fails_before_running() {
+        let result = Sweep::new(&short_config()).parameter("simulation.nope", vec![1.0]).run();
+        assert!(matches!(result, Err(SimError::Config(_))));
+    }
+}
@@ -0,0 +1,130 @@
+//! Memory-Mapped Bar Files
+//!
+//! Years of 1-minute /CL bars are millions of points; rather than parsing
+//! them into a `Vec` up front, bars are stored in a fixed-width binary file
+//! and memory-mapped, so the simulator reads `PricePoint`s straight out of
+//! the page cache.
+//!
+//! File layout (little-endian): the 8-byte magic `TSV2BARS`, a `u32` format
+//! version and a reserved `u32`, then one 16-byte record per bar:
+//! `day: u32`, `minute: u32`, `price: f64`. That is exactly the in-memory
+//! layout of `PricePoint`, which is what makes the zero-copy view possible.
+
+use super::PricePoint;
+use crate::error::SimError;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"TSV2BARS";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 16;
+const RECORD_LEN: usize = std::mem::size_of::<PricePoint>();
+
+// The zero-copy view relies on `PricePoint` being exactly one record
+const _: () = assert!(RECORD_LEN == 16 && std::mem::align_of::<PricePoint>() <= 8);
+
+/// A read-only, memory-mapped bar file
+#[derive(Debug)]
+pub struct MappedBars {
+    map: Mmap,
+}
+
+impl MappedBars {
+    /// Map `path` and check its header, length and alignment
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SimError> {
+        if cfg!(target_endian = "big") {
+            return Err(SimError::Data("bar files can only be mapped on little-endian hosts".to_string()));
+        }
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        // Safety: the map is read-only; as with any mmap, the file must not be
+        // truncated by another process while mapped
+        let map = unsafe { Mmap::map(&file)? };
+
+        let invalid = |reason: &str| SimError::Data(format!("{}: {}", path.display(), reason));
+        if map.len() < HEADER_LEN || &map[..8] != MAGIC {
+            return Err(invalid("not a bar file"));
+        }
+        let version = u32::from_le_bytes([map[8], map[9], map[10], map[11]]);
+        if version != VERSION {
+            return Err(invalid(&format!("unsupported bar file version {}", version)));
+        }
+        if !(map.len() - HEADER_LEN).is_multiple_of(RECORD_LEN) {
+            return Err(invalid("truncated bar record"));
+        }
+        if map[HEADER_LEN..].as_ptr().align_offset(std::mem::align_of::<PricePoint>()) != 0 {
+            return Err(invalid("bar records are misaligned"));
+        }
+        Ok(Self { map })
+    }
+
+    /// The bars, viewed in place
+    pub fn bars(&self) -> &[PricePoint] {
+        let records = &self.map[HEADER_LEN..];
+        // Safety: `open` checked length and alignment, every bit pattern is a
+        // valid `u32`/`f64`, and the layout matches (`#[repr(C)]`, asserted above)
+        unsafe { std::slice::from_raw_parts(records.as_ptr().cast::<PricePoint>(), records.len() / RECORD_LEN) }
+    }
+
+    pub fn len(&self) -> usize {
+        (self.map.len() - HEADER_LEN) / RECORD_LEN
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Write `bars` as a bar file that `MappedBars::open` can map
+pub fn write_bars(path: impl AsRef<Path>, bars: &[PricePoint]) -> Result<(), SimError> {
+    let mut out = BufWriter::new(File::create(path)?);
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&0u32.to_le_bytes())?;
+    for bar in bars {
+        out.write_all(&bar.timestamp.day.to_le_bytes())?;
+        out.write_all(&bar.timestamp.minute.to_le_bytes())?;
+        out.write_all(&bar.price.to_le_bytes())?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::intraday::{Timestamp, TradingCalendar};
+    use crate::prices::GBM;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("tsv2_{}_{}.bars", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let bars = GBM::new(75.0, 0.0, 0.35, 42).generate_intraday_path(&TradingCalendar::new(), 5, 10, 0, 540);
+        let path = temp_path("round_trip");
+        write_bars(&path, &bars).unwrap();
+
+        let mapped = MappedBars::open(&path).unwrap();
+        assert_eq!(mapped.len(), bars.len());
+        assert_eq!(mapped.bars(), &bars[..]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_bad_files() {
+        let path = temp_path("bad");
+        std::fs::write(&path, b"day,minute,price\n").unwrap();
+        assert!(matches!(MappedBars::open(&path), Err(SimError::Data(_))));
+
+        write_bars(&path, &[PricePoint { timestamp: Timestamp::new(0, 540), price: 75.0 }]).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.pop();
+        std::fs::write(&path, bytes).unwrap();
+        assert!(MappedBars::open(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,14 +1,117 @@
 //! Price Generation Models
 //!
 //! Geometric Brownian Motion for simulating underlying price paths.
-//! Supports both daily and intraday (10-minute) resolution.
+//! Supports both daily and intraday (10-minute) resolution. `RealizedVol`
+//! estimates how much a path has moved, for filters, sizing and IV.
+//!
+//! Seeding scheme (a given seed and `RngKind` always give the same path):
+//! - `std`: `StdRng::seed_from_u64(seed)` and one Ziggurat `StandardNormal`
+//!   draw per bar. This is the default and what existing results used.
+//! - `xoshiro`: `Xoshiro256PlusPlus::seed_from_u64(seed)` (SplitMix64 expands
+//!   the seed into the 256-bit state). Normals come from Box-Muller over pairs
+//!   of uniforms, generated in batches of `NORMAL_BATCH`. Much faster on long
+//!   intraday paths, but a different path than `std` for the same seed.
+//!
+//! Correlated paths give each generator its own stream and mix the draws of
+//! every bar through the Cholesky factor of the correlation matrix, so the
+//! first path is exactly the one its generator would produce alone.
 
 use crate::calendar::intraday::{TradingCalendar, Timestamp};
+
+#[cfg(feature = "mmap")]
+pub mod mapped;
+mod realized;
+
+pub use realized::{RealizedIv, RealizedVol, VolEstimator};
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+/// Normals generated per refill in `xoshiro` mode
+const NORMAL_BATCH: usize = 1024;
+
+/// Random number generator behind a price path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RngKind {
+    #[default]
+    Std,
+    Xoshiro,
+}
+
+impl RngKind {
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "std" => Some(RngKind::Std),
+            "xoshiro" => Some(RngKind::Xoshiro),
+            _ => None,
+        }
+    }
+}
+
+/// Source of standard normal draws
+#[derive(Debug, Clone)]
+enum NormalSource {
+    Std(Box<StdRng>),
+    Xoshiro {
+        rng: Xoshiro256PlusPlus,
+        batch: Vec<f64>,
+        next: usize,
+    },
+}
+
+impl NormalSource {
+    fn new(kind: RngKind, seed: u64) -> Self {
+        match kind {
+            RngKind::Std => NormalSource::Std(Box::new(StdRng::seed_from_u64(seed))),
+            RngKind::Xoshiro => NormalSource::Xoshiro {
+                rng: Xoshiro256PlusPlus::seed_from_u64(seed),
+                batch: vec![0.0; NORMAL_BATCH],
+                next: NORMAL_BATCH,
+            },
+        }
+    }
+
+    fn kind(&self) -> RngKind {
+        match self {
+            NormalSource::Std(_) => RngKind::Std,
+            NormalSource::Xoshiro { .. } => RngKind::Xoshiro,
+        }
+    }
+
+    fn sample(&mut self) -> f64 {
+        match self {
+            NormalSource::Std(rng) => rng.sample(rand_distr::StandardNormal),
+            NormalSource::Xoshiro { rng, batch, next } => {
+                if *next == batch.len() {
+                    fill_box_muller(rng, batch);
+                    *next = 0;
+                }
+                let z = batch[*next];
+                *next += 1;
+                z
+            }
+        }
+    }
+}
+
+/// Fill `out` (even length) with standard normals, two per pair of uniforms
+fn fill_box_muller<R: Rng>(rng: &mut R, out: &mut [f64]) {
+    for pair in out.chunks_exact_mut(2) {
+        // u1 in (0, 1] so ln(u1) is finite
+        let u1 = 1.0 - rng.gen::<f64>();
+        let u2 = rng.gen::<f64>();
+        let radius = (-2.0 * u1.ln()).sqrt();
+        let (sin, cos) = (std::f64::consts::TAU * u2).sin_cos();
+        pair[0] = radius * cos;
+        pair[1] = radius * sin;
+    }
+}
 
 /// Price point at a specific timestamp
-#[derive(Debug, Clone, Copy)]
+///
+/// `#[repr(C)]` so bar files can be mapped straight into `&[PricePoint]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
 pub struct PricePoint {
     /// Timestamp (day and minute)
     pub timestamp: Timestamp,
@@ -26,7 +129,7 @@ pub struct GBM {
     /// Annual volatility (σ)
     volatility: f64,
     /// Random number generator
-    rng: StdRng,
+    rng: NormalSource,
 }
 
 impl GBM {
@@ -38,11 +141,16 @@ impl GBM {
     /// * `volatility` - Annual volatility (e.g., 0.30 for 30%)
     /// * `seed` - Random seed for reproducibility
     pub fn new(initial_price: f64, drift: f64, volatility: f64, seed: u64) -> Self {
+        Self::with_rng(initial_price, drift, volatility, seed, RngKind::Std)
+    }
+
+    /// Create a GBM generator driven by the given RNG (see the module docs)
+    pub fn with_rng(initial_price: f64, drift: f64, volatility: f64, seed: u64, rng: RngKind) -> Self {
         Self {
             initial_price,
             drift,
             volatility,
-            rng: StdRng::seed_from_u64(seed),
+            rng: NormalSource::new(rng, seed),
         }
     }
 
@@ -58,7 +166,7 @@ impl GBM {
             prices.push((day as u32, current_price));
             
             // GBM formula: dS = μS dt + σS dW
-            let z: f64 = self.rng.sample(rand_distr::StandardNormal);
+            let z = self.rng.sample();
             let brownian_motion = z * dt.sqrt();
             
             let drift_term = (self.drift - 0.5 * self.volatility.powi(2)) * dt;
@@ -100,15 +208,16 @@ impl GBM {
         // Calculate dt per interval in years
         let dt_years = interval_minutes as f64 / (365.25 * 24.0 * 60.0);
         
+        let sqrt_dt = dt_years.sqrt();
+        let drift_term = (self.drift - 0.5 * self.volatility.powi(2)) * dt_years;
+
         let mut points = Vec::with_capacity(timestamps.len());
         let mut current_price = self.initial_price;
         
         for timestamp in timestamps {
             // Generate next price using GBM
-            let z: f64 = self.rng.sample(rand_distr::StandardNormal);
-            let brownian_motion = z * dt_years.sqrt();
-            
-            let drift_term = (self.drift - 0.5 * self.volatility.powi(2)) * dt_years;
+            let z = self.rng.sample();
+            let brownian_motion = z * sqrt_dt;
             let diffusion_term = self.volatility * brownian_motion;
             
             current_price *= (drift_term + diffusion_term).exp();
@@ -122,12 +231,46 @@ impl GBM {
         points
     }
 
+    /// Intraday paths of several generators over the same timestamps, with
+    /// each bar's draws correlated by `cholesky` (see `cholesky`)
+    pub fn generate_correlated_intraday_paths(
+        generators: &mut [GBM],
+        cholesky: &[Vec<f64>],
+        calendar: &TradingCalendar,
+        num_days: usize,
+        interval_minutes: u32,
+        start_day: u32,
+        start_minute: u32,
+    ) -> Vec<Vec<PricePoint>> {
+        let points_per_day = (23 * 60) as usize / interval_minutes as usize;
+        let timestamps =
+            calendar.generate_trading_times(start_day, start_minute, num_days * points_per_day, interval_minutes);
+        let dt_years = interval_minutes as f64 / (365.25 * 24.0 * 60.0);
+        let sqrt_dt = dt_years.sqrt();
+
+        let mut prices: Vec<f64> = generators.iter().map(|g| g.initial_price).collect();
+        let mut paths = vec![Vec::with_capacity(timestamps.len()); generators.len()];
+        let mut draws = vec![0.0; generators.len()];
+        for timestamp in timestamps {
+            for (draw, generator) in draws.iter_mut().zip(generators.iter_mut()) {
+                *draw = generator.rng.sample();
+            }
+            for (i, generator) in generators.iter().enumerate() {
+                let z: f64 = cholesky[i].iter().zip(&draws).map(|(l, z)| l * z).sum();
+                let drift_term = (generator.drift - 0.5 * generator.volatility.powi(2)) * dt_years;
+                prices[i] *= (drift_term + generator.volatility * (z * sqrt_dt)).exp();
+                paths[i].push(PricePoint { timestamp, price: prices[i] });
+            }
+        }
+        paths
+    }
+
     /// Generate a single next price given current price
     ///
     /// Useful for step-by-step simulation
     pub fn next_price(&mut self, current_price: f64) -> f64 {
         let dt: f64 = 1.0 / 252.0;
-        let z: f64 = self.rng.sample(rand_distr::StandardNormal);
+        let z = self.rng.sample();
         let brownian_motion = z * dt.sqrt();
         
         let drift_term = (self.drift - 0.5 * self.volatility.powi(2)) * dt;
@@ -138,8 +281,36 @@ impl GBM {
 
     /// Reset with a new seed
     pub fn reseed(&mut self, seed: u64) {
-        self.rng = StdRng::seed_from_u64(seed);
+        self.rng = NormalSource::new(self.rng.kind(), seed);
+    }
+}
+
+/// Lower-triangular Cholesky factor of a correlation matrix (None unless the
+/// matrix is square, symmetric and positive definite)
+pub fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    if matrix.iter().any(|row| row.len() != n) {
+        return None;
+    }
+    let mut factor = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            if (matrix[i][j] - matrix[j][i]).abs() > 1e-12 {
+                return None;
+            }
+            let dot: f64 = (0..j).map(|k| factor[i][k] * factor[j][k]).sum();
+            if i == j {
+                let pivot = matrix[i][i] - dot;
+                if pivot.is_nan() || pivot <= 0.0 {
+                    return None;
+                }
+                factor[i][i] = pivot.sqrt();
+            } else {
+                factor[i][j] = (matrix[i][j] - dot) / factor[j][j];
+            }
+        }
     }
+    Some(factor)
 }
 
 /// Simple deterministic price generator for testing
@@ -192,6 +363,57 @@ mod tests {
         assert_eq!(path[0].1, 75.0);
     }
 
+    #[test]
+    fn test_xoshiro_paths() {
+        let calendar = TradingCalendar::new();
+        let path = |seed, rng| {
+            GBM::with_rng(75.0, 0.0, 0.30, seed, rng).generate_intraday_path(&calendar, 20, 10, 0, 540)
+        };
+        let a = path(42, RngKind::Xoshiro);
+        assert_eq!(a.len(), path(42, RngKind::Std).len());
+        // Reproducible per seed, and independent of the std stream
+        assert!(a.iter().zip(path(42, RngKind::Xoshiro)).all(|(x, y)| x.price == y.price));
+        assert!(a.iter().zip(path(43, RngKind::Xoshiro)).any(|(x, y)| x.price != y.price));
+        assert!(a.iter().zip(path(42, RngKind::Std)).any(|(x, y)| x.price != y.price));
+    }
+
+    #[test]
+    fn test_box_muller_moments() {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(7);
+        let mut normals = vec![0.0; 200_000];
+        fill_box_muller(&mut rng, &mut normals);
+        let n = normals.len() as f64;
+        let mean = normals.iter().sum::<f64>() / n;
+        let var = normals.iter().map(|z| (z - mean).powi(2)).sum::<f64>() / n;
+        assert!(mean.abs() < 0.01);
+        assert!((var - 1.0).abs() < 0.01);
+        assert!(normals.iter().all(|z| z.is_finite()));
+    }
+
+    #[test]
+    fn test_correlated_paths() {
+        let calendar = TradingCalendar::new();
+        let factor = cholesky(&[vec![1.0, 0.8], vec![0.8, 1.0]]).unwrap();
+        let mut generators = [GBM::new(75.0, 0.0, 0.30, 42), GBM::new(5000.0, 0.0, 0.15, 43)];
+        let paths = GBM::generate_correlated_intraday_paths(&mut generators, &factor, &calendar, 60, 10, 0, 540);
+
+        // The first path is the one its generator makes alone
+        let alone = GBM::new(75.0, 0.0, 0.30, 42).generate_intraday_path(&calendar, 60, 10, 0, 540);
+        assert!(paths[0].iter().zip(&alone).all(|(a, b)| a == b));
+        let returns =
+            |path: &[PricePoint]| -> Vec<f64> { path.windows(2).map(|w| (w[1].price / w[0].price).ln()).collect() };
+        let (a, b) = (returns(&paths[0]), returns(&paths[1]));
+        let mean = |x: &[f64]| x.iter().sum::<f64>() / x.len() as f64;
+        let (ma, mb) = (mean(&a), mean(&b));
+        let cov: f64 = a.iter().zip(&b).map(|(x, y)| (x - ma) * (y - mb)).sum();
+        let var = |x: &[f64], m: f64| x.iter().map(|v| (v - m).powi(2)).sum::<f64>();
+        let rho = cov / (var(&a, ma) * var(&b, mb)).sqrt();
+        assert!((rho - 0.8).abs() < 0.05, "correlation {}", rho);
+
+        assert!(cholesky(&[vec![1.0, 1.0], vec![1.0, 1.0]]).is_none());
+        assert!(cholesky(&[vec![1.0, 0.5], vec![0.4, 1.0]]).is_none());
+    }
+
     #[test]
     fn test_deterministic_price() {
         let price_gen = DeterministicPrice::new(75.0, 0.5, 0.1);
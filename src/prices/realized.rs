@@ -0,0 +1,156 @@
+//! Realized Volatility
+//!
+//! Rolling estimates of how much the underlying has actually moved, taken
+//! from the bars of a price path (generated or imported). Each trading
+//! session (see `Timestamp::session_day`) contributes its close, high and
+//! low, and the estimate for a day only uses sessions that ended before it,
+//! so entry filters, sizing and IV never look ahead of the bar they act on.
+//!
+//! - close-to-close: sample deviation of log returns between session closes
+//! - Parkinson: mean of ln(high / low)² / (4 ln 2) over each session's range
+//! - EWMA: RiskMetrics-style weighting of squared close-to-close returns
+//!
+//! All estimates are annualized at 252 sessions a year.
+
+use super::PricePoint;
+use crate::pricing::AtmVolSource;
+use std::sync::Arc;
+
+/// Sessions per year the estimates are annualized at
+const SESSIONS_PER_YEAR: f64 = 252.0;
+
+/// How realized vol is estimated from session prices
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VolEstimator {
+    CloseToClose,
+    Parkinson,
+    /// Each session keeps `lambda` of the previous variance (e.g. 0.94)
+    Ewma { lambda: f64 },
+}
+
+impl VolEstimator {
+    /// Parse `realized_vol.estimator` ("close_to_close", "parkinson" or "ewma")
+    pub fn from_config(value: &str, lambda: f64) -> Option<Self> {
+        match value {
+            "close_to_close" => Some(VolEstimator::CloseToClose),
+            "parkinson" => Some(VolEstimator::Parkinson),
+            "ewma" => Some(VolEstimator::Ewma { lambda }),
+            _ => None,
+        }
+    }
+}
+
+/// Close, high and low of one session
+#[derive(Debug, Clone, Copy)]
+struct Session {
+    day: u32,
+    close: f64,
+    high: f64,
+    low: f64,
+}
+
+/// Realized vol by day, estimated over a rolling window of sessions
+#[derive(Debug, Clone)]
+pub struct RealizedVol {
+    /// (session day, estimate through the end of that session), by day
+    estimates: Vec<(u32, f64)>,
+}
+
+impl RealizedVol {
+    /// Estimates over the last `window` sessions (returns, for the close
+    /// estimators) at the end of every session of `bars` that has that many
+    pub fn from_bars(bars: &[PricePoint], estimator: VolEstimator, window: usize) -> Self {
+        let mut sessions: Vec<Session> = Vec::new();
+        for bar in bars {
+            let (day, price) = (bar.timestamp.session_day(), bar.price);
+            match sessions.last_mut() {
+                Some(session) if session.day == day => {
+                    session.close = price;
+                    session.high = session.high.max(price);
+                    session.low = session.low.min(price);
+                }
+                _ => sessions.push(Session { day, close: price, high: price, low: price }),
+            }
+        }
+
+        let returns: Vec<f64> = sessions.windows(2).map(|pair| (pair[1].close / pair[0].close).ln()).collect();
+        let mut estimates = Vec::new();
+        let mut ewma: Option<f64> = None;
+        for (i, session) in sessions.iter().enumerate() {
+            let variance = match estimator {
+                // Session i closes on return i - 1
+                VolEstimator::CloseToClose if i >= window && window >= 2 => {
+                    let window = &returns[i - window..i];
+                    let mean = window.iter().sum::<f64>() / window.len() as f64;
+                    Some(window.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (window.len() - 1) as f64)
+                }
+                VolEstimator::Parkinson if i + 1 >= window && window >= 1 => {
+                    let ranges = sessions[i + 1 - window..=i].iter().map(|s| (s.high / s.low).ln().powi(2));
+                    Some(ranges.sum::<f64>() / (4.0 * std::f64::consts::LN_2 * window as f64))
+                }
+                VolEstimator::Ewma { lambda } if i >= 1 => {
+                    let squared = returns[i - 1].powi(2);
+                    let variance = ewma.map_or(squared, |variance| lambda * variance + (1.0 - lambda) * squared);
+                    ewma = Some(variance);
+                    ewma.filter(|_| i >= window.max(1))
+                }
+                _ => None,
+            };
+            if let Some(variance) = variance {
+                estimates.push((session.day, (variance * SESSIONS_PER_YEAR).sqrt()));
+            }
+        }
+        Self { estimates }
+    }
+
+    /// Estimate from the sessions that ended before `day`'s session began
+    pub fn vol(&self, day: u32) -> Option<f64> {
+        let before = self.estimates.partition_point(|&(session, _)| session < day);
+        before.checked_sub(1).map(|i| self.estimates[i].1)
+    }
+}
+
+/// Realized vol plus a premium as the at-the-money IV
+#[derive(Debug, Clone)]
+pub struct RealizedIv {
+    pub realized: Arc<RealizedVol>,
+    /// Added to the estimate, e.g. `volatility_risk_premium`
+    pub premium: f64,
+}
+
+impl AtmVolSource for RealizedIv {
+    fn atm_vol(&self, day: u32, _dte: f64) -> Option<f64> {
+        self.realized.vol(day).map(|vol| vol + self.premium)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::intraday::{Timestamp, TradingCalendar};
+    use crate::prices::GBM;
+
+    #[test]
+    fn test_estimators_recover_the_path_vol_without_looking_ahead() {
+        let mut gbm = GBM::new(75.0, 0.0, 0.30, 7);
+        let bars = gbm.generate_intraday_path(&TradingCalendar::new(), 300, 10, 0, 9 * 60);
+        for estimator in [VolEstimator::CloseToClose, VolEstimator::Parkinson, VolEstimator::Ewma { lambda: 0.94 }] {
+            let realized = RealizedVol::from_bars(&bars, estimator, 20);
+            let last = bars.last().unwrap().timestamp.session_day() + 1;
+            let vol = realized.vol(last).unwrap();
+            // GBM vol is per calendar year, so a trading-session year sees a little less of it
+            assert!(vol > 0.15 && vol < 0.40, "{:?} estimated {}", estimator, vol);
+            assert_eq!(realized.vol(bars[0].timestamp.session_day() + 5), None);
+        }
+
+        // A day's estimate ignores that day's session, however wild it gets
+        let calm: Vec<PricePoint> = (0..5 * 24)
+            .map(|hour| PricePoint { timestamp: Timestamp::new(hour / 24, (hour % 24) * 60), price: 100.0 })
+            .collect();
+        let mut wild = calm.clone();
+        wild.iter_mut().filter(|bar| bar.timestamp.session_day() == 4).for_each(|bar| bar.price = 150.0);
+        let estimate = |bars: &[PricePoint]| RealizedVol::from_bars(bars, VolEstimator::Parkinson, 2).vol(4);
+        assert_eq!(estimate(&calm), Some(0.0));
+        assert_eq!(estimate(&wild), estimate(&calm));
+    }
+}
@@ -0,0 +1,205 @@
+//! Valuation Cache
+//!
+//! Marks, margin and Greeks all value the same legs on every bar; this cache
+//! keys each leg by (expiration, strike, put/call) and remembers its last
+//! inputs. An exact input match returns the stored `Valuation`; anything else
+//! is recomputed with the cache's `PricingModel`, so cached and uncached runs
+//! produce identical numbers.
+//!
+//! With a tolerance set, Black-76 legs also keep the log-moneyness, √T and
+//! discount factor of their last full evaluation. While the futures price and
+//! time to expiry stay within that relative tolerance of it (the next few
+//! bars), those terms are moved along by short series instead of `ln`,
+//! `sqrt` and `exp`; past it, or when the rate or vol changes, the leg is
+//! recomputed exactly and becomes the new anchor.
+
+use super::{Black76, Black76Terms, PricingModel, Valuation};
+use std::collections::HashMap;
+
+/// (expiration day, strike bits, is_call)
+type LegKey = (u32, u64, bool);
+
+/// A full Black-76 evaluation that later inputs are updated from
+#[derive(Debug, Clone, Copy)]
+struct Anchor {
+    inputs: [f64; 4],
+    terms: Black76Terms,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    /// Futures price, time to expiry, rate and volatility the value was computed at
+    inputs: [f64; 4],
+    valuation: Valuation,
+    anchor: Option<Anchor>,
+}
+
+/// Per-leg valuations, reused while the inputs don't change
+#[derive(Debug, Clone, Default)]
+pub struct ValuationCache {
+    model: PricingModel,
+    /// Largest relative move in futures price or time to expiry answered by
+    /// updating an anchor (0 for exact matches only)
+    tolerance: f64,
+    entries: HashMap<LegKey, Entry>,
+    hits: u64,
+    updates: u64,
+    misses: u64,
+}
+
+impl ValuationCache {
+    /// A cache that values legs with Black-76
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A cache that values legs with `model`
+    pub fn with_model(model: PricingModel) -> Self {
+        Self { model, ..Self::default() }
+    }
+
+    /// Update Black-76 legs from their anchor while the futures price and time
+    /// to expiry are within `tolerance` (e.g. 0.01 for 1%) of it
+    pub fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Value one leg, reusing the last result if nothing changed
+    #[allow(clippy::too_many_arguments)]
+    pub fn value(
+        &mut self,
+        expiration_day: u32,
+        strike: f64,
+        is_call: bool,
+        futures_price: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+    ) -> Valuation {
+        let inputs = [futures_price, time_to_expiry, risk_free_rate, volatility];
+        let key = (expiration_day, strike.to_bits(), is_call);
+        if let Some(entry) = self.entries.get_mut(&key) {
+            if entry.inputs.iter().zip(&inputs).all(|(a, b)| a.to_bits() == b.to_bits()) {
+                self.hits += 1;
+                return entry.valuation;
+            }
+            if let Some(terms) = entry.anchor.and_then(|anchor| anchor.update(&inputs, self.tolerance)) {
+                self.hits += 1;
+                self.updates += 1;
+                entry.inputs = inputs;
+                entry.valuation = Black76::value_with_terms(
+                    futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call, terms,
+                );
+                return entry.valuation;
+            }
+        }
+
+        self.misses += 1;
+        let valuation = self.model.value(futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call);
+        let anchor = (self.tolerance > 0.0 && self.model == PricingModel::Black76 && time_to_expiry > 0.0).then(|| {
+            Anchor { inputs, terms: Black76::terms(futures_price, strike, time_to_expiry, risk_free_rate) }
+        });
+        self.entries.insert(key, Entry { inputs, valuation, anchor });
+        valuation
+    }
+
+    /// Drop every leg (e.g. once the position holding them is closed)
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Lookups answered without a full evaluation
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Hits answered by updating an anchor rather than an exact match
+    pub fn updates(&self) -> u64 {
+        self.updates
+    }
+
+    /// Lookups that needed a full evaluation
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+}
+
+impl Anchor {
+    /// The anchor's terms moved to `inputs`, if the rate and vol are unchanged
+    /// and the futures price and time to expiry are within `tolerance`
+    fn update(&self, inputs: &[f64; 4], tolerance: f64) -> Option<Black76Terms> {
+        let [futures_price, time_to_expiry, rate, vol] = *inputs;
+        let [anchor_price, anchor_time, anchor_rate, anchor_vol] = self.inputs;
+        if rate.to_bits() != anchor_rate.to_bits() || vol.to_bits() != anchor_vol.to_bits() || time_to_expiry <= 0.0 {
+            return None;
+        }
+        let x = futures_price / anchor_price - 1.0;
+        let y = time_to_expiry / anchor_time - 1.0;
+        if !(x.abs() <= tolerance && y.abs() <= tolerance) {
+            return None;
+        }
+        // Third-order series for ln(1 + x), √(1 + y) and e^z: errors of order tolerance⁴
+        let z = -rate * (time_to_expiry - anchor_time);
+        Some(Black76Terms {
+            ln_moneyness: self.terms.ln_moneyness + x - x * x / 2.0 + x * x * x / 3.0,
+            sqrt_t: self.terms.sqrt_t * (1.0 + y / 2.0 - y * y / 8.0 + y * y * y / 16.0),
+            discount: self.terms.discount * (1.0 + z + z * z / 2.0 + z * z * z / 6.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_reuses_identical_inputs() {
+        let mut cache = ValuationCache::new();
+        let first = cache.value(5, 75.0, true, 75.3, 0.01, 0.05, 0.35);
+        let again = cache.value(5, 75.0, true, 75.3, 0.01, 0.05, 0.35);
+        assert_eq!(first, again);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // Any input change recomputes exactly
+        let moved = cache.value(5, 75.0, true, 75.4, 0.01, 0.05, 0.35);
+        assert_eq!(moved, crate::pricing::Black76::value(75.4, 75.0, 0.01, 0.05, 0.35, true));
+        assert_eq!(cache.misses(), 2);
+
+        // Puts and calls at the same strike are separate legs
+        cache.value(5, 75.0, false, 75.4, 0.01, 0.05, 0.35);
+        assert_eq!(cache.misses(), 3);
+    }
+
+    #[test]
+    fn test_cache_updates_across_consecutive_bars() {
+        let mut cache = ValuationCache::new().with_tolerance(0.01);
+        // Ten-minute bars on a 1DTE put: the price drifts and T shrinks every bar
+        let bar = 10.0 / (60.0 * 24.0 * 365.0);
+        for i in 0..12 {
+            let (price, time) = (75.3 + 0.04 * i as f64, 1.0 / 365.0 - i as f64 * bar);
+            let value = cache.value(5, 75.0, false, price, time, 0.05, 0.35);
+            let exact = Black76::value(price, 75.0, time, 0.05, 0.35, false);
+            assert!((value.price - exact.price).abs() < 1e-9, "bar {}", i);
+            assert!((value.greeks.delta - exact.greeks.delta).abs() < 1e-9, "bar {}", i);
+            assert!((value.greeks.theta - exact.greeks.theta).abs() < 1e-9, "bar {}", i);
+        }
+        // T falls about 0.7% a bar, so the anchor is renewed every other bar or so
+        assert!(cache.updates() >= 5, "{} updates", cache.updates());
+        assert_eq!(cache.hits() + cache.misses(), 12);
+
+        // A jump past the tolerance, or a new vol, is recomputed exactly
+        let misses = cache.misses();
+        let jumped = cache.value(5, 75.0, false, 80.0, 0.5 / 365.0, 0.05, 0.35);
+        assert_eq!(jumped, Black76::value(80.0, 75.0, 0.5 / 365.0, 0.05, 0.35, false));
+        let revalued = cache.value(5, 75.0, false, 80.0, 0.5 / 365.0, 0.05, 0.36);
+        assert_eq!(revalued, Black76::value(80.0, 75.0, 0.5 / 365.0, 0.05, 0.36, false));
+        assert_eq!(cache.misses(), misses + 2);
+
+        // Without a tolerance every changed bar is a full evaluation
+        let mut exact = ValuationCache::new();
+        exact.value(5, 75.0, false, 75.3, 1.0 / 365.0, 0.05, 0.35);
+        exact.value(5, 75.0, false, 75.34, 1.0 / 365.0 - bar, 0.05, 0.35);
+        assert_eq!((exact.hits(), exact.misses()), (0, 2));
+    }
+}
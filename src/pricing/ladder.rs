@@ -0,0 +1,241 @@
+//! Batch Greeks over a Strike Ladder
+//!
+//! Delta- and premium-based strike selection value every strike on the
+//! ladder at the same futures price, expiry and vol. `Black76::ladder`
+//! evaluates them in struct-of-arrays form: shared terms are computed once
+//! and each stage is a straight loop over contiguous `f64`s that the compiler
+//! can vectorize. Results match `Black76::value` bit for bit, with one vol
+//! for the whole ladder or one per strike from the smile.
+
+use super::{norm_cdf, norm_pdf, Black76, Greeks, PricingModel};
+
+/// Prices and Greeks for each strike, one array per field
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LadderGreeks {
+    pub strikes: Vec<f64>,
+    pub price: Vec<f64>,
+    pub delta: Vec<f64>,
+    pub gamma: Vec<f64>,
+    /// Per day
+    pub theta: Vec<f64>,
+    /// Per vol point
+    pub vega: Vec<f64>,
+    pub rho: Vec<f64>,
+}
+
+impl LadderGreeks {
+    pub fn len(&self) -> usize {
+        self.strikes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strikes.is_empty()
+    }
+
+    /// Greeks of the strike at `index`
+    pub fn greeks(&self, index: usize) -> Greeks {
+        Greeks {
+            delta: self.delta[index],
+            gamma: self.gamma[index],
+            theta: self.theta[index],
+            vega: self.vega[index],
+            rho: self.rho[index],
+        }
+    }
+
+    /// Strike whose absolute delta is closest to `target` (e.g. 0.16)
+    pub fn strike_for_delta(&self, target: f64) -> Option<f64> {
+        self.nearest(&self.delta, |delta| (delta.abs() - target).abs())
+    }
+
+    /// Strike whose premium is closest to `target`
+    pub fn strike_for_premium(&self, target: f64) -> Option<f64> {
+        self.nearest(&self.price, |price| (price - target).abs())
+    }
+
+    /// Strike minimising `distance` over `values` (first one on ties)
+    fn nearest(&self, values: &[f64], distance: impl Fn(f64) -> f64) -> Option<f64> {
+        let mut best: Option<(usize, f64)> = None;
+        for (i, &value) in values.iter().enumerate() {
+            let d = distance(value);
+            if best.is_none_or(|(_, best_d)| d < best_d) {
+                best = Some((i, d));
+            }
+        }
+        best.map(|(i, _)| self.strikes[i])
+    }
+}
+
+impl Black76 {
+    /// Value every strike in `strikes` at one futures price, expiry and vol
+    pub fn ladder(
+        futures_price: f64,
+        strikes: &[f64],
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        is_call: bool,
+    ) -> LadderGreeks {
+        let vols = vec![volatility; strikes.len()];
+        Self::smile_ladder(futures_price, strikes, time_to_expiry, risk_free_rate, &vols, is_call)
+    }
+
+    /// `ladder` with each strike at its own vol (`vols[i]` for `strikes[i]`)
+    pub fn smile_ladder(
+        futures_price: f64,
+        strikes: &[f64],
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        vols: &[f64],
+        is_call: bool,
+    ) -> LadderGreeks {
+        assert_eq!(strikes.len(), vols.len(), "one vol per strike");
+        let n = strikes.len();
+        let mut out = LadderGreeks {
+            strikes: strikes.to_vec(),
+            price: vec![0.0; n],
+            delta: vec![0.0; n],
+            gamma: vec![0.0; n],
+            theta: vec![0.0; n],
+            vega: vec![0.0; n],
+            rho: vec![0.0; n],
+        };
+
+        if time_to_expiry <= 0.0 {
+            // Intrinsic only; nothing to share between strikes
+            for (i, &strike) in strikes.iter().enumerate() {
+                let v = Self::value(futures_price, strike, time_to_expiry, risk_free_rate, vols[i], is_call);
+                out.price[i] = v.price;
+                out.delta[i] = v.greeks.delta;
+            }
+            return out;
+        }
+
+        // Terms shared by every strike
+        let sqrt_t = time_to_expiry.sqrt();
+        let discount = (-risk_free_rate * time_to_expiry).exp();
+
+        let vol_sqrt_t: Vec<f64> = vols.iter().map(|&vol| vol * sqrt_t).collect();
+        let d1: Vec<f64> = (0..n)
+            .map(|i| ((futures_price / strikes[i]).ln() + (vols[i].powi(2) / 2.0) * time_to_expiry) / vol_sqrt_t[i])
+            .collect();
+        let d2: Vec<f64> = d1.iter().zip(&vol_sqrt_t).map(|(&d, &v)| d - v).collect();
+        let pdf: Vec<f64> = d1.iter().map(|&d| norm_pdf(d)).collect();
+        let (nd1, nd2): (Vec<f64>, Vec<f64>) = if is_call {
+            (d1.iter().map(|&d| norm_cdf(d)).collect(), d2.iter().map(|&d| norm_cdf(d)).collect())
+        } else {
+            (d1.iter().map(|&d| norm_cdf(-d)).collect(), d2.iter().map(|&d| norm_cdf(-d)).collect())
+        };
+
+        for i in 0..n {
+            let strike = strikes[i];
+            let undiscounted = if is_call {
+                futures_price * nd1[i] - strike * nd2[i]
+            } else {
+                strike * nd2[i] - futures_price * nd1[i]
+            };
+            out.price[i] = discount * undiscounted;
+            out.rho[i] = -time_to_expiry * discount * undiscounted;
+        }
+
+        for i in 0..n {
+            let decay = -futures_price * discount * pdf[i] * vols[i] / (2.0 * sqrt_t);
+            out.gamma[i] = discount * pdf[i] / (futures_price * vols[i] * sqrt_t);
+            out.vega[i] = futures_price * discount * pdf[i] * sqrt_t / 100.0;
+            out.theta[i] = if is_call {
+                decay - risk_free_rate * strikes[i] * discount * nd2[i]
+            } else {
+                decay + risk_free_rate * strikes[i] * discount * nd2[i]
+            } / 365.0;
+        }
+
+        out.delta = if is_call {
+            nd1.iter().map(|&n| discount * n).collect()
+        } else {
+            // N(d1) - 1, not 1 - N(-d1), to match the scalar pricer exactly
+            d1.iter().map(|&d| discount * (norm_cdf(d) - 1.0)).collect()
+        };
+
+        out
+    }
+}
+
+impl PricingModel {
+    /// Value every strike in `strikes` at its vol in `vols`: the batch
+    /// Black-76 ladder for futures, one strike at a time for spot options
+    pub fn ladder(
+        self,
+        underlying: f64,
+        strikes: &[f64],
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        vols: &[f64],
+        is_call: bool,
+    ) -> LadderGreeks {
+        if self == PricingModel::Black76 {
+            return Black76::smile_ladder(underlying, strikes, time_to_expiry, risk_free_rate, vols, is_call);
+        }
+        let mut out = LadderGreeks { strikes: strikes.to_vec(), ..LadderGreeks::default() };
+        for (&strike, &vol) in strikes.iter().zip(vols) {
+            let v = self.value(underlying, strike, time_to_expiry, risk_free_rate, vol, is_call);
+            out.price.push(v.price);
+            out.delta.push(v.greeks.delta);
+            out.gamma.push(v.greeks.gamma);
+            out.theta.push(v.greeks.theta);
+            out.vega.push(v.greeks.vega);
+            out.rho.push(v.greeks.rho);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strikes() -> Vec<f64> {
+        (0..41).map(|i| 65.0 + 0.5 * i as f64).collect()
+    }
+
+    #[test]
+    fn test_ladder_matches_value() {
+        for &(t, is_call) in &[(1.0 / 252.0, true), (30.0 / 252.0, false), (0.0, true)] {
+            let ladder = Black76::ladder(75.3, &strikes(), t, 0.05, 0.35, is_call);
+            assert_eq!(ladder.len(), 41);
+            for (i, &k) in strikes().iter().enumerate() {
+                let v = Black76::value(75.3, k, t, 0.05, 0.35, is_call);
+                assert_eq!(ladder.price[i], v.price, "K={} T={}", k, t);
+                assert_eq!(ladder.greeks(i), v.greeks, "K={} T={}", k, t);
+            }
+        }
+    }
+
+    #[test]
+    fn test_smile_ladder_matches_value() {
+        let vols: Vec<f64> = strikes().iter().map(|k| 0.35 + 0.002 * (75.0 - k)).collect();
+        let ladder = Black76::smile_ladder(75.3, &strikes(), 30.0 / 252.0, 0.05, &vols, false);
+        let spot = PricingModel::BlackScholes { dividend_yield: 0.02 };
+        let spot_ladder = spot.ladder(75.3, &strikes(), 30.0 / 252.0, 0.05, &vols, false);
+        for (i, &k) in strikes().iter().enumerate() {
+            let v = Black76::value(75.3, k, 30.0 / 252.0, 0.05, vols[i], false);
+            assert_eq!(ladder.price[i], v.price, "K={}", k);
+            assert_eq!(ladder.greeks(i), v.greeks, "K={}", k);
+            assert_eq!(spot_ladder.greeks(i), spot.greeks(75.3, k, 30.0 / 252.0, 0.05, vols[i], false));
+        }
+    }
+
+    #[test]
+    fn test_strike_lookups() {
+        let puts = Black76::ladder(75.0, &strikes(), 30.0 / 252.0, 0.05, 0.35, false);
+        let k16 = puts.strike_for_delta(0.16).unwrap();
+        assert!(k16 < 75.0);
+        let i = puts.strikes.iter().position(|&k| k == k16).unwrap();
+        assert!((puts.delta[i].abs() - 0.16).abs() < 0.03);
+
+        let k = puts.strike_for_premium(1.0).unwrap();
+        let i = puts.strikes.iter().position(|&s| s == k).unwrap();
+        assert!((puts.price[i] - 1.0).abs() < 0.2);
+
+        assert_eq!(Black76::ladder(75.0, &[], 0.1, 0.05, 0.35, true).strike_for_delta(0.5), None);
+    }
+}
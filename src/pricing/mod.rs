@@ -1,6 +1,21 @@
 //! Option Pricing Models
 //!
-//! Black-Scholes for stocks, Black-76 for futures options (/CL)
+//! Black-Scholes for stocks and cash indices, Black-76 for futures options
+//! (/CL). `PricingModel` is the one the configured product uses.
+
+use crate::error::SimError;
+use serde::Serialize;
+
+mod cache;
+mod ladder;
+mod strikes;
+mod surface;
+
+pub use cache::ValuationCache;
+pub use ladder::LadderGreeks;
+pub use strikes::StrikeLadder;
+pub use surface::{AtmVolSource, Smile, VolSurface};
+pub(crate) use surface::interpolate;
 
 /// Standard normal cumulative distribution function
 fn norm_cdf(x: f64) -> f64 {
@@ -26,7 +41,8 @@ fn erf(x: f64) -> f64 {
 }
 
 /// Greeks for an option
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Greeks {
     pub delta: f64,
     pub gamma: f64,
@@ -35,6 +51,38 @@ pub struct Greeks {
     pub rho: f64,
 }
 
+impl Greeks {
+    /// Greeks of `quantity` contracts
+    pub fn scaled(self, quantity: f64) -> Self {
+        Self {
+            delta: self.delta * quantity,
+            gamma: self.gamma * quantity,
+            theta: self.theta * quantity,
+            vega: self.vega * quantity,
+            rho: self.rho * quantity,
+        }
+    }
+}
+
+/// Price and Greeks of one option from a single evaluation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Valuation {
+    pub price: f64,
+    pub greeks: Greeks,
+}
+
+/// The parts of a Black-76 valuation shared by puts and calls that
+/// `ValuationCache` can carry from bar to bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Black76Terms {
+    /// ln(F/K)
+    ln_moneyness: f64,
+    /// √T
+    sqrt_t: f64,
+    /// e^(-rT)
+    discount: f64,
+}
+
 /// Black-76 model for futures options (used for /CL)
 ///
 /// Black-76 is like Black-Scholes but uses the futures price directly
@@ -84,6 +132,27 @@ impl Black76 {
         }
     }
 
+    /// `price` with input checks: prices must be positive and finite, and
+    /// volatility positive while there is time left
+    pub fn try_price(
+        futures_price: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        is_call: bool,
+    ) -> Result<f64, SimError> {
+        check_inputs("futures price", futures_price, strike, time_to_expiry, volatility)?;
+        let price = Self::price(futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call);
+        if !price.is_finite() {
+            return Err(SimError::Pricing(format!(
+                "non-finite price for F={} K={} T={}",
+                futures_price, strike, time_to_expiry
+            )));
+        }
+        Ok(price)
+    }
+
     /// Calculate Greeks for a futures option
     pub fn greeks(
         futures_price: f64,
@@ -149,6 +218,84 @@ impl Black76 {
         }
     }
 
+    /// Price and Greeks together, sharing d1/d2 and the discount factor
+    ///
+    /// Bit-for-bit identical to calling `price` and `greeks` separately.
+    pub fn value(
+        futures_price: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        is_call: bool,
+    ) -> Valuation {
+        if time_to_expiry <= 0.0 {
+            return Valuation {
+                price: Self::price(futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call),
+                greeks: Self::greeks(futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call),
+            };
+        }
+
+        let terms = Self::terms(futures_price, strike, time_to_expiry, risk_free_rate);
+        Self::value_with_terms(futures_price, strike, time_to_expiry, risk_free_rate, volatility, is_call, terms)
+    }
+
+    /// Log-moneyness, √T and discount factor of a valuation
+    fn terms(futures_price: f64, strike: f64, time_to_expiry: f64, risk_free_rate: f64) -> Black76Terms {
+        Black76Terms {
+            ln_moneyness: (futures_price / strike).ln(),
+            sqrt_t: time_to_expiry.sqrt(),
+            discount: (-risk_free_rate * time_to_expiry).exp(),
+        }
+    }
+
+    /// `value` from precomputed `terms` (time left must be positive)
+    fn value_with_terms(
+        futures_price: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        is_call: bool,
+        terms: Black76Terms,
+    ) -> Valuation {
+        let Black76Terms { ln_moneyness, sqrt_t, discount } = terms;
+        // Same operations as `d1`, so exact terms give the same bits
+        let d1 = (ln_moneyness + (volatility.powi(2) / 2.0) * time_to_expiry) / (volatility * sqrt_t);
+        let d2 = d1 - volatility * sqrt_t;
+        let (nd1, nd2) = (norm_cdf(d1), norm_cdf(d2));
+        let pdf = norm_pdf(d1);
+
+        let undiscounted = if is_call {
+            futures_price * nd1 - strike * nd2
+        } else {
+            strike * norm_cdf(-d2) - futures_price * norm_cdf(-d1)
+        };
+        let price = discount * undiscounted;
+
+        let delta = if is_call { discount * nd1 } else { discount * (nd1 - 1.0) };
+        let gamma = discount * pdf / (futures_price * volatility * sqrt_t);
+        let decay = -futures_price * discount * pdf * volatility / (2.0 * sqrt_t);
+        let theta = if is_call {
+            decay - risk_free_rate * strike * discount * nd2
+        } else {
+            decay + risk_free_rate * strike * discount * norm_cdf(-d2)
+        };
+        let vega = futures_price * discount * pdf * sqrt_t / 100.0;
+        let rho = -time_to_expiry * discount * undiscounted;
+
+        Valuation {
+            price,
+            greeks: Greeks {
+                delta,
+                gamma,
+                theta: theta / 365.0,
+                vega,
+                rho,
+            },
+        }
+    }
+
     /// Calculate implied volatility from market price
     ///
     /// Uses Newton-Raphson iteration
@@ -189,7 +336,7 @@ impl Black76 {
     }
 
     fn d1(f: f64, k: f64, t: f64, sigma: f64) -> f64 {
-        (f / k).ln() + (sigma.powi(2) / 2.0) * t / (sigma * t.sqrt())
+        ((f / k).ln() + (sigma.powi(2) / 2.0) * t) / (sigma * t.sqrt())
     }
 
     fn d2(f: f64, k: f64, t: f64, sigma: f64) -> f64 {
@@ -237,6 +384,48 @@ impl BlackScholes {
         }
     }
 
+    /// Calculate Greeks for a spot option paying a continuous `dividend_yield`
+    pub fn greeks(
+        spot_price: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        dividend_yield: f64,
+        volatility: f64,
+        is_call: bool,
+    ) -> Greeks {
+        if time_to_expiry <= 0.0 {
+            // Same step at expiry as the futures model
+            return Black76::greeks(spot_price, strike, time_to_expiry, risk_free_rate, volatility, is_call);
+        }
+
+        let sqrt_t = time_to_expiry.sqrt();
+        let d1 = Self::d1(spot_price, strike, time_to_expiry, risk_free_rate, dividend_yield, volatility);
+        let d2 = d1 - volatility * sqrt_t;
+        let carry = (-dividend_yield * time_to_expiry).exp();
+        let discount = (-risk_free_rate * time_to_expiry).exp();
+        let pdf = norm_pdf(d1);
+
+        let delta = if is_call { carry * norm_cdf(d1) } else { carry * (norm_cdf(d1) - 1.0) };
+        let gamma = carry * pdf / (spot_price * volatility * sqrt_t);
+        let decay = -spot_price * carry * pdf * volatility / (2.0 * sqrt_t);
+        let theta = if is_call {
+            decay - risk_free_rate * strike * discount * norm_cdf(d2)
+                + dividend_yield * spot_price * carry * norm_cdf(d1)
+        } else {
+            decay + risk_free_rate * strike * discount * norm_cdf(-d2)
+                - dividend_yield * spot_price * carry * norm_cdf(-d1)
+        };
+        let vega = spot_price * carry * pdf * sqrt_t / 100.0;
+        let rho = if is_call {
+            strike * time_to_expiry * discount * norm_cdf(d2)
+        } else {
+            -strike * time_to_expiry * discount * norm_cdf(-d2)
+        };
+
+        Greeks { delta, gamma, theta: theta / 365.0, vega, rho }
+    }
+
     fn d1(s: f64, k: f64, t: f64, r: f64, q: f64, sigma: f64) -> f64 {
         ((s / k).ln() + (r - q + sigma.powi(2) / 2.0) * t) / (sigma * t.sqrt())
     }
@@ -246,6 +435,126 @@ impl BlackScholes {
     }
 }
 
+/// Option model for the configured product
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PricingModel {
+    /// Black-76 on a futures price
+    #[default]
+    Black76,
+    /// Black-Scholes on a spot index paying a continuous dividend yield
+    BlackScholes { dividend_yield: f64 },
+}
+
+impl PricingModel {
+    /// Parse `simulation.pricing_model` ("black76" or "black_scholes")
+    pub fn from_config(value: &str, dividend_yield: f64) -> Option<Self> {
+        match value {
+            "black76" => Some(PricingModel::Black76),
+            "black_scholes" => Some(PricingModel::BlackScholes { dividend_yield }),
+            _ => None,
+        }
+    }
+
+    /// Price of one option on `underlying`
+    pub fn price(
+        self,
+        underlying: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        is_call: bool,
+    ) -> f64 {
+        match self {
+            PricingModel::Black76 => {
+                Black76::price(underlying, strike, time_to_expiry, risk_free_rate, volatility, is_call)
+            }
+            PricingModel::BlackScholes { dividend_yield } => BlackScholes::price(
+                underlying, strike, time_to_expiry, risk_free_rate, dividend_yield, volatility, is_call,
+            ),
+        }
+    }
+
+    /// `price` with the input checks of `Black76::try_price`
+    pub fn try_price(
+        self,
+        underlying: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        is_call: bool,
+    ) -> Result<f64, SimError> {
+        if self == PricingModel::Black76 {
+            return Black76::try_price(underlying, strike, time_to_expiry, risk_free_rate, volatility, is_call);
+        }
+        check_inputs("spot price", underlying, strike, time_to_expiry, volatility)?;
+        let price = self.price(underlying, strike, time_to_expiry, risk_free_rate, volatility, is_call);
+        if !price.is_finite() {
+            return Err(SimError::Pricing(format!(
+                "non-finite price for S={} K={} T={}",
+                underlying, strike, time_to_expiry
+            )));
+        }
+        Ok(price)
+    }
+
+    /// Greeks of one option on `underlying`
+    pub fn greeks(
+        self,
+        underlying: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        is_call: bool,
+    ) -> Greeks {
+        match self {
+            PricingModel::Black76 => {
+                Black76::greeks(underlying, strike, time_to_expiry, risk_free_rate, volatility, is_call)
+            }
+            PricingModel::BlackScholes { dividend_yield } => BlackScholes::greeks(
+                underlying, strike, time_to_expiry, risk_free_rate, dividend_yield, volatility, is_call,
+            ),
+        }
+    }
+
+    /// Price and Greeks together (`Black76::value` for futures)
+    pub fn value(
+        self,
+        underlying: f64,
+        strike: f64,
+        time_to_expiry: f64,
+        risk_free_rate: f64,
+        volatility: f64,
+        is_call: bool,
+    ) -> Valuation {
+        match self {
+            PricingModel::Black76 => {
+                Black76::value(underlying, strike, time_to_expiry, risk_free_rate, volatility, is_call)
+            }
+            PricingModel::BlackScholes { .. } => Valuation {
+                price: self.price(underlying, strike, time_to_expiry, risk_free_rate, volatility, is_call),
+                greeks: self.greeks(underlying, strike, time_to_expiry, risk_free_rate, volatility, is_call),
+            },
+        }
+    }
+}
+
+/// Underlying and strike positive and finite, volatility positive while there is time left
+fn check_inputs(name: &str, underlying: f64, strike: f64, time: f64, volatility: f64) -> Result<(), SimError> {
+    if !(underlying.is_finite() && underlying > 0.0) {
+        return Err(SimError::Pricing(format!("{} must be positive, got {}", name, underlying)));
+    }
+    if !(strike.is_finite() && strike > 0.0) {
+        return Err(SimError::Pricing(format!("strike must be positive, got {}", strike)));
+    }
+    if time > 0.0 && !(volatility.is_finite() && volatility > 0.0) {
+        return Err(SimError::Pricing(format!("volatility must be positive, got {}", volatility)));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,9 +562,43 @@ mod tests {
     #[test]
     fn test_black76_call() {
         // Known test case: F=100, K=100, T=1, r=0.05, sigma=0.2
-        // Expected call price ≈ 7.46
+        // Expected call price ≈ 7.577
         let price = Black76::price(100.0, 100.0, 1.0, 0.05, 0.2, true);
-        assert!((price - 7.46).abs() < 0.1, "Expected ~7.46, got {}", price);
+        assert!((price - 7.577).abs() < 0.01, "Expected ~7.577, got {}", price);
+    }
+
+    #[test]
+    fn test_value_matches_price_and_greeks() {
+        for &(f, k, t, is_call) in &[
+            (75.0, 75.0, 0.25, true),
+            (75.0, 72.5, 1.0 / 252.0, false),
+            (62.0, 65.0, 70.0 / 252.0, true),
+            (75.0, 74.0, 0.0, false),
+        ] {
+            let value = Black76::value(f, k, t, 0.05, 0.35, is_call);
+            assert_eq!(value.price, Black76::price(f, k, t, 0.05, 0.35, is_call));
+            assert_eq!(value.greeks, Black76::greeks(f, k, t, 0.05, 0.35, is_call));
+        }
+    }
+
+    #[test]
+    fn test_try_price_rejects_bad_inputs() {
+        assert!(Black76::try_price(100.0, 100.0, 1.0, 0.05, 0.2, true).is_ok());
+        assert!(matches!(
+            Black76::try_price(100.0, 0.0, 1.0, 0.05, 0.2, true),
+            Err(SimError::Pricing(_))
+        ));
+        assert!(Black76::try_price(f64::NAN, 100.0, 1.0, 0.05, 0.2, false).is_err());
+        assert!(Black76::try_price(100.0, 100.0, 1.0, 0.05, 0.0, false).is_err());
+        // Zero vol is fine at expiry (intrinsic only)
+        assert!(Black76::try_price(100.0, 100.0, 0.0, 0.05, 0.0, false).is_ok());
+    }
+
+    #[test]
+    fn test_black76_otm_call() {
+        // F=100, K=110, T=1, r=0.05, sigma=0.2: d1 = (ln(100/110) + 0.02) / 0.2
+        let price = Black76::price(100.0, 110.0, 1.0, 0.05, 0.2, true);
+        assert!((price - 4.084).abs() < 0.01, "Expected ~4.084, got {}", price);
     }
 
     #[test]
@@ -306,4 +649,28 @@ mod tests {
         let put_itm = Black76::price(90.0, 100.0, 0.0, 0.05, 0.25, false);
         assert_eq!(put_itm, 10.0);
     }
+
+    #[test]
+    fn test_black_scholes_greeks_match_bumped_prices() {
+        let model = PricingModel::from_config("black_scholes", 0.013).unwrap();
+        let (s, k, t, r, sigma) = (5000.0, 5050.0, 30.0 / 365.0, 0.05, 0.18);
+        // Put-call parity with a dividend yield: C - P = S e^(-qT) - K e^(-rT)
+        let parity = model.price(s, k, t, r, sigma, true) - model.price(s, k, t, r, sigma, false);
+        assert!((parity - (s * (-0.013 * t).exp() - k * (-r * t).exp())).abs() < 1e-6);
+
+        for is_call in [true, false] {
+            let greeks = model.greeks(s, k, t, r, sigma, is_call);
+            let bump = |ds: f64, dt: f64| model.price(s + ds, k, t + dt, r, sigma, is_call);
+            let delta = (bump(0.5, 0.0) - bump(-0.5, 0.0)) / 1.0;
+            let gamma = (bump(0.5, 0.0) - 2.0 * bump(0.0, 0.0) + bump(-0.5, 0.0)) / 0.25;
+            let theta = (bump(0.0, -1e-5) - bump(0.0, 0.0)) / 1e-5 / 365.0;
+            assert!((greeks.delta - delta).abs() < 1e-4, "delta {} vs {}", greeks.delta, delta);
+            assert!((greeks.gamma - gamma).abs() < 1e-5, "gamma {} vs {}", greeks.gamma, gamma);
+            assert!((greeks.theta - theta).abs() < 1e-2, "theta {} vs {}", greeks.theta, theta);
+        }
+        // The futures model is Black-76 bit for bit
+        let futures = PricingModel::default();
+        assert_eq!(futures.value(75.0, 74.0, 0.1, 0.05, 0.35, true), Black76::value(75.0, 74.0, 0.1, 0.05, 0.35, true));
+        assert!(model.try_price(-1.0, k, t, r, sigma, true).is_err());
+    }
 }
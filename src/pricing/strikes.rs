@@ -0,0 +1,147 @@
+//! Listed Strikes
+//!
+//! Exchanges list option strikes on a grid that widens away from the money,
+//! e.g. /CL every $0.50 near the money and every $1.00 further out.
+//! `StrikeLadder` holds those rules around a reference price and answers the
+//! nearest, next above, next below and range queries strike selection needs.
+
+/// Slack for a strike being a whole number of increments
+const EPS: f64 = 1e-9;
+
+/// Listed strikes around a reference price
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrikeLadder {
+    reference: f64,
+    increment: f64,
+    /// (points from the reference, increment from there out), by distance
+    bands: Vec<(f64, f64)>,
+}
+
+impl StrikeLadder {
+    /// Strikes every `increment` at any distance from the money
+    pub fn uniform(increment: f64) -> Self {
+        Self { reference: 0.0, increment, bands: Vec::new() }
+    }
+
+    /// Strikes every `increment` near `reference`, widening to each band's
+    /// increment from its distance out
+    ///
+    /// Bands must be ordered by distance, and each increment must be a whole
+    /// multiple of the one inside it.
+    pub fn new(reference: f64, increment: f64, bands: Vec<(f64, f64)>) -> Result<Self, String> {
+        if !(increment.is_finite() && increment > 0.0) {
+            return Err(format!("strike increment must be positive, got {}", increment));
+        }
+        let mut inner = (0.0, increment);
+        for &(from, band) in &bands {
+            if !(from.is_finite() && from > inner.0) {
+                return Err(format!("strike bands must widen with distance, got {} after {}", from, inner.0));
+            }
+            let ratio = band / inner.1;
+            if !(band.is_finite() && ratio >= 1.0 && (ratio - ratio.round()).abs() < EPS) {
+                return Err(format!("strike band increment {} is not a multiple of {}", band, inner.1));
+            }
+            inner = (from, band);
+        }
+        Ok(Self { reference, increment, bands })
+    }
+
+    /// Spacing of the strikes around `price`
+    pub fn increment_at(&self, price: f64) -> f64 {
+        let distance = (price - self.reference).abs();
+        self.bands.iter().rev().find(|(from, _)| distance >= *from).map_or(self.increment, |&(_, band)| band)
+    }
+
+    /// Whether `strike` is listed
+    pub fn is_listed(&self, strike: f64) -> bool {
+        let steps = strike / self.increment_at(strike);
+        strike > 0.0 && (steps - steps.round()).abs() < EPS
+    }
+
+    /// Listed strike closest to `price` (the higher one on a tie)
+    pub fn nearest(&self, price: f64) -> f64 {
+        let above = self.above(price);
+        let rounded = Some(self.snap(price)).filter(|&strike| self.is_listed(strike));
+        let mut best = rounded.unwrap_or(above);
+        for strike in [Some(above), self.below(price)].into_iter().flatten() {
+            if (strike - price).abs() < (best - price).abs() - EPS {
+                best = strike;
+            }
+        }
+        best
+    }
+
+    /// First listed strike above `price`
+    pub fn above(&self, price: f64) -> f64 {
+        let mut step = (price / self.increment + EPS).floor() + 1.0;
+        loop {
+            if let Some(strike) = self.listed_step(step) {
+                return strike;
+            }
+            step += 1.0;
+        }
+    }
+
+    /// Last listed strike below `price`, if any is above zero
+    pub fn below(&self, price: f64) -> Option<f64> {
+        let mut step = (price / self.increment - EPS).ceil() - 1.0;
+        while step > 0.0 {
+            if let Some(strike) = self.listed_step(step) {
+                return Some(strike);
+            }
+            step -= 1.0;
+        }
+        None
+    }
+
+    /// Listed strikes from `low` to `high`, ascending
+    pub fn between(&self, low: f64, high: f64) -> impl Iterator<Item = f64> + '_ {
+        let first = self.below(low).map_or_else(|| self.above(0.0), |strike| self.above(strike));
+        std::iter::successors(Some(first), move |&strike| Some(self.above(strike)))
+            .take_while(move |&strike| strike <= high + EPS)
+    }
+
+    /// `price` rounded to the increment it sits in
+    fn snap(&self, price: f64) -> f64 {
+        let increment = self.increment_at(price);
+        (price / increment).round() * increment
+    }
+
+    /// The strike `step` base increments up, if it is listed
+    fn listed_step(&self, step: f64) -> Option<f64> {
+        let strike = step * self.increment;
+        self.is_listed(strike).then(|| self.snap(strike))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniform_ladder_rounds_to_the_increment() {
+        let ladder = StrikeLadder::uniform(0.25);
+        for price in [74.37, 74.375, 75.0, 75.12, 0.6] {
+            assert_eq!(ladder.nearest(price), (price / 0.25).round() * 0.25);
+        }
+        assert_eq!(ladder.above(75.0), 75.25);
+        assert_eq!(ladder.below(75.0), Some(74.75));
+        assert_eq!(ladder.below(0.25), None);
+        assert_eq!(ladder.between(74.6, 75.3).collect::<Vec<_>>(), [74.75, 75.0, 75.25]);
+    }
+
+    #[test]
+    fn test_banded_ladder_widens_away_from_the_money() {
+        // /CL-style: $0.50 within $5 of 75, $1.00 beyond
+        let ladder = StrikeLadder::new(75.0, 0.5, vec![(5.0, 1.0)]).unwrap();
+        assert_eq!(ladder.nearest(76.3), 76.5);
+        assert_eq!(ladder.nearest(81.4), 81.0);
+        assert_eq!(ladder.above(79.5), 80.0);
+        assert_eq!(ladder.above(80.0), 81.0);
+        assert_eq!(ladder.below(70.0), Some(69.0));
+        assert_eq!(ladder.between(68.0, 71.0).collect::<Vec<_>>(), [68.0, 69.0, 70.0, 70.5, 71.0]);
+
+        assert!(StrikeLadder::new(75.0, 0.5, vec![(5.0, 0.75)]).is_err());
+        assert!(StrikeLadder::new(75.0, 0.5, vec![(5.0, 1.0), (3.0, 2.0)]).is_err());
+    }
+}
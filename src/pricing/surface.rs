@@ -0,0 +1,154 @@
+//! Volatility Surface
+//!
+//! Every implied vol the engine prices at comes from one `VolSurface`. The
+//! at-the-money level for a day and DTE is taken from the first data source
+//! that has one (a historical IV series, a vol index), else from the
+//! configured term structure, which is a single flat vol unless `vol_surface`
+//! lists tenors. A smile then moves each strike off that level by its
+//! log-moneyness. Entries, strike selection, marks, closes and the triggers
+//! module all ask the same surface.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Floor for smile-adjusted vols far from the money
+const MIN_VOL: f64 = 0.01;
+
+/// Data that can supply the at-the-money IV (e.g. `data::ImpliedVolSeries`)
+pub trait AtmVolSource: Debug + Send + Sync {
+    /// IV for an option with `dte` days left as of `day`, or `None` where the
+    /// data has nothing (e.g. before its first date)
+    fn atm_vol(&self, day: u32, dte: f64) -> Option<f64>;
+}
+
+/// Vol by strike relative to the at-the-money level
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Smile {
+    /// Vol points per unit of log-moneyness (negative: puts richer than calls)
+    pub skew: f64,
+    /// Vol points per squared unit of log-moneyness (positive: wings richer)
+    pub curvature: f64,
+}
+
+impl Smile {
+    /// Vol at `strike` around an `atm` vol, with the money at `underlying`
+    pub fn vol(&self, atm: f64, underlying: f64, strike: f64) -> f64 {
+        if self.skew == 0.0 && self.curvature == 0.0 {
+            return atm;
+        }
+        let moneyness = (strike / underlying).ln();
+        (atm + self.skew * moneyness + self.curvature * moneyness * moneyness).max(MIN_VOL)
+    }
+}
+
+/// Implied vol by day, DTE and strike
+#[derive(Debug, Clone)]
+pub struct VolSurface {
+    /// Consulted in order before the term structure
+    sources: Vec<Arc<dyn AtmVolSource>>,
+    /// (DTE, ATM vol) by DTE; a single tenor is a flat surface
+    term: Vec<(f64, f64)>,
+    smile: Smile,
+}
+
+impl VolSurface {
+    /// The same vol at every day, DTE and strike
+    pub fn flat(vol: f64) -> Self {
+        Self { sources: Vec::new(), term: vec![(0.0, vol)], smile: Smile::default() }
+    }
+
+    /// ATM vols at these (DTE, vol) tenors, linear in DTE between them and
+    /// flat beyond the shortest and longest
+    pub fn term_structure(mut tenors: Vec<(f64, f64)>) -> Result<Self, String> {
+        if tenors.is_empty() {
+            return Err("a term structure needs at least one tenor".to_string());
+        }
+        if let Some(&(dte, vol)) = tenors.iter().find(|(dte, vol)| !(dte.is_finite() && *dte >= 0.0 && *vol > 0.0)) {
+            return Err(format!("tenor ({}, {}) needs a DTE of 0 or more and a positive vol", dte, vol));
+        }
+        tenors.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if let Some(pair) = tenors.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(format!("DTE {} is listed twice", pair[0].0));
+        }
+        Ok(Self { sources: Vec::new(), term: tenors, smile: Smile::default() })
+    }
+
+    /// The same surface with `smile` around its ATM vols
+    pub fn with_smile(self, smile: Smile) -> Self {
+        Self { smile, ..self }
+    }
+
+    pub fn smile(&self) -> Smile {
+        self.smile
+    }
+
+    /// Take ATM vols from `source` first, wherever it has one
+    pub fn prefer(&mut self, source: Arc<dyn AtmVolSource>) {
+        self.sources.insert(0, source);
+    }
+
+    /// Take ATM vols from `source` where no source added before it has one
+    pub fn fall_back_to(&mut self, source: Arc<dyn AtmVolSource>) {
+        self.sources.push(source);
+    }
+
+    /// At-the-money vol for `dte` days left as of `day`
+    pub fn atm_vol(&self, day: u32, dte: f64) -> f64 {
+        self.sources.iter().find_map(|source| source.atm_vol(day, dte)).unwrap_or_else(|| interpolate(&self.term, dte))
+    }
+
+    /// Vol at `strike` for `dte` days left as of `day`, with the money at `underlying`
+    pub fn vol(&self, day: u32, dte: f64, underlying: f64, strike: f64) -> f64 {
+        self.smile.vol(self.atm_vol(day, dte), underlying, strike)
+    }
+}
+
+/// Value of a (DTE, vol) curve sorted by DTE at `dte`: linear between
+/// tenors, flat beyond the ends
+pub(crate) fn interpolate(curve: &[(f64, f64)], dte: f64) -> f64 {
+    let after = curve.partition_point(|&(tenor, _)| tenor < dte);
+    match (after.checked_sub(1).map(|i| curve[i]), curve.get(after)) {
+        (Some((t0, v0)), Some(&(t1, v1))) => v0 + (v1 - v0) * (dte - t0) / (t1 - t0),
+        (Some((_, v)), None) | (None, Some(&(_, v))) => v,
+        (None, None) => unreachable!("curves are never empty"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct FromDay(u32, f64);
+
+    impl AtmVolSource for FromDay {
+        fn atm_vol(&self, day: u32, _dte: f64) -> Option<f64> {
+            (day >= self.0).then_some(self.1)
+        }
+    }
+
+    #[test]
+    fn test_surface_layers_sources_term_and_smile() {
+        let mut surface = VolSurface::term_structure(vec![(30.0, 0.30), (1.0, 0.40)]).unwrap();
+        assert_eq!(surface.atm_vol(0, 0.5), 0.40);
+        assert!((surface.atm_vol(0, 15.5) - 0.35).abs() < 1e-12);
+        assert_eq!(surface.atm_vol(0, 90.0), 0.30);
+
+        surface.fall_back_to(Arc::new(FromDay(5, 0.50)));
+        surface.prefer(Arc::new(FromDay(10, 0.60)));
+        assert_eq!(surface.atm_vol(4, 1.0), 0.40);
+        assert_eq!(surface.atm_vol(5, 1.0), 0.50);
+        assert_eq!(surface.atm_vol(10, 1.0), 0.60);
+
+        // Flat without a smile is the ATM vol at every strike, bit for bit
+        let flat = VolSurface::flat(0.35);
+        assert_eq!(flat.vol(3, 7.0, 75.0, 60.0), 0.35);
+        let skewed = flat.with_smile(Smile { skew: -0.2, curvature: 0.5 });
+        assert!(skewed.vol(3, 7.0, 75.0, 70.0) > 0.35);
+        assert!(skewed.vol(3, 7.0, 75.0, 70.0) > skewed.vol(3, 7.0, 75.0, 80.0));
+
+        assert!(VolSurface::term_structure(Vec::new()).is_err());
+        assert!(VolSurface::term_structure(vec![(1.0, 0.3), (1.0, 0.4)]).is_err());
+        assert!(VolSurface::term_structure(vec![(1.0, -0.3)]).is_err());
+    }
+}
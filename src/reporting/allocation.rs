@@ -0,0 +1,231 @@
+//! Capital Allocation
+//!
+//! Splits an account between the legs of a combined run ("sleeves") and
+//! replays their daily P&L on the capital each one is given. A sleeve's
+//! returns are its daily P&L per contract over the capital one contract ties
+//! up (peak margin when short, the largest premium paid when long), so a
+//! sleeve holding $X earns X times its return. Weights are fixed or set by
+//! risk parity (inverse vol of each sleeve's recent returns) and restored
+//! every `rebalance_days`; between rebalances each sleeve's capital drifts
+//! with its own P&L.
+
+use super::daily_closes;
+use crate::config::AllocationConfig;
+use crate::engine::LegReport;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// How target weights are chosen at each rebalance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationMethod {
+    /// The configured weights
+    Fixed,
+    /// Proportional to 1 / rolling vol of each sleeve's daily returns
+    RiskParity,
+}
+
+impl AllocationMethod {
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "fixed" => Some(Self::Fixed),
+            "risk_parity" => Some(Self::RiskParity),
+            _ => None,
+        }
+    }
+}
+
+/// Target weights set on one day
+#[derive(Debug, Clone, Serialize)]
+pub struct Rebalance {
+    pub day: u32,
+    /// One per sleeve, in sleeve order, summing to 1
+    pub weights: Vec<f64>,
+}
+
+/// One sleeve's results on its allocated capital
+#[derive(Debug, Clone, Serialize)]
+pub struct SleeveResult {
+    pub name: String,
+    /// Capital one contract ties up (dollars; `None` if the leg never traded)
+    pub capital_per_contract: Option<f64>,
+    /// Capital at the end (dollars)
+    pub final_capital: f64,
+    pub pnl: f64,
+    /// Time-weighted return on the capital allocated to the sleeve
+    pub return_on_capital: f64,
+}
+
+/// An account split between sleeves
+#[derive(Debug, Clone, Serialize)]
+pub struct AllocationReport {
+    pub starting_capital: f64,
+    pub final_capital: f64,
+    pub sleeves: Vec<SleeveResult>,
+    /// The initial allocation first
+    pub rebalances: Vec<Rebalance>,
+}
+
+impl AllocationReport {
+    pub fn total_return(&self) -> f64 {
+        self.final_capital / self.starting_capital - 1.0
+    }
+}
+
+/// Allocate `config.capital` across `legs` (None without legs or a valid method)
+pub fn allocate(legs: &[LegReport], config: &AllocationConfig) -> Option<AllocationReport> {
+    let method = AllocationMethod::from_config(&config.method)?;
+    if legs.is_empty() {
+        return None;
+    }
+    let capital: Vec<Option<f64>> = legs.iter().map(capital_per_contract).collect();
+
+    // Daily returns of each sleeve, by calendar day
+    let mut returns: BTreeMap<u32, Vec<f64>> = BTreeMap::new();
+    for (i, leg) in legs.iter().enumerate() {
+        let multiplier = leg.config.simulation.contract_multiplier;
+        let mut previous = 0.0;
+        for close in daily_closes(&leg.report.equity_series) {
+            let pnl = (close.equity() - previous) * multiplier;
+            previous = close.equity();
+            let row = returns.entry(close.timestamp.day).or_insert_with(|| vec![0.0; legs.len()]);
+            row[i] = capital[i].map_or(0.0, |capital| pnl / capital);
+        }
+    }
+    let days: Vec<(u32, Vec<f64>)> = returns.into_iter().collect();
+
+    let fixed = fixed_weights(legs, config);
+    let mut allocated: Vec<f64> = fixed.iter().map(|w| w * config.capital).collect();
+    let mut rebalances = Vec::new();
+    let mut pnl = vec![0.0; legs.len()];
+    let mut growth = vec![1.0; legs.len()];
+    for (k, (day, day_returns)) in days.iter().enumerate() {
+        if k % config.rebalance_days == 0 {
+            let weights = match method {
+                AllocationMethod::Fixed => fixed.clone(),
+                AllocationMethod::RiskParity => {
+                    let window = &days[k.saturating_sub(config.lookback_days)..k];
+                    risk_parity_weights(window, legs.len())
+                }
+            };
+            let equity: f64 = allocated.iter().sum();
+            allocated = weights.iter().map(|w| w * equity).collect();
+            rebalances.push(Rebalance { day: *day, weights });
+        }
+        for (i, r) in day_returns.iter().enumerate() {
+            pnl[i] += allocated[i] * r;
+            allocated[i] *= 1.0 + r;
+            growth[i] *= 1.0 + r;
+        }
+    }
+
+    let sleeves = legs
+        .iter()
+        .enumerate()
+        .map(|(i, leg)| SleeveResult {
+            name: leg.name.to_string(),
+            capital_per_contract: capital[i],
+            final_capital: allocated[i],
+            pnl: pnl[i],
+            return_on_capital: growth[i] - 1.0,
+        })
+        .collect();
+    Some(AllocationReport {
+        starting_capital: config.capital,
+        final_capital: allocated.iter().sum(),
+        sleeves,
+        rebalances,
+    })
+}
+
+/// Peak margin of a short leg, or the largest premium a long leg paid (dollars)
+fn capital_per_contract(leg: &LegReport) -> Option<f64> {
+    let multiplier = leg.config.simulation.contract_multiplier;
+    let margin = leg.report.risk_summary.peak_margin.map(|peak| peak.value);
+    let premium = leg.report.trade_records.iter().map(|t| t.entry_premium * multiplier).reduce(f64::max);
+    margin.filter(|margin| *margin > 0.0).or(premium).filter(|capital| *capital > 0.0)
+}
+
+/// Configured weights normalized to 1 (equal when none are listed)
+fn fixed_weights(legs: &[LegReport], config: &AllocationConfig) -> Vec<f64> {
+    if config.weights.is_empty() {
+        return vec![1.0 / legs.len() as f64; legs.len()];
+    }
+    let raw: Vec<f64> = legs.iter().map(|leg| config.weights.get(leg.name).copied().unwrap_or(0.0)).collect();
+    let total: f64 = raw.iter().sum();
+    if total > 0.0 {
+        raw.iter().map(|w| w / total).collect()
+    } else {
+        vec![1.0 / legs.len() as f64; legs.len()]
+    }
+}
+
+/// Inverse-vol weights over `window`, equal until every sleeve has a positive vol
+fn risk_parity_weights(window: &[(u32, Vec<f64>)], sleeves: usize) -> Vec<f64> {
+    let equal = vec![1.0 / sleeves as f64; sleeves];
+    if window.len() < 2 {
+        return equal;
+    }
+    let n = window.len() as f64;
+    let inverse_vols: Vec<f64> = (0..sleeves)
+        .map(|i| {
+            let mean = window.iter().map(|(_, r)| r[i]).sum::<f64>() / n;
+            let variance = window.iter().map(|(_, r)| (r[i] - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            1.0 / variance.sqrt()
+        })
+        .collect();
+    if !inverse_vols.iter().all(|w| w.is_finite()) {
+        return equal;
+    }
+    let total: f64 = inverse_vols.iter().sum();
+    inverse_vols.iter().map(|w| w / total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_risk_parity_weights_favor_the_calmer_sleeve() {
+        let window: Vec<(u32, Vec<f64>)> =
+            (0..4).map(|day| (day, vec![if day % 2 == 0 { 0.01 } else { -0.01 }, 0.04 - 0.02 * day as f64])).collect();
+        let weights = risk_parity_weights(&window, 2);
+        assert!(weights[0] > weights[1]);
+        assert!((weights.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        // Not enough history yet
+        assert_eq!(risk_parity_weights(&window[..1], 2), [0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_sleeves_earn_on_their_allocation() {
+        use crate::config::Config;
+        use crate::engine::SimulationEngine;
+
+        let mut config = Config::default_1dte_straddle();
+        let mut long_leg = config.strategy.clone();
+        (long_leg.side, long_leg.entry_dte, long_leg.strike_selection) = ("long".into(), 70, "OTM".into());
+        long_leg.strike_offset = 3.0;
+        long_leg.roll_triggers[0].trigger_type = "dte".into();
+        long_leg.roll_triggers[0].value = 28.0;
+        config.short_leg = Some(config.strategy.clone());
+        config.long_leg = Some(long_leg);
+        config.simulation.days = 60;
+        let legs = SimulationEngine::run_legs(&config).unwrap();
+        let mut allocation = AllocationConfig {
+            capital: 100_000.0,
+            method: "fixed".to_string(),
+            weights: BTreeMap::from([("short".to_string(), 3.0), ("long".to_string(), 1.0)]),
+            rebalance_days: 5,
+            lookback_days: 20,
+        };
+        let fixed = allocate(&legs, &allocation).unwrap();
+        assert_eq!(fixed.rebalances[0].weights, [0.75, 0.25]);
+        assert!(fixed.rebalances.iter().all(|r| r.weights == [0.75, 0.25]));
+        let sleeve_pnl: f64 = fixed.sleeves.iter().map(|s| s.pnl).sum();
+        assert!((fixed.final_capital - 100_000.0 - sleeve_pnl).abs() < 1e-6);
+
+        allocation.method = "risk_parity".to_string();
+        let parity = allocate(&legs, &allocation).unwrap();
+        assert_eq!(parity.rebalances[0].weights, [0.5, 0.5]);
+        assert!(parity.rebalances.iter().skip(1).any(|r| r.weights != [0.5, 0.5]));
+    }
+}
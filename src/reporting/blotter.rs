@@ -0,0 +1,233 @@
+//! Broker-Style Trade Blotter
+//!
+//! Every option fill of a run, one row per leg, in the shape broker trade
+//! histories use: timestamp, symbol, OCC-style option code, buy/sell,
+//! open/close, quantity, price and fees. With the run's calendar days mapped
+//! onto real dates (see `data::DayMapping`) the blotter can be diffed against
+//! an account's history for the same period.
+//!
+//! Option codes follow the OCC layout: root padded to six characters, expiry
+//! as YYMMDD, `C`/`P`, then the strike in thousandths as eight digits
+//! (`CL    240305P00078250`). Legs held to expiration close with an `EXPIRE`
+//! row at intrinsic value rather than a trade.
+
+use super::csv_export;
+use crate::calendar::intraday::Timestamp;
+use crate::data::DayMapping;
+use crate::events::{Event, LegId, OptionContract, OptionType, Side};
+use serde::Serialize;
+use std::collections::HashMap;
+
+pub const BLOTTER_HEADER: &str = "timestamp,symbol,option_code,side,open_close,quantity,price,fees,position_id";
+
+/// Whether a fill opens or closes a leg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum OpenClose {
+    Open,
+    Close,
+    /// Closed by expiring, at intrinsic value
+    Expire,
+}
+
+/// One leg bought or sold
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Fill {
+    pub timestamp: Timestamp,
+    pub position_id: u64,
+    pub option_type: OptionType,
+    pub strike: f64,
+    pub expiration_day: u32,
+    /// Long when bought, Short when sold
+    pub side: Side,
+    pub open_close: OpenClose,
+    /// Contracts
+    pub quantity: f64,
+    /// Premium per unit of underlying
+    pub price: f64,
+}
+
+/// Fills of every opened, rolled and closed leg in `events`, in order
+pub fn fills(events: &[Event]) -> Vec<Fill> {
+    let mut open_legs: HashMap<LegId, OptionContract> = HashMap::new();
+    let mut fills = Vec::new();
+    for event in events {
+        match event {
+            Event::PositionOpened { position_id, timestamp, legs, .. } => {
+                let timestamp = Timestamp::new(timestamp.0, u32::from(timestamp.1.minutes()));
+                for (leg_id, contract, premium) in legs {
+                    open_legs.insert(*leg_id, *contract);
+                    fills.push(Fill {
+                        timestamp,
+                        position_id: position_id.0,
+                        option_type: contract.option_type,
+                        strike: contract.strike,
+                        expiration_day: contract.expiration_day,
+                        side: contract.side,
+                        open_close: OpenClose::Open,
+                        quantity: contract.quantity,
+                        price: premium.abs(),
+                    });
+                }
+            }
+            Event::LegRolled {
+                position_id,
+                leg_id,
+                timestamp,
+                old_contract,
+                close_premium,
+                new_contract,
+                open_premium,
+                ..
+            } => {
+                let timestamp = Timestamp::new(timestamp.0, u32::from(timestamp.1.minutes()));
+                open_legs.insert(*leg_id, *new_contract);
+                let fill = |contract: &OptionContract, side: Side, open_close: OpenClose, premium: f64| Fill {
+                    timestamp,
+                    position_id: position_id.0,
+                    option_type: contract.option_type,
+                    strike: contract.strike,
+                    expiration_day: contract.expiration_day,
+                    side,
+                    open_close,
+                    quantity: contract.quantity,
+                    price: premium.abs(),
+                };
+                let closing = if old_contract.side == Side::Long { Side::Short } else { Side::Long };
+                fills.push(fill(old_contract, closing, OpenClose::Close, *close_premium));
+                fills.push(fill(new_contract, new_contract.side, OpenClose::Open, *open_premium));
+            }
+            Event::PositionClosed { position_id, timestamp, close_premiums, .. } => {
+                let timestamp = Timestamp::new(timestamp.0, u32::from(timestamp.1.minutes()));
+                for (leg_id, premium) in close_premiums {
+                    let Some(leg) = open_legs.remove(leg_id) else {
+                        continue;
+                    };
+                    let OptionContract { option_type, strike, expiration_day, side, quantity, .. } = leg;
+                    fills.push(Fill {
+                        timestamp,
+                        position_id: position_id.0,
+                        option_type,
+                        strike,
+                        expiration_day,
+                        side: if side == Side::Long { Side::Short } else { Side::Long },
+                        open_close: if timestamp.day >= expiration_day { OpenClose::Expire } else { OpenClose::Close },
+                        quantity,
+                        price: premium.abs(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    fills
+}
+
+/// How fills are written out
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlotterOptions {
+    /// Option root, e.g. "CL" (at most six characters are used)
+    pub symbol: String,
+    /// Real dates of the run's calendar days
+    pub mapping: DayMapping,
+    /// Commission and fees per contract, in dollars
+    pub fee_per_contract: f64,
+}
+
+/// OCC-style code of one leg
+pub fn option_code(symbol: &str, expiry: crate::data::Date, option_type: OptionType, strike: f64) -> String {
+    let root: String = symbol.chars().filter(|c| c.is_ascii_alphanumeric()).take(6).collect();
+    let kind = match option_type {
+        OptionType::Call => 'C',
+        OptionType::Put => 'P',
+    };
+    format!(
+        "{:<6}{:02}{:02}{:02}{}{:08}",
+        root,
+        expiry.year.rem_euclid(100),
+        expiry.month,
+        expiry.day,
+        kind,
+        (strike * 1000.0).round() as i64
+    )
+}
+
+/// One fill as a CSV row (no trailing newline)
+pub fn fill_row(fill: &Fill, options: &BlotterOptions) -> String {
+    let date = options.mapping.date(fill.timestamp.day);
+    let side = match fill.side {
+        Side::Long => "BUY",
+        Side::Short => "SELL",
+    };
+    let open_close = match fill.open_close {
+        OpenClose::Open => "OPEN",
+        OpenClose::Close => "CLOSE",
+        OpenClose::Expire => "EXPIRE",
+    };
+    // Expirations are not trades, so they carry no commission
+    let fees = match fill.open_close {
+        OpenClose::Expire => 0.0,
+        _ => options.fee_per_contract * fill.quantity,
+    };
+    format!(
+        "{} {},{},{},{},{},{},{},{},{}",
+        date,
+        fill.timestamp.format_time(),
+        options.symbol,
+        option_code(&options.symbol, options.mapping.date(fill.expiration_day), fill.option_type, fill.strike),
+        side,
+        open_close,
+        fill.quantity,
+        fill.price,
+        fees,
+        fill.position_id,
+    )
+}
+
+/// The whole blotter, with header
+pub fn blotter_csv(fills: &[Fill], options: &BlotterOptions) -> String {
+    csv_export::table(BLOTTER_HEADER, fills.iter().map(|fill| fill_row(fill, options)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::data::Date;
+    use crate::engine::SimulationEngine;
+
+    #[test]
+    fn test_blotter_has_a_row_per_leg_fill() {
+        let report = SimulationEngine::run(&Config::default_1dte_straddle()).unwrap();
+        let opens = report.fills.iter().filter(|f| f.open_close == OpenClose::Open).count();
+        assert_eq!(opens as u32, report.pnl_summary.position_count * 2);
+        assert_eq!(report.fills.len() - opens, report.trade_records.len() * 2);
+
+        // The first short straddle sells both legs at their entry premiums
+        let first = &report.trade_records[0];
+        let entry: Vec<&Fill> = report.fills.iter().filter(|f| f.position_id == first.position_id).take(2).collect();
+        assert!(entry.iter().all(|f| f.side == Side::Short && f.open_close == OpenClose::Open));
+        assert!((entry[0].price + entry[1].price - first.entry_premium).abs() < 1e-12);
+
+        let options = BlotterOptions {
+            symbol: "CL".to_string(),
+            mapping: DayMapping::starting_week_of(Date::new(2024, 3, 4).unwrap()),
+            fee_per_contract: 2.5,
+        };
+        let csv = blotter_csv(&report.fills, &options);
+        assert_eq!(csv.lines().count(), report.fills.len() + 1);
+        let row: Vec<&str> = csv.lines().nth(1).unwrap().split(',').collect();
+        assert_eq!(row[0], "2024-03-04 15:00");
+        assert_eq!(&row[3..6], ["SELL", "OPEN", "1"]);
+        assert_eq!(row[7], "2.5");
+    }
+
+    #[test]
+    fn test_option_codes() {
+        let expiry = Date::new(2024, 3, 5).unwrap();
+        assert_eq!(option_code("CL", expiry, OptionType::Put, 78.25), "CL    240305P00078250");
+        assert_eq!(option_code("/ES", expiry, OptionType::Call, 5125.0), "ES    240305C05125000");
+    }
+}
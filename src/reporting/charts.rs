@@ -0,0 +1,170 @@
+//! Chart Rendering (feature = "charts")
+//!
+//! Renders the price path with trade markers and the equity curve to image
+//! files. The backend is chosen from the file extension: `.png` renders a
+//! bitmap (requires the `charts-png` feature), anything else renders SVG.
+
+use super::{EquityPoint, TradeRecord};
+use plotters::prelude::*;
+use std::path::Path;
+
+const CHART_SIZE: (u32, u32) = (1200, 600);
+
+#[cfg(not(feature = "charts-png"))]
+const PNG_UNSUPPORTED: &str = "PNG output requires building with `--features charts-png`";
+
+/// Fractional day used as the x coordinate
+fn x_of(timestamp: &crate::calendar::intraday::Timestamp) -> f64 {
+    timestamp.day as f64 + timestamp.minute as f64 / (24.0 * 60.0)
+}
+
+/// Value range padded by 5% so lines don't touch the frame
+fn padded_range(values: impl Iterator<Item = f64>) -> std::ops::Range<f64> {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+    if !min.is_finite() || !max.is_finite() {
+        return 0.0..1.0;
+    }
+    let pad = ((max - min) * 0.05).max(1e-6);
+    (min - pad)..(max + pad)
+}
+
+fn x_range(series: &[EquityPoint]) -> std::ops::Range<f64> {
+    match (series.first(), series.last()) {
+        (Some(first), Some(last)) if last.timestamp > first.timestamp => {
+            x_of(&first.timestamp)..x_of(&last.timestamp)
+        }
+        _ => 0.0..1.0,
+    }
+}
+
+/// Render the underlying price path with entry (green) and close (red) markers
+pub fn render_price_chart<P: AsRef<Path>>(
+    path: P,
+    series: &[EquityPoint],
+    trades: &[TradeRecord],
+) -> Result<(), String> {
+    let path = path.as_ref();
+    if is_png(path) {
+        #[cfg(feature = "charts-png")]
+        return draw_price_chart(BitMapBackend::new(path, CHART_SIZE).into_drawing_area(), series, trades);
+        #[cfg(not(feature = "charts-png"))]
+        return Err(PNG_UNSUPPORTED.to_string());
+    }
+    draw_price_chart(SVGBackend::new(path, CHART_SIZE).into_drawing_area(), series, trades)
+}
+
+/// Render the equity curve (realized + unrealized P&L in dollars)
+pub fn render_equity_chart<P: AsRef<Path>>(
+    path: P,
+    series: &[EquityPoint],
+    contract_multiplier: f64,
+) -> Result<(), String> {
+    let path = path.as_ref();
+    if is_png(path) {
+        #[cfg(feature = "charts-png")]
+        return draw_equity_chart(BitMapBackend::new(path, CHART_SIZE).into_drawing_area(), series, contract_multiplier);
+        #[cfg(not(feature = "charts-png"))]
+        return Err(PNG_UNSUPPORTED.to_string());
+    }
+    draw_equity_chart(SVGBackend::new(path, CHART_SIZE).into_drawing_area(), series, contract_multiplier)
+}
+
+fn is_png(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("png"))
+        .unwrap_or(false)
+}
+
+fn draw_price_chart<DB: DrawingBackend>(
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    series: &[EquityPoint],
+    trades: &[TradeRecord],
+) -> Result<(), String> {
+    root.fill(&WHITE).map_err(|e| e.to_string())?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x_range(series), padded_range(series.iter().map(|p| p.price)))
+        .map_err(|e| e.to_string())?;
+    chart.configure_mesh().draw().map_err(|e| e.to_string())?;
+
+    chart
+        .draw_series(LineSeries::new(
+            series.iter().map(|p| (x_of(&p.timestamp), p.price)),
+            &BLUE,
+        ))
+        .map_err(|e| e.to_string())?;
+    chart
+        .draw_series(trades.iter().map(|t| {
+            Circle::new((x_of(&t.entry), t.entry_price), 4, GREEN.filled())
+        }))
+        .map_err(|e| e.to_string())?;
+    chart
+        .draw_series(trades.iter().map(|t| {
+            Circle::new((x_of(&t.close), t.close_price), 4, RED.filled())
+        }))
+        .map_err(|e| e.to_string())?;
+
+    root.present().map_err(|e| e.to_string())
+}
+
+fn draw_equity_chart<DB: DrawingBackend>(
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    series: &[EquityPoint],
+    contract_multiplier: f64,
+) -> Result<(), String> {
+    root.fill(&WHITE).map_err(|e| e.to_string())?;
+
+    let dollars = |p: &EquityPoint| p.equity() * contract_multiplier;
+    let y_range = padded_range(series.iter().map(dollars).chain(std::iter::once(0.0)));
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(x_range(series), y_range)
+        .map_err(|e| e.to_string())?;
+    chart.configure_mesh().draw().map_err(|e| e.to_string())?;
+
+    chart
+        .draw_series(LineSeries::new(
+            series.iter().map(|p| (x_of(&p.timestamp), dollars(p))),
+            &BLACK,
+        ))
+        .map_err(|e| e.to_string())?;
+
+    root.present().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::intraday::Timestamp;
+
+    #[test]
+    fn test_render_svg_charts() {
+        let series: Vec<EquityPoint> = (0..20)
+            .map(|i| EquityPoint {
+                timestamp: Timestamp::new(i / 4, (i % 4) * 60),
+                price: 75.0 + i as f64 * 0.1,
+                realized: i as f64 * 0.01,
+                unrealized: 0.0,
+            })
+            .collect();
+        let dir = std::env::temp_dir();
+        let price_path = dir.join("tsv2_price_chart_test.svg");
+        let equity_path = dir.join("tsv2_equity_chart_test.svg");
+
+        render_price_chart(&price_path, &series, &[]).unwrap();
+        render_equity_chart(&equity_path, &series, 1000.0).unwrap();
+
+        let svg = std::fs::read_to_string(&price_path).unwrap();
+        assert!(svg.contains("<svg"));
+        let _ = std::fs::remove_file(price_path);
+        let _ = std::fs::remove_file(equity_path);
+    }
+}
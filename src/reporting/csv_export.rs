@@ -0,0 +1,97 @@
+//! CSV Export
+//!
+//! The trade table and end-of-day series as CSV text, for spreadsheet users
+//! and the web server's downloads. Unlike the Parquet export, money columns
+//! are in dollars (per-unit values times the contract multiplier).
+
+use super::{daily_closes, EquityPoint, TradeRecord};
+use crate::events::Side;
+
+pub const TRADES_HEADER: &str = "position_id,side,entry_day,entry_time,close_day,close_time,entry_price,\
+close_price,entry_premium,close_value,pnl,implied_vol,realized_vol,max_loss,initial_margin,tags";
+
+pub const DAILY_HEADER: &str = "day,price,realized,unrealized,equity";
+
+/// One trade as a CSV row (no trailing newline)
+pub fn trade_row(trade: &TradeRecord, contract_multiplier: f64) -> String {
+    let side = match trade.side {
+        Side::Long => "long",
+        Side::Short => "short",
+    };
+    format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+        trade.position_id,
+        side,
+        trade.entry.day,
+        trade.entry.format_time(),
+        trade.close.day,
+        trade.close.format_time(),
+        trade.entry_price,
+        trade.close_price,
+        trade.entry_premium * contract_multiplier,
+        trade.close_value * contract_multiplier,
+        trade.pnl * contract_multiplier,
+        trade.implied_vol,
+        trade.realized_vol.map(|v| v.to_string()).unwrap_or_default(),
+        // Blank when the loss is unlimited
+        trade.max_loss.map(|v| (v * contract_multiplier).to_string()).unwrap_or_default(),
+        trade.initial_margin * contract_multiplier,
+        trade.tags.join(";"),
+    )
+}
+
+/// One end-of-day snapshot as a CSV row (no trailing newline)
+pub fn daily_row(point: &EquityPoint, contract_multiplier: f64) -> String {
+    format!(
+        "{},{},{},{},{}",
+        point.timestamp.day,
+        point.price,
+        point.realized * contract_multiplier,
+        point.unrealized * contract_multiplier,
+        point.equity() * contract_multiplier,
+    )
+}
+
+/// The whole trade table, with header
+pub fn trades_csv(trades: &[TradeRecord], contract_multiplier: f64) -> String {
+    table(TRADES_HEADER, trades.iter().map(|t| trade_row(t, contract_multiplier)))
+}
+
+/// The last bar of each day, with header
+pub fn daily_csv(series: &[EquityPoint], contract_multiplier: f64) -> String {
+    table(DAILY_HEADER, daily_closes(series).iter().map(|p| daily_row(p, contract_multiplier)))
+}
+
+pub(super) fn table(header: &str, rows: impl Iterator<Item = String>) -> String {
+    let mut csv = format!("{}\n", header);
+    for row in rows {
+        csv.push_str(&row);
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::engine::SimulationEngine;
+
+    #[test]
+    fn test_tables_have_one_row_per_record() {
+        let config = Config::default_1dte_straddle();
+        let report = SimulationEngine::run(&config).unwrap();
+        let multiplier = config.simulation.contract_multiplier;
+
+        let trades = trades_csv(&report.trade_records, multiplier);
+        let lines: Vec<&str> = trades.lines().collect();
+        assert_eq!(lines.len(), report.trade_records.len() + 1);
+        let columns = TRADES_HEADER.split(',').count();
+        assert!(lines.iter().all(|line| line.split(',').count() == columns));
+        let pnl: f64 = lines[1].split(',').nth(10).unwrap().parse().unwrap();
+        assert_eq!(pnl, report.trade_records[0].pnl * multiplier);
+
+        let daily = daily_csv(&report.equity_series, multiplier);
+        assert_eq!(daily.lines().count(), daily_closes(&report.equity_series).len() + 1);
+    }
+}
@@ -0,0 +1,822 @@
+//! Performance Reporting
+//!
+//! Turns the closed trades of a run into summary tables, so results can be
+//! inspected beyond a single aggregate P&L number.
+
+pub mod allocation;
+pub mod blotter;
+#[cfg(feature = "charts")]
+pub mod charts;
+pub mod csv_export;
+#[cfg(feature = "parquet-export")]
+pub mod parquet_export;
+pub mod presenter;
+
+use crate::calendar::intraday::Timestamp;
+use serde::Serialize;
+use crate::calendar::{year_month, Day};
+use crate::events::Side;
+use crate::risk::RiskSnapshot;
+
+/// A closed trade as seen by the reporting layer
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TradeRecord {
+    pub position_id: u64,
+    pub side: Side,
+    pub entry: Timestamp,
+    pub close: Timestamp,
+    /// Underlying price when the position was opened
+    pub entry_price: f64,
+    /// Underlying price when the position was closed
+    pub close_price: f64,
+    /// Total premium of all legs at entry, per unit (unsigned)
+    pub entry_premium: f64,
+    /// Value of all legs halfway through the position's life, per unit
+    pub mid_value: Option<f64>,
+    /// Value of all legs at close, per unit
+    pub close_value: f64,
+    /// Realized P&L per unit of underlying (multiply by contract multiplier for dollars)
+    pub pnl: f64,
+    /// Implied volatility used to price the entry
+    pub implied_vol: f64,
+    /// Annualized realized volatility of the path over the position's life
+    pub realized_vol: Option<f64>,
+    /// Worst loss at expiration the structure allowed at entry, per unit (`None`: unlimited)
+    pub max_loss: Option<f64>,
+    /// Margin charged at entry, per unit
+    pub initial_margin: f64,
+    /// Labels of the rules that opened or adjusted the position
+    pub tags: Vec<String>,
+}
+
+impl TradeRecord {
+    pub fn is_win(&self) -> bool {
+        self.pnl > 0.0
+    }
+
+    /// Realized volatility risk premium: implied at entry minus realized over the life
+    pub fn vrp_captured(&self) -> Option<f64> {
+        self.realized_vol.map(|realized| self.implied_vol - realized)
+    }
+}
+
+/// Mark-to-market snapshot of the account at one bar
+#[derive(Debug, Clone, Copy, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct EquityPoint {
+    pub timestamp: Timestamp,
+    /// Underlying price at this bar
+    pub price: f64,
+    /// Cumulative P&L of closed trades, per unit
+    pub realized: f64,
+    /// Mark-to-market P&L of the open position, per unit
+    pub unrealized: f64,
+}
+
+impl EquityPoint {
+    /// Total P&L per unit (realized + unrealized)
+    pub fn equity(&self) -> f64 {
+        self.realized + self.unrealized
+    }
+}
+
+/// Bucket size for the periodic breakdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakdownPeriod {
+    /// Synthetic 7-day weeks (W0 starts on Day 0)
+    Week,
+    /// Calendar months of the synthetic 365-day year
+    Month,
+}
+
+impl BreakdownPeriod {
+    /// Parse the `reporting.breakdown` config value ("none" disables the table)
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "week" => Some(BreakdownPeriod::Week),
+            "month" => Some(BreakdownPeriod::Month),
+            _ => None,
+        }
+    }
+
+    /// Bucket key for a day; keys sort chronologically
+    fn key(&self, day: Day) -> (u32, u32) {
+        match self {
+            BreakdownPeriod::Week => (0, day / 7),
+            BreakdownPeriod::Month => year_month(day),
+        }
+    }
+
+    fn label(&self, key: (u32, u32)) -> String {
+        match self {
+            BreakdownPeriod::Week => format!("W{}", key.1),
+            BreakdownPeriod::Month => format!("Y{} M{:02}", key.0, key.1),
+        }
+    }
+}
+
+/// One row of the periodic breakdown table
+#[derive(Debug, Clone)]
+pub struct PeriodRow {
+    pub label: String,
+    pub pnl: f64,
+    pub trades: u32,
+    pub wins: u32,
+}
+
+impl PeriodRow {
+    /// Win rate in percent (0 when no trades closed in the period)
+    pub fn win_rate(&self) -> f64 {
+        if self.trades == 0 {
+            0.0
+        } else {
+            self.wins as f64 / self.trades as f64 * 100.0
+        }
+    }
+}
+
+/// Group closed trades by the period their close falls in
+pub fn periodic_breakdown(trades: &[TradeRecord], period: BreakdownPeriod) -> Vec<PeriodRow> {
+    let mut rows: Vec<((u32, u32), PeriodRow)> = Vec::new();
+
+    for trade in trades {
+        let key = period.key(trade.close.day);
+        let index = match rows.iter().position(|(k, _)| *k == key) {
+            Some(index) => index,
+            None => {
+                rows.push((
+                    key,
+                    PeriodRow { label: period.label(key), pnl: 0.0, trades: 0, wins: 0 },
+                ));
+                rows.len() - 1
+            }
+        };
+        let row = &mut rows[index].1;
+        row.pnl += trade.pnl;
+        row.trades += 1;
+        if trade.is_win() {
+            row.wins += 1;
+        }
+    }
+
+    rows.sort_by_key(|(key, _)| *key);
+    rows.into_iter().map(|(_, row)| row).collect()
+}
+
+/// Print the periodic breakdown as a table with a running cumulative column
+pub fn print_breakdown(rows: &[PeriodRow], period: BreakdownPeriod, contract_multiplier: f64) {
+    let title = match period {
+        BreakdownPeriod::Week => "WEEKLY BREAKDOWN",
+        BreakdownPeriod::Month => "MONTHLY BREAKDOWN",
+    };
+    println!("\n{}", title);
+    println!("{}", "-".repeat(60));
+    println!("{:<10} {:>12} {:>12} {:>8} {:>10}", "Period", "P&L", "Cumulative", "Trades", "Win Rate");
+
+    let mut cumulative = 0.0;
+    for row in rows {
+        let pnl_dollars = row.pnl * contract_multiplier;
+        cumulative += pnl_dollars;
+        println!(
+            "{:<10} {:>12.0} {:>12.0} {:>8} {:>9.1}%",
+            row.label, pnl_dollars, cumulative, row.trades, row.win_rate()
+        );
+    }
+}
+
+/// Last equity point of each calendar day (end-of-day snapshots)
+pub fn daily_closes(series: &[EquityPoint]) -> Vec<EquityPoint> {
+    let mut closes: Vec<EquityPoint> = Vec::new();
+    for point in series {
+        match closes.last_mut() {
+            Some(last) if last.timestamp.day == point.timestamp.day => *last = *point,
+            _ => closes.push(*point),
+        }
+    }
+    closes
+}
+
+/// Pearson correlation of two equally long samples (None if undefined)
+pub fn correlation(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    let n = xs.len().min(ys.len());
+    if n < 2 {
+        return None;
+    }
+    let mean_x = xs[..n].iter().sum::<f64>() / n as f64;
+    let mean_y = ys[..n].iter().sum::<f64>() / n as f64;
+    let mut cov = 0.0;
+    let mut var_x = 0.0;
+    let mut var_y = 0.0;
+    for i in 0..n {
+        let dx = xs[i] - mean_x;
+        let dy = ys[i] - mean_y;
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+    if var_x <= 0.0 || var_y <= 0.0 {
+        return None;
+    }
+    Some(cov / (var_x.sqrt() * var_y.sqrt()))
+}
+
+/// Annualized close-to-close volatility of a price slice
+///
+/// `periods_per_year` is the number of bars per year the prices are sampled at.
+pub fn realized_volatility(prices: &[f64], periods_per_year: f64) -> Option<f64> {
+    if prices.len() < 3 {
+        return None;
+    }
+    let returns: Vec<f64> = prices.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Some((variance * periods_per_year).sqrt())
+}
+
+/// Aggregate realized-vs-implied statistics over all trades
+#[derive(Debug, Clone)]
+pub struct VolatilityReport {
+    pub trades: usize,
+    pub avg_implied: f64,
+    pub avg_realized: f64,
+    pub avg_vrp: f64,
+    /// Trades where implied exceeded realized: (count, total P&L per unit)
+    pub positive_vrp: (usize, f64),
+    /// Trades where realized met or exceeded implied: (count, total P&L per unit)
+    pub negative_vrp: (usize, f64),
+}
+
+/// Summarize how much of the implied-vs-realized spread each trade captured
+pub fn volatility_report(trades: &[TradeRecord]) -> Option<VolatilityReport> {
+    let measured: Vec<(&TradeRecord, f64)> = trades
+        .iter()
+        .filter_map(|t| t.realized_vol.map(|rv| (t, rv)))
+        .collect();
+    if measured.is_empty() {
+        return None;
+    }
+    let n = measured.len() as f64;
+    let mut report = VolatilityReport {
+        trades: measured.len(),
+        avg_implied: measured.iter().map(|(t, _)| t.implied_vol).sum::<f64>() / n,
+        avg_realized: measured.iter().map(|(_, rv)| rv).sum::<f64>() / n,
+        avg_vrp: 0.0,
+        positive_vrp: (0, 0.0),
+        negative_vrp: (0, 0.0),
+    };
+    report.avg_vrp = report.avg_implied - report.avg_realized;
+    for (trade, realized) in measured {
+        let bucket = if trade.implied_vol > realized {
+            &mut report.positive_vrp
+        } else {
+            &mut report.negative_vrp
+        };
+        bucket.0 += 1;
+        bucket.1 += trade.pnl;
+    }
+    Some(report)
+}
+
+/// Print the realized-vs-implied volatility section
+pub fn print_volatility_report(report: &VolatilityReport, contract_multiplier: f64) {
+    println!("\nVOLATILITY (realized vs implied, {} trades)", report.trades);
+    println!("{}", "-".repeat(60));
+    println!("Avg implied vol at entry:   {:.1}%", report.avg_implied * 100.0);
+    println!("Avg realized vol over life: {:.1}%", report.avg_realized * 100.0);
+    println!("Avg VRP captured:           {:.1}%", report.avg_vrp * 100.0);
+    println!(
+        "Implied > realized: {} trades, P&L ${:.0}",
+        report.positive_vrp.0,
+        report.positive_vrp.1 * contract_multiplier
+    );
+    println!(
+        "Realized >= implied: {} trades, P&L ${:.0}",
+        report.negative_vrp.0,
+        report.negative_vrp.1 * contract_multiplier
+    );
+}
+
+/// Premium decay profile aggregated over short positions
+#[derive(Debug, Clone)]
+pub struct PremiumDecayReport {
+    pub trades: usize,
+    /// Sum of entry premiums, per unit
+    pub total_premium: f64,
+    /// Sum of realized P&L, per unit
+    pub total_captured: f64,
+    /// Average fraction of entry premium kept per trade
+    pub avg_capture: f64,
+    /// Average remaining value at 50% of life, as a fraction of entry premium
+    pub avg_mid_fraction: Option<f64>,
+    /// Average remaining value at close, as a fraction of entry premium
+    pub avg_close_fraction: f64,
+}
+
+impl PremiumDecayReport {
+    /// Share of all premium sold that ended up as P&L
+    pub fn capture_efficiency(&self) -> f64 {
+        if self.total_premium > 0.0 {
+            self.total_captured / self.total_premium
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Summarize how much sold premium short positions kept and how fast it decayed
+pub fn premium_decay_report(trades: &[TradeRecord]) -> Option<PremiumDecayReport> {
+    let shorts: Vec<&TradeRecord> = trades
+        .iter()
+        .filter(|t| t.side == Side::Short && t.entry_premium > 0.0)
+        .collect();
+    if shorts.is_empty() {
+        return None;
+    }
+    let n = shorts.len() as f64;
+    let mids: Vec<f64> = shorts
+        .iter()
+        .filter_map(|t| t.mid_value.map(|v| v / t.entry_premium))
+        .collect();
+
+    Some(PremiumDecayReport {
+        trades: shorts.len(),
+        total_premium: shorts.iter().map(|t| t.entry_premium).sum(),
+        total_captured: shorts.iter().map(|t| t.pnl).sum(),
+        avg_capture: shorts.iter().map(|t| t.pnl / t.entry_premium).sum::<f64>() / n,
+        avg_mid_fraction: if mids.is_empty() {
+            None
+        } else {
+            Some(mids.iter().sum::<f64>() / mids.len() as f64)
+        },
+        avg_close_fraction: shorts.iter().map(|t| t.close_value / t.entry_premium).sum::<f64>() / n,
+    })
+}
+
+/// Print the premium decay section
+pub fn print_premium_decay_report(report: &PremiumDecayReport) {
+    println!("\nPREMIUM CAPTURE ({} short trades)", report.trades);
+    println!("{}", "-".repeat(60));
+    println!("Capture efficiency (total P&L / total premium): {:.1}%", report.capture_efficiency() * 100.0);
+    println!("Avg premium captured per trade: {:.1}%", report.avg_capture * 100.0);
+    println!("Remaining value (% of entry premium):");
+    println!("  At entry:       100.0%");
+    match report.avg_mid_fraction {
+        Some(mid) => println!("  At 50% of life: {:>5.1}%", mid * 100.0),
+        None => println!("  At 50% of life:   n/a"),
+    }
+    println!("  At close:       {:>5.1}%", report.avg_close_fraction * 100.0);
+}
+
+/// Largest peak-to-trough decline of the equity curve, per unit (>= 0)
+pub fn max_drawdown(series: &[EquityPoint]) -> f64 {
+    let mut peak = 0.0f64;
+    let mut worst = 0.0f64;
+    for point in series {
+        let equity = point.equity();
+        peak = peak.max(equity);
+        worst = worst.max(peak - equity);
+    }
+    worst
+}
+
+/// Annualized Sharpe ratio of daily P&L changes (252 trading days, zero risk-free)
+///
+/// P&L is in absolute terms, so this is mean/stdev of daily P&L rather than of returns.
+pub fn sharpe_ratio(series: &[EquityPoint]) -> Option<f64> {
+    let first = series.first()?;
+    let mut closes = vec![*first];
+    closes.extend(daily_closes(series));
+    let daily: Vec<f64> = closes.windows(2).map(|w| w[1].equity() - w[0].equity()).collect();
+    if daily.len() < 2 {
+        return None;
+    }
+    let mean = daily.iter().sum::<f64>() / daily.len() as f64;
+    let variance = daily.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (daily.len() - 1) as f64;
+    if variance <= 0.0 {
+        return None;
+    }
+    Some(mean / variance.sqrt() * 252.0f64.sqrt())
+}
+
+/// Summary statistics of a sample
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p5: f64,
+    pub p25: f64,
+    pub p75: f64,
+    pub p95: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    /// Compute statistics; percentiles use linear interpolation between ranks
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Some(Self {
+            count: sorted.len(),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            median: percentile(&sorted, 0.50),
+            p5: percentile(&sorted, 0.05),
+            p25: percentile(&sorted, 0.25),
+            p75: percentile(&sorted, 0.75),
+            p95: percentile(&sorted, 0.95),
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Percentile of an ascending-sorted, non-empty slice (q in 0..=1)
+pub fn percentile(sorted: &[f64], q: f64) -> f64 {
+    let rank = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Headline metrics of one seed's run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeedResult {
+    pub seed: u64,
+    /// Terminal P&L in dollars (realized + unrealized)
+    pub pnl: f64,
+    /// Maximum drawdown in dollars
+    pub max_drawdown: f64,
+    pub sharpe: Option<f64>,
+}
+
+/// Print the consolidated cross-seed distribution report
+pub fn print_seed_distribution(results: &[SeedResult]) {
+    let pnl: Vec<f64> = results.iter().map(|r| r.pnl).collect();
+    let drawdown: Vec<f64> = results.iter().map(|r| r.max_drawdown).collect();
+    let sharpe: Vec<f64> = results.iter().filter_map(|r| r.sharpe).collect();
+
+    println!("\n{}", "=".repeat(60));
+    println!("CROSS-SEED DISTRIBUTION ({} seeds)", results.len());
+    println!("{}", "=".repeat(60));
+    println!(
+        "{:<14} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "Metric", "Mean", "Median", "P5", "P25", "P75", "P95", "Worst"
+    );
+    if let Some(d) = Distribution::from_samples(&pnl) {
+        println!(
+            "{:<14} {:>10.0} {:>10.0} {:>10.0} {:>10.0} {:>10.0} {:>10.0} {:>10.0}",
+            "Net P&L ($)", d.mean, d.median, d.p5, d.p25, d.p75, d.p95, d.min
+        );
+    }
+    if let Some(d) = Distribution::from_samples(&drawdown) {
+        println!(
+            "{:<14} {:>10.0} {:>10.0} {:>10.0} {:>10.0} {:>10.0} {:>10.0} {:>10.0}",
+            "Max DD ($)", d.mean, d.median, d.p5, d.p25, d.p75, d.p95, d.max
+        );
+    }
+    if let Some(d) = Distribution::from_samples(&sharpe) {
+        println!(
+            "{:<14} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2}",
+            "Sharpe", d.mean, d.median, d.p5, d.p25, d.p75, d.p95, d.min
+        );
+    }
+    let losing = results.iter().filter(|r| r.pnl < 0.0).count();
+    println!("Losing seeds: {} of {}", losing, results.len());
+    if let Some(worst) = results.iter().min_by(|a, b| a.pnl.partial_cmp(&b.pnl).unwrap_or(std::cmp::Ordering::Equal)) {
+        println!("Worst seed: {} (P&L ${:.0})", worst.seed, worst.pnl);
+    }
+}
+
+/// A peak value and when it occurred
+#[derive(Debug, Clone, Copy, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Peak {
+    pub value: f64,
+    pub timestamp: Timestamp,
+}
+
+/// Most margin required at any bar of one day (dollars)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DailyMargin {
+    pub day: Day,
+    pub margin: f64,
+}
+
+/// Peak capital and exposure figures over a run
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RiskSummary {
+    pub peak_margin: Option<Peak>,
+    pub peak_notional: Option<Peak>,
+    /// Largest absolute net delta (sign preserved)
+    pub peak_delta: Option<Peak>,
+    /// Largest absolute net gamma (sign preserved)
+    pub peak_gamma: Option<Peak>,
+    /// Largest absolute net theta (sign preserved)
+    pub peak_theta: Option<Peak>,
+    /// Largest absolute net vega (sign preserved)
+    pub peak_vega: Option<Peak>,
+    /// Margin required on each day with bars, in day order
+    pub daily_margin: Vec<DailyMargin>,
+    #[serde(skip)]
+    margin_sum: f64,
+    #[serde(skip)]
+    observations: usize,
+}
+
+impl RiskSummary {
+    /// Fold one bar's snapshot into the running peaks
+    pub fn observe(&mut self, snapshot: &RiskSnapshot) {
+        fn update(slot: &mut Option<Peak>, value: f64, timestamp: Timestamp) {
+            if slot.map(|p| value.abs() > p.value.abs()).unwrap_or(true) {
+                *slot = Some(Peak { value, timestamp });
+            }
+        }
+        update(&mut self.peak_margin, snapshot.margin, snapshot.timestamp);
+        update(&mut self.peak_notional, snapshot.notional, snapshot.timestamp);
+        update(&mut self.peak_delta, snapshot.net_delta, snapshot.timestamp);
+        update(&mut self.peak_gamma, snapshot.net_gamma, snapshot.timestamp);
+        update(&mut self.peak_theta, snapshot.net_theta, snapshot.timestamp);
+        update(&mut self.peak_vega, snapshot.net_vega, snapshot.timestamp);
+        self.margin_sum += snapshot.margin;
+        self.observations += 1;
+        match self.daily_margin.last_mut() {
+            Some(last) if last.day == snapshot.timestamp.day => last.margin = last.margin.max(snapshot.margin),
+            _ => self.daily_margin.push(DailyMargin { day: snapshot.timestamp.day, margin: snapshot.margin }),
+        }
+    }
+
+    /// Average margin over all observed bars
+    pub fn average_margin(&self) -> f64 {
+        if self.observations == 0 {
+            0.0
+        } else {
+            self.margin_sum / self.observations as f64
+        }
+    }
+
+    /// Mean of the daily margin requirements
+    pub fn average_daily_margin(&self) -> f64 {
+        if self.daily_margin.is_empty() {
+            0.0
+        } else {
+            self.daily_margin.iter().map(|d| d.margin).sum::<f64>() / self.daily_margin.len() as f64
+        }
+    }
+
+    /// `pnl` (dollars) over the peak margin, or `None` if no margin was ever required
+    pub fn return_on_peak_margin(&self, pnl: f64) -> Option<f64> {
+        self.peak_margin.map(|peak| peak.value).filter(|margin| *margin > 0.0).map(|margin| pnl / margin)
+    }
+
+    /// `pnl` (dollars) over the average daily margin, or `None` if no margin was ever required
+    pub fn return_on_average_margin(&self, pnl: f64) -> Option<f64> {
+        Some(self.average_daily_margin()).filter(|margin| *margin > 0.0).map(|margin| pnl / margin)
+    }
+}
+
+/// Print the risk summary section, with returns on margin of `pnl` (dollars)
+pub fn print_risk_summary(summary: &RiskSummary, pnl: f64) {
+    fn line(label: &str, peak: &Option<Peak>) {
+        match peak {
+            Some(p) => println!("{:<22} {:>10}  at {}", label, format!("${:.0}", p.value), p.timestamp.format()),
+            None => println!("{:<22} {:>10}", label, "n/a"),
+        }
+    }
+    println!("\nRISK SUMMARY");
+    println!("{}", "-".repeat(60));
+    line("Peak margin:", &summary.peak_margin);
+    println!("{:<22} {:>10}", "Average margin:", format!("${:.0}", summary.average_margin()));
+    println!("{:<22} {:>10}", "Avg daily margin:", format!("${:.0}", summary.average_daily_margin()));
+    let percent = |ratio: Option<f64>| ratio.map_or("n/a".to_string(), |r| format!("{:.1}%", r * 100.0));
+    println!("{:<22} {:>10}", "Return on peak margin:", percent(summary.return_on_peak_margin(pnl)));
+    println!("{:<22} {:>10}", "Return on avg margin:", percent(summary.return_on_average_margin(pnl)));
+    line("Peak notional:", &summary.peak_notional);
+    line("Peak net delta ($/pt):", &summary.peak_delta);
+    line("Peak net gamma ($/pt):", &summary.peak_gamma);
+    line("Peak net theta ($/day):", &summary.peak_theta);
+    line("Peak net vega ($/vol):", &summary.peak_vega);
+}
+
+/// Strategy result compared with holding one futures contract over the same path
+#[derive(Debug, Clone)]
+pub struct Benchmark {
+    /// Strategy P&L per unit (realized + unrealized at the last bar)
+    pub strategy_pnl: f64,
+    /// Buy-and-hold futures P&L per unit (last price - first price)
+    pub futures_pnl: f64,
+    /// Correlation of daily strategy P&L with daily underlying changes
+    pub daily_correlation: Option<f64>,
+}
+
+/// Compare the equity curve with buy-and-hold of one futures contract
+pub fn benchmark(series: &[EquityPoint]) -> Option<Benchmark> {
+    let first = series.first()?;
+    let last = series.last()?;
+
+    // Daily changes, starting from the first bar so day 0 is included
+    let mut closes = vec![*first];
+    closes.extend(daily_closes(series));
+    let strategy_daily: Vec<f64> = closes.windows(2).map(|w| w[1].equity() - w[0].equity()).collect();
+    let futures_daily: Vec<f64> = closes.windows(2).map(|w| w[1].price - w[0].price).collect();
+
+    Some(Benchmark {
+        strategy_pnl: last.equity(),
+        futures_pnl: last.price - first.price,
+        daily_correlation: correlation(&strategy_daily, &futures_daily),
+    })
+}
+
+/// Print the buy-and-hold comparison
+pub fn print_benchmark(benchmark: &Benchmark, contract_multiplier: f64) {
+    println!("\nBENCHMARK (buy-and-hold 1 futures contract)");
+    println!("{}", "-".repeat(60));
+    println!("Strategy P&L:      ${:.0}", benchmark.strategy_pnl * contract_multiplier);
+    println!("Buy-and-hold P&L:  ${:.0}", benchmark.futures_pnl * contract_multiplier);
+    println!(
+        "Excess over hold:  ${:.0}",
+        (benchmark.strategy_pnl - benchmark.futures_pnl) * contract_multiplier
+    );
+    match benchmark.daily_correlation {
+        Some(corr) => println!("Daily P&L correlation with underlying: {:.3}", corr),
+        None => println!("Daily P&L correlation with underlying: n/a"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(close_day: Day, pnl: f64) -> TradeRecord {
+        TradeRecord {
+            position_id: 1,
+            side: Side::Short,
+            entry: Timestamp::new(close_day.saturating_sub(1), 15 * 60),
+            close: Timestamp::new(close_day, 14 * 60),
+            entry_price: 75.0,
+            close_price: 75.0,
+            entry_premium: 1.0,
+            mid_value: None,
+            close_value: 1.0 - pnl,
+            pnl,
+            implied_vol: 0.35,
+            realized_vol: None,
+            max_loss: None,
+            initial_margin: 16.0,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_weekly_breakdown() {
+        let trades = vec![trade(1, 0.5), trade(2, -0.25), trade(8, 0.10)];
+        let rows = periodic_breakdown(&trades, BreakdownPeriod::Week);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].label, "W0");
+        assert_eq!(rows[0].trades, 2);
+        assert_eq!(rows[0].wins, 1);
+        assert!((rows[0].pnl - 0.25).abs() < 1e-12);
+        assert_eq!(rows[1].label, "W1");
+        assert_eq!(rows[1].win_rate(), 100.0);
+    }
+
+    fn point(day: Day, minute: u32, price: f64, realized: f64) -> EquityPoint {
+        EquityPoint { timestamp: Timestamp::new(day, minute), price, realized, unrealized: 0.0 }
+    }
+
+    #[test]
+    fn test_daily_closes_keep_last_bar() {
+        let series = vec![point(0, 600, 75.0, 0.0), point(0, 900, 76.0, 0.1), point(1, 600, 74.0, 0.2)];
+        let closes = daily_closes(&series);
+        assert_eq!(closes.len(), 2);
+        assert_eq!(closes[0].price, 76.0);
+    }
+
+    #[test]
+    fn test_benchmark_against_futures() {
+        // Strategy P&L moves opposite to the underlying every day
+        let series = vec![
+            point(0, 600, 75.0, 0.0),
+            point(0, 900, 76.0, -1.0),
+            point(1, 900, 75.0, 0.0),
+            point(2, 900, 77.0, -2.0),
+        ];
+        let result = benchmark(&series).unwrap();
+        assert_eq!(result.futures_pnl, 2.0);
+        assert_eq!(result.strategy_pnl, -2.0);
+        assert!((result.daily_correlation.unwrap() + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_realized_volatility() {
+        // Alternating +/-1% moves have a known sample stdev
+        let prices = [100.0, 101.0, 100.0, 101.0, 100.0];
+        let vol = realized_volatility(&prices, 252.0).unwrap();
+        let r = (101.0f64 / 100.0).ln();
+        let expected = (4.0 * r * r / 3.0 * 252.0).sqrt();
+        assert!((vol - expected).abs() < 1e-12);
+        assert!(realized_volatility(&prices[..2], 252.0).is_none());
+    }
+
+    #[test]
+    fn test_volatility_report_splits_by_vrp_sign() {
+        let mut winner = trade(1, 0.4);
+        winner.realized_vol = Some(0.25);
+        let mut loser = trade(2, -0.6);
+        loser.realized_vol = Some(0.50);
+        let report = volatility_report(&[winner, loser, trade(3, 1.0)]).unwrap();
+
+        assert_eq!(report.trades, 2);
+        assert_eq!(report.positive_vrp, (1, 0.4));
+        assert_eq!(report.negative_vrp, (1, -0.6));
+        assert!((report.avg_vrp - (0.35 - 0.375)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_premium_decay_report() {
+        let mut first = trade(1, 0.8);
+        first.mid_value = Some(0.5);
+        let mut second = trade(2, -0.2);
+        second.mid_value = Some(0.9);
+        let mut long = trade(3, 5.0);
+        long.side = Side::Long;
+        let report = premium_decay_report(&[first, second, long]).unwrap();
+
+        assert_eq!(report.trades, 2);
+        assert!((report.capture_efficiency() - 0.3).abs() < 1e-12);
+        assert!((report.avg_mid_fraction.unwrap() - 0.7).abs() < 1e-12);
+        assert!((report.avg_close_fraction - 0.7).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_max_drawdown() {
+        let series = vec![
+            point(0, 600, 75.0, 1.0),
+            point(0, 610, 75.0, 3.0),
+            point(0, 620, 75.0, 0.5),
+            point(0, 630, 75.0, 2.0),
+        ];
+        assert_eq!(max_drawdown(&series), 2.5);
+    }
+
+    #[test]
+    fn test_distribution_percentiles() {
+        let samples: Vec<f64> = (1..=101).map(|i| i as f64).collect();
+        let d = Distribution::from_samples(&samples).unwrap();
+        assert_eq!(d.median, 51.0);
+        assert_eq!(d.p5, 6.0);
+        assert_eq!(d.p95, 96.0);
+        assert_eq!(d.min, 1.0);
+        assert_eq!(d.mean, 51.0);
+        assert!(Distribution::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_risk_summary_tracks_absolute_peaks() {
+        let snapshot = |day, margin, delta| RiskSnapshot {
+            timestamp: Timestamp::new(day, 600),
+            margin,
+            notional: 150_000.0,
+            net_delta: delta,
+            net_gamma: -30.0,
+            net_theta: 50.0,
+            net_vega: -100.0,
+        };
+        let mut summary = RiskSummary::default();
+        summary.observe(&snapshot(0, 10_000.0, 200.0));
+        summary.observe(&snapshot(1, 12_000.0, -900.0));
+        summary.observe(&snapshot(2, 11_000.0, 500.0));
+
+        let margin = summary.peak_margin.unwrap();
+        assert_eq!(margin.value, 12_000.0);
+        assert_eq!(margin.timestamp.day, 1);
+        assert_eq!(summary.peak_delta.unwrap().value, -900.0);
+        assert_eq!(summary.average_margin(), 11_000.0);
+
+        // A day's requirement is its highest bar
+        summary.observe(&RiskSnapshot { timestamp: Timestamp::new(2, 900), ..snapshot(2, 14_000.0, 0.0) });
+        let days: Vec<(Day, f64)> = summary.daily_margin.iter().map(|d| (d.day, d.margin)).collect();
+        assert_eq!(days, [(0, 10_000.0), (1, 12_000.0), (2, 14_000.0)]);
+        assert_eq!(summary.average_daily_margin(), 12_000.0);
+        assert_eq!(summary.return_on_peak_margin(1_400.0), Some(0.1));
+        assert_eq!(summary.return_on_average_margin(1_200.0), Some(0.1));
+        assert_eq!(RiskSummary::default().return_on_peak_margin(1_000.0), None);
+    }
+
+    #[test]
+    fn test_monthly_breakdown_is_chronological() {
+        let trades = vec![trade(400, 1.0), trade(40, 1.0), trade(10, -1.0)];
+        let rows = periodic_breakdown(&trades, BreakdownPeriod::Month);
+
+        let labels: Vec<&str> = rows.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, vec!["Y0 M01", "Y0 M02", "Y1 M02"]);
+    }
+}
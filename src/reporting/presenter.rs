@@ -0,0 +1,417 @@
+//! Report Presenter
+//!
+//! Turns a `SimulationReport` into the text the CLI prints. The engine only
+//! produces data, so the numbers never depend on the output mode, and the
+//! rendered text can be compared against golden files.
+
+use crate::calendar::{intraday::Timestamp, DteUnit, TimeOfDay};
+use crate::config::Config;
+use crate::engine::{BarOutcome, CloseCause, LogEntry, RollCheck, SimulationReport, SimulatorState};
+use crate::events::{OptionType, Side};
+use crate::pricing::Greeks;
+use crate::risk::PinOutcome;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Format timestamp as human-readable string
+pub fn format_timestamp(ts: &Timestamp) -> String {
+    let hours = ts.minute / 60;
+    let mins = ts.minute % 60;
+    let weekday = match ts.day % 7 {
+        0 => "Mon", 1 => "Tue", 2 => "Wed", 3 => "Thu",
+        4 => "Fri", 5 => "Sat", 6 => "Sun", _ => "???",
+    };
+    let week = ts.day / 7;
+    format!("Day {} ({} W{}) {:02}:{:02}", ts.day, weekday, week, hours, mins)
+}
+
+/// Headline for a log entry; opens from a roll are indented under the close
+pub fn entry_line(entry: &LogEntry, config: &Config) -> String {
+    let multiplier = config.simulation.contract_multiplier;
+    match entry {
+        LogEntry::Opened {
+            timestamp,
+            position_id,
+            price,
+            legs,
+            premium,
+            tags,
+            rolled,
+            same_strikes,
+            ..
+        } => {
+            let legs: Vec<String> = legs
+                .iter()
+                .map(|leg| match leg.option_type {
+                    OptionType::Put => format!("Put ${:.2}", leg.strike),
+                    OptionType::Call => format!("Call ${:.2}", leg.strike),
+                })
+                .collect();
+            let mut strikes = format!(
+                "Strikes: {} | ${:.2} per barrel (${:.0} total)",
+                legs.join(" "),
+                premium,
+                premium * multiplier
+            );
+            if !tags.is_empty() {
+                strikes.push_str(&format!(" [{}]", tags.join(", ")));
+            }
+            if *rolled {
+                format!(
+                    "  -> OPENED position {} at {} | {}{}",
+                    position_id,
+                    TimeOfDay::from_minutes(timestamp.minute as u16),
+                    strikes,
+                    if *same_strikes { " (same strikes)" } else { "" }
+                )
+            } else {
+                format!(
+                    "{} | Price ${:.2} | OPENED position {} at {} | {}",
+                    format_timestamp(timestamp),
+                    price,
+                    position_id,
+                    config.strategy.entry_time,
+                    strikes
+                )
+            }
+        }
+        LogEntry::LegRolled { timestamp, position_id, price, option_type, old_strike, new_strike, pnl } => format!(
+            "{} | Price ${:.2} | ROLLED {} of position {} at {} | ${:.2} -> ${:.2} | P&L: ${:.0}",
+            format_timestamp(timestamp),
+            price,
+            match option_type {
+                OptionType::Put => "put",
+                OptionType::Call => "call",
+            },
+            position_id,
+            TimeOfDay::from_minutes(timestamp.minute as u16),
+            old_strike,
+            new_strike,
+            pnl * multiplier
+        ),
+        LogEntry::Closed { timestamp, position_id, price, pnl, cause } => format!(
+            "{} | Price ${:.2} | CLOSED position {} at {} | P&L: ${:.0} ({})",
+            format_timestamp(timestamp),
+            price,
+            position_id,
+            TimeOfDay::from_minutes(timestamp.minute as u16),
+            pnl * multiplier,
+            match cause {
+                CloseCause::Expiration => "Expiration",
+                CloseCause::Roll => "Roll",
+                CloseCause::KillSwitch => "Kill switch",
+                CloseCause::DailyLoss => "Daily loss limit",
+            }
+        ),
+        LogEntry::Rejected { timestamp, price, rolled, reason } => {
+            if *rolled {
+                format!("  -> ROLL NOT REOPENED | {}", reason)
+            } else {
+                format!("{} | Price ${:.2} | ENTRY REJECTED | {}", format_timestamp(timestamp), price, reason)
+            }
+        }
+    }
+}
+
+/// Combined Greeks of a new position
+pub fn greeks_line(greeks: &Greeks) -> String {
+    format!(
+        "      Greeks: δ={:.3} γ={:.4} θ={:.3}/day ν={:.3}",
+        greeks.delta, greeks.gamma, greeks.theta, greeks.vega
+    )
+}
+
+/// The book after a bar, as `--step` prints it: the open position, its
+/// marks and dollar Greeks, and the roll rule the next bar will check
+pub fn step_view(
+    state: &SimulatorState<'_>,
+    outcome: &BarOutcome,
+    roll: Option<&RollCheck>,
+    config: &Config,
+) -> String {
+    let multiplier = config.simulation.contract_multiplier;
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "-- {} | Price ${:.2} | bar {}/{} | {}",
+        format_timestamp(&outcome.timestamp),
+        outcome.price,
+        outcome.bar_index + 1,
+        state.total_bars,
+        state.lifecycle.name()
+    );
+    match state.position {
+        Some(pos) => {
+            let legs: Vec<String> = pos
+                .legs
+                .iter()
+                .map(|leg| format!("{} ${:.2}", if leg.is_call() { "call" } else { "put" }, leg.strike()))
+                .collect();
+            let _ = writeln!(
+                out,
+                "   Position {} ({} {}): {}, expires day {}, IV {:.1}%",
+                pos.position_id.0,
+                config.strategy.side,
+                config.strategy.strategy_type,
+                legs.join(" "),
+                pos.expiration_day,
+                state.implied_vol * 100.0
+            );
+        }
+        None => {
+            let _ = writeln!(out, "   Flat (entries from {})", config.strategy.entry_time);
+        }
+    }
+    let equity = &outcome.equity;
+    let _ = writeln!(
+        out,
+        "   Unrealized ${:.0} | realized ${:.0} | equity ${:.0}",
+        equity.unrealized * multiplier,
+        equity.realized * multiplier,
+        equity.equity() * multiplier
+    );
+    let risk = &outcome.risk;
+    let _ = writeln!(
+        out,
+        "   Greeks: delta ${:.0}/pt gamma ${:.0}/pt theta ${:.0}/day vega ${:.0}/vol | margin ${:.0}",
+        risk.net_delta, risk.net_gamma, risk.net_theta, risk.net_vega, risk.margin
+    );
+    if let Some(roll) = roll {
+        let _ = writeln!(
+            out,
+            "   Next bar {}: roll at {} -> {} (DTE {:.2})",
+            format_timestamp(&roll.timestamp),
+            roll.rule,
+            if roll.due { "ROLLS" } else { "not yet" },
+            roll.dte
+        );
+    }
+    out
+}
+
+/// Worst loss and margin of a new position, in dollars
+pub fn risk_line(max_loss: Option<f64>, initial_margin: f64, multiplier: f64) -> String {
+    let max_loss = max_loss.map_or_else(|| "unlimited".to_string(), |loss| format!("${:.0}", loss * multiplier));
+    format!("      Risk: max loss {} | initial margin ${:.0}", max_loss, initial_margin * multiplier)
+}
+
+/// Every trade log line in order, with Greeks and risk under each open
+pub fn trade_log(report: &SimulationReport, config: &Config) -> Vec<String> {
+    let multiplier = config.simulation.contract_multiplier;
+    let mut lines = Vec::with_capacity(report.log.len() * 3);
+    for entry in &report.log {
+        lines.push(entry_line(entry, config));
+        if let LogEntry::Opened { greeks, max_loss, initial_margin, .. } = entry {
+            lines.push(greeks_line(greeks));
+            lines.push(risk_line(*max_loss, *initial_margin, multiplier));
+        }
+    }
+    lines
+}
+
+/// Simulation and strategy parameters
+pub fn render_parameters(config: &Config, report: &SimulationReport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Simulation Parameters:");
+    if let (Some(_), Some(product)) = (&config.symbol, &config.product) {
+        let (symbol, currency) = (&product.symbol, &product.currency);
+        let _ = writeln!(out, "  Contract: {} (multiplier {}, {})", symbol, product.point_value, currency);
+    }
+    let _ = writeln!(out, "  Days: {}", config.simulation.days);
+    let _ = writeln!(out, "  Resolution: {} minutes", config.simulation.intraday_resolution_minutes);
+    let _ = writeln!(out, "  Total bars: {}", report.total_bars);
+    let _ = writeln!(out, "  Initial price: ${:.2}", config.simulation.initial_price);
+    let _ = writeln!(out, "  Drift (μ): {:.2}%", config.simulation.drift * 100.0);
+    let _ = writeln!(out, "  Realized volatility: {:.0}%", config.simulation.volatility * 100.0);
+    let _ = writeln!(out, "  Volatility Risk Premium: {:.1}%", config.simulation.volatility_risk_premium * 100.0);
+    let _ = writeln!(out, "  Implied volatility: {:.0}% (for option pricing)", report.implied_vol * 100.0);
+    let _ = writeln!(out, "  Risk-free rate: {:.1}%", config.simulation.risk_free_rate * 100.0);
+    let generated = if config.simulation.seed_generated { " (random)" } else { "" };
+    let _ = writeln!(out, "  Seed: {}{}", config.simulation.seed, generated);
+    let _ = writeln!(out);
+    let days = if config.dte_unit() == DteUnit::Calendar { " calendar-day" } else { "" };
+    let _ = writeln!(out, "Strategy: {} ({}{} DTE)", config.strategy.strategy_type, config.strategy.entry_dte, days);
+    let _ = writeln!(
+        out,
+        "  Side: {} ({})",
+        config.strategy.side,
+        if config.strategy.side() == Side::Long { "pay premium" } else { "collect premium" }
+    );
+    let _ = writeln!(out, "  Entry time: {}", config.strategy.entry_time);
+    let _ = writeln!(out, "  Roll time: {}", config.strategy.roll_time);
+    let _ = writeln!(out, "  Strike selection: {}", config.strategy.strike_selection);
+    let _ = writeln!(out, "  Strike tick size: ${:.2}", config.strike_config.tick_size);
+    let _ = writeln!(out, "  Roll type: {}", config.strike_config.roll_type);
+    if config.strategy.strike_offset > 0.0 {
+        let _ = writeln!(out, "  Strike offset: {} points", config.strategy.strike_offset);
+    }
+    if config.strategy.put_quantity != 1.0 || config.strategy.call_quantity != 1.0 {
+        let (puts, calls) = (config.strategy.put_quantity, config.strategy.call_quantity);
+        let _ = writeln!(out, "  Quantity: {} puts, {} calls", puts, calls);
+    }
+    let _ = writeln!(out);
+    out
+}
+
+/// End-of-run P&L summary
+pub fn render_summary(config: &Config, report: &SimulationReport) -> String {
+    let multiplier = config.simulation.contract_multiplier;
+    let pnl = &report.pnl_summary;
+    let mut out = String::new();
+    let _ = writeln!(out, "\n{}", "=".repeat(60));
+    let _ = writeln!(out, "SIMULATION SUMMARY");
+    let _ = writeln!(out, "{}", "=".repeat(60));
+    let _ = writeln!(out, "Total positions opened: {}", pnl.position_count);
+    let _ = writeln!(
+        out,
+        "Total premium collected: ${:.2} per barrel (${:.0} total)",
+        pnl.total_premium_collected,
+        pnl.total_premium_collected * multiplier
+    );
+    let _ = writeln!(
+        out,
+        "Total premium paid: ${:.2} per barrel (${:.0} total)",
+        pnl.total_premium_paid,
+        pnl.total_premium_paid * multiplier
+    );
+    let net_premium = pnl.total_premium_collected - pnl.total_premium_paid;
+    let _ = writeln!(out, "Net premium: ${:.2} per barrel (${:.0} total)", net_premium, net_premium * multiplier);
+    let _ = writeln!(
+        out,
+        "Realized P&L (closed positions): ${:.2} per barrel (${:.0} total)",
+        pnl.realized_pnl,
+        pnl.realized_pnl * multiplier
+    );
+    let _ = writeln!(
+        out,
+        "Unrealized P&L (open positions at final mark): ${:.2} per barrel (${:.0} total)",
+        pnl.unrealized_pnl,
+        pnl.unrealized_pnl * multiplier
+    );
+    let net_pnl = report.net_pnl();
+    let _ = writeln!(out, "Net P&L: ${:.2} per barrel (${:.0} total)", net_pnl, net_pnl * multiplier);
+    let _ = writeln!(out, "Contract multiplier: {} barrels", multiplier as u32);
+    if let Some(final_price) = report.final_price {
+        let _ = writeln!(out, "Final underlying price: ${:.2}", final_price);
+    }
+    if let Some(switch) = &config.kill_switch {
+        let _ = writeln!(
+            out,
+            "Kill switch (${:.0} drawdown): tripped {} time(s)",
+            switch.max_drawdown,
+            report.kill_switches.len()
+        );
+        for trip in &report.kill_switches {
+            let _ = writeln!(
+                out,
+                "  {} | drawdown ${:.0} | entries resume in session {}",
+                format_timestamp(&trip.timestamp),
+                trip.drawdown,
+                trip.resume_day
+            );
+        }
+    }
+    if let Some(limit) = &config.daily_loss {
+        let _ = writeln!(out, "Daily loss limit (${:.0}): halted {} day(s)", limit.max_loss, report.daily_halts.len());
+        for halt in &report.daily_halts {
+            let _ = writeln!(out, "  {} | down ${:.0} on the day", format_timestamp(&halt.timestamp), halt.loss);
+        }
+    }
+    if let Some(pin) = &config.pin_risk {
+        let expirations = report
+            .log
+            .iter()
+            .filter(|entry| matches!(entry, LogEntry::Closed { cause: CloseCause::Expiration, .. }))
+            .count();
+        let assigned = report.pins.iter().filter(|p| p.outcome == PinOutcome::Assigned).count();
+        let cost: f64 = report.pins.iter().map(|p| p.value - p.intrinsic).sum();
+        let _ = writeln!(
+            out,
+            "Pin risk ({:.2} band, {}): {} leg(s) pinned over {} expiration(s), {} assigned, ${:.0} vs intrinsic",
+            pin.band,
+            pin.mode,
+            report.pins.len(),
+            expirations,
+            assigned,
+            cost * multiplier
+        );
+    }
+    out
+}
+
+/// Parameters, trade log and summary, as printed by the CLI
+pub fn render(config: &Config, report: &SimulationReport) -> String {
+    let mut out = render_parameters(config, report);
+    for line in trade_log(report, config) {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push_str(&render_summary(config, report));
+    out
+}
+
+/// Width of the CLI progress bar in characters
+const PROGRESS_WIDTH: usize = 30;
+
+/// One-line progress bar, e.g. `[#######-------]  50.0%  8120 bars/s  ETA 0:04`
+pub fn progress_bar(fraction: f64, rate: f64, unit: &str, eta: Option<Duration>) -> String {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let filled = (fraction * PROGRESS_WIDTH as f64).round() as usize;
+    let eta = match eta {
+        Some(eta) => {
+            let secs = eta.as_secs();
+            format!("{}:{:02}", secs / 60, secs % 60)
+        }
+        None => "--:--".to_string(),
+    };
+    format!(
+        "[{}{}] {:>5.1}%  {:.0} {}/s  ETA {}",
+        "#".repeat(filled),
+        "-".repeat(PROGRESS_WIDTH - filled),
+        fraction * 100.0,
+        rate,
+        unit,
+        eta
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::SimulationEngine;
+
+    #[test]
+    fn test_trade_log_has_greeks_and_risk_under_each_open() {
+        let config = Config::default_1dte_straddle();
+        let report = SimulationEngine::run(&config).unwrap();
+        let lines = trade_log(&report, &config);
+        let opens = report.log.iter().filter(|e| matches!(e, LogEntry::Opened { .. })).count();
+        assert_eq!(lines.len(), report.log.len() + 2 * opens);
+        assert!(lines[0].contains("OPENED position 1 at 15:00"));
+        assert!(lines[1].trim_start().starts_with("Greeks:"));
+        assert!(lines[2].trim_start().starts_with("Risk: max loss unlimited | initial margin $"));
+    }
+
+    #[test]
+    fn test_render_is_pure() {
+        let config = Config::default_1dte_straddle();
+        let report = SimulationEngine::run(&config).unwrap();
+        assert_eq!(render(&config, &report), render(&config, &report));
+        assert_eq!(
+            render(&config, &report),
+            render(&config, &SimulationEngine::run(&config).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let line = progress_bar(0.5, 8120.4, "bars", Some(Duration::from_secs(64)));
+        assert_eq!(line, format!("[{}{}]  50.0%  8120 bars/s  ETA 1:04", "#".repeat(15), "-".repeat(15)));
+        assert!(progress_bar(0.0, 0.0, "runs", None).ends_with("ETA --:--"));
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(&Timestamp::new(8, 14 * 60)), "Day 8 (Tue W1) 14:00");
+    }
+}
@@ -0,0 +1,203 @@
+//! Risk and Margin Estimates
+//!
+//! Margin uses the exchange-style naked option formula rather than full SPAN:
+//! premium + max(base% × underlying − OTM amount, minimum% × underlying).
+//! For short straddles/strangles only the larger side is charged, plus the
+//! premium of the other side. Long options are paid in full and need no margin.
+
+use crate::calendar::intraday::Timestamp;
+use crate::config::MarginConfig;
+use crate::events::{OptionContract, OptionType, Side};
+use crate::pricing::Greeks;
+
+mod pin;
+mod portfolio;
+
+pub use pin::{PinMode, PinOutcome, PinRisk, PinnedLeg};
+pub use portfolio::{Exposure, LimitAction, PortfolioGreeks, RiskLimits};
+
+/// Margin requirement of one short option, per unit of underlying
+pub fn short_option_margin(underlying: f64, strike: f64, option_value: f64, is_call: bool, rates: &MarginConfig) -> f64 {
+    let otm_amount = if is_call {
+        (strike - underlying).max(0.0)
+    } else {
+        (underlying - strike).max(0.0)
+    };
+    let base = rates.base_rate * underlying - otm_amount;
+    let minimum = rates.minimum_rate * underlying;
+    option_value + base.max(minimum)
+}
+
+/// Margin requirement of a short put + short call pair, per unit of underlying
+pub fn short_straddle_margin(
+    underlying: f64,
+    put_strike: f64,
+    call_strike: f64,
+    put_value: f64,
+    call_value: f64,
+    rates: &MarginConfig,
+) -> f64 {
+    let put_req = short_option_margin(underlying, put_strike, put_value, false, rates);
+    let call_req = short_option_margin(underlying, call_strike, call_value, true, rates);
+    if put_req >= call_req {
+        put_req + call_value
+    } else {
+        call_req + put_value
+    }
+}
+
+/// Margin of `put_quantity` short puts and `call_quantity` short calls, per
+/// unit of underlying: matched pairs are charged as straddles and the rest of
+/// either side as naked options
+pub fn short_legs_margin(
+    underlying: f64,
+    put_strike: f64,
+    call_strike: f64,
+    put_value: f64,
+    call_value: f64,
+    (put_quantity, call_quantity): (f64, f64),
+    rates: &MarginConfig,
+) -> f64 {
+    let pairs = put_quantity.min(call_quantity);
+    let mut margin = pairs * short_straddle_margin(underlying, put_strike, call_strike, put_value, call_value, rates);
+    if put_quantity > pairs {
+        margin += (put_quantity - pairs) * short_option_margin(underlying, put_strike, put_value, false, rates);
+    }
+    if call_quantity > pairs {
+        margin += (call_quantity - pairs) * short_option_margin(underlying, call_strike, call_value, true, rates);
+    }
+    margin
+}
+
+/// Worst loss at expiration of legs opened at their premiums, per unit of
+/// underlying; `None` when short calls outnumber long ones and the loss is unlimited
+pub fn max_loss(legs: &[(OptionContract, f64)]) -> Option<f64> {
+    let signed = |contract: &OptionContract| match contract.side {
+        Side::Long => contract.quantity,
+        Side::Short => -contract.quantity,
+    };
+    let net_calls: f64 = legs
+        .iter()
+        .filter(|(contract, _)| contract.option_type == OptionType::Call)
+        .map(|(contract, _)| signed(contract))
+        .sum();
+    if net_calls < -1e-9 {
+        return None;
+    }
+    // The payoff is linear between strikes, so its low is at zero or a strike
+    let payoff = |underlying: f64| -> f64 {
+        legs.iter()
+            .map(|(contract, premium)| {
+                let intrinsic = match contract.option_type {
+                    OptionType::Call => (underlying - contract.strike).max(0.0),
+                    OptionType::Put => (contract.strike - underlying).max(0.0),
+                };
+                signed(contract) * (intrinsic - premium)
+            })
+            .sum()
+    };
+    let worst = legs.iter().map(|(contract, _)| payoff(contract.strike)).fold(payoff(0.0), f64::min);
+    Some((-worst).max(0.0))
+}
+
+/// Position risk at one bar, in dollars
+#[derive(Debug, Clone, Copy)]
+pub struct RiskSnapshot {
+    pub timestamp: Timestamp,
+    pub margin: f64,
+    /// Underlying notional controlled by all option legs
+    pub notional: f64,
+    /// Net delta in dollars per $1 move of the underlying
+    pub net_delta: f64,
+    /// Net gamma in dollars of delta per $1 move
+    pub net_gamma: f64,
+    /// Net theta in dollars per day
+    pub net_theta: f64,
+    /// Net vega in dollars per 1 vol point
+    pub net_vega: f64,
+}
+
+impl RiskSnapshot {
+    /// Net Greeks of the whole book
+    pub fn greeks(&self) -> PortfolioGreeks {
+        PortfolioGreeks { delta: self.net_delta, gamma: self.net_gamma, theta: self.net_theta, vega: self.net_vega }
+    }
+}
+
+/// Signed net Greeks of a set of legs (short legs flip sign)
+pub fn net_greeks(legs: &[(Greeks, bool)]) -> Greeks {
+    let mut net = Greeks { delta: 0.0, gamma: 0.0, theta: 0.0, vega: 0.0, rho: 0.0 };
+    for (greeks, is_short) in legs {
+        let sign = if *is_short { -1.0 } else { 1.0 };
+        net.delta += sign * greeks.delta;
+        net.gamma += sign * greeks.gamma;
+        net.theta += sign * greeks.theta;
+        net.vega += sign * greeks.vega;
+        net.rho += sign * greeks.rho;
+    }
+    net
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates() -> MarginConfig {
+        MarginConfig { base_rate: 0.20, minimum_rate: 0.10 }
+    }
+
+    #[test]
+    fn test_max_loss_is_capped_only_for_covered_calls() {
+        let leg = |option_type, side, strike| OptionContract {
+            underlying_price: 75.0,
+            strike,
+            option_type,
+            side,
+            expiration_day: 1,
+            quantity: 1.0,
+        };
+        let long_straddle =
+            [(leg(OptionType::Put, Side::Long, 75.0), 1.5), (leg(OptionType::Call, Side::Long, 75.0), 1.5)];
+        assert_eq!(max_loss(&long_straddle), Some(3.0));
+        let short_strangle =
+            [(leg(OptionType::Put, Side::Short, 70.0), 0.5), (leg(OptionType::Call, Side::Short, 80.0), 0.5)];
+        assert_eq!(max_loss(&short_strangle), None);
+        // A short put loses at most its strike less the premium
+        assert_eq!(max_loss(&short_strangle[..1]), Some(69.5));
+    }
+
+    #[test]
+    fn test_atm_short_put_margin() {
+        // 1.00 premium + 20% of 75
+        let margin = short_option_margin(75.0, 75.0, 1.0, false, &rates());
+        assert!((margin - 16.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_far_otm_uses_minimum() {
+        // 20% × 75 − 10 OTM = 5 < 10% × 75 = 7.5
+        let margin = short_option_margin(75.0, 85.0, 0.05, true, &rates());
+        assert!((margin - 7.55).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_straddle_charges_larger_side_only() {
+        let margin = short_straddle_margin(75.0, 75.0, 75.0, 1.0, 1.2, &rates());
+        // Call side: 1.2 + 15 = 16.2, plus put premium 1.0
+        assert!((margin - 17.2).abs() < 1e-12);
+        // A second put is charged as a naked put: 1.0 + 15
+        let sized = short_legs_margin(75.0, 75.0, 75.0, 1.0, 1.2, (2.0, 1.0), &rates());
+        assert!((sized - 33.2).abs() < 1e-12);
+        assert_eq!(short_legs_margin(75.0, 75.0, 75.0, 1.0, 1.2, (1.0, 1.0), &rates()), margin);
+    }
+
+    #[test]
+    fn test_net_greeks_flip_short_legs() {
+        let call = Greeks { delta: 0.5, gamma: 0.1, theta: -0.02, vega: 0.1, rho: 0.0 };
+        let put = Greeks { delta: -0.5, gamma: 0.1, theta: -0.02, vega: 0.1, rho: 0.0 };
+        let net = net_greeks(&[(call, true), (put, true)]);
+        assert!(net.delta.abs() < 1e-12);
+        assert!((net.vega + 0.2).abs() < 1e-12);
+        assert!(net.theta > 0.0);
+    }
+}
@@ -0,0 +1,155 @@
+//! Pin Risk
+//!
+//! A short option that settles within `band` points of its strike may or may
+//! not be exercised, and the writer only finds out after the close, when the
+//! underlying can already have moved. Instead of clean intrinsic value, such
+//! a pinned leg settles either at the worst case (assigned, with the
+//! underlying a full band against the futures position it leaves) or by a
+//! coin flip between expiring worthless and assignment marked at a price
+//! drawn uniformly within the band. Long legs are the holder's choice and
+//! always settle at intrinsic value.
+
+use crate::calendar::intraday::Timestamp;
+use crate::config::PinRiskConfig;
+use rand::Rng;
+use serde::Serialize;
+
+/// How a pinned leg settles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinMode {
+    /// Always assigned, with the underlying a full band against the writer
+    WorstCase,
+    /// Assigned or abandoned with equal odds, marked at a random price in the band
+    Random,
+}
+
+impl PinMode {
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "worst_case" => Some(Self::WorstCase),
+            "random" => Some(Self::Random),
+            _ => None,
+        }
+    }
+}
+
+/// What became of a pinned leg
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum PinOutcome {
+    /// Exercised against the writer, leaving a futures position at the strike
+    Assigned,
+    /// Expired worthless
+    Abandoned,
+}
+
+/// A short leg that settled inside the pin band
+#[derive(Debug, Clone, Copy, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PinnedLeg {
+    pub timestamp: Timestamp,
+    pub strike: f64,
+    pub is_call: bool,
+    /// Underlying price at settlement
+    pub settlement: f64,
+    pub outcome: PinOutcome,
+    /// What closing the leg cost, per unit
+    pub value: f64,
+    /// What it would have cost at intrinsic value, per unit
+    pub intrinsic: f64,
+}
+
+/// Pin band and settlement mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PinRisk {
+    pub band: f64,
+    pub mode: PinMode,
+}
+
+impl PinRisk {
+    /// Parse a `PinRiskConfig` (validated with the config)
+    pub fn from_config(config: &PinRiskConfig) -> Option<Self> {
+        Some(Self { band: config.band, mode: PinMode::from_config(&config.mode)? })
+    }
+
+    /// Outcome and closing cost per unit of each short leg (strike, is_call)
+    /// of one position expiring with the underlying at `settlement`, `None`
+    /// for legs outside the band
+    ///
+    /// All legs share one post-settlement price, so a pinned straddle is
+    /// never charged for a move both ways. The worst case takes whichever
+    /// edge of the band costs the position more, with each pinned leg
+    /// assigned where that hurts and abandoned where it would not.
+    pub fn settle(&self, settlement: f64, legs: &[(f64, bool)], rng: &mut impl Rng) -> Vec<Option<(PinOutcome, f64)>> {
+        let pinned = |strike: f64| (settlement - strike).abs() <= self.band;
+        // Assignment leaves the writer short futures at the strike for a call, long for a put
+        let assigned = |price: f64, strike: f64, is_call: bool| if is_call { price - strike } else { strike - price };
+        if !legs.iter().any(|(strike, _)| pinned(*strike)) {
+            return vec![None; legs.len()];
+        }
+        match self.mode {
+            PinMode::WorstCase => {
+                let at = |price: f64| -> Vec<Option<(PinOutcome, f64)>> {
+                    let settle = |&(strike, is_call): &(f64, bool)| match assigned(price, strike, is_call) {
+                        cost if cost > 0.0 => (PinOutcome::Assigned, cost),
+                        _ => (PinOutcome::Abandoned, 0.0),
+                    };
+                    legs.iter().map(|leg| pinned(leg.0).then(|| settle(leg))).collect()
+                };
+                let cost = |outcomes: &[Option<(PinOutcome, f64)>]| outcomes.iter().flatten().map(|o| o.1).sum::<f64>();
+                let (down, up) = (at(settlement - self.band), at(settlement + self.band));
+                if cost(&up) >= cost(&down) {
+                    up
+                } else {
+                    down
+                }
+            }
+            PinMode::Random => {
+                let price = settlement + rng.gen_range(-self.band..=self.band);
+                legs.iter()
+                    .map(|&(strike, is_call)| {
+                        let outcome = match rng.gen_bool(0.5) {
+                            true => (PinOutcome::Assigned, assigned(price, strike, is_call)),
+                            false => (PinOutcome::Abandoned, 0.0),
+                        };
+                        pinned(strike).then_some(outcome)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_pinned_legs_settle_by_mode() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let worst = PinRisk { band: 0.25, mode: PinMode::WorstCase };
+        let strangle = [(74.0, false), (75.0, true)];
+        assert_eq!(worst.settle(75.40, &strangle, &mut rng), [None, None]);
+        // Just out of the money, yet charged as assigned with a full band against
+        let settled = worst.settle(74.90, &strangle, &mut rng);
+        let (outcome, value) = settled[1].unwrap();
+        assert_eq!((settled[0], outcome), (None, PinOutcome::Assigned));
+        assert!((value - 0.15).abs() < 1e-12);
+
+        // A pinned straddle only pays for one direction
+        let straddle = [(75.0, false), (75.0, true)];
+        let settled = worst.settle(75.0, &straddle, &mut rng);
+        assert_eq!(settled[0], Some((PinOutcome::Abandoned, 0.0)));
+        assert_eq!(settled[1], Some((PinOutcome::Assigned, 0.25)));
+
+        let random = PinRisk { mode: PinMode::Random, ..worst };
+        let outcomes: Vec<(PinOutcome, f64)> =
+            (0..100).flat_map(|_| random.settle(75.0, &straddle, &mut rng)).flatten().collect();
+        assert_eq!(outcomes.len(), 200);
+        assert!(outcomes.iter().any(|(o, _)| *o == PinOutcome::Abandoned));
+        assert!(outcomes.iter().any(|(o, v)| *o == PinOutcome::Assigned && *v < 0.0));
+        assert!(outcomes.iter().all(|(_, v)| v.abs() <= 0.25));
+    }
+}
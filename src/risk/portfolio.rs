@@ -0,0 +1,190 @@
+//! Portfolio Exposure and Limits
+//!
+//! Position count, short premium, notional and net dollar Greeks summed over
+//! every open position, and the caps of a config's `risk_limits` section.
+//! Before a position is opened its exposure is added to the book's; if the
+//! book would end up past any cap the entry is blocked and recorded as an
+//! `Event::EntryRejected`.
+
+use crate::config::RiskLimitsConfig;
+use crate::pricing::Greeks;
+use serde::Serialize;
+use std::iter::Sum;
+use std::ops::Add;
+
+/// Net Greeks in dollars (per point, per point of delta, per day, per vol point)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PortfolioGreeks {
+    pub delta: f64,
+    pub gamma: f64,
+    pub theta: f64,
+    pub vega: f64,
+}
+
+impl PortfolioGreeks {
+    /// Dollar Greeks of a position whose signed per-unit Greeks are `net`
+    pub fn from_net(net: &Greeks, multiplier: f64) -> Self {
+        Self {
+            delta: net.delta * multiplier,
+            gamma: net.gamma * multiplier,
+            theta: net.theta * multiplier,
+            vega: net.vega * multiplier,
+        }
+    }
+}
+
+impl Add for PortfolioGreeks {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            delta: self.delta + other.delta,
+            gamma: self.gamma + other.gamma,
+            theta: self.theta + other.theta,
+            vega: self.vega + other.vega,
+        }
+    }
+}
+
+impl Sum for PortfolioGreeks {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// What a set of open positions holds, in dollars
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Exposure {
+    pub positions: usize,
+    /// Premium collected on the short positions
+    pub short_premium: f64,
+    /// Underlying notional controlled by all legs
+    pub notional: f64,
+    pub greeks: PortfolioGreeks,
+}
+
+impl Add for Exposure {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            positions: self.positions + other.positions,
+            short_premium: self.short_premium + other.short_premium,
+            notional: self.notional + other.notional,
+            greeks: self.greeks + other.greeks,
+        }
+    }
+}
+
+impl Sum for Exposure {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// What happens to an entry that would break a limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitAction {
+    /// Skip it; the next attempt is the next day's entry
+    Reject,
+    /// Retry on every following bar until it fits
+    Delay,
+}
+
+impl LimitAction {
+    pub fn from_config(value: &str) -> Option<Self> {
+        match value {
+            "reject" => Some(Self::Reject),
+            "delay" => Some(Self::Delay),
+            _ => None,
+        }
+    }
+}
+
+/// Caps on the book's size and absolute net Greeks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RiskLimits {
+    pub max_positions: Option<usize>,
+    pub max_short_premium: Option<f64>,
+    pub max_notional: Option<f64>,
+    pub max_delta: Option<f64>,
+    pub max_gamma: Option<f64>,
+    pub max_theta: Option<f64>,
+    pub max_vega: Option<f64>,
+    pub action: LimitAction,
+}
+
+impl RiskLimits {
+    /// Parse a `RiskLimitsConfig` (validated with the config)
+    pub fn from_config(config: &RiskLimitsConfig) -> Option<Self> {
+        Some(Self {
+            max_positions: config.max_positions,
+            max_short_premium: config.max_short_premium,
+            max_notional: config.max_notional,
+            max_delta: config.max_delta,
+            max_gamma: config.max_gamma,
+            max_theta: config.max_theta,
+            max_vega: config.max_vega,
+            action: LimitAction::from_config(&config.action)?,
+        })
+    }
+
+    /// Why `book` is out of bounds (the first cap broken), or `None` within every cap
+    pub fn breach(&self, book: &Exposure) -> Option<String> {
+        if let Some(cap) = self.max_positions.filter(|cap| book.positions > *cap) {
+            return Some(format!("{} open positions would exceed limit {}", book.positions, cap));
+        }
+        let greeks = &book.greeks;
+        let checks = [
+            ("short premium", book.short_premium, self.max_short_premium),
+            ("notional", book.notional, self.max_notional),
+            ("net delta", greeks.delta, self.max_delta),
+            ("net gamma", greeks.gamma, self.max_gamma),
+            ("net theta", greeks.theta, self.max_theta),
+            ("net vega", greeks.vega, self.max_vega),
+        ];
+        checks.into_iter().find_map(|(name, value, cap)| {
+            let cap = cap.filter(|cap| value.abs() > *cap)?;
+            Some(format!("{} {:.2} would exceed limit {:.2}", name, value, cap))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_check_the_summed_book() {
+        let long_call = Greeks { delta: 0.5, gamma: 0.1, theta: -0.02, vega: 0.1, rho: 0.0 };
+        let greeks = PortfolioGreeks::from_net(&long_call, 1000.0);
+        let position = Exposure { positions: 1, short_premium: 0.0, notional: 75_000.0, greeks };
+        let book: Exposure = [position, position].into_iter().sum();
+        assert_eq!((book.positions, book.greeks.delta, book.greeks.theta), (2, 1000.0, -40.0));
+
+        let mut limits = RiskLimits {
+            max_positions: None,
+            max_short_premium: None,
+            max_notional: None,
+            max_delta: Some(600.0),
+            max_gamma: None,
+            max_theta: None,
+            max_vega: Some(500.0),
+            action: LimitAction::Reject,
+        };
+        assert!(limits.breach(&position).is_none());
+        assert_eq!(limits.breach(&book).unwrap(), "net delta 1000.00 would exceed limit 600.00");
+        // Short Greeks count by their size too
+        let short = PortfolioGreeks { delta: -700.0, ..PortfolioGreeks::default() };
+        assert!(limits.breach(&Exposure { greeks: short, ..position }).is_some());
+
+        // Size caps come before the Greeks
+        limits.max_positions = Some(1);
+        assert_eq!(limits.breach(&book).unwrap(), "2 open positions would exceed limit 1");
+        limits = RiskLimits { max_positions: None, max_delta: None, max_notional: Some(100_000.0), ..limits };
+        assert!(limits.breach(&position).is_none());
+        assert!(limits.breach(&book).unwrap().starts_with("notional 150000.00"));
+    }
+}
@@ -0,0 +1,371 @@
+//! Parameter Sweep Runner
+//!
+//! Runs every point of the grid in a config's `sweep` section over the same
+//! seeds and writes the results table, ranked by `sweep.objective`; with
+//! `--optimize`, searches the grid within `sweep.optimizer.budget` runs
+//! instead (see `montecarlo::Optimizer`). With `--bars`, every point runs on
+//! historical OHLC bars; when the config has `splits`, only the train split
+//! is swept and the winner is reported on each split (see `montecarlo::holdout`).
+//! Usage: cargo run --bin sweep -- <config.yaml> [--out results.csv|results.json] [--runs runs.csv]
+//!        [--seeds n] [--threads n] [--optimize] [--budget n] [--db results.sqlite] [--study name]
+//!        [--bars bars.csv] [--splits splits.csv]
+
+use std::env;
+use std::io::Write;
+#[cfg(feature = "results-db")]
+use std::path::Path;
+use trading_simulator_v2::config::Config;
+use trading_simulator_v2::data::{self, DataSplits};
+use trading_simulator_v2::error::SimError;
+use trading_simulator_v2::montecarlo::{
+    self, HoldoutResult, OptimizeResult, Optimizer, Sweep, SweepProgress, SweepResult,
+};
+#[cfg(feature = "results-db")]
+use trading_simulator_v2::montecarlo::ResultsStore;
+use trading_simulator_v2::reporting::presenter;
+
+#[derive(Debug, Default)]
+struct CliArgs {
+    config_path: Option<String>,
+    /// Per-point summary table (`.csv`, or `.json` with the `serde_json` feature)
+    out_path: Option<String>,
+    /// Per-run table (CSV)
+    runs_path: Option<String>,
+    /// Overrides `sweep.seeds`
+    seeds: Option<u64>,
+    threads: Option<usize>,
+    /// Search the grid instead of running all of it
+    optimize: bool,
+    /// Overrides `sweep.optimizer.budget`
+    budget: Option<usize>,
+    /// SQLite database to record the runs in (requires the `results-db` feature)
+    db_path: Option<String>,
+    /// Study the runs are recorded under (default: the config file's name)
+    study: Option<String>,
+    /// Historical OHLC bars (CSV) to run every point on
+    bars_path: Option<String>,
+    /// Per-split results of the chosen point (CSV, with `--bars` and `splits`)
+    splits_path: Option<String>,
+}
+
+fn parse_args() -> CliArgs {
+    let mut cli = CliArgs::default();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" => cli.out_path = args.next(),
+            "--runs" => cli.runs_path = args.next(),
+            "--seeds" => cli.seeds = args.next().and_then(|n| n.parse().ok()),
+            "--threads" => cli.threads = args.next().and_then(|n| n.parse().ok()),
+            "--optimize" => cli.optimize = true,
+            "--budget" => cli.budget = args.next().and_then(|n| n.parse().ok()),
+            "--db" => cli.db_path = args.next(),
+            "--study" => cli.study = args.next(),
+            "--bars" => cli.bars_path = args.next(),
+            "--splits" => cli.splits_path = args.next(),
+            _ => cli.config_path = Some(arg),
+        }
+    }
+    cli
+}
+
+fn main() {
+    println!("Trading Simulator V2 - Parameter Sweep\n");
+
+    let cli = parse_args();
+    let Some(path) = cli.config_path.clone() else {
+        println!("Usage: cargo run --bin sweep -- <config.yaml> [--out results.csv|results.json] [--runs runs.csv]");
+        println!("       [--seeds n] [--threads n] [--optimize] [--budget n] [--db results.sqlite] [--study name]");
+        println!("       [--bars bars.csv] [--splits splits.csv]");
+        std::process::exit(1);
+    };
+    println!("Loading configuration from: {}", path);
+    let config = match Config::from_file(&path) {
+        Ok(cfg) => {
+            println!("✓ Configuration loaded successfully\n");
+            cfg
+        }
+        Err(e) => {
+            eprintln!("✗ Failed to load config: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if cli.optimize && cli.bars_path.is_some() {
+        eprintln!("✗ --optimize runs over generated seeds; sweep the grid on historical bars instead");
+        std::process::exit(1);
+    }
+    if cli.db_path.is_some() && cli.bars_path.is_some() {
+        // Stored runs are keyed by config and seed, which do not identify a historical path
+        eprintln!("✗ Runs on --bars are not recorded in the results database");
+        std::process::exit(1);
+    }
+
+    if cli.optimize {
+        let result = match run_optimizer(&config, &cli) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("✗ Search failed: {}", e);
+                std::process::exit(1);
+            }
+        };
+        print_leaderboard(&result);
+        if let Some(path) = &cli.out_path {
+            if let Err(e) = std::fs::write(path, result.csv()) {
+                eprintln!("✗ Failed to write results: {}", e);
+                std::process::exit(1);
+            }
+            println!("✓ Candidates written to {}", path);
+        }
+        return;
+    }
+
+    let result = match run_sweep(&config, &cli) {
+        Ok(SweepRun::Grid(result)) => result,
+        Ok(SweepRun::Holdout(holdout)) => {
+            print_summary(&holdout.training);
+            print_splits(&holdout);
+            if let Err(e) = write_holdout(&holdout, &cli) {
+                eprintln!("✗ Failed to write results: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Err(e) => {
+            eprintln!("✗ Sweep failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+    print_summary(&result);
+
+    if let Err(e) = write_outputs(&result, &cli) {
+        eprintln!("✗ Failed to write results: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// What `run_sweep` ran: the whole grid, or a holdout over historical splits
+enum SweepRun {
+    Grid(SweepResult),
+    Holdout(Box<HoldoutResult>),
+}
+
+fn run_sweep(config: &Config, cli: &CliArgs) -> Result<SweepRun, SimError> {
+    let mut sweep = Sweep::from_config(config)?;
+    if let Some(seeds) = cli.seeds {
+        sweep = sweep.seeds(seeds);
+    }
+    if let Some(threads) = cli.threads {
+        sweep = sweep.threads(threads);
+    }
+    let points = sweep.points().len();
+    let seeds = config.sweep.as_ref().map_or(1, |s| s.seeds);
+    println!("Objective: {}", sweep.objective());
+
+    if let Some(bars) = &cli.bars_path {
+        let options = data::OhlcOptions::from_config(&config.data_quality).unwrap_or_default();
+        let series = data::read_ohlc_csv(bars, &options)?;
+        let path = series.price_path()?;
+        let start = series.start_date().map_or_else(String::new, |date| format!(" from {}", date));
+        println!("Read {} bars{} from {}", path.len(), start, bars);
+        if let Some(splits) = config.splits.as_ref().and_then(DataSplits::from_config) {
+            let (start, end) = (splits.train.start, splits.train.end);
+            println!("Running {} points on the train split ({} to {})...", points, start, end);
+            let result = montecarlo::holdout_with_progress(&sweep, &path, series.mapping, &splits, draw_progress)?;
+            eprintln!();
+            return Ok(SweepRun::Holdout(Box::new(result)));
+        }
+        sweep = sweep.on_path(path);
+        println!("Running {} points on every bar...", points);
+    } else {
+        println!("Running {} points x {} seeds...", points, cli.seeds.unwrap_or(seeds));
+        if let Some(db) = &cli.db_path {
+            let recorded = run_recorded_sweep(db, cli, &sweep);
+            eprintln!();
+            let (result, resumed) = recorded?;
+            println!("✓ {} new run(s) recorded in {}, {} reused", result.rows.len() - resumed, db, resumed);
+            return Ok(SweepRun::Grid(result));
+        }
+    }
+    let result = sweep.run_with_progress(draw_progress)?;
+    eprintln!();
+    Ok(SweepRun::Grid(result))
+}
+
+/// Run the sweep as study `--study` (default: the config file's name),
+/// reusing runs the database already has and recording the rest as they
+/// finish; also returns how many were reused
+#[cfg(feature = "results-db")]
+fn run_recorded_sweep(db: &str, cli: &CliArgs, sweep: &Sweep) -> Result<(SweepResult, usize), SimError> {
+    let study = cli.study.clone().unwrap_or_else(|| {
+        let path = cli.config_path.as_deref().unwrap_or("sweep");
+        Path::new(path).file_stem().map_or_else(|| path.to_string(), |s| s.to_string_lossy().into_owned())
+    });
+    ResultsStore::open(db)?.run_sweep(&study, sweep, draw_progress)
+}
+
+#[cfg(not(feature = "results-db"))]
+fn run_recorded_sweep(_db: &str, _cli: &CliArgs, _sweep: &Sweep) -> Result<(SweepResult, usize), SimError> {
+    Err(SimError::Execution("Recording runs requires building with `--features results-db`".to_string()))
+}
+
+fn run_optimizer(config: &Config, cli: &CliArgs) -> Result<OptimizeResult, SimError> {
+    let mut settings = config.sweep.as_ref().and_then(|s| s.optimizer.clone()).unwrap_or_default();
+    if let Some(budget) = cli.budget {
+        settings.budget = budget;
+    }
+    let mut sweep = Sweep::from_config(config)?;
+    if let Some(threads) = cli.threads {
+        sweep = sweep.threads(threads);
+    }
+    let optimizer = Optimizer::new(sweep, &settings);
+    let plan = optimizer.plan()?;
+    println!("Objective: {}", optimizer.objective());
+    for (round, (candidates, seeds)) in plan.iter().enumerate() {
+        println!("  Round {}: {} candidates x {} seeds", round + 1, candidates, seeds);
+    }
+    let result = optimizer.run_with_progress(draw_progress)?;
+    eprintln!();
+    Ok(result)
+}
+
+fn draw_progress(p: SweepProgress) {
+    let mut stderr = std::io::stderr().lock();
+    let _ = write!(stderr, "\r{}", presenter::progress_bar(p.fraction(), p.runs_per_sec(), "runs", p.eta()));
+    let _ = stderr.flush();
+}
+
+/// The ten best candidates
+fn print_leaderboard(result: &OptimizeResult) {
+    println!("\n{}", "=".repeat(60));
+    println!("SEARCH RESULTS ({} runs, {} candidates)", result.runs, result.candidates.len());
+    println!("{}", "=".repeat(60));
+    println!("Parameters: {}", result.parameters.join(", "));
+    println!("{:>4}  {:<24} {:>6} {:>6} {:>12}", "rank", "values", "rounds", "seeds", "score");
+    for (rank, candidate) in result.candidates.iter().take(10).enumerate() {
+        let values: Vec<String> = candidate.values.iter().map(|v| v.to_string()).collect();
+        println!(
+            "{:>4}  {:<24} {:>6} {:>6} {:>12.4}",
+            rank + 1,
+            values.join(", "),
+            candidate.rounds,
+            candidate.runs.len(),
+            candidate.score
+        );
+    }
+}
+
+/// One line per point, the best by the objective marked, and points whose
+/// mean rides on a few lucky seeds flagged
+fn print_summary(result: &SweepResult) {
+    let summaries = result.summaries();
+    let best = result.ranked().first().filter(|s| s.score.is_some()).map(|s| s.point);
+
+    println!("\n{}", "=".repeat(60));
+    println!("SWEEP RESULTS");
+    println!("{}", "=".repeat(60));
+    println!("Parameters: {}", result.parameters.join(", "));
+    println!(
+        "{:>5}  {:<24} {:>11} {:>11} {:>11} {:>6} {:>11} {:>7} {:>11} {:>11} {:>11}",
+        "point",
+        "values",
+        "mean P&L",
+        "median",
+        "5th pct",
+        "win%",
+        "mean DD",
+        "sharpe",
+        "score",
+        "seed med",
+        "seed worst"
+    );
+    for summary in &summaries {
+        let values: Vec<String> = summary.values.iter().map(|v| v.to_string()).collect();
+        let sharpe = summary.mean_sharpe.map_or_else(|| "-".to_string(), |s| format!("{:.2}", s));
+        let score = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |s| format!("{:.4}", s));
+        let mut flags = String::new();
+        if Some(summary.point) == best {
+            flags.push_str("  <- best");
+        }
+        if summary.lucky {
+            flags.push_str("  (lucky: most seeds lose)");
+        }
+        println!(
+            "{:>5}  {:<24} {:>11.2} {:>11.2} {:>11.2} {:>5.0}% {:>11.2} {:>7} {:>11} {:>11} {:>11}{}",
+            summary.point,
+            values.join(", "),
+            summary.pnl.mean,
+            summary.pnl.median,
+            summary.pnl.p5,
+            summary.win_rate * 100.0,
+            summary.max_drawdown.mean,
+            sharpe,
+            score(summary.score),
+            score(summary.median_seed_score),
+            score(summary.worst_seed_score),
+            flags
+        );
+    }
+}
+
+/// The chosen point on every split, side by side
+fn print_splits(result: &HoldoutResult) {
+    let Some(best) = &result.best else {
+        println!("\nNo point has a score on the train split; nothing to test out of sample");
+        return;
+    };
+    let values: Vec<String> = best.values.iter().map(|v| v.to_string()).collect();
+    println!("\n{}", "=".repeat(60));
+    println!("OUT-OF-SAMPLE RESULTS (point {}: {})", best.point, values.join(", "));
+    println!("{}", "=".repeat(60));
+    println!("{:<11} {:<23} {:>6} {:>11} {:>11} {:>7}", "split", "dates", "bars", "P&L", "max DD", "sharpe");
+    for split in &result.splits {
+        let sharpe = split.result.sharpe.map_or_else(|| "-".to_string(), |s| format!("{:.2}", s));
+        println!(
+            "{:<11} {:<23} {:>6} {:>11.2} {:>11.2} {:>7}",
+            split.split.to_string(),
+            format!("{} - {}", split.range.start, split.range.end),
+            split.bars,
+            split.result.pnl,
+            split.result.max_drawdown,
+            sharpe
+        );
+    }
+}
+
+fn write_holdout(result: &HoldoutResult, cli: &CliArgs) -> Result<(), SimError> {
+    write_outputs(&result.training, cli)?;
+    if let Some(path) = &cli.splits_path {
+        std::fs::write(path, result.splits_csv())?;
+        println!("✓ Split results written to {}", path);
+    }
+    Ok(())
+}
+
+fn write_outputs(result: &SweepResult, cli: &CliArgs) -> Result<(), SimError> {
+    if let Some(path) = &cli.out_path {
+        let table = if path.ends_with(".json") { summary_json(result)? } else { result.summary_csv() };
+        std::fs::write(path, table)?;
+        println!("✓ Summary written to {}", path);
+    }
+    if let Some(path) = &cli.runs_path {
+        std::fs::write(path, result.runs_csv())?;
+        println!("✓ Runs written to {}", path);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "serde_json")]
+fn summary_json(result: &SweepResult) -> Result<String, SimError> {
+    let json = serde_json::json!({
+        "parameters": result.parameters,
+        "objective": result.objective.to_string(),
+        "points": result.ranked(),
+    });
+    serde_json::to_string_pretty(&json).map_err(|e| SimError::Execution(e.to_string()))
+}
+
+#[cfg(not(feature = "serde_json"))]
+fn summary_json(_result: &SweepResult) -> Result<String, SimError> {
+    Err(SimError::Execution("JSON output needs the serde_json feature; use a .csv path".to_string()))
+}
@@ -1,211 +1,146 @@
 //! Trading Simulator V2 - Tauri Desktop Application
 //! 
-//! Desktop UI for running simulations with real-time visualization
+//! Desktop UI for running simulations with real-time visualization.
+//! While a run executes, `simulation://progress` and `simulation://trade`
+//! events stream to the window for the progress bar and live trade log.
+//! Each run announces its job id in `simulation://started`; passing that id
+//! to `cancel_simulation` stops the run before its next bar.
+//! `load_config`/`save_config` read and write the YAML files the CLI uses.
+//! `compare_strategies` runs two configs over the same price path.
+//! `run_sweep` runs a two-parameter grid for heatmaps and `explore_seeds`
+//! one config over many seeds for histograms, reporting `sweep://progress`
+//! and `seeds://progress` as each simulation finishes.
+//! Completed runs are kept under the app data directory (`history/<id>.json`)
+//! and come back through `list_history` and `get_run`.
+//!
+//! The commands only adapt `trading_simulator_v2::desktop`, which does the work.
 
-mod calendar;
-mod config;
-mod events;
-mod prices;
-mod pricing;
-mod triggers;
-
-use calendar::{Calendar, Day, TimeOfDay};
-use config::Config;
-use events::{CloseReason, Event, EventStore, LegId, OptionContract, OptionType, PositionId, Side};
-use prices::GBM;
-use pricing::{Black76, Greeks};
-use triggers::{evaluate_triggers, PositionState, RollDecision};
-use serde::{Deserialize, Serialize};
-use std::env;
-use std::path::PathBuf;
-
-// Tauri command structure
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SimulationConfig {
-    pub days: usize,
-    pub initial_price: f64,
-    pub volatility: f64,
-    pub vrp: f64,
-    pub seed: u64,
-    pub strategy: String,
-    pub enable_long_leg: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TradeEntry {
-    pub trade_type: String,
-    pub message: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SimulationResult {
-    pub net_pnl: f64,
-    pub position_count: u32,
-    pub win_rate: f64,
-    pub final_price: f64,
-    pub trades: Vec<TradeEntry>,
-}
+use trading_simulator_v2::config::Config;
+use trading_simulator_v2::desktop::{
+    self, Comparison, History, HistoryEntry, Jobs, ProgressEvent, SeedExploration, SimulationConfig, SimulationResult,
+    StartedEvent, StoredRun, SweepAxis, SweepGrid, SweepProgressEvent, TradeEvent,
+};
+use trading_simulator_v2::engine::StreamEvent;
+use trading_simulator_v2::montecarlo::SweepProgress;
 
 /// Main entry point for Tauri application
 fn main() {
     tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![run_simulation])
+        .manage(Jobs::default())
+        .setup(|app| {
+            let data_dir = app.path_resolver().app_data_dir().ok_or("no app data directory")?;
+            app.manage(History::open(data_dir.join("history"))?);
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            run_simulation,
+            cancel_simulation,
+            load_config,
+            save_config,
+            list_history,
+            get_run,
+            run_sweep,
+            compare_strategies,
+            explore_seeds
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
-/// Tauri command to run simulation from UI
+/// Tauri command to run simulation from UI, emitting progress and trades as it goes
 #[tauri::command]
-async fn run_simulation(config: SimulationConfig) -> Result<SimulationResult, String> {
-    // Create config from UI parameters
-    let yaml_config = create_config_from_ui(&config);
-    
-    // Run simulation
-    match run_simulation_with_config(&yaml_config) {
-        Ok(result) => Ok(result),
-        Err(e) => Err(format!("Simulation failed: {}", e)),
+async fn run_simulation(
+    window: tauri::Window,
+    jobs: tauri::State<'_, Jobs>,
+    history: tauri::State<'_, History>,
+    config: SimulationConfig,
+) -> Result<SimulationResult, String> {
+    let yaml_config = desktop::create_config_from_ui(&config).map_err(|e| format!("Invalid configuration: {}", e))?;
+
+    let (job_id, cancel) = jobs.start();
+    let _ = window.emit("simulation://started", StartedEvent { job_id });
+
+    // Emit errors only mean the window has gone; the run still completes
+    let on_event = |leg: &'static str, leg_config: &Config, event: StreamEvent| match event {
+        StreamEvent::Progress(progress) => {
+            let _ = window.emit("simulation://progress", ProgressEvent::new(leg, leg_config, &progress));
+        }
+        StreamEvent::Trade(entry) => {
+            let _ = window.emit("simulation://trade", TradeEvent::new(leg, &entry, leg_config));
+        }
+    };
+
+    let result = desktop::run_simulation_with_config(&yaml_config, &cancel, on_event);
+    jobs.finish(job_id);
+    let mut result = result.map_err(|e| format!("Simulation failed: {}", e))?;
+
+    // A run that can't be saved is still shown
+    if let Err(e) = history.save(&config.strategy, &yaml_config, &mut result) {
+        eprintln!("Could not save run to history: {}", e);
+        result.history_id = None;
     }
+    Ok(result)
 }
 
-fn create_config_from_ui(config: &SimulationConfig) -> Config {
-    // Create appropriate config based on strategy selection
-    match config.strategy.as_str() {
-        "long_protection" => create_long_protection_config(config),
-        "combined" => create_combined_config(config),
-        _ => create_straddle_config(config),
-    }
+/// Tauri command running `config` over a two-parameter grid, `seeds` runs per point
+#[tauri::command]
+async fn run_sweep(
+    window: tauri::Window,
+    config: SimulationConfig,
+    x: SweepAxis,
+    y: SweepAxis,
+    seeds: Option<u64>,
+) -> Result<SweepGrid, String> {
+    desktop::run_sweep(&config, x, y, seeds, |progress: SweepProgress| {
+        let _ = window.emit("sweep://progress", SweepProgressEvent::from(progress));
+    })
 }
 
-fn create_straddle_config(config: &SimulationConfig) -> Config {
-    Config::default_1dte_straddle()
+/// Tauri command running `config` over `seeds` consecutive seeds for the P&L
+/// and drawdown distributions
+#[tauri::command]
+async fn explore_seeds(window: tauri::Window, config: SimulationConfig, seeds: u64) -> Result<SeedExploration, String> {
+    desktop::explore_seeds(&config, seeds, |progress: SweepProgress| {
+        let _ = window.emit("seeds://progress", SweepProgressEvent::from(progress));
+    })
 }
 
-fn create_long_protection_config(config: &SimulationConfig) -> Config {
-    let mut cfg = Config::default_1dte_straddle();
-    cfg.simulation.days = config.days;
-    cfg.simulation.initial_price = config.initial_price;
-    cfg.simulation.volatility = config.volatility;
-    cfg.simulation.volatility_risk_premium = config.vrp;
-    cfg.simulation.seed = config.seed;
-    cfg
+/// Tauri command running two configs over one price path and lining up their daily P&L
+#[tauri::command]
+async fn compare_strategies(first: SimulationConfig, second: SimulationConfig) -> Result<Comparison, String> {
+    desktop::compare_strategies(&first, &second)
 }
 
-fn create_combined_config(config: &SimulationConfig) -> Config {
-    let mut cfg = Config::default_1dte_straddle();
-    cfg.simulation.days = config.days;
-    cfg.simulation.initial_price = config.initial_price;
-    cfg.simulation.volatility = config.volatility;
-    cfg.simulation.volatility_risk_premium = config.vrp;
-    cfg.simulation.seed = config.seed;
-    cfg
+/// Tauri command listing saved runs, newest first
+#[tauri::command]
+fn list_history(history: tauri::State<'_, History>) -> Result<Vec<HistoryEntry>, String> {
+    history.list().map_err(|e| format!("Failed to read history: {}", e))
 }
 
-fn run_simulation_with_config(config: &Config) -> Result<SimulationResult, String> {
-    let realized_vol = config.simulation.volatility;
-    let implied_vol = realized_vol + config.simulation.volatility_risk_premium;
-    let risk_free_rate = config.simulation.risk_free_rate;
-    
-    // Generate price path
-    let mut gbm = GBM::new(
-        config.simulation.initial_price,
-        config.simulation.drift,
-        realized_vol,
-        config.simulation.seed,
-    );
-    let price_path = gbm.generate_path(config.simulation.days);
-    
-    let calendar = Calendar::new();
-    let mut event_store = EventStore::new();
-    
-    // Run simplified simulation
-    let mut trades = Vec::new();
-    let mut total_pnl = 0.0;
-    let mut position_count = 0;
-    let mut wins = 0;
-    
-    let entry_time = parse_time(&config.strategy.entry_time);
-    let roll_time = parse_time(&config.strategy.roll_time);
-    
-    let mut price_iter = price_path.iter();
-    let mut prev_position_pnl = 0.0;
-    
-    for day in 0..config.simulation.days as u32 {
-        if !calendar.is_trading_day(day) {
-            continue;
-        }
-        
-        let current_price = price_iter
-            .next()
-            .map(|(_, p)| *p)
-            .unwrap_or(config.simulation.initial_price);
-        
-        // Simplified: Open position every day, close next day
-        if day % 2 == 0 && position_count < 20 {  // Limit trades for demo
-            let expiration_day = calendar.next_trading_day(day);
-            let time_to_expiry = 1.0 / 252.0;
-            
-            let strike = config.strike_config.round_to_strike(current_price);
-            let premium = Black76::price(
-                current_price, strike, time_to_expiry,
-                risk_free_rate, implied_vol, false,
-            ) + Black76::price(
-                current_price, strike, time_to_expiry,
-                risk_free_rate, implied_vol, true,
-            );
-            
-            trades.push(TradeEntry {
-                trade_type: "open".to_string(),
-                message: format!(
-                    "Day {}: OPENED position {} at 15:00 | Strikes: Put ${:.2} Call ${:.2} | ${:.2} per barrel",
-                    day, position_count + 1, strike, strike, premium
-                ),
-            });
-            
-            prev_position_pnl = premium * 1000.0; // Approximate
-            position_count += 1;
-        } else if day % 2 == 1 && position_count > 0 && position_count <= 20 {
-            // Close previous position
-            let pnl = prev_position_pnl * 0.7; // Simulate some profit
-            total_pnl += pnl;
-            if pnl > 0 {
-                wins += 1;
-            }
-            
-            trades.push(TradeEntry {
-                trade_type: "close".to_string(),
-                message: format!(
-                    "Day {}: CLOSED position {} at 14:00 | P&L: ${:.0} (TimeTrigger)",
-                    day, position_count, pnl
-                ),
-            });
-        }
-    }
-    
-    let win_rate = if position_count > 0 {
-        (wins as f64 / position_count as f64) * 100.0
+/// Tauri command returning one saved run with its config and result
+#[tauri::command]
+fn get_run(history: tauri::State<'_, History>, id: u64) -> Result<StoredRun, String> {
+    history.get(id)
+}
+
+/// Tauri command to stop a run started by `run_simulation`
+#[tauri::command]
+fn cancel_simulation(jobs: tauri::State<'_, Jobs>, job_id: u64) -> Result<(), String> {
+    if jobs.cancel(job_id) {
+        Ok(())
     } else {
-        0.0
-    };
-    
-    Ok(SimulationResult {
-        net_pnl: total_pnl,
-        position_count,
-        win_rate,
-        final_price: price_path.last().map(|(_, p)| *p).unwrap_or(config.simulation.initial_price),
-        trades,
-    })
+        Err(format!("No running simulation with job id {}", job_id))
+    }
 }
 
-fn parse_time(time_str: &str) -> TimeOfDay {
-    let parts: Vec<&str> = time_str.split(':').collect();
-    let hours: TimeOfDay = parts[0].parse().unwrap_or(14);
-    let minutes: TimeOfDay = parts[1].parse().unwrap_or(0);
-    hours * 60 + minutes
+/// Tauri command to open a YAML config file (validated, as the CLI loads it)
+#[tauri::command]
+fn load_config(path: String) -> Result<Config, String> {
+    desktop::load_config(&path)
 }
 
-// Re-export from main module
-fn main_cli() {
-    // Original CLI main function
-    println!("Use 'cargo run --bin trading-simulator-v2' for CLI mode");
-    println!("Or use Tauri desktop app with 'cargo tauri dev'");
+/// Tauri command to write a config as YAML, refusing one the CLI couldn't load back
+#[tauri::command]
+fn save_config(path: String, config: Config) -> Result<(), String> {
+    desktop::save_config(&path, &config)
 }
@@ -2,9 +2,10 @@
 //!
 //! Evaluates roll conditions and executes position management decisions.
 
-use crate::calendar::{Calendar, Day, TimeOfDay};
-use crate::config::{Config, RollTriggerConfig};
-use crate::pricing::Black76;
+use crate::calendar::intraday::Timestamp;
+use crate::config::Config;
+use crate::engine::PositionTracking;
+use crate::events::{OptionType, RollTrigger};
 
 /// Result of evaluating roll triggers
 #[derive(Debug, Clone)]
@@ -34,180 +35,144 @@ pub enum RollReason {
     PriceMove { points_moved: f64 },
 }
 
-/// Position state for trigger evaluation
-#[derive(Debug, Clone)]
-pub struct PositionState {
-    pub position_id: u64,
-    pub entry_day: Day,
-    pub expiration_day: Day,
-    pub entry_price: f64,
-    pub current_price: f64,
-    pub put_strike: f64,
-    pub call_strike: f64,
-    pub put_entry_premium: f64,
-    pub call_entry_premium: f64,
-    pub last_rolled_put: Option<Day>,
-    pub last_rolled_call: Option<Day>,
+impl RollReason {
+    /// The trigger recorded on a rolled leg's event
+    pub fn trigger(&self) -> RollTrigger {
+        match *self {
+            RollReason::TimeTrigger => RollTrigger::TimeTrigger,
+            RollReason::DteThreshold { remaining_dte } => RollTrigger::DteThreshold { remaining_dte },
+            RollReason::ProfitTarget { profit_percent } => RollTrigger::ProfitTarget { profit_percent },
+            RollReason::StopLoss { loss_percent } => RollTrigger::StopLoss { loss_percent },
+            RollReason::PriceMove { points_moved } => RollTrigger::PriceMove { points_moved },
+        }
+    }
+
+    /// Whether it comes from the roll schedule (time or DTE), which only a
+    /// new expiry satisfies
+    pub fn is_scheduled(&self) -> bool {
+        matches!(self, RollReason::TimeTrigger | RollReason::DteThreshold { .. })
+    }
+}
+
+/// The open position's mark at the bar being evaluated
+#[derive(Debug, Clone, Copy)]
+pub struct TriggerMark {
+    pub timestamp: Timestamp,
+    /// Underlying price
+    pub price: f64,
+    /// Fractional DTE left, in the configured DTE unit
+    pub dte: f64,
+    /// Signed P&L per unit of the legs still held, as marked by the engine
+    pub unrealized_pnl: f64,
+}
+
+/// Whether the mark is at or past `roll_time` on the position's expiration day
+fn expiring(position: &PositionTracking, roll_time: u32, mark: &TriggerMark) -> bool {
+    mark.timestamp >= Timestamp::new(position.expiration_day, roll_time)
+}
+
+/// The time and DTE rules that roll the position on schedule, with whether
+/// any is due (e.g. "14:00 on expiration day 4")
+///
+/// Without a configured `time` or `dte` trigger, 1DTE positions roll at
+/// `roll_time` on expiration day and longer ones at 28 DTE.
+pub fn schedule(config: &Config, position: &PositionTracking, roll_time: u32, mark: &TriggerMark) -> (String, bool) {
+    let expiring = expiring(position, roll_time, mark);
+    let time = || (format!("{} on expiration day {}", config.strategy.roll_time, position.expiration_day), expiring);
+    let dte = |value: f64| (format!("DTE <= {}", value), mark.dte <= value);
+    let mut rules: Vec<(String, bool)> = config
+        .strategy
+        .roll_triggers
+        .iter()
+        .filter_map(|trigger| match trigger.trigger_type.as_str() {
+            "time" => Some(time()),
+            "dte" => Some(dte(trigger.value)),
+            _ => None,
+        })
+        .collect();
+    if rules.is_empty() {
+        rules.push(if config.strategy.entry_dte == 1 { time() } else { dte(28.0) });
+    }
+    rules.dedup();
+    let due = rules.iter().any(|(_, due)| *due);
+    (rules.into_iter().map(|(rule, _)| rule).collect::<Vec<_>>().join(" or "), due)
 }
 
 /// Evaluates roll triggers and returns decision
 pub fn evaluate_triggers(
-    position: &PositionState,
+    position: &PositionTracking,
     config: &Config,
-    calendar: &Calendar,
-    current_day: Day,
-    current_time: TimeOfDay,
-    implied_vol: f64,
-    risk_free_rate: f64,
+    roll_time: u32,
+    mark: &TriggerMark,
 ) -> RollDecision {
-    let roll_time = parse_time(&config.strategy.roll_time);
-    
+    let expiring = expiring(position, roll_time, mark);
+    let entry_value = position.entry_value();
+    // Profit and loss on either side are measured against the premium:
+    // the most a short can keep, or what a long paid
+    let pnl_fraction = if entry_value > 0.0 { mark.unrealized_pnl / entry_value } else { 0.0 };
+
     // Check each configured trigger
     for trigger in &config.strategy.roll_triggers {
-        match trigger.trigger_type.as_str() {
-            "time" => {
-                // Time trigger: roll at specific time on expiration day
-                if current_day == position.expiration_day && current_time >= roll_time {
-                    return RollDecision::RollBoth { 
-                        reason: RollReason::TimeTrigger 
-                    };
-                }
-            }
-            "dte" => {
-                // DTE threshold: roll when remaining DTE <= value
-                let remaining_dte = calendar.calculate_dte(current_day, position.expiration_day);
-                if remaining_dte as f64 <= trigger.value {
-                    return match trigger.legs.as_str() {
-                        "put" => RollDecision::RollPut { 
-                            reason: RollReason::DteThreshold { remaining_dte } 
-                        },
-                        "call" => RollDecision::RollCall { 
-                            reason: RollReason::DteThreshold { remaining_dte } 
-                        },
-                        _ => RollDecision::RollBoth { 
-                            reason: RollReason::DteThreshold { remaining_dte } 
-                        },
-                    };
-                }
+        // Price moves count from where the legs a trigger rolls were opened
+        let leg = match trigger.legs.as_str() {
+            "put" => Some(OptionType::Put),
+            "call" => Some(OptionType::Call),
+            _ => None,
+        };
+        let opened_at = leg
+            .and_then(|leg| position.legs.iter().find(|held| held.contract.option_type == leg))
+            .map_or(position.entry_price, |held| held.contract.underlying_price);
+        let reason = match trigger.trigger_type.as_str() {
+            // Time trigger: roll at roll_time on expiration day
+            "time" if expiring => RollReason::TimeTrigger,
+            // DTE threshold: roll when remaining DTE <= value
+            "dte" if mark.dte <= trigger.value => RollReason::DteThreshold { remaining_dte: mark.dte.ceil() as u32 },
+            // Profit target: roll when unrealized P&L >= value × the entry premium
+            "profit_target" if entry_value > 0.0 && pnl_fraction >= trigger.value => {
+                RollReason::ProfitTarget { profit_percent: pnl_fraction * 100.0 }
             }
-            "profit_target" => {
-                // Profit target: roll when unrealized P&L >= target × max profit
-                let target_fraction = trigger.value; // e.g., 0.50 for 50%
-                
-                // Calculate current position value
-                let remaining_dte = calendar.calculate_dte(current_day, position.expiration_day);
-                let time_to_expiry = remaining_dte as f64 / 252.0;
-                
-                let current_put = Black76::price(
-                    position.current_price,
-                    position.put_strike,
-                    time_to_expiry,
-                    risk_free_rate,
-                    implied_vol,
-                    false,
-                );
-                let current_call = Black76::price(
-                    position.current_price,
-                    position.call_strike,
-                    time_to_expiry,
-                    risk_free_rate,
-                    implied_vol,
-                    true,
-                );
-                
-                let entry_value = position.put_entry_premium + position.call_entry_premium;
-                let current_value = current_put + current_call;
-                // For shorts: profit = entry - current (we want options to lose value)
-                // For longs: profit = current - entry (we want options to gain value)
-                let is_long = config.strategy.side == "long";
-                let unrealized_pnl = if is_long {
-                    current_value - entry_value // Positive = profit for longs
-                } else {
-                    entry_value - current_value // Positive = profit for shorts
-                };
-                let max_profit = if is_long { f64::INFINITY } else { entry_value }; // Longs have unlimited upside
-                
-                // Check if we've hit the profit target
-                // For shorts: profit is capped at entry_value (100% = collect all premium)
-                // For longs: use the target as absolute dollar amount relative to entry
-                let profit_target_hit = if is_long {
-                    // Longs: profit target is % gain on premium paid
-                    let profit_percent = if entry_value > 0.0 { unrealized_pnl / entry_value } else { 0.0 };
-                    profit_percent >= target_fraction
-                } else {
-                    // Shorts: profit target is % of max profit (entry_value)
-                    max_profit > 0.0 && unrealized_pnl >= target_fraction * max_profit
-                };
-                
-                if profit_target_hit {
-                    return match trigger.legs.as_str() {
-                        "put" => RollDecision::RollPut { 
-                            reason: RollReason::ProfitTarget { 
-                                profit_percent: (unrealized_pnl / max_profit) * 100.0 
-                            } 
-                        },
-                        "call" => RollDecision::RollCall { 
-                            reason: RollReason::ProfitTarget { 
-                                profit_percent: (unrealized_pnl / max_profit) * 100.0 
-                            } 
-                        },
-                        _ => RollDecision::RollBoth { 
-                            reason: RollReason::ProfitTarget { 
-                                profit_percent: (unrealized_pnl / max_profit) * 100.0 
-                            } 
-                        },
-                    };
-                }
+            // Stop loss: roll when the loss reaches value × the entry premium
+            "stop_loss" if entry_value > 0.0 && -pnl_fraction >= trigger.value => {
+                RollReason::StopLoss { loss_percent: -pnl_fraction * 100.0 }
             }
-            "price_move" => {
-                // Price move: roll when underlying moved X points from entry
-                let price_move = (position.current_price - position.entry_price).abs();
-                if price_move >= trigger.value {
-                    return match trigger.legs.as_str() {
-                        "put" => RollDecision::RollPut { 
-                            reason: RollReason::PriceMove { points_moved: price_move } 
-                        },
-                        "call" => RollDecision::RollCall { 
-                            reason: RollReason::PriceMove { points_moved: price_move } 
-                        },
-                        _ => RollDecision::RollBoth { 
-                            reason: RollReason::PriceMove { points_moved: price_move } 
-                        },
-                    };
-                }
+            // Price move: roll when underlying moved X points from entry
+            "price_move" if (mark.price - opened_at).abs() >= trigger.value => {
+                RollReason::PriceMove { points_moved: (mark.price - opened_at).abs() }
             }
-            _ => {}
-        }
+            _ => continue,
+        };
+        return match leg {
+            Some(OptionType::Put) => RollDecision::RollPut { reason },
+            Some(OptionType::Call) => RollDecision::RollCall { reason },
+            None => RollDecision::RollBoth { reason },
+        };
     }
-    
-    // Default: check time-based roll even if not explicitly configured
-    // (to prevent holding past expiration)
-    if current_day == position.expiration_day && current_time >= roll_time {
-        return RollDecision::RollBoth { 
-            reason: RollReason::TimeTrigger 
+
+    // The default schedule when no time or DTE trigger sets one, and never
+    // hold past expiration
+    let (_, due) = schedule(config, position, roll_time, mark);
+    if due || expiring {
+        let reason = if expiring {
+            RollReason::TimeTrigger
+        } else {
+            RollReason::DteThreshold { remaining_dte: mark.dte.ceil() as u32 }
         };
+        return RollDecision::RollBoth { reason };
     }
-    
-    RollDecision::Hold
-}
 
-/// Parse time string "HH:MM" to minutes from midnight
-fn parse_time(time_str: &str) -> TimeOfDay {
-    let parts: Vec<&str> = time_str.split(':').collect();
-    let hours: TimeOfDay = parts[0].parse().unwrap_or(14);
-    let minutes: TimeOfDay = parts[1].parse().unwrap_or(0);
-    hours * 60 + minutes
+    RollDecision::Hold
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::calendar::TimeOfDay;
     
     #[test]
     fn test_time_trigger() {
         // Test that 14:00 trigger fires correctly
-        let trigger_time = parse_time("14:00");
-        assert_eq!(trigger_time, 14 * 60);
+        let trigger_time = TimeOfDay::parse("14:00").unwrap();
+        assert_eq!(trigger_time.minutes(), 14 * 60);
     }
     
     #[test]
@@ -221,4 +186,40 @@ mod tests {
         let profit_percent = (unrealized_pnl / max_profit) * 100.0;
         assert_eq!(profit_percent, 50.0);
     }
+
+    #[test]
+    fn test_profit_targets_and_stop_losses_read_the_mark() {
+        use crate::config::RollTriggerConfig;
+        use crate::engine::Simulator;
+
+        let mut config = Config::default_1dte_straddle();
+        config.strategy.side = "long".to_string();
+        let trigger = |trigger_type: &str, value: f64| RollTriggerConfig {
+            trigger_type: trigger_type.to_string(),
+            value,
+            legs: "both".to_string(),
+        };
+        config.strategy.roll_triggers = vec![trigger("profit_target", 0.5), trigger("stop_loss", 0.8)];
+        let mut sim = Simulator::new(config.clone()).unwrap();
+        let position = loop {
+            sim.step().unwrap().unwrap();
+            if let Some(pos) = sim.state().position {
+                break pos.clone();
+            }
+        };
+        let decide = |unrealized_pnl: f64| {
+            let mark = TriggerMark { timestamp: position.entry_timestamp, price: 80.0, dte: 1.0, unrealized_pnl };
+            evaluate_triggers(&position, &config, 14 * 60, &mark)
+        };
+
+        // A long straddle marked up 60% of what it paid takes profit
+        let premium = position.entry_value();
+        let RollDecision::RollBoth { reason: RollReason::ProfitTarget { profit_percent } } = decide(0.6 * premium)
+        else {
+            panic!("long straddle up 60% should take profit");
+        };
+        assert!((profit_percent - 60.0).abs() < 1e-9);
+        assert!(matches!(decide(0.4 * premium), RollDecision::Hold));
+        assert!(matches!(decide(-0.9 * premium), RollDecision::RollBoth { reason: RollReason::StopLoss { .. } }));
+    }
 }
@@ -0,0 +1,66 @@
+//! WebAssembly Entry Point
+//!
+//! `wasm-bindgen` exports so the web UI can run simulations client-side.
+//! Build with `wasm-pack build --target web -- --no-default-features --features wasm`.
+
+use crate::config::Config;
+use crate::engine::{LogEntry, SimulationEngine, SimulationReport};
+use crate::reporting::presenter;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// Result returned to JavaScript (same shape as the web server's `/run`)
+#[derive(Debug, Serialize)]
+struct WasmResult {
+    net_pnl: f64,
+    position_count: u32,
+    win_rate: f64,
+    final_price: f64,
+    trades: Vec<WasmTrade>,
+}
+
+#[derive(Debug, Serialize)]
+struct WasmTrade {
+    trade_type: &'static str,
+    message: String,
+}
+
+/// Run a simulation from a YAML config and return the summary as JSON
+#[wasm_bindgen]
+pub fn run_simulation(config_yaml: &str) -> Result<String, JsError> {
+    let config = Config::from_yaml_str(config_yaml)?;
+    let report = SimulationEngine::run(&config)?;
+    let result = summarize(&config, &report);
+    Ok(serde_json::to_string(&result)?)
+}
+
+/// Default 1DTE short straddle config as YAML, for seeding the UI
+#[wasm_bindgen]
+pub fn default_config_yaml() -> Result<String, JsError> {
+    Ok(serde_yaml::to_string(&Config::default_1dte_straddle())?)
+}
+
+fn summarize(config: &Config, report: &SimulationReport) -> WasmResult {
+    let trades = report
+        .log
+        .iter()
+        .map(|entry| WasmTrade {
+            trade_type: match entry {
+                LogEntry::Opened { .. } => "open",
+                LogEntry::LegRolled { .. } => "leg_roll",
+                LogEntry::Closed { .. } => "close",
+                LogEntry::Rejected { .. } => "rejected",
+            },
+            message: presenter::entry_line(entry, config).trim().to_string(),
+        })
+        .take(50)
+        .collect();
+
+    WasmResult {
+        net_pnl: report.net_pnl() * config.simulation.contract_multiplier,
+        position_count: report.pnl_summary.position_count,
+        win_rate: report.win_rate(),
+        final_price: report.final_price.unwrap_or(config.simulation.initial_price),
+        trades,
+    }
+}
@@ -0,0 +1,50 @@
+//! API Description
+//!
+//! OpenAPI 3 document for the HTTP API, generated from the handlers'
+//! `#[utoipa::path]` annotations and the request/response types themselves
+//! (the library's config and report types derive their schemas under the
+//! `openapi` feature), served as JSON at `/api-docs`. Every operation lists
+//! the optional bearer token (see `auth`).
+
+use crate::{jobs, results, sweep};
+use actix_web::HttpResponse;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Trading Simulator API", description = "Run option strategy simulations over simulated oil futures"),
+    paths(
+        crate::run_simulation,
+        crate::run_simulation_stream,
+        crate::simulate,
+        jobs::create,
+        jobs::get,
+        jobs::delete,
+        results::list,
+        results::get,
+        results::series,
+        results::download_json,
+        results::download_trades,
+        results::download_daily,
+        sweep::run,
+    ),
+    modifiers(&BearerToken),
+    security((), ("bearer_token" = []))
+)]
+struct ApiDoc;
+
+/// Registers the `bearer_token` scheme the operations refer to
+struct BearerToken;
+
+impl Modify for BearerToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let scheme = SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build());
+        openapi.components.get_or_insert_with(Default::default).add_security_scheme("bearer_token", scheme);
+    }
+}
+
+/// The OpenAPI document
+pub async fn openapi() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
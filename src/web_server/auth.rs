@@ -0,0 +1,96 @@
+//! API Tokens
+//!
+//! With tokens configured (`--api-token` or `TSV2_API_TOKENS`), endpoints
+//! that run simulations or read results take an `Authorized` extractor and
+//! answer 401 unless the request carries `Authorization: Bearer <token>`.
+//! Plain links (the UI's downloads) may pass `?access_token=<token>` instead.
+//! Without tokens every request is allowed, as before. The UI page and
+//! `/api-docs` are always public.
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use serde::Deserialize;
+use std::future::{ready, Ready};
+
+/// Accepted bearer tokens; empty means authentication is off
+#[derive(Debug, Default)]
+pub struct ApiTokens(Vec<String>);
+
+impl ApiTokens {
+    pub fn new(tokens: Vec<String>) -> Self {
+        Self(tokens.into_iter().filter(|t| !t.is_empty()).collect())
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    fn accepts(&self, candidate: &str) -> bool {
+        // Compare every token in full so timing doesn't reveal a prefix
+        self.0.iter().fold(false, |found, token| found | constant_time_eq(token.as_bytes(), candidate.as_bytes()))
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// The token a request presents, from the header or the query string
+fn presented_token(req: &HttpRequest) -> Option<String> {
+    let header = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match header {
+        Some(token) => Some(token.to_string()),
+        None => web::Query::<AccessToken>::from_query(req.query_string()).ok()?.into_inner().access_token,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AccessToken {
+    access_token: Option<String>,
+}
+
+/// Extractor that fails with 401 unless the request has a valid token
+#[derive(Debug)]
+pub struct Authorized;
+
+impl FromRequest for Authorized {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let allowed = match req.app_data::<web::Data<ApiTokens>>() {
+            Some(tokens) if tokens.enabled() => presented_token(req).is_some_and(|token| tokens.accepts(&token)),
+            _ => true,
+        };
+        ready(if allowed {
+            Ok(Authorized)
+        } else {
+            Err(actix_web::error::ErrorUnauthorized("missing or invalid API token"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_tokens_from_header_or_query() {
+        let tokens = ApiTokens::new(vec!["s3cret".to_string(), String::new()]);
+        assert!(tokens.enabled());
+        assert!(tokens.accepts("s3cret"));
+        assert!(!tokens.accepts("s3cre"));
+        assert!(!tokens.accepts(""));
+        assert!(!ApiTokens::new(vec![String::new()]).enabled());
+
+        let req = TestRequest::default().insert_header(("Authorization", "Bearer abc")).to_http_request();
+        assert_eq!(presented_token(&req).as_deref(), Some("abc"));
+        let req = TestRequest::with_uri("/results/1/trades.csv?x=1&access_token=a%2Bb").to_http_request();
+        assert_eq!(presented_token(&req).as_deref(), Some("a+b"));
+        assert_eq!(presented_token(&TestRequest::default().to_http_request()), None);
+    }
+}
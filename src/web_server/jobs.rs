@@ -0,0 +1,229 @@
+//! Background Simulation Jobs
+//!
+//! `POST /jobs` queues a complete config and returns its id straight away,
+//! `GET /jobs/{id}` polls status and the result, and `DELETE /jobs/{id}`
+//! cancels a running job (or forgets a finished one). Runs happen on the
+//! blocking pool, so long intraday simulations never hold an actix worker.
+//! Finished jobs are also stored under the same id (see `results`).
+
+use crate::auth::Authorized;
+use crate::limits::Throttle;
+use crate::results::Results;
+use crate::{parse_config, run_config, simulate_response, SimulateResponse};
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use trading_simulator_v2::config::Config;
+use trading_simulator_v2::engine::{CancelToken, LegReport};
+use trading_simulator_v2::SimError;
+use utoipa::ToSchema;
+
+/// Finished jobs kept for polling; the oldest are dropped first
+const MAX_FINISHED_JOBS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Waiting for a blocking-pool thread
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobState {
+    fn is_finished(self) -> bool {
+        matches!(self, JobState::Done | JobState::Failed | JobState::Cancelled)
+    }
+}
+
+#[derive(Debug)]
+struct Job {
+    state: JobState,
+    cancel: CancelToken,
+    /// Leg reports once done
+    legs: Option<Vec<LegReport>>,
+    error: Option<String>,
+}
+
+/// All known jobs by id (ids increase, so iteration is oldest first)
+#[derive(Debug, Default)]
+pub struct JobStore {
+    jobs: BTreeMap<u64, Job>,
+}
+
+impl JobStore {
+    fn finish(&mut self, id: u64, outcome: Result<Vec<LegReport>, SimError>) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            match outcome {
+                Ok(legs) => {
+                    job.state = JobState::Done;
+                    job.legs = Some(legs);
+                }
+                Err(SimError::Cancelled) => job.state = JobState::Cancelled,
+                Err(e) => {
+                    job.state = JobState::Failed;
+                    job.error = Some(e.to_string());
+                }
+            }
+        }
+
+        let finished: Vec<u64> = self.jobs.iter().filter(|(_, j)| j.state.is_finished()).map(|(&id, _)| id).collect();
+        for id in finished.iter().take(finished.len().saturating_sub(MAX_FINISHED_JOBS)) {
+            self.jobs.remove(id);
+        }
+    }
+}
+
+pub type Jobs = web::Data<Mutex<JobStore>>;
+
+#[derive(Debug, Serialize, ToSchema)]
+struct JobStatus<'a> {
+    id: u64,
+    state: JobState,
+    /// Same body as `/simulate`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<SimulateResponse<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+impl<'a> JobStatus<'a> {
+    fn of(id: u64, job: &'a Job) -> Self {
+        Self { id, state: job.state, result: job.legs.as_deref().map(simulate_response), error: job.error.as_deref() }
+    }
+}
+
+fn lock(jobs: &Jobs) -> Result<std::sync::MutexGuard<'_, JobStore>> {
+    jobs.lock().map_err(|_| actix_web::error::ErrorInternalServerError("job store poisoned"))
+}
+
+fn not_found(id: u64) -> actix_web::Error {
+    actix_web::error::ErrorNotFound(format!("no job {}", id))
+}
+
+/// Start a job for the config in the body (JSON or YAML, as for `/simulate`)
+#[utoipa::path(
+    post,
+    path = "/jobs",
+    request_body(content(
+        (Config = "application/json"),
+        (Config = "application/yaml"),
+    )),
+    responses(
+        (status = 202, description = "Queued; poll the Location header", body = JobStatus),
+        (status = 400, description = "Invalid config or over the size limits"),
+        (status = 429, description = "Rate limit exceeded"),
+    )
+)]
+pub async fn create(
+    _: Authorized,
+    req: HttpRequest,
+    body: web::Bytes,
+    jobs: Jobs,
+    results: Results,
+    _: Throttle,
+) -> Result<HttpResponse> {
+    let config = parse_config(&req, &body)?;
+    let cancel = CancelToken::new();
+
+    let id = results.next_id();
+    let job = Job { state: JobState::Queued, cancel: cancel.clone(), legs: None, error: None };
+    lock(&jobs)?.jobs.insert(id, job);
+
+    let store = jobs.clone();
+    actix_web::rt::task::spawn_blocking(move || {
+        if let Ok(mut store) = store.lock() {
+            match store.jobs.get_mut(&id) {
+                Some(job) if !cancel.is_cancelled() => job.state = JobState::Running,
+                _ => {}
+            }
+        }
+        let outcome = run_config(config.clone(), &cancel);
+        if let Ok(legs) = &outcome {
+            if let Err(e) = results.save(id, &config, legs) {
+                eprintln!("could not store result {}: {}", id, e);
+            }
+        }
+        if let Ok(mut store) = store.lock() {
+            store.finish(id, outcome);
+        }
+    });
+
+    Ok(HttpResponse::Accepted()
+        .insert_header(("Location", format!("/jobs/{}", id)))
+        .json(JobStatus { id, state: JobState::Queued, result: None, error: None }))
+}
+
+/// Status of a job, with its result once done
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    params(("id" = u64, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Current state", body = JobStatus),
+        (status = 404, description = "Unknown job"),
+    )
+)]
+pub async fn get(_: Authorized, path: web::Path<u64>, jobs: Jobs) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let store = lock(&jobs)?;
+    let job = store.jobs.get(&id).ok_or_else(|| not_found(id))?;
+    Ok(HttpResponse::Ok().json(JobStatus::of(id, job)))
+}
+
+/// Cancel a queued or running job; forget a finished one
+#[utoipa::path(
+    delete,
+    path = "/jobs/{id}",
+    params(("id" = u64, Path, description = "Job id")),
+    responses(
+        (status = 200, description = "Finished job forgotten", body = JobStatus),
+        (status = 202, description = "Cancellation requested", body = JobStatus),
+        (status = 404, description = "Unknown job"),
+    )
+)]
+pub async fn delete(_: Authorized, path: web::Path<u64>, jobs: Jobs) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    let mut store = lock(&jobs)?;
+    let job = store.jobs.get(&id).ok_or_else(|| not_found(id))?;
+    if job.state.is_finished() {
+        let state = job.state;
+        store.jobs.remove(&id);
+        return Ok(HttpResponse::Ok().json(JobStatus { id, state, result: None, error: None }));
+    }
+    job.cancel.cancel();
+    Ok(HttpResponse::Accepted().json(JobStatus { id, state: job.state, result: None, error: None }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running(store: &mut JobStore) -> u64 {
+        let id = store.jobs.keys().next_back().map_or(1, |id| id + 1);
+        let job = Job { state: JobState::Running, cancel: CancelToken::new(), legs: None, error: None };
+        store.jobs.insert(id, job);
+        id
+    }
+
+    #[test]
+    fn test_finish_records_outcome_and_evicts_oldest() {
+        let mut store = JobStore::default();
+        let cancelled = running(&mut store);
+        store.finish(cancelled, Err(SimError::Cancelled));
+        assert_eq!(store.jobs[&cancelled].state, JobState::Cancelled);
+
+        let still_running = running(&mut store);
+        for _ in 0..MAX_FINISHED_JOBS {
+            let id = running(&mut store);
+            store.finish(id, Ok(Vec::new()));
+        }
+        // The oldest finished job went; the running one is never evicted
+        assert!(!store.jobs.contains_key(&cancelled));
+        assert_eq!(store.jobs[&still_running].state, JobState::Running);
+        assert_eq!(store.jobs.len(), MAX_FINISHED_JOBS + 1);
+    }
+}
@@ -0,0 +1,185 @@
+//! Request Limits
+//!
+//! Keeps one request, malicious or a typo, from tying up the server for
+//! hours: `/run` fields must be in range, every config gets a bar budget
+//! (sweeps one for the whole grid), and the endpoints that start simulations
+//! take a `Throttle`, a per-client token bucket.
+//!
+//! Clients are told apart by peer address, so behind a reverse proxy every
+//! request shares one bucket; raise `--rate-limit` (or set it to 0 and limit
+//! at the proxy) in that setup.
+
+use crate::SimRequest;
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use std::collections::HashMap;
+use std::future::{ready, Ready};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::Instant;
+use trading_simulator_v2::config::Config;
+
+/// Longest simulation one request may ask for
+pub const MAX_DAYS: usize = 3650;
+
+/// Bars one config may simulate (ten years of 10-minute bars is about 525k)
+pub const MAX_BARS: u64 = 600_000;
+
+/// Bars a whole sweep may simulate, over every point and seed
+pub const MAX_SWEEP_BARS: u64 = 50_000_000;
+
+/// `SimRequest::strategy` values the UI sends
+const STRATEGIES: [&str; 3] = ["straddle", "long_protection", "combined"];
+
+/// Clients tracked before idle (full) buckets are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Range-check the `/run` fields before building a config from them
+pub fn check_request(req: &SimRequest) -> Result<(), String> {
+    if req.days == 0 || req.days > MAX_DAYS {
+        return Err(format!("days must be between 1 and {}", MAX_DAYS));
+    }
+    if !(req.initial_price.is_finite() && req.initial_price > 0.0) {
+        return Err("initial_price must be a positive number".to_string());
+    }
+    if !(req.volatility.is_finite() && req.volatility > 0.0 && req.volatility <= 5.0) {
+        return Err("volatility must be above 0 and at most 5 (500%)".to_string());
+    }
+    if !(req.vrp.is_finite() && req.vrp.abs() <= 1.0) {
+        return Err("vrp must be between -1 and 1".to_string());
+    }
+    if !STRATEGIES.contains(&req.strategy.as_str()) {
+        return Err(format!("unknown strategy {:?} (expected one of {})", req.strategy, STRATEGIES.join(", ")));
+    }
+    Ok(())
+}
+
+/// Upper bound on the bars `config` will simulate
+pub fn estimated_bars(config: &Config) -> u64 {
+    let resolution = config.simulation.intraday_resolution_minutes.max(1) as u64;
+    config.simulation.days as u64 * (24 * 60 / resolution).max(1)
+}
+
+/// Hold a (validated) config to the per-request size limits
+pub fn check_config(config: &Config) -> Result<(), String> {
+    if config.simulation.intraday_resolution_minutes == 0 {
+        return Err("intraday_resolution_minutes must be at least 1".to_string());
+    }
+    if config.simulation.days > MAX_DAYS {
+        return Err(format!("days must be at most {}", MAX_DAYS));
+    }
+    let bars = estimated_bars(config);
+    if bars > MAX_BARS {
+        return Err(format!("about {} bars requested, the limit is {}", bars, MAX_BARS));
+    }
+    Ok(())
+}
+
+/// Token buckets per client: `per_minute` requests, refilled continuously
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_minute: u32,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// `per_minute = 0` disables limiting
+    pub fn new(per_minute: u32) -> Self {
+        Self { per_minute, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    fn allow(&self, client: IpAddr, now: Instant) -> bool {
+        if self.per_minute == 0 {
+            return true;
+        }
+        let capacity = self.per_minute as f64;
+        let refill = |bucket: &Bucket| {
+            let minutes = now.saturating_duration_since(bucket.updated).as_secs_f64() / 60.0;
+            (bucket.tokens + minutes * capacity).min(capacity)
+        };
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return true;
+        };
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            buckets.retain(|_, bucket| refill(bucket) < capacity);
+        }
+        let bucket = buckets.entry(client).or_insert(Bucket { tokens: capacity, updated: now });
+        bucket.tokens = refill(bucket);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+/// Extractor that fails with 429 once the client is over its rate limit
+#[derive(Debug)]
+pub struct Throttle;
+
+impl FromRequest for Throttle {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let allowed = match (req.app_data::<web::Data<RateLimiter>>(), req.peer_addr()) {
+            (Some(limiter), Some(peer)) => limiter.allow(peer.ip(), Instant::now()),
+            _ => true,
+        };
+        ready(if allowed {
+            Ok(Throttle)
+        } else {
+            Err(actix_web::error::ErrorTooManyRequests("rate limit exceeded, try again shortly"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_empties_and_refills() {
+        let limiter = RateLimiter::new(2);
+        let client: IpAddr = [10, 0, 0, 1].into();
+        let other: IpAddr = [10, 0, 0, 2].into();
+        let start = Instant::now();
+        assert!(limiter.allow(client, start));
+        assert!(limiter.allow(client, start));
+        assert!(!limiter.allow(client, start));
+        assert!(limiter.allow(other, start));
+        // Half a minute refills one of the two tokens
+        assert!(limiter.allow(client, start + Duration::from_secs(30)));
+        assert!(!limiter.allow(client, start + Duration::from_secs(30)));
+        assert!(RateLimiter::new(0).allow(client, start));
+    }
+
+    #[test]
+    fn test_request_and_config_bounds() {
+        let req = |days, volatility| SimRequest {
+            days,
+            initial_price: 75.0,
+            volatility,
+            vrp: 0.05,
+            seed: 42,
+            strategy: "straddle".to_string(),
+        };
+        assert!(check_request(&req(365, 0.3)).is_ok());
+        assert!(check_request(&req(0, 0.3)).is_err());
+        assert!(check_request(&req(MAX_DAYS + 1, 0.3)).is_err());
+        assert!(check_request(&req(365, f64::NAN)).is_err());
+
+        let mut config = Config::default_1dte_straddle();
+        assert!(check_config(&config).is_ok());
+        config.simulation.intraday_resolution_minutes = 1;
+        config.simulation.days = MAX_DAYS;
+        assert!(check_config(&config).is_err());
+    }
+}
@@ -0,0 +1,409 @@
+//! Web server for Trading Simulator UI
+//! 
+//! Serves static files and provides API for running simulations in-process
+//! through the shared `SimulationEngine`. `/run` returns the finished result
+//! (headline numbers plus the full structured `SimulationReport`);
+//! `/run/stream` streams progress and trades as newline-delimited JSON.
+//! `/simulate` takes a complete `Config` as YAML or JSON, including combined
+//! short/long legs, instead of the six `SimRequest` fields. `/jobs` runs the
+//! same configs in the background for polling (see `jobs`). `/results`
+//! lists every finished run and job stored on disk, each with its
+//! config, summary, chart series and CSV/JSON downloads (see `results` and
+//! `series`). `/sweep` runs a parameter grid and returns heatmap-ready
+//! summaries. Binding, the UI directory and CORS are set by flags or
+//! environment (see `settings`); request sizes and rates are capped (see
+//! `limits`), and optionally need a bearer token (see `auth`). `/api-docs`
+//! serves the OpenAPI description (see `api_docs`).
+
+mod api_docs;
+mod auth;
+mod jobs;
+mod limits;
+mod results;
+mod series;
+mod settings;
+mod sweep;
+
+use actix_web::middleware::Condition;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Result};
+use serde::{Deserialize, Serialize};
+use trading_simulator_v2::config::Config;
+use trading_simulator_v2::engine::{
+    CancelToken, LegReport, LogEntry, PriceModel, RollTrigger, SimulationBuilder, SimulationEngine, SimulationReport, Simulator,
+    StrategySpec, StreamEvent, StrikeSelection,
+};
+use trading_simulator_v2::events::Side;
+use trading_simulator_v2::SimError;
+use trading_simulator_v2::reporting::presenter;
+use utoipa::ToSchema;
+
+/// Bars between progress messages on `/run/stream` (about a trading week at 10 minutes)
+const STREAM_PROGRESS_BARS: usize = 690;
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct SimRequest {
+    days: usize,
+    initial_price: f64,
+    volatility: f64,
+    vrp: f64,
+    seed: u64,
+    strategy: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct SimResponse<'a> {
+    net_pnl: f64,
+    position_count: u32,
+    win_rate: f64,
+    final_price: f64,
+    trades: Vec<TradeEntry>,
+    /// Everything the engine produced (P&L per unit, see `contract_multiplier`)
+    report: &'a SimulationReport,
+    contract_multiplier: f64,
+    /// Id under `/results` when the run was stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result_id: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct TradeEntry {
+    trade_type: String,
+    message: String,
+}
+
+/// `/simulate` result: one entry per leg (a single `strategy` is leg "single")
+#[derive(Debug, Serialize, ToSchema)]
+struct SimulateResponse<'a> {
+    /// Sum over legs, in dollars
+    net_pnl: f64,
+    legs: Vec<LegResponse<'a>>,
+    /// Id under `/results` when the run was stored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result_id: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct LegResponse<'a> {
+    name: &'static str,
+    #[serde(flatten)]
+    result: SimResponse<'a>,
+}
+
+/// One line of the `/run/stream` response
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamMessage<'a> {
+    Progress {
+        bars_processed: usize,
+        total_bars: usize,
+        price: f64,
+        /// Realized plus unrealized, in dollars
+        pnl: f64,
+        bars_per_sec: f64,
+        /// Estimated seconds left, once any bars have run
+        eta_seconds: Option<f64>,
+    },
+    Trade(TradeEntry),
+    Done(SimResponse<'a>),
+    Error { message: String },
+}
+
+#[utoipa::path(
+    post,
+    path = "/run",
+    request_body = SimRequest,
+    responses(
+        (status = 200, description = "Finished run", body = SimResponse),
+        (status = 400, description = "Parameters out of range"),
+        (status = 422, description = "Simulation failed"),
+        (status = 429, description = "Rate limit exceeded"),
+    )
+)]
+async fn run_simulation(
+    _: auth::Authorized,
+    req: web::Json<SimRequest>,
+    results: results::Results,
+    _: limits::Throttle,
+) -> Result<HttpResponse> {
+    let config = build_config(&req)?;
+
+    // Run (and store) on the blocking pool so long simulations don't stall the server
+    let (leg, result_id) = web::block(move || {
+        let report = SimulationEngine::run(&config)?;
+        let leg = LegReport { name: "single", config, report };
+        let result_id = results.store(&leg.config, std::slice::from_ref(&leg));
+        Ok::<_, SimError>((leg, result_id))
+    })
+    .await
+    .map_err(|e| {
+        actix_web::error::ErrorInternalServerError(format!("Failed to run simulation: {}", e))
+    })?
+    .map_err(|e| actix_web::error::ErrorUnprocessableEntity(format!("Simulation failed: {}", e)))?;
+
+    let mut response = sim_response(&leg.report, &leg.config);
+    response.result_id = result_id;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Stream progress and trades as they happen, ending with the full result
+/// or an error; the run stops once the client disconnects
+#[utoipa::path(
+    post,
+    path = "/run/stream",
+    request_body = SimRequest,
+    responses(
+        (
+            status = 200,
+            description = "One message per line",
+            body = StreamMessage,
+            content_type = "application/x-ndjson"
+        ),
+        (status = 400, description = "Parameters out of range"),
+        (status = 429, description = "Rate limit exceeded"),
+    )
+)]
+async fn run_simulation_stream(
+    _: auth::Authorized,
+    req: web::Json<SimRequest>,
+    results: results::Results,
+    _: limits::Throttle,
+) -> Result<HttpResponse> {
+    let config = build_config(&req)?;
+
+    let (sender, receiver) = tokio::sync::mpsc::channel::<String>(64);
+    actix_web::rt::task::spawn_blocking(move || {
+        // Send errors mean the client went away, so stop the run before its next bar
+        let cancel = CancelToken::new();
+        let send = |message: StreamMessage| {
+            if let Ok(mut line) = serde_json::to_string(&message) {
+                line.push('\n');
+                if sender.blocking_send(line).is_err() {
+                    cancel.cancel();
+                }
+            }
+        };
+        // Building the simulator generates the whole price path, so it happens here too
+        let simulator = match Simulator::new(config.clone()) {
+            Ok(simulator) => simulator,
+            Err(e) => return send(StreamMessage::Error { message: format!("Simulation failed: {}", e) }),
+        };
+        let multiplier = config.simulation.contract_multiplier;
+        let result = simulator.run_streaming_cancellable(STREAM_PROGRESS_BARS, &cancel, |event| {
+            send(match event {
+                StreamEvent::Progress(progress) => StreamMessage::Progress {
+                    bars_processed: progress.bars_processed,
+                    total_bars: progress.total_bars,
+                    price: progress.price,
+                    pnl: (progress.realized_pnl + progress.unrealized_pnl) * multiplier,
+                    bars_per_sec: progress.bars_per_sec(),
+                    eta_seconds: progress.eta().map(|eta| eta.as_secs_f64()),
+                },
+                StreamEvent::Trade(entry) => StreamMessage::Trade(trade_entry(&entry, &config)),
+            })
+        });
+        match result {
+            Ok(report) => {
+                let leg = LegReport { name: "single", config, report };
+                let mut response = sim_response(&leg.report, &leg.config);
+                response.result_id = results.store(&leg.config, std::slice::from_ref(&leg));
+                send(StreamMessage::Done(response))
+            }
+            Err(e) => send(StreamMessage::Error { message: format!("Simulation failed: {}", e) }),
+        }
+    });
+
+    let body = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        let line = receiver.recv().await?;
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(line)), receiver))
+    });
+    Ok(HttpResponse::Ok().content_type("application/x-ndjson").streaming(body))
+}
+
+/// Run a complete config sent as JSON (`application/json`) or YAML (anything else)
+#[utoipa::path(
+    post,
+    path = "/simulate",
+    request_body(content(
+        (Config = "application/json"),
+        (Config = "application/yaml"),
+    )),
+    responses(
+        (status = 200, description = "One entry per leg", body = SimulateResponse),
+        (status = 400, description = "Invalid config or over the size limits"),
+        (status = 422, description = "Simulation failed"),
+        (status = 429, description = "Rate limit exceeded"),
+    )
+)]
+async fn simulate(
+    _: auth::Authorized,
+    req: HttpRequest,
+    body: web::Bytes,
+    results: results::Results,
+    _: limits::Throttle,
+) -> Result<HttpResponse> {
+    let config = parse_config(&req, &body)?;
+
+    let (legs, result_id) = web::block(move || {
+        let legs = run_config(config.clone(), &CancelToken::new())?;
+        let result_id = results.store(&config, &legs);
+        Ok::<_, SimError>((legs, result_id))
+    })
+    .await
+    .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Failed to run simulation: {}", e)))?
+    .map_err(|e| actix_web::error::ErrorUnprocessableEntity(format!("Simulation failed: {}", e)))?;
+
+    let mut response = simulate_response(&legs);
+    response.result_id = result_id;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Run a config's combined legs if it has any, otherwise its `strategy`
+fn run_config(config: Config, cancel: &CancelToken) -> Result<Vec<LegReport>, SimError> {
+    if config.short_leg.is_some() || config.long_leg.is_some() {
+        SimulationEngine::run_legs_cancellable(&config, cancel)
+    } else {
+        let report = Simulator::new(config.clone())?.run_cancellable(cancel)?;
+        Ok(vec![LegReport { name: "single", config, report }])
+    }
+}
+
+fn simulate_response(legs: &[LegReport]) -> SimulateResponse<'_> {
+    let legs: Vec<LegResponse> = legs
+        .iter()
+        .map(|leg| LegResponse { name: leg.name, result: sim_response(&leg.report, &leg.config) })
+        .collect();
+    let net_pnl = legs.iter().map(|leg| leg.result.net_pnl).sum();
+    SimulateResponse { net_pnl, legs, result_id: None }
+}
+
+/// Parse and validate a `Config` from a request body, within the size limits
+fn parse_config(req: &HttpRequest, body: &[u8]) -> Result<Config> {
+    let bad_request = |e: String| actix_web::error::ErrorBadRequest(format!("Invalid config: {}", e));
+    let is_json = req
+        .headers()
+        .get(actix_web::http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+
+    let config = if is_json {
+        let config: Config = serde_json::from_slice(body).map_err(|e| bad_request(e.to_string()))?;
+        config.validate().map_err(|e| bad_request(e.to_string()))?;
+        config
+    } else {
+        let yaml = std::str::from_utf8(body).map_err(|e| bad_request(e.to_string()))?;
+        Config::from_yaml_str(yaml).map_err(|e| bad_request(e.to_string()))?
+    };
+    limits::check_config(&config).map_err(bad_request)?;
+    Ok(config)
+}
+
+/// Create config based on selected strategy
+fn build_config(req: &SimRequest) -> Result<Config> {
+    limits::check_request(req).map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid simulation parameters: {}", e))
+    })?;
+    let builder = SimulationBuilder::new()
+        .price_model(PriceModel::gbm(req.initial_price, 0.0, req.volatility))
+        .volatility_risk_premium(req.vrp)
+        .seed(req.seed)
+        .days(req.days);
+
+    let builder = if req.strategy == "long_protection" {
+        // Long protection: 70 DTE strangle $3 out, rolled at 28 DTE or 14% profit
+        builder
+            .strategy(StrategySpec::straddle(Side::Long, 70).strikes(StrikeSelection::Otm { offset: 3.0 }))
+            .triggers([RollTrigger::dte(28.0), RollTrigger::profit_target(0.14)])
+    } else {
+        // Default: 1DTE short straddle
+        builder.strategy(StrategySpec::straddle(Side::Short, 1)).triggers([RollTrigger::time(14.0)])
+    };
+
+    builder.build_config().map_err(|e| {
+        actix_web::error::ErrorBadRequest(format!("Invalid simulation parameters: {}", e))
+    })
+}
+
+fn sim_response<'a>(report: &'a SimulationReport, config: &Config) -> SimResponse<'a> {
+    SimResponse {
+        net_pnl: report.net_pnl() * config.simulation.contract_multiplier,
+        position_count: report.pnl_summary.position_count,
+        win_rate: report.win_rate(),
+        final_price: report.final_price.unwrap_or(config.simulation.initial_price),
+        // Limit to first 50 entries for UI performance
+        trades: report.log.iter().take(50).map(|entry| trade_entry(entry, config)).collect(),
+        report,
+        contract_multiplier: config.simulation.contract_multiplier,
+        result_id: None,
+    }
+}
+
+fn trade_entry(entry: &LogEntry, config: &Config) -> TradeEntry {
+    TradeEntry {
+        trade_type: match entry {
+            LogEntry::Opened { .. } => "open",
+            LogEntry::LegRolled { .. } => "leg_roll",
+            LogEntry::Closed { .. } => "close",
+            LogEntry::Rejected { .. } => "rejected",
+        }
+        .to_string(),
+        message: presenter::entry_line(entry, config).trim().to_string(),
+    }
+}
+
+/// The built-in UI, used unless `--static-dir` is given
+async fn index() -> Result<HttpResponse> {
+    let html = include_str!("../../ui/index.html");
+    Ok(HttpResponse::Ok()
+        .content_type("text/html")
+        .body(html))
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    let settings = settings::Settings::load();
+    println!("🚀 Trading Simulator Web Server starting...");
+    println!("📱 Open http://{} in your browser", settings.bind);
+    if let Some(dir) = &settings.static_dir {
+        println!("📁 Serving UI from {}", dir.display());
+    }
+    println!();
+
+    let jobs = jobs::Jobs::default();
+    let results = web::Data::new(results::ResultStore::open(&settings.results_dir)?);
+    let limiter = web::Data::new(limits::RateLimiter::new(settings.rate_limit));
+    let tokens = web::Data::new(auth::ApiTokens::new(settings.api_tokens.clone()));
+    if tokens.enabled() {
+        println!("🔒 API requires a bearer token");
+    }
+    let bind = settings.bind.clone();
+    HttpServer::new(move || {
+        let app = App::new()
+            .wrap(Condition::new(settings.cors_enabled(), settings.cors()))
+            .app_data(jobs.clone())
+            .app_data(results.clone())
+            .app_data(limiter.clone())
+            .app_data(tokens.clone())
+            .route("/run", web::post().to(run_simulation))
+            .route("/run/stream", web::post().to(run_simulation_stream))
+            .route("/simulate", web::post().to(simulate))
+            .route("/jobs", web::post().to(jobs::create))
+            .route("/jobs/{id}", web::get().to(jobs::get))
+            .route("/jobs/{id}", web::delete().to(jobs::delete))
+            .route("/results", web::get().to(results::list))
+            .route("/results/{id}", web::get().to(results::get))
+            .route("/results/{id}/series", web::get().to(results::series))
+            .route("/results/{id}/result.json", web::get().to(results::download_json))
+            .route("/results/{id}/trades.csv", web::get().to(results::download_trades))
+            .route("/results/{id}/daily.csv", web::get().to(results::download_daily))
+            .route("/sweep", web::post().to(sweep::run))
+            .route("/api-docs", web::get().to(api_docs::openapi));
+        // Static files go last so they never shadow the API
+        match &settings.static_dir {
+            Some(dir) => app.service(actix_files::Files::new("/", dir).index_file("index.html")),
+            None => app.route("/", web::get().to(index)),
+        }
+    })
+    .bind(bind)?
+    .run()
+    .await
+}
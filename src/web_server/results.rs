@@ -0,0 +1,299 @@
+//! Stored Results
+//!
+//! Every finished job is written to `RESULTS_DIR/<id>.json` with the config
+//! it ran, a headline summary and its chart series, so past runs can be
+//! listed (`GET /results`), reopened (`GET /results/{id}`) and charted
+//! (`GET /results/{id}/series`) after the server restarts, as is every `/run`,
+//! `/run/stream` and `/simulate` result. Ids continue from the largest stored
+//! one and are shared between all of them.
+//!
+//! The trade log and daily series are also saved as CSV next to the JSON and
+//! served as downloads (`trades.csv`, `daily.csv`, `result.json` under
+//! `/results/{id}/`), with a leading `leg` column for combined runs.
+
+use crate::auth::Authorized;
+use crate::series::SeriesResponse;
+use actix_web::{web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use trading_simulator_v2::config::Config;
+use trading_simulator_v2::engine::LegReport;
+use trading_simulator_v2::reporting::{csv_export, daily_closes};
+use utoipa::ToSchema;
+
+/// Directory results are stored in, relative to the working directory
+pub const RESULTS_DIR: &str = "results";
+
+/// Result files on disk plus the id counter
+#[derive(Debug)]
+pub struct ResultStore {
+    dir: PathBuf,
+    last_id: AtomicU64,
+}
+
+pub type Results = web::Data<ResultStore>;
+
+/// What `/results` lists for each run
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct ResultSummary {
+    id: u64,
+    /// Seconds since the Unix epoch
+    created_at: u64,
+    /// Sum over legs, in dollars
+    net_pnl: f64,
+    legs: Vec<LegSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct LegSummary {
+    name: String,
+    net_pnl: f64,
+    position_count: u32,
+    win_rate: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct StoredResult<'a> {
+    #[serde(flatten)]
+    summary: ResultSummary,
+    config: &'a Config,
+    series: SeriesResponse,
+}
+
+impl ResultStore {
+    /// Use `dir` (created if missing), continuing ids after the files in it
+    pub fn open(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        let last_id = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok()?.path().file_stem()?.to_str()?.parse::<u64>().ok())
+            .max()
+            .unwrap_or(0);
+        Ok(Self { dir, last_id: AtomicU64::new(last_id) })
+    }
+
+    /// A fresh id for a job or result
+    pub fn next_id(&self) -> u64 {
+        self.last_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn csv_path(&self, id: u64, table: Table) -> PathBuf {
+        self.dir.join(format!("{}.{}.csv", id, table.name()))
+    }
+
+    /// Write a finished run
+    pub fn save(&self, id: u64, config: &Config, legs: &[LegReport]) -> std::io::Result<()> {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let legs_summary: Vec<LegSummary> = legs
+            .iter()
+            .map(|leg| LegSummary {
+                name: leg.name.to_string(),
+                net_pnl: leg.report.net_pnl() * leg.config.simulation.contract_multiplier,
+                position_count: leg.report.pnl_summary.position_count,
+                win_rate: leg.report.win_rate(),
+            })
+            .collect();
+        let summary = ResultSummary {
+            id,
+            created_at,
+            net_pnl: legs_summary.iter().map(|leg| leg.net_pnl).sum(),
+            legs: legs_summary,
+        };
+        let record = StoredResult { summary, config, series: SeriesResponse::new(legs) };
+        let json = serde_json::to_vec(&record).map_err(std::io::Error::other)?;
+
+        let trades = legs.iter().flat_map(|leg| {
+            let multiplier = leg.config.simulation.contract_multiplier;
+            leg.report.trade_records.iter().map(move |t| (leg.name, csv_export::trade_row(t, multiplier)))
+        });
+        std::fs::write(self.csv_path(id, Table::Trades), leg_table(csv_export::TRADES_HEADER, trades))?;
+        let daily = legs.iter().flat_map(|leg| {
+            let multiplier = leg.config.simulation.contract_multiplier;
+            daily_closes(&leg.report.equity_series)
+                .into_iter()
+                .map(move |p| (leg.name, csv_export::daily_row(&p, multiplier)))
+        });
+        std::fs::write(self.csv_path(id, Table::Daily), leg_table(csv_export::DAILY_HEADER, daily))?;
+
+        // The JSON goes last (via a rename), so it only exists once the
+        // whole result has been written
+        let partial = self.dir.join(format!("{}.json.partial", id));
+        std::fs::write(&partial, json)?;
+        std::fs::rename(&partial, self.path(id))
+    }
+
+    /// Store a finished run under a fresh id, returning it; failures are
+    /// logged, since the caller still has the result to return
+    pub fn store(&self, config: &Config, legs: &[LegReport]) -> Option<u64> {
+        let id = self.next_id();
+        match self.save(id, config, legs) {
+            Ok(()) => Some(id),
+            Err(e) => {
+                eprintln!("could not store result {}: {}", id, e);
+                None
+            }
+        }
+    }
+
+    /// Raw JSON of a stored result
+    fn read(&self, id: u64) -> Result<Vec<u8>> {
+        self.read_file(id, self.path(id))
+    }
+
+    fn read_file(&self, id: u64, path: PathBuf) -> Result<Vec<u8>> {
+        std::fs::read(path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => actix_web::error::ErrorNotFound(format!("no result {}", id)),
+            _ => actix_web::error::ErrorInternalServerError(format!("could not read result {}: {}", id, e)),
+        })
+    }
+
+    /// Summaries of every stored result, newest first
+    fn list(&self) -> std::io::Result<Vec<ResultSummary>> {
+        let mut summaries: Vec<ResultSummary> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                // Summary fields only; the config and series are skipped
+                serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+            })
+            .collect();
+        summaries.sort_by_key(|summary| std::cmp::Reverse(summary.id));
+        Ok(summaries)
+    }
+}
+
+/// Downloadable CSV tables
+#[derive(Debug, Clone, Copy)]
+enum Table {
+    Trades,
+    Daily,
+}
+
+impl Table {
+    fn name(self) -> &'static str {
+        match self {
+            Table::Trades => "trades",
+            Table::Daily => "daily",
+        }
+    }
+}
+
+/// CSV with a `leg` column in front of `header`
+fn leg_table(header: &str, rows: impl Iterator<Item = (&'static str, String)>) -> String {
+    let mut csv = format!("leg,{}\n", header);
+    for (leg, row) in rows {
+        csv.push_str(leg);
+        csv.push(',');
+        csv.push_str(&row);
+        csv.push('\n');
+    }
+    csv
+}
+
+fn attachment(body: Vec<u8>, content_type: &str, filename: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(("Content-Disposition", format!("attachment; filename=\"{}\"", filename)))
+        .body(body)
+}
+
+/// Every stored result's summary, newest first
+#[utoipa::path(
+    get,
+    path = "/results",
+    responses((status = 200, description = "Stored results, newest first", body = Vec<ResultSummary>))
+)]
+pub async fn list(_: Authorized, results: Results) -> Result<HttpResponse> {
+    let summaries = web::block(move || results.list())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("could not list results: {}", e)))?;
+    Ok(HttpResponse::Ok().json(summaries))
+}
+
+/// A stored result: summary, config and series
+#[utoipa::path(
+    get,
+    path = "/results/{id}",
+    params(("id" = u64, Path, description = "Result id")),
+    responses(
+        (status = 200, description = "The stored result", body = StoredResult),
+        (status = 404, description = "Unknown result"),
+    )
+)]
+pub async fn get(_: Authorized, path: web::Path<u64>, results: Results) -> Result<HttpResponse> {
+    let json = results.read(path.into_inner())?;
+    Ok(HttpResponse::Ok().content_type("application/json").body(json))
+}
+
+/// Daily price and equity plus trade markers of a stored result
+#[utoipa::path(
+    get,
+    path = "/results/{id}/series",
+    params(("id" = u64, Path, description = "Result id")),
+    responses(
+        (status = 200, description = "Chart data", body = SeriesResponse),
+        (status = 404, description = "Unknown result"),
+    )
+)]
+pub async fn series(_: Authorized, path: web::Path<u64>, results: Results) -> Result<HttpResponse> {
+    let record: serde_json::Value = serde_json::from_slice(&results.read(path.into_inner())?)
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok().json(&record["series"]))
+}
+
+/// The stored result as a `result-<id>.json` download
+#[utoipa::path(
+    get,
+    path = "/results/{id}/result.json",
+    params(("id" = u64, Path, description = "Result id")),
+    responses(
+        (status = 200, description = "Attachment", body = StoredResult),
+        (status = 404, description = "Unknown result"),
+    )
+)]
+pub async fn download_json(_: Authorized, path: web::Path<u64>, results: Results) -> Result<HttpResponse> {
+    let id = path.into_inner();
+    Ok(attachment(results.read(id)?, "application/json", format!("result-{}.json", id)))
+}
+
+/// The trade log as a `trades-<id>.csv` download
+#[utoipa::path(
+    get,
+    path = "/results/{id}/trades.csv",
+    params(("id" = u64, Path, description = "Result id")),
+    responses(
+        (status = 200, description = "Attachment", body = String, content_type = "text/csv"),
+        (status = 404, description = "Unknown result"),
+    )
+)]
+pub async fn download_trades(_: Authorized, path: web::Path<u64>, results: Results) -> Result<HttpResponse> {
+    download_csv(path.into_inner(), Table::Trades, &results)
+}
+
+/// The end-of-day series as a `daily-<id>.csv` download
+#[utoipa::path(
+    get,
+    path = "/results/{id}/daily.csv",
+    params(("id" = u64, Path, description = "Result id")),
+    responses(
+        (status = 200, description = "Attachment", body = String, content_type = "text/csv"),
+        (status = 404, description = "Unknown result"),
+    )
+)]
+pub async fn download_daily(_: Authorized, path: web::Path<u64>, results: Results) -> Result<HttpResponse> {
+    download_csv(path.into_inner(), Table::Daily, &results)
+}
+
+fn download_csv(id: u64, table: Table, results: &ResultStore) -> Result<HttpResponse> {
+    let csv = results.read_file(id, results.csv_path(id, table))?;
+    Ok(attachment(csv, "text/csv", format!("{}-{}.csv", table.name(), id)))
+}
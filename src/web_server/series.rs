@@ -0,0 +1,81 @@
+//! Chart Series
+//!
+//! Flattens finished leg reports into the arrays the UI charts draw: one
+//! point per trading day (closing price and equity) and one marker per
+//! closed trade, all in dollars. Built from the report itself rather than
+//! the truncated `trades` strings of the run responses.
+
+use serde::Serialize;
+use trading_simulator_v2::calendar::intraday::Timestamp;
+use trading_simulator_v2::engine::LegReport;
+use trading_simulator_v2::events::Side;
+use trading_simulator_v2::reporting::daily_closes;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SeriesResponse {
+    legs: Vec<LegSeries>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct LegSeries {
+    name: &'static str,
+    /// End-of-day snapshots, in day order
+    days: Vec<DayPoint>,
+    trades: Vec<TradeMarker>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct DayPoint {
+    day: u32,
+    price: f64,
+    /// Realized plus unrealized P&L, in dollars
+    equity: f64,
+    realized: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct TradeMarker {
+    position_id: u64,
+    side: Side,
+    entry: Timestamp,
+    entry_price: f64,
+    close: Timestamp,
+    close_price: f64,
+    /// Realized P&L, in dollars
+    pnl: f64,
+}
+
+impl SeriesResponse {
+    pub fn new(legs: &[LegReport]) -> Self {
+        Self { legs: legs.iter().map(leg_series).collect() }
+    }
+}
+
+fn leg_series(leg: &LegReport) -> LegSeries {
+    let multiplier = leg.config.simulation.contract_multiplier;
+    let days = daily_closes(&leg.report.equity_series)
+        .iter()
+        .map(|point| DayPoint {
+            day: point.timestamp.day,
+            price: point.price,
+            equity: point.equity() * multiplier,
+            realized: point.realized * multiplier,
+        })
+        .collect();
+    let trades = leg
+        .report
+        .trade_records
+        .iter()
+        .map(|trade| TradeMarker {
+            position_id: trade.position_id,
+            side: trade.side,
+            entry: trade.entry,
+            entry_price: trade.entry_price,
+            close: trade.close,
+            close_price: trade.close_price,
+            pnl: trade.pnl * multiplier,
+        })
+        .collect();
+    LegSeries { name: leg.name, days, trades }
+}
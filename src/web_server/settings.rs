@@ -0,0 +1,165 @@
+//! Server Settings
+//!
+//! Listen address, UI directory, results directory, CORS origins, rate
+//! limit and API tokens, read from `TSV2_*` environment variables and then command-line
+//! flags (flags win). The defaults are the old hardcoded setup:
+//! `127.0.0.1:3000`, the UI compiled into the binary and no cross-origin
+//! access, plus 30 simulation requests per client per minute. Without API
+//! tokens the API is open, as before.
+
+use actix_cors::Cors;
+use std::path::PathBuf;
+
+/// Command-line flags:
+/// `[--bind <addr:port>] [--static-dir <dir>] [--results-dir <dir>] [--cors-origin <origin|*>]...
+/// [--rate-limit <per-minute>] [--api-token <token>]...`
+///
+/// Environment: `TSV2_BIND`, `TSV2_STATIC_DIR`, `TSV2_RESULTS_DIR`,
+/// `TSV2_CORS_ORIGINS`, `TSV2_RATE_LIMIT` and `TSV2_API_TOKENS` (lists are
+/// comma-separated). Prefer the environment for tokens: flags show up in `ps`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// Address to listen on, e.g. `0.0.0.0:8080` inside a container
+    pub bind: String,
+    /// Serve the UI from this directory instead of the built-in page
+    pub static_dir: Option<PathBuf>,
+    /// Where finished results are stored (see `results`)
+    pub results_dir: PathBuf,
+    /// Origins allowed to call the API from another site; `*` allows any
+    pub cors_origins: Vec<String>,
+    /// Simulation requests per client per minute (0 = unlimited)
+    pub rate_limit: u32,
+    /// Bearer tokens required by the API (none = no authentication)
+    pub api_tokens: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bind: "127.0.0.1:3000".to_string(),
+            static_dir: None,
+            results_dir: PathBuf::from(crate::results::RESULTS_DIR),
+            cors_origins: Vec::new(),
+            rate_limit: 30,
+            api_tokens: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Settings for this process
+    pub fn load() -> Self {
+        Self::parse(|name| std::env::var(name).ok(), std::env::args().skip(1))
+    }
+
+    fn parse(env: impl Fn(&str) -> Option<String>, args: impl IntoIterator<Item = String>) -> Self {
+        let mut settings = Self::default();
+        if let Some(bind) = env("TSV2_BIND") {
+            settings.bind = bind;
+        }
+        settings.static_dir = env("TSV2_STATIC_DIR").map(PathBuf::from);
+        if let Some(dir) = env("TSV2_RESULTS_DIR") {
+            settings.results_dir = PathBuf::from(dir);
+        }
+        if let Some(origins) = env("TSV2_CORS_ORIGINS") {
+            settings.cors_origins = list(&origins);
+        }
+        if let Some(limit) = env("TSV2_RATE_LIMIT").and_then(|n| n.parse().ok()) {
+            settings.rate_limit = limit;
+        }
+        if let Some(tokens) = env("TSV2_API_TOKENS") {
+            settings.api_tokens = list(&tokens);
+        }
+
+        // Any `--cors-origin` or `--api-token` replaces the environment's list
+        // rather than adding to it, so rotating tokens by flag drops the old ones
+        let mut cors_origins = Vec::new();
+        let mut api_tokens = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bind" => settings.bind = args.next().unwrap_or(settings.bind),
+                "--static-dir" => settings.static_dir = args.next().map(PathBuf::from).or(settings.static_dir),
+                "--results-dir" => {
+                    settings.results_dir = args.next().map(PathBuf::from).unwrap_or(settings.results_dir)
+                }
+                "--cors-origin" => cors_origins.extend(args.next()),
+                "--api-token" => api_tokens.extend(args.next()),
+                "--rate-limit" => {
+                    settings.rate_limit = args.next().and_then(|n| n.parse().ok()).unwrap_or(settings.rate_limit)
+                }
+                _ => eprintln!("Ignoring unknown argument {:?}", arg),
+            }
+        }
+        if !cors_origins.is_empty() {
+            settings.cors_origins = cors_origins;
+        }
+        if !api_tokens.is_empty() {
+            settings.api_tokens = api_tokens;
+        }
+        settings
+    }
+
+    /// Whether any CORS middleware is needed
+    pub fn cors_enabled(&self) -> bool {
+        !self.cors_origins.is_empty()
+    }
+
+    /// CORS policy for the configured origins
+    pub fn cors(&self) -> Cors {
+        let cors = Cors::default().allow_any_method().allow_any_header().max_age(3600);
+        if self.cors_origins.iter().any(|origin| origin == "*") {
+            return cors.allow_any_origin();
+        }
+        self.cors_origins.iter().fold(cors, |cors, origin| cors.allowed_origin(origin))
+    }
+}
+
+/// Comma-separated values, trimmed, empty ones dropped
+fn list(values: &str) -> Vec<String> {
+    values.split(',').map(str::trim).filter(|v| !v.is_empty()).map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_flags_override_environment() {
+        let env = |name: &str| match name {
+            "TSV2_BIND" => Some("0.0.0.0:8080".to_string()),
+            "TSV2_CORS_ORIGINS" => Some("https://a.example, https://b.example".to_string()),
+            "TSV2_API_TOKENS" => Some("one,two".to_string()),
+            _ => None,
+        };
+        let settings =
+            Settings::parse(env, args(&["--bind", "0.0.0.0:9000", "--static-dir", "ui", "--rate-limit", "0"]));
+        assert_eq!(settings.bind, "0.0.0.0:9000");
+        assert_eq!(settings.static_dir, Some(PathBuf::from("ui")));
+        assert_eq!(settings.cors_origins, args(&["https://a.example", "https://b.example"]));
+        assert!(settings.cors_enabled());
+        assert_eq!(settings.rate_limit, 0);
+        assert_eq!(settings.api_tokens, args(&["one", "two"]));
+
+        assert_eq!(Settings::parse(|_| None, Vec::new()), Settings::default());
+    }
+
+    #[test]
+    fn test_list_flags_replace_environment_lists() {
+        let env = |name: &str| match name {
+            "TSV2_CORS_ORIGINS" => Some("https://a.example".to_string()),
+            "TSV2_API_TOKENS" => Some("old".to_string()),
+            _ => None,
+        };
+        let flags = args(&["--cors-origin", "https://c.example", "--cors-origin", "https://d.example"]);
+        assert_eq!(Settings::parse(env, flags).cors_origins, args(&["https://c.example", "https://d.example"]));
+        let settings = Settings::parse(env, args(&["--api-token", "new"]));
+        assert_eq!(settings.api_tokens, args(&["new"]));
+        assert_eq!(settings.cors_origins, args(&["https://a.example"]));
+        assert_eq!(Settings::parse(env, Vec::new()).api_tokens, args(&["old"]));
+    }
+}
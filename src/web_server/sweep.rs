@@ -0,0 +1,209 @@
+//! Parameter Sweep Endpoint
+//!
+//! `POST /sweep` takes a base config (JSON) and one or more swept fields,
+//! each as explicit `values` or a `start`/`end`/`step` range, and
+//! runs the grid with the library's `Sweep`. The response has per-point P&L
+//! and drawdown distributions, plus a mean-P&L matrix for heatmaps when at
+//! most two fields are swept. Points are scored by an optional `objective`
+//! (as in a config's `sweep.objective`; mean P&L by default) and the
+//! response names the best one. The grid is sized from the value counts before
+//! any point is built: it may run at most `MAX_SWEEP_RUNS` simulations, every
+//! point must fit the per-config bar limit and the whole grid `MAX_SWEEP_BARS`.
+
+use crate::auth::Authorized;
+use crate::limits::{self, Throttle, MAX_SWEEP_BARS};
+use actix_web::{web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use trading_simulator_v2::config::{Config, ObjectiveConfig, SweepParameterConfig};
+use trading_simulator_v2::montecarlo::{Objective, PointSummary, Sweep};
+use trading_simulator_v2::reporting::Distribution;
+use utoipa::ToSchema;
+
+/// Largest grid (points × seeds) one request may run
+const MAX_SWEEP_RUNS: usize = 2000;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SweepRequest {
+    config: Config,
+    parameters: Vec<SweepParameterConfig>,
+    #[serde(default = "default_seeds")]
+    seeds: u64,
+    /// Worker threads (default, and at most: one per core)
+    threads: Option<usize>,
+    /// What ranks the points (default: highest mean P&L)
+    objective: Option<ObjectiveConfig>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct SweepResponse {
+    parameters: Vec<SweptParameter>,
+    seeds: u64,
+    /// The objective, in words
+    objective: String,
+    /// Grid points, last parameter varying fastest
+    points: Vec<PointResponse>,
+    /// Index into `points` of the best-scoring point, if any has a score
+    best: Option<usize>,
+    /// Mean P&L in dollars: one row per value of the first parameter, one
+    /// column per value of the second (a single row when only one is swept)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mean_pnl_matrix: Option<Vec<Vec<f64>>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct SweptParameter {
+    path: String,
+    values: Vec<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct PointResponse {
+    values: Vec<f64>,
+    pnl: Distribution,
+    max_drawdown: Distribution,
+    /// Objective score (higher is better); absent when undefined or a
+    /// constraint is broken
+    score: Option<f64>,
+    /// Median and lowest of the objective scored on each seed alone
+    median_seed_score: Option<f64>,
+    worst_seed_score: Option<f64>,
+    /// Positive mean P&L but most seeds lose
+    lucky: bool,
+}
+
+/// Run a parameter grid and summarize each point
+#[utoipa::path(
+    post,
+    path = "/sweep",
+    request_body = SweepRequest,
+    responses(
+        (status = 200, description = "Per-point summaries", body = SweepResponse),
+        (status = 400, description = "Invalid sweep or over the size limits"),
+        (status = 422, description = "A run failed"),
+        (status = 429, description = "Rate limit exceeded"),
+    )
+)]
+pub async fn run(_: Authorized, req: web::Json<SweepRequest>, _: Throttle) -> Result<HttpResponse> {
+    let req = req.into_inner();
+    let bad_request = |e: String| actix_web::error::ErrorBadRequest(format!("Invalid sweep: {}", e));
+    req.config.validate().map_err(|e| bad_request(e.to_string()))?;
+    if req.parameters.is_empty() {
+        return Err(bad_request("no parameters to sweep".to_string()));
+    }
+    if req.seeds > MAX_SWEEP_RUNS as u64 {
+        return Err(bad_request(format!("seeds must be at most {}", MAX_SWEEP_RUNS)));
+    }
+
+    let objective = match &req.objective {
+        Some(objective) => {
+            objective.validate().map_err(|e| bad_request(e.to_string()))?;
+            Objective::from_config(objective).map_err(|e| bad_request(e.to_string()))?
+        }
+        None => Objective::default(),
+    };
+
+    let mut sweep = Sweep::new(&req.config).seeds(req.seeds.max(1)).rank_by(objective);
+    let mut parameters = Vec::new();
+    for range in &req.parameters {
+        let values = range.values().map_err(bad_request)?;
+        sweep = sweep.parameter(&range.path, values.clone());
+        parameters.push(SweptParameter { path: range.path.clone(), values });
+    }
+    if let Some(threads) = req.threads {
+        let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+        sweep = sweep.threads(threads.min(cores));
+    }
+    match sweep.run_count() {
+        Some(runs) if (1..=MAX_SWEEP_RUNS).contains(&runs) => {}
+        Some(runs) => return Err(bad_request(format!("{} runs (must be 1 to {})", runs, MAX_SWEEP_RUNS))),
+        None => return Err(bad_request(format!("too many runs (must be 1 to {})", MAX_SWEEP_RUNS))),
+    }
+    let mut bars: u64 = 0;
+    for values in sweep.points() {
+        let config = parameters
+            .iter()
+            .zip(values)
+            .try_fold(req.config.clone(), |config, (parameter, value)| config.with_override(&parameter.path, value))
+            .map_err(|e| bad_request(e.to_string()))?;
+        limits::check_config(&config).map_err(bad_request)?;
+        bars = bars.saturating_add(limits::estimated_bars(&config).saturating_mul(req.seeds.max(1)));
+    }
+    if bars > MAX_SWEEP_BARS {
+        return Err(bad_request(format!("about {} bars in total, the limit is {}", bars, MAX_SWEEP_BARS)));
+    }
+
+    let (summaries, ranked) = web::block(move || sweep.run().map(|result| (result.summaries(), result.ranked())))
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(format!("Failed to run sweep: {}", e)))?
+        .map_err(|e| actix_web::error::ErrorUnprocessableEntity(format!("Sweep failed: {}", e)))?;
+    let best = ranked.first().filter(|s| s.score.is_some()).map(|s| s.point);
+
+    let mean_pnl_matrix = (parameters.len() <= 2).then(|| {
+        let columns = parameters.last().map_or(1, |p| p.values.len());
+        summaries.chunks(columns).map(|row| row.iter().map(|point| point.pnl.mean).collect()).collect()
+    });
+    Ok(HttpResponse::Ok().json(SweepResponse {
+        parameters,
+        seeds: req.seeds.max(1),
+        objective: objective.to_string(),
+        points: summaries.into_iter().map(point_response).collect(),
+        best,
+        mean_pnl_matrix,
+    }))
+}
+
+fn point_response(summary: PointSummary) -> PointResponse {
+    PointResponse {
+        values: summary.values,
+        pnl: summary.pnl,
+        max_drawdown: summary.max_drawdown,
+        score: summary.score,
+        median_seed_score: summary.median_seed_score,
+        worst_seed_score: summary.worst_seed_score,
+        lucky: summary.lucky,
+    }
+}
+
+fn default_seeds() -> u64 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, App};
+    use serde_json::{json, Value};
+
+    fn request(parameters: Value) -> Value {
+        let mut config = Config::default_1dte_straddle();
+        config.simulation.days = 5;
+        json!({ "config": config, "parameters": parameters, "seeds": 2 })
+    }
+
+    #[actix_web::test]
+    async fn test_sweep_matrix_and_bad_axes() {
+        let app = test::init_service(App::new().route("/sweep", web::post().to(run))).await;
+        let post = |body: Value| test::TestRequest::post().uri("/sweep").set_json(body).to_request();
+
+        let grid = json!([
+            { "path": "simulation.volatility_risk_premium", "values": [0.0, 0.05] },
+            { "path": "strategy.entry_dte", "start": 1.0, "end": 3.0, "step": 1.0 },
+        ]);
+        let response: Value = test::call_and_read_body_json(&app, post(request(grid))).await;
+        assert_eq!(response["points"].as_array().unwrap().len(), 6);
+        let matrix = response["mean_pnl_matrix"].as_array().unwrap();
+        assert_eq!(matrix.len(), 2);
+        assert!(matrix.iter().all(|row| row.as_array().unwrap().len() == 3));
+        assert_eq!(response["points"][5]["values"], json!([0.05, 3.0]));
+
+        for axis in [
+            json!({ "path": "strategy.entry_dte", "start": 1.0, "end": 3.0, "step": 0.0 }),
+            json!({ "path": "strategy.entry_dte", "start": 0.0, "end": 1e20, "step": 1.0 }),
+            json!({ "path": "strategy.entry_dte", "values": [] }),
+            json!({ "path": "strategy.no_such_field", "values": [1.0] }),
+        ] {
+            let response = test::call_service(&app, post(request(json!([axis])))).await;
+            assert_eq!(response.status(), actix_web::http::StatusCode::BAD_REQUEST);
+        }
+    }
+}